@@ -2,10 +2,15 @@ use env_logger::Env;
 use std::sync::{Mutex, Once};
 use tauri::{menu::Menu, Builder, Error, Manager, WindowEvent, Wry};
 
+use crate::commands::schedule_commands;
 use crate::services::app_service::AppService;
 use crate::services::codex_service::CodexService;
 use crate::services::command_service::CommandService;
 use crate::services::data_service::DataService;
+use crate::services::openai_service::OpenAiService;
+use crate::services::scheduler_service::SchedulerService;
+use crate::services::session_manager::SessionManager;
+use crate::services::watcher_service::WatcherService;
 
 pub struct App;
 
@@ -55,6 +60,21 @@ impl App {
                 Ok(service) => service,
                 Err(error) => return Err(error.into()),
             };
+            match data_service.recover_interrupted_turns() {
+                Ok(0) => {}
+                Ok(count) => log::info!("marked {} interrupted chat turns", count),
+                Err(error) => log::error!("failed to recover interrupted chat turns: {}", error),
+            }
+            match data_service.recover_interrupted_task_runs() {
+                Ok(0) => {}
+                Ok(count) => log::info!("paused {} interrupted task runs", count),
+                Err(error) => log::error!("failed to recover interrupted task runs: {}", error),
+            }
+            match data_service.skip_unstarted_scheduled_runs() {
+                Ok(0) => {}
+                Ok(count) => log::info!("skipped {} scheduled runs that never started", count),
+                Err(error) => log::error!("failed to skip unstarted scheduled runs: {}", error),
+            }
 
             if let Some(main_window) = app.get_webview_window("main") {
                 #[cfg(target_os = "macos")]
@@ -69,10 +89,23 @@ impl App {
                 let _ = main_window.show();
             }
 
+            let codex_service = CodexService::new(app_service.app_data_dir().clone());
+            codex_service.set_recording_enabled(app_service.is_session_recording_enabled());
+            let session_manager = SessionManager::new();
+            session_manager.set_max_concurrent(app_service.get_max_concurrent_sessions());
+
             app.manage(Mutex::new(app_service));
             app.manage(Mutex::new(data_service));
-            app.manage(CodexService::new());
+            app.manage(codex_service);
             app.manage(CommandService::new());
+            app.manage(OpenAiService::new());
+            app.manage(session_manager);
+            app.manage(WatcherService::new());
+            app.manage(SchedulerService::new());
+            // Schedules advance from startup; the UI takes the due runs once it loads.
+            let handle = app.handle().clone();
+            app.state::<SchedulerService>()
+                .start(move || schedule_commands::queue_due_runs(&handle));
             log::info!("backend logging initialized");
             log::info!("app name: {}", app.package_info().name);
 
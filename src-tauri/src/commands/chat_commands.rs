@@ -1,19 +1,456 @@
+use std::path::Path;
 use std::sync::Mutex;
 
-use crate::models::chat::{ChatMessage, ChatRequest, ChatThread};
-use crate::models::event_handler::TauriCodexEventHandler;
+use crate::models::budget::{BudgetExceededNotification, BudgetViolation};
+use crate::models::chat::{ChatMessage, ChatRequest, ChatThread, ChatTurnStatus, TurnOutcome};
+use crate::models::event_handler::{CodexEventHandler, RunEvent, TauriCodexEventHandler};
+use crate::models::project::Project;
+use crate::models::prompt::PromptRecord;
+use crate::models::session::ActiveSession;
+use crate::models::session_recording::SessionRecording;
+use crate::models::setting::AgentConfig;
+use crate::models::task::{TaskStatus, TaskStepResult};
+use crate::models::usage::{TokenUsage, UsageRecord, UsageReport, UsageReportQuery};
+use crate::services::app_service::AppService;
+use crate::services::budget_service::{BudgetEventHandler, BudgetService};
+use crate::services::chat_persistence::PersistingEventHandler;
 use crate::services::codex_service::CodexService;
+use crate::services::context_service::ContextService;
 use crate::services::data_service::DataService;
-use tauri::State;
+use crate::services::openai_service::OpenAiService;
+use crate::services::rule_service::RuleService;
+use crate::services::session_manager::SessionManager;
+use crate::services::template_service::TemplateService;
+use tauri::ipc::Channel;
+use tauri::{Emitter, State};
+
+const BUDGET_EXCEEDED_EVENT: &str = "budget:exceeded";
 
 #[tauri::command]
 pub async fn chat(
+    mut payload: ChatRequest,
+    on_event: Option<Channel<RunEvent>>,
+    app: tauri::AppHandle,
+    app_service: State<'_, Mutex<AppService>>,
+    data_service: State<'_, Mutex<DataService>>,
+    codex_service: State<'_, CodexService>,
+    openai_service: State<'_, OpenAiService>,
+    session_manager: State<'_, SessionManager>,
+) -> Result<(), String> {
+    let (agent, pricing, budget, context_token_budget) = {
+        let service = app_service
+            .lock()
+            .map_err(|error| format!("failed to lock app service: {error}"))?;
+        (
+            service.get_agent_config(payload.agent_id.as_deref()),
+            service.get_model_pricing(),
+            BudgetService::new(service.get_budget_limits()),
+            service.get_context_token_budget(),
+        )
+    };
+
+    let run_id = payload.ensure_run_id();
+    let project_id = payload.project_id.clone();
+    let task_run_id = payload.task_run_id.clone();
+    let task_id = payload.task_id.clone();
+    let step_id = payload.step_id.clone();
+    let model = payload
+        .model
+        .clone()
+        .filter(|model| !model.trim().is_empty())
+        .or_else(|| agent.as_ref().map(|agent| agent.model.clone()))
+        .unwrap_or_default();
+    let agent_name = agent
+        .as_ref()
+        .map(|agent| agent.name.clone())
+        .unwrap_or_else(|| AgentConfig::CODEX_CLI.to_string());
+    let agent_id = agent.as_ref().map(|agent| agent.id.clone());
+    let chat_thread_id = payload.chat_thread_id.clone();
+    // Fresh threads and threads the caller resumes itself are not the chat's.
+    let own_thread = !payload.fresh_thread && payload.thread_id.is_none();
+
+    if let (Some(chat_thread_id), None, false) =
+        (&chat_thread_id, &payload.thread_id, payload.fresh_thread)
+    {
+        let service = data_service
+            .lock()
+            .map_err(|error| format!("failed to lock data service: {error}"))?;
+        let thread = service
+            .load_chat_thread(chat_thread_id)
+            .map_err(|error| format!("failed to load chat thread: {error}"))?;
+        payload.thread_id = thread.and_then(|thread| {
+            thread
+                .resumable_thread_id(agent_id.as_deref())
+                .map(str::to_string)
+        });
+    }
+
+    let mut prompt_with_rules = None;
+    let mut context_block = None;
+    if let Some(project_id) = &project_id {
+        let service = data_service
+            .lock()
+            .map_err(|error| format!("failed to lock data service: {error}"))?;
+        let project = service
+            .load_project(project_id)
+            .map_err(|error| format!("failed to load project: {error}"))?;
+        if let Some(project) = project {
+            if let (Some(task_id), Some(step_id), false) = (&task_id, &step_id, payload.verbatim) {
+                payload.content = render_step_prompt(
+                    &service,
+                    &project,
+                    task_id,
+                    step_id,
+                    task_run_id.as_deref(),
+                    &payload.content,
+                )?;
+            }
+
+            let context_set_ids = payload.context_set_ids.clone().unwrap_or_default();
+            if !context_set_ids.is_empty() {
+                let context_sets = service
+                    .load_context_sets(project_id)
+                    .map_err(|error| format!("failed to load context sets: {error}"))?
+                    .into_iter()
+                    .filter(|context_set| context_set_ids.contains(&context_set.id))
+                    .collect::<Vec<_>>();
+                context_block = Some(ContextService::new().build_block(
+                    Path::new(&project.path),
+                    &context_sets,
+                    context_token_budget,
+                )?);
+            }
+
+            if payload.thread_id.is_none() {
+                let files = context_block
+                    .iter()
+                    .flat_map(|block| block.files.iter().map(|file| file.path.clone()))
+                    .collect::<Vec<_>>();
+                let rule_service = RuleService::new();
+                let context = rule_service.context_for(&project, &payload, &files);
+                prompt_with_rules =
+                    Some(rule_service.assemble_prompt(&project.rules, &context, &payload.content));
+            }
+        }
+    }
+
+    let prompt = payload.content.clone();
+    if let Some(prompt_with_rules) = prompt_with_rules {
+        payload.content = prompt_with_rules;
+    }
+    if let Some(context_block) = &context_block {
+        payload.content = ContextService::prepend(context_block, &payload.content);
+    }
+
+    let _session = session_manager
+        .acquire(&run_id, project_id.clone(), task_run_id.clone(), agent_name)
+        .await?;
+
+    let turn = UsageRecord::for_turn(
+        project_id.clone(),
+        None,
+        task_run_id.clone(),
+        model.clone(),
+        TokenUsage::default(),
+        &pricing,
+    );
+    let handler = PersistingEventHandler::new(
+        data_service.inner(),
+        chat_thread_id.clone(),
+        model.clone(),
+        BudgetEventHandler::new(
+            &budget,
+            data_service.inner(),
+            session_manager.inner(),
+            &pricing,
+            turn,
+            session_manager.handler(TauriCodexEventHandler::with_channel(app.clone(), on_event)),
+        ),
+    );
+    handler.save_prompt(&prompt);
+    {
+        let service = data_service
+            .lock()
+            .map_err(|error| format!("failed to lock data service: {error}"))?;
+        let record = PromptRecord::for_run(
+            run_id.clone(),
+            project_id.clone(),
+            chat_thread_id.clone(),
+            task_run_id.clone(),
+            prompt,
+        );
+        if let Err(error) = service.save_prompt(&record) {
+            log::error!("failed to record prompt of run {}: {}", run_id, error);
+        }
+        if let Some(chat_thread_id) = &chat_thread_id {
+            if let Err(error) = service.start_chat_turn(&run_id, chat_thread_id) {
+                log::error!("failed to start chat turn {}: {}", run_id, error);
+            }
+        }
+    }
+
+    {
+        let service = data_service
+            .lock()
+            .map_err(|error| format!("failed to lock data service: {error}"))?;
+        let violation = budget
+            .check_before_turn(&service, project_id.as_deref(), task_run_id.as_deref())
+            .map_err(|error| format!("failed to check budget: {error}"))?;
+        if let Some(violation) = violation {
+            let notification = BudgetExceededNotification::new(
+                project_id,
+                task_id,
+                step_id,
+                task_run_id,
+                violation,
+            );
+            let reason = stop_over_budget(&app, &service, notification);
+            finish_turn(&service, &run_id, Err(&reason));
+            return Err(reason);
+        }
+    }
+
+    log::info!("Starting chat run {}", run_id);
+    let resumed_thread_id = payload.thread_id.clone();
+    let result = invoke_agent(agent, payload, &handler, &codex_service, &openai_service).await;
+
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+    finish_turn(
+        &service,
+        &run_id,
+        result.as_ref().map(|_| ()).map_err(String::as_str),
+    );
+
+    // Failed turns still cost what the agent reported before failing.
+    let (agent_thread_id, usage) = match &result {
+        Ok(outcome) => (outcome.thread_id.clone(), outcome.usage),
+        Err(_) => (resumed_thread_id, handler.usage()),
+    };
+    let record = UsageRecord::for_turn(
+        project_id,
+        agent_thread_id,
+        task_run_id.clone(),
+        model.clone(),
+        usage,
+        &pricing,
+    );
+    if result.is_ok() || usage.total_tokens() > 0 {
+        if let Err(error) = service.save_usage_record(&record) {
+            log::error!("failed to save usage record: {}", error);
+        }
+    }
+    let outcome = match result {
+        Ok(outcome) => outcome,
+        Err(error) => {
+            // A turn cancelled over budget fails with the violation as its reason.
+            return match check_after_turn(&budget, &service, &record) {
+                Some(violation) => {
+                    let notification = BudgetExceededNotification::new(
+                        record.project_id.clone(),
+                        task_id,
+                        step_id,
+                        record.task_run_id.clone(),
+                        violation,
+                    );
+                    Err(stop_over_budget(&app, &service, notification))
+                }
+                None => Err(error),
+            };
+        }
+    };
+
+    // Other threads are not stored, so later turns still resume the chat's thread.
+    if let (Some(chat_thread_id), Some(agent_thread_id), true) =
+        (&chat_thread_id, &outcome.thread_id, own_thread)
+    {
+        if let Err(error) = service.update_chat_thread_agent(
+            chat_thread_id,
+            agent_thread_id,
+            agent_id.as_deref(),
+            Some(&model)
+                .filter(|model| !model.is_empty())
+                .map(String::as_str),
+        ) {
+            log::error!(
+                "failed to store agent thread for chat {}: {}",
+                chat_thread_id,
+                error
+            );
+        }
+    }
+
+    if let (Some(task_run_id), Some(task_id), Some(step_id)) = (&task_run_id, &task_id, &step_id) {
+        let result = TaskStepResult::new(
+            task_run_id.clone(),
+            task_id.clone(),
+            step_id.clone(),
+            outcome.last_message.clone().unwrap_or_default(),
+        );
+        if let Err(error) = service.save_task_step_result(&result) {
+            log::error!("failed to save result of step {}: {}", step_id, error);
+        }
+    }
+
+    match check_after_turn(&budget, &service, &record) {
+        Some(violation) => {
+            let notification = BudgetExceededNotification::new(
+                record.project_id.clone(),
+                task_id,
+                step_id,
+                record.task_run_id.clone(),
+                violation,
+            );
+            Err(stop_over_budget(&app, &service, notification))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Runs one turn on the configured agent; Codex CLI when none is set.
+pub(crate) async fn invoke_agent<H: CodexEventHandler>(
+    agent: Option<AgentConfig>,
     payload: ChatRequest,
+    handler: H,
+    codex_service: &CodexService,
+    openai_service: &OpenAiService,
+) -> Result<TurnOutcome, String> {
+    match agent {
+        Some(agent) if agent.is_openai_compatible() => {
+            openai_service.invoke_stream(payload, &agent, handler).await
+        }
+        _ => codex_service.invoke_stream(payload, handler).await,
+    }
+}
+
+fn render_step_prompt(
+    data_service: &DataService,
+    project: &Project,
+    task_id: &str,
+    step_id: &str,
+    task_run_id: Option<&str>,
+    source: &str,
+) -> Result<String, String> {
+    let Some(task) = project.tasks.iter().find(|task| task.id == task_id) else {
+        return Ok(source.to_string());
+    };
+    let Some(step) = task
+        .presteps
+        .iter()
+        .chain(&task.steps)
+        .chain(&task.poststeps)
+        .find(|step| step.id == step_id)
+    else {
+        return Ok(source.to_string());
+    };
+
+    let previous = match task_run_id {
+        Some(task_run_id) => data_service
+            .load_previous_step_result(task_run_id, step_id)
+            .map_err(|error| format!("failed to load previous step result: {error}"))?,
+        None => None,
+    };
+
+    Ok(TemplateService::new().render_step(source, project, task, step, previous.as_ref()))
+}
+
+fn finish_turn(data_service: &DataService, run_id: &str, result: Result<(), &str>) {
+    let (status, error) = match result {
+        Ok(()) => (ChatTurnStatus::Completed, None),
+        Err(error) => (ChatTurnStatus::Failed, Some(error)),
+    };
+
+    if let Err(error) = data_service.finish_chat_turn(run_id, status, error) {
+        log::error!("failed to finish chat turn {}: {}", run_id, error);
+    }
+    if let Err(error) = data_service.finish_prompt(run_id, status, error) {
+        log::error!("failed to record outcome of prompt {}: {}", run_id, error);
+    }
+}
+
+pub(crate) fn check_after_turn(
+    budget: &BudgetService,
+    data_service: &DataService,
+    record: &UsageRecord,
+) -> Option<BudgetViolation> {
+    budget
+        .check_after_turn(data_service, record)
+        .unwrap_or_else(|error| {
+            log::error!("failed to check budget: {}", error);
+            None
+        })
+}
+
+pub(crate) fn stop_over_budget(
+    app: &tauri::AppHandle,
+    data_service: &DataService,
+    notification: BudgetExceededNotification,
+) -> String {
+    log::info!("Stopping run: {}", notification.reason);
+
+    if let (Some(task_id), Some(step_id)) = (&notification.task_id, &notification.step_id) {
+        if let Err(error) = data_service.update_task_step_status(
+            task_id,
+            step_id,
+            &TaskStatus::Failed,
+            Some(&notification.reason),
+        ) {
+            log::error!("failed to mark step {} as failed: {}", step_id, error);
+        }
+    }
+
+    let reason = notification.reason.clone();
+    if let Err(error) = app.emit(BUDGET_EXCEEDED_EVENT, notification) {
+        log::error!("failed to emit budget notification: {}", error);
+    }
+
+    reason
+}
+
+#[tauri::command]
+pub fn list_active_sessions(session_manager: State<'_, SessionManager>) -> Vec<ActiveSession> {
+    session_manager.list_sessions()
+}
+
+#[tauri::command]
+pub fn usage_report(
+    query: UsageReportQuery,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<UsageReport, String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+
+    service
+        .load_usage_report(&query)
+        .map_err(|error| format!("failed to load usage report: {error}"))
+}
+
+#[tauri::command]
+pub async fn replay_session(
+    recording_id: String,
+    on_event: Option<Channel<RunEvent>>,
     app: tauri::AppHandle,
     codex_service: State<'_, CodexService>,
 ) -> Result<(), String> {
-    let handler = TauriCodexEventHandler::new(app);
-    codex_service.invoke_stream(payload, handler).await
+    let recording = codex_service
+        .recording_service()
+        .load_recording(&recording_id)?;
+
+    let run_id = uuid::Uuid::new_v4().to_string();
+    let handler = TauriCodexEventHandler::with_channel(app, on_event);
+    recording.replay(&run_id, handler).await.map(|_| ())
+}
+
+#[tauri::command]
+pub fn list_session_recordings(
+    codex_service: State<'_, CodexService>,
+) -> Result<Vec<SessionRecording>, String> {
+    codex_service
+        .recording_service()
+        .list_recordings()
+        .map_err(|error| format!("failed to list session recordings: {error}"))
 }
 
 #[tauri::command]
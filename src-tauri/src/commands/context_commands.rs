@@ -0,0 +1,156 @@
+use std::path::Path;
+use std::sync::Mutex;
+
+use tauri::State;
+
+use crate::models::context::{ContextBlock, ContextSet};
+use crate::models::project::Project;
+use crate::models::symbol::{CodeSymbol, SymbolContext, SymbolIndexReport};
+use crate::services::app_service::AppService;
+use crate::services::context_service::ContextService;
+use crate::services::data_service::DataService;
+use crate::services::symbol_service::SymbolService;
+
+const DEFAULT_SYMBOL_LIMIT: usize = 50;
+const DEFAULT_SYMBOL_CONTEXT_LINES: usize = 3;
+
+#[tauri::command]
+pub fn list_project_files(
+    project_id: String,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<Vec<String>, String> {
+    let project = load_project(&data_service, &project_id)?;
+
+    ContextService::new().list_files(Path::new(&project.path))
+}
+
+#[tauri::command]
+pub fn load_context_sets(
+    project_id: String,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<Vec<ContextSet>, String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+
+    service
+        .load_context_sets(&project_id)
+        .map_err(|error| format!("failed to load context sets: {error}"))
+}
+
+#[tauri::command]
+pub fn save_context_set(
+    context_set: ContextSet,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<(), String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+
+    service
+        .save_context_set(&context_set)
+        .map_err(|error| format!("failed to save context set: {error}"))
+}
+
+#[tauri::command]
+pub fn delete_context_set(
+    context_set_id: String,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<(), String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+
+    service
+        .delete_context_set(&context_set_id)
+        .map_err(|error| format!("failed to delete context set: {error}"))
+}
+
+/// Builds the block the chat would prepend for these sets, for token estimates.
+#[tauri::command]
+pub fn preview_context(
+    project_id: String,
+    context_set_ids: Vec<String>,
+    app_service: State<'_, Mutex<AppService>>,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<ContextBlock, String> {
+    let token_budget = app_service
+        .lock()
+        .map_err(|error| format!("failed to lock app service: {error}"))?
+        .get_context_token_budget();
+    let project = load_project(&data_service, &project_id)?;
+    let context_sets = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?
+        .load_context_sets(&project_id)
+        .map_err(|error| format!("failed to load context sets: {error}"))?
+        .into_iter()
+        .filter(|context_set| context_set_ids.contains(&context_set.id))
+        .collect::<Vec<_>>();
+
+    ContextService::new().build_block(Path::new(&project.path), &context_sets, token_budget)
+}
+
+#[tauri::command]
+pub fn index_symbols(
+    project_id: String,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<SymbolIndexReport, String> {
+    let project = load_project(&data_service, &project_id)?;
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+
+    SymbolService::new().refresh(&service, &project_id, Path::new(&project.path))
+}
+
+/// Searches the symbol index as it stands; the project watcher and
+/// `index_symbols` keep it up to date.
+#[tauri::command]
+pub fn search_symbols(
+    project_id: String,
+    query: String,
+    limit: Option<usize>,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<Vec<CodeSymbol>, String> {
+    data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?
+        .search_symbols(&project_id, &query, limit.unwrap_or(DEFAULT_SYMBOL_LIMIT))
+        .map_err(|error| format!("failed to search symbols: {error}"))
+}
+
+#[tauri::command]
+pub fn get_symbol_context(
+    project_id: String,
+    name: String,
+    path: Option<String>,
+    context_lines: Option<usize>,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<Vec<SymbolContext>, String> {
+    let project = load_project(&data_service, &project_id)?;
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+
+    SymbolService::new().symbol_context(
+        &service,
+        &project_id,
+        Path::new(&project.path),
+        &name,
+        path.as_deref(),
+        context_lines.unwrap_or(DEFAULT_SYMBOL_CONTEXT_LINES),
+    )
+}
+
+fn load_project(
+    data_service: &State<'_, Mutex<DataService>>,
+    project_id: &str,
+) -> Result<Project, String> {
+    data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?
+        .load_project(project_id)
+        .map_err(|error| format!("failed to load project: {error}"))?
+        .ok_or_else(|| format!("project not found: {project_id}"))
+}
@@ -1,8 +1,13 @@
 pub mod chat_commands;
 pub mod command_commands;
+pub mod context_commands;
 pub mod project_commands;
+pub mod prompt_commands;
+pub mod schedule_commands;
 pub mod settings_commands;
 pub mod system_commands;
+pub mod task_commands;
+pub mod watcher_commands;
 
 #[macro_export]
 macro_rules! known_commands {
@@ -14,6 +19,44 @@ macro_rules! known_commands {
             crate::commands::project_commands::save_project,
             crate::commands::project_commands::delete_project,
             crate::commands::project_commands::load_or_create_project_by_path,
+            crate::commands::project_commands::repair_project_folder,
+            crate::commands::project_commands::load_project_manifest,
+            crate::commands::project_commands::set_store_in_repo,
+            crate::commands::project_commands::sync_project_rules,
+            crate::commands::project_commands::resolve_rule_conflict,
+            crate::commands::project_commands::validate_prompt_template,
+            crate::commands::task_commands::import_tasks,
+            crate::commands::task_commands::plan_task,
+            crate::commands::task_commands::verify_step,
+            crate::commands::task_commands::task_graph,
+            crate::commands::task_commands::advance_task_run,
+            crate::commands::task_commands::save_task_run_step,
+            crate::commands::task_commands::load_task_run_steps,
+            crate::commands::task_commands::start_task_run,
+            crate::commands::task_commands::set_task_run_status,
+            crate::commands::task_commands::load_latest_task_run,
+            crate::commands::task_commands::resume_task_run,
+            crate::commands::task_commands::load_task_run_history,
+            crate::commands::schedule_commands::take_due_task_runs,
+            crate::commands::schedule_commands::load_task_schedules,
+            crate::commands::schedule_commands::save_task_schedule,
+            crate::commands::schedule_commands::delete_task_schedule,
+            crate::commands::schedule_commands::preview_task_schedule,
+            crate::commands::context_commands::list_project_files,
+            crate::commands::context_commands::load_context_sets,
+            crate::commands::context_commands::save_context_set,
+            crate::commands::context_commands::delete_context_set,
+            crate::commands::context_commands::preview_context,
+            crate::commands::context_commands::index_symbols,
+            crate::commands::context_commands::search_symbols,
+            crate::commands::context_commands::get_symbol_context,
+            crate::commands::watcher_commands::watch_project,
+            crate::commands::watcher_commands::unwatch_project,
+            crate::commands::watcher_commands::files_changed_since,
+            crate::commands::prompt_commands::search_prompts,
+            crate::commands::prompt_commands::star_prompt,
+            crate::commands::prompt_commands::pin_prompt,
+            crate::commands::prompt_commands::unpin_prompt,
             crate::commands::settings_commands::load_settings,
             crate::commands::settings_commands::save_settings,
             crate::commands::system_commands::open_folder,
@@ -29,6 +72,10 @@ macro_rules! known_commands {
             crate::commands::system_commands::is_codex_installed,
             crate::commands::system_commands::codex_version,
             crate::commands::chat_commands::chat,
+            crate::commands::chat_commands::replay_session,
+            crate::commands::chat_commands::list_active_sessions,
+            crate::commands::chat_commands::list_session_recordings,
+            crate::commands::chat_commands::usage_report,
             crate::commands::chat_commands::save_chat_thread,
             crate::commands::chat_commands::save_chat_message,
             crate::commands::chat_commands::load_chat_threads,
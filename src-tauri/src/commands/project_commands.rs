@@ -1,9 +1,18 @@
+use std::path::Path;
 use std::sync::Mutex;
 
 use tauri::State;
 
+use crate::models::agent_rule::{RuleConflictResolution, RuleSyncReport};
+use crate::models::manifest::{ProjectManifest, ScaffoldReport};
 use crate::models::project::Project;
+use crate::models::task_file::TaskSyncReport;
+use crate::services::app_service::AppService;
 use crate::services::data_service::DataService;
+use crate::services::project_folder_service::ProjectFolderService;
+use crate::services::rule_sync_service::RuleSyncService;
+use crate::services::task_file_service::TaskFileService;
+use crate::services::template_service::{TemplateError, TemplateService};
 
 #[tauri::command]
 pub fn load_recent_projects(
@@ -28,9 +37,14 @@ pub fn load_project(
         .lock()
         .map_err(|error| format!("failed to lock data service: {error}"))?;
 
-    service
+    let mut project = service
         .load_project(&project_id)
-        .map_err(|error| format!("failed to load project: {error}"))
+        .map_err(|error| format!("failed to load project: {error}"))?;
+    if let Some(project) = project.as_mut() {
+        import_task_files(&service, project);
+    }
+
+    Ok(project)
 }
 
 #[tauri::command]
@@ -42,9 +56,38 @@ pub fn save_project(
         .lock()
         .map_err(|error| format!("failed to lock data service: {error}"))?;
 
+    let previous = service
+        .load_project(&project.id)
+        .map_err(|error| format!("failed to save project: {error}"))?;
+    TemplateService::new()
+        .validate_project(&project, previous.as_ref())
+        .map_err(|error| format!("failed to save project: {error}"))?;
     service
         .upsert_project(&project)
-        .map_err(|error| format!("failed to save project: {error}"))
+        .map_err(|error| format!("failed to save project: {error}"))?;
+
+    if !TaskFileService::is_enabled(Path::new(&project.path)) {
+        return Ok(());
+    }
+    let removed_ids = previous
+        .map(|previous| previous.tasks)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|task| !project.tasks.iter().any(|current| current.id == task.id))
+        .map(|task| task.id)
+        .collect::<Vec<_>>();
+    TaskFileService::new()
+        .export(&project, &removed_ids)
+        .map(|_| ())
+        .map_err(|error| format!("failed to write task files: {error}"))
+}
+
+#[tauri::command]
+pub fn validate_prompt_template(
+    template: String,
+    parameters: Vec<String>,
+) -> Result<(), TemplateError> {
+    TemplateService::new().validate(&template, &parameters)
 }
 
 #[tauri::command]
@@ -61,16 +104,167 @@ pub fn delete_project(
         .map_err(|error| format!("failed to delete project: {error}"))
 }
 
+/// Loads the project for a folder, creating it on first open; creates a
+/// missing `.vibeflow/` too when the project setting asks for it. Moving
+/// legacy files is left to `repair_project_folder`.
 #[tauri::command]
 pub fn load_or_create_project_by_path(
     project_path: String,
+    app_service: State<'_, Mutex<AppService>>,
     data_service: State<'_, Mutex<DataService>>,
 ) -> Result<Project, String> {
+    let generate_folder = app_service
+        .lock()
+        .map_err(|error| format!("failed to lock app service: {error}"))?
+        .should_generate_vibeflow_folder();
     let service = data_service
         .lock()
         .map_err(|error| format!("failed to lock data service: {error}"))?;
 
-    service
+    let mut project = service
         .load_or_create_project_by_path(&project_path)
-        .map_err(|error| format!("failed to load or create project: {error}"))
+        .map_err(|error| format!("failed to load or create project: {error}"))?;
+    if generate_folder {
+        if let Err(error) = ProjectFolderService::new().scaffold(&project) {
+            log::error!("failed to scaffold {}: {}", project_path, error);
+        }
+    }
+    import_task_files(&service, &mut project);
+
+    Ok(project)
+}
+
+#[tauri::command]
+pub fn load_project_manifest(
+    project_id: String,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<Option<ProjectManifest>, String> {
+    let project = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?
+        .load_project(&project_id)
+        .map_err(|error| format!("failed to load project: {error}"))?
+        .ok_or_else(|| format!("project not found: {project_id}"))?;
+
+    ProjectFolderService::read_manifest(Path::new(&project.path))
+}
+
+/// Turns keeping the project's tasks in `.vibeflow/tasks/` on or off and
+/// returns the project with any tasks merged from existing files.
+#[tauri::command]
+pub fn set_store_in_repo(
+    project_id: String,
+    enabled: bool,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<Project, String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+
+    let mut project = service
+        .load_project(&project_id)
+        .map_err(|error| format!("failed to load project: {error}"))?
+        .ok_or_else(|| format!("project not found: {project_id}"))?;
+
+    // Scaffolding on the way may have moved rule files too.
+    TaskFileService::new().set_enabled(&mut project, enabled)?;
+    service
+        .upsert_project(&project)
+        .map_err(|error| format!("failed to save imported tasks: {error}"))?;
+
+    Ok(project)
+}
+
+/// Upgrades the project's `.vibeflow/` folder to the current layout,
+/// creating it if needed.
+#[tauri::command]
+pub fn repair_project_folder(
+    project_id: String,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<ScaffoldReport, String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+
+    let mut project = service
+        .load_project(&project_id)
+        .map_err(|error| format!("failed to load project: {error}"))?
+        .ok_or_else(|| format!("project not found: {project_id}"))?;
+
+    let report = ProjectFolderService::new().repair(&mut project)?;
+    service
+        .upsert_project(&project)
+        .map_err(|error| format!("failed to save project: {error}"))?;
+
+    Ok(report)
+}
+
+#[tauri::command]
+pub fn sync_project_rules(
+    project_id: String,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<RuleSyncReport, String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+
+    let mut project = service
+        .load_project(&project_id)
+        .map_err(|error| format!("failed to load project: {error}"))?
+        .ok_or_else(|| format!("project not found: {project_id}"))?;
+
+    let report = RuleSyncService::new().sync(&mut project)?;
+    service
+        .upsert_project(&project)
+        .map_err(|error| format!("failed to save synced rules: {error}"))?;
+
+    Ok(report)
+}
+
+#[tauri::command]
+pub fn resolve_rule_conflict(
+    project_id: String,
+    rule_id: String,
+    resolution: RuleConflictResolution,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<Project, String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+
+    let mut project = service
+        .load_project(&project_id)
+        .map_err(|error| format!("failed to load project: {error}"))?
+        .ok_or_else(|| format!("project not found: {project_id}"))?;
+
+    RuleSyncService::new().resolve_conflict(&mut project, &rule_id, resolution)?;
+    service
+        .upsert_project(&project)
+        .map_err(|error| format!("failed to save resolved rule: {error}"))?;
+
+    Ok(project)
+}
+
+/// Re-reads `.vibeflow/tasks/` into `project` when the project stores its
+/// tasks there, saving the result; failures are logged and leave the cached
+/// tasks in place.
+pub(crate) fn import_task_files(service: &DataService, project: &mut Project) -> TaskSyncReport {
+    if !TaskFileService::is_enabled(Path::new(&project.path)) {
+        return TaskSyncReport::default();
+    }
+
+    let report = match TaskFileService::new().import(project) {
+        Ok(report) => report,
+        Err(error) => {
+            log::error!("failed to import task files: {}", error);
+            return TaskSyncReport::default();
+        }
+    };
+    if report.changed_project() {
+        if let Err(error) = service.upsert_project(project) {
+            log::error!("failed to save imported tasks: {}", error);
+        }
+    }
+
+    report
 }
@@ -0,0 +1,85 @@
+use std::sync::Mutex;
+
+use tauri::State;
+
+use crate::models::prompt::{PromptQuery, PromptRecord};
+use crate::services::data_service::DataService;
+
+#[tauri::command]
+pub fn search_prompts(
+    query: PromptQuery,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<Vec<PromptRecord>, String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+
+    service
+        .search_prompts(&query)
+        .map_err(|error| format!("failed to search prompts: {error}"))
+}
+
+#[tauri::command]
+pub fn star_prompt(
+    prompt_id: String,
+    starred: bool,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<PromptRecord, String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+
+    service
+        .set_prompt_starred(&prompt_id, starred)
+        .map_err(|error| format!("failed to star prompt: {error}"))?;
+    load_prompt(&service, &prompt_id)
+}
+
+#[tauri::command]
+pub fn pin_prompt(
+    prompt_id: String,
+    name: String,
+    tags: Vec<String>,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<PromptRecord, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("failed to pin prompt: a library name is required".to_string());
+    }
+
+    let tags = tags
+        .iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect::<Vec<_>>();
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+
+    service
+        .set_prompt_pinned(&prompt_id, Some(name), &tags)
+        .map_err(|error| format!("failed to pin prompt: {error}"))?;
+    load_prompt(&service, &prompt_id)
+}
+
+#[tauri::command]
+pub fn unpin_prompt(
+    prompt_id: String,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<PromptRecord, String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+
+    service
+        .set_prompt_pinned(&prompt_id, None, &[])
+        .map_err(|error| format!("failed to unpin prompt: {error}"))?;
+    load_prompt(&service, &prompt_id)
+}
+
+fn load_prompt(data_service: &DataService, prompt_id: &str) -> Result<PromptRecord, String> {
+    data_service
+        .load_prompt(prompt_id)
+        .map_err(|error| format!("failed to load prompt: {error}"))?
+        .ok_or_else(|| format!("prompt not found: {prompt_id}"))
+}
@@ -0,0 +1,111 @@
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::models::schedule::{ScheduledRunEvent, TaskSchedule};
+use crate::services::data_service::DataService;
+use crate::services::scheduler_service::{CronTimeZone, SchedulerService, TASK_RUN_DUE_EVENT};
+use crate::utils::current_timestamp_millis;
+
+const PREVIEW_RUN_COUNT: usize = 5;
+
+/// The due runs queued since the last call, for the UI to start. It calls
+/// this on load and whenever `task-schedule:due` fires.
+#[tauri::command]
+pub fn take_due_task_runs(
+    scheduler_service: State<'_, SchedulerService>,
+) -> Vec<ScheduledRunEvent> {
+    scheduler_service.take_queued()
+}
+
+#[tauri::command]
+pub fn load_task_schedules(
+    project_id: String,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<Vec<TaskSchedule>, String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+    service
+        .load_task_schedules(Some(&project_id))
+        .map_err(|error| format!("failed to load task schedules: {error}"))
+}
+
+/// Saves the schedule with its next run counted from now. The scheduler
+/// owns the last run, so the stored one is kept.
+#[tauri::command]
+pub fn save_task_schedule(
+    mut schedule: TaskSchedule,
+    data_service: State<'_, Mutex<DataService>>,
+    scheduler_service: State<'_, SchedulerService>,
+) -> Result<TaskSchedule, String> {
+    scheduler_service.reschedule(&mut schedule, current_timestamp_millis())?;
+
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+    if let Some(stored) = service
+        .load_task_schedule(&schedule.id)
+        .map_err(|error| format!("failed to load task schedule: {error}"))?
+    {
+        schedule.created_at = stored.created_at;
+        schedule.last_run_at = stored.last_run_at;
+        schedule.last_run_id = stored.last_run_id;
+    }
+    service
+        .save_task_schedule(&schedule)
+        .map_err(|error| format!("failed to save task schedule: {error}"))?;
+    Ok(schedule)
+}
+
+#[tauri::command]
+pub fn delete_task_schedule(
+    schedule_id: String,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<(), String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+    service
+        .delete_task_schedule(&schedule_id)
+        .map_err(|error| format!("failed to delete task schedule: {error}"))
+}
+
+/// The next few times `cron` matches, to show while editing a schedule.
+#[tauri::command]
+pub fn preview_task_schedule(
+    cron: String,
+    time_zone: Option<String>,
+    utc_offset_minutes: i32,
+    scheduler_service: State<'_, SchedulerService>,
+) -> Result<Vec<i64>, String> {
+    let zone = CronTimeZone::new(time_zone.as_deref(), utc_offset_minutes)?;
+    scheduler_service.preview(&cron, &zone, current_timestamp_millis(), PREVIEW_RUN_COUNT)
+}
+
+/// Queues the runs that came due and tells the UI to take them. The app
+/// runs this from startup, whether or not the UI has loaded yet.
+pub(crate) fn queue_due_runs(app: &AppHandle) {
+    let data_service = app.state::<Mutex<DataService>>();
+    let scheduler_service = app.state::<SchedulerService>();
+    let events = {
+        let Ok(service) = data_service.lock() else {
+            return;
+        };
+        match scheduler_service.due_runs(&service, current_timestamp_millis()) {
+            Ok(events) => events,
+            Err(error) => {
+                log::error!("failed to check task schedules: {}", error);
+                return;
+            }
+        }
+    };
+
+    if events.is_empty() {
+        return;
+    }
+    scheduler_service.queue(events);
+    if let Err(error) = app.emit(TASK_RUN_DUE_EVENT, ()) {
+        log::error!("failed to emit scheduled task runs: {}", error);
+    }
+}
@@ -2,8 +2,11 @@ use std::sync::Mutex;
 
 use tauri::State;
 
-use crate::models::setting::SettingModel;
-use crate::services::app_service::AppService;
+use crate::models::setting::{SettingModel, SettingValue};
+use crate::services::app_service::{AppService, PROMPT_TEMPLATE_KEY};
+use crate::services::codex_service::CodexService;
+use crate::services::session_manager::SessionManager;
+use crate::services::template_service::PromptTemplate;
 
 #[tauri::command]
 pub fn load_settings(
@@ -20,12 +23,28 @@ pub fn load_settings(
 pub fn save_settings(
     settings: Vec<SettingModel>,
     app_service: State<'_, Mutex<AppService>>,
+    codex_service: State<'_, CodexService>,
+    session_manager: State<'_, SessionManager>,
 ) -> Result<(), String> {
+    for setting in settings
+        .iter()
+        .filter(|setting| setting.key == PROMPT_TEMPLATE_KEY)
+    {
+        if let SettingValue::String(template) = &setting.value {
+            PromptTemplate::parse(template)
+                .map_err(|error| format!("failed to save settings: prompt template {error}"))?;
+        }
+    }
+
     let mut service = app_service
         .lock()
         .map_err(|error| format!("failed to lock app service: {error}"))?;
 
     service
         .set_settings(settings)
-        .map_err(|error| format!("failed to save settings: {error}"))
+        .map_err(|error| format!("failed to save settings: {error}"))?;
+
+    codex_service.set_recording_enabled(service.is_session_recording_enabled());
+    session_manager.set_max_concurrent(service.get_max_concurrent_sessions());
+    Ok(())
 }
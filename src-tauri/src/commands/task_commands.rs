@@ -0,0 +1,522 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::ipc::Channel;
+use tauri::State;
+
+use crate::commands::chat_commands::{check_after_turn, invoke_agent, stop_over_budget};
+use crate::models::budget::BudgetExceededNotification;
+use crate::models::chat::{ChatRequest, ChatTurnStatus};
+use crate::models::event_handler::{RunEvent, TauriCodexEventHandler};
+use crate::models::prompt::PromptRecord;
+use crate::models::setting::AgentConfig;
+use crate::models::task::{Task, TaskRun, TaskRunStep, TaskSandbox, TaskStatus};
+use crate::models::task_graph::{TaskGraph, TaskGraphAdvance, TaskRunResume};
+use crate::models::task_import::TaskImport;
+use crate::models::usage::{TokenUsage, UsageRecord};
+use crate::models::verification::StepVerification;
+use crate::services::app_service::AppService;
+use crate::services::budget_service::{BudgetEventHandler, BudgetService};
+use crate::services::chat_persistence::PersistingEventHandler;
+use crate::services::codex_service::CodexService;
+use crate::services::command_service::CommandService;
+use crate::services::data_service::DataService;
+use crate::services::openai_service::OpenAiService;
+use crate::services::session_manager::SessionManager;
+use crate::services::task_file_service::TaskFileService;
+use crate::services::task_graph_service::TaskGraphService;
+use crate::services::task_import_service::TaskImportService;
+use crate::services::task_planner_service::TaskPlannerService;
+use crate::services::watcher_service::WatcherService;
+use crate::utils::current_timestamp_millis;
+
+/// Imports Markdown task files into the project, or only previews what the
+/// import would change when `dry_run` is set. Without `paths`, every task
+/// file in `.vibeflow/tasks/` and the legacy `.codex/` folder is read.
+#[tauri::command]
+pub fn import_tasks(
+    project_id: String,
+    paths: Option<Vec<String>>,
+    dry_run: bool,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<Vec<TaskImport>, String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+
+    let mut project = service
+        .load_project(&project_id)
+        .map_err(|error| format!("failed to load project: {error}"))?
+        .ok_or_else(|| format!("project not found: {project_id}"))?;
+    let import_service = TaskImportService::new();
+    let paths = match paths.filter(|paths| !paths.is_empty()) {
+        Some(paths) => paths,
+        None => import_service.discover(Path::new(&project.path))?,
+    };
+
+    let imports = import_service.preview(&project, &paths)?;
+    if dry_run {
+        return Ok(imports);
+    }
+
+    import_service.apply(&mut project, &imports);
+    service
+        .upsert_project(&project)
+        .map_err(|error| format!("failed to save imported tasks: {error}"))?;
+    if TaskFileService::is_enabled(Path::new(&project.path)) {
+        TaskFileService::new()
+            .export(&project, &[])
+            .map_err(|error| format!("failed to write task files: {error}"))?;
+    }
+
+    Ok(imports)
+}
+
+/// Asks the agent to break `goal` into steps and returns the plan as a
+/// draft task for the user to edit; the project is left unchanged. The agent
+/// runs read-only in the project folder.
+#[tauri::command]
+pub async fn plan_task(
+    project_id: String,
+    goal: String,
+    agent_id: Option<String>,
+    on_event: Option<Channel<RunEvent>>,
+    app: tauri::AppHandle,
+    app_service: State<'_, Mutex<AppService>>,
+    data_service: State<'_, Mutex<DataService>>,
+    codex_service: State<'_, CodexService>,
+    openai_service: State<'_, OpenAiService>,
+    session_manager: State<'_, SessionManager>,
+) -> Result<Task, String> {
+    let (agent, pricing, budget) = {
+        let service = app_service
+            .lock()
+            .map_err(|error| format!("failed to lock app service: {error}"))?;
+        (
+            service.get_agent_config(agent_id.as_deref()),
+            service.get_model_pricing(),
+            BudgetService::new(service.get_budget_limits()),
+        )
+    };
+    let project = {
+        let service = data_service
+            .lock()
+            .map_err(|error| format!("failed to lock data service: {error}"))?;
+        let violation = budget
+            .check_before_turn(&service, Some(project_id.as_str()), None)
+            .map_err(|error| format!("failed to check budget: {error}"))?;
+        if let Some(violation) = violation {
+            let notification =
+                BudgetExceededNotification::new(Some(project_id), None, None, None, violation);
+            return Err(stop_over_budget(&app, &service, notification));
+        }
+        service
+            .load_project(&project_id)
+            .map_err(|error| format!("failed to load project: {error}"))?
+            .ok_or_else(|| format!("project not found: {project_id}"))?
+    };
+
+    let planner = TaskPlannerService::new();
+    let mut payload = ChatRequest {
+        content: planner.build_prompt(&goal, &project)?,
+        sandbox: Some(TaskSandbox::ReadOnly),
+        working_directory: Some(project.path.clone()),
+        agent_id: agent_id.clone(),
+        project_id: Some(project_id.clone()),
+        output_schema: Some(TaskPlannerService::output_schema()),
+        ..ChatRequest::default()
+    };
+    let run_id = payload.ensure_run_id();
+    let model = agent
+        .as_ref()
+        .map(|agent| agent.model.clone())
+        .unwrap_or_default();
+    let agent_name = agent
+        .as_ref()
+        .map(|agent| agent.name.clone())
+        .unwrap_or_else(|| AgentConfig::CODEX_CLI.to_string());
+
+    {
+        let service = data_service
+            .lock()
+            .map_err(|error| format!("failed to lock data service: {error}"))?;
+        let record = PromptRecord::for_run(
+            run_id.clone(),
+            Some(project_id.clone()),
+            None,
+            None,
+            payload.content.clone(),
+        );
+        if let Err(error) = service.save_prompt(&record) {
+            log::error!("failed to record prompt of run {}: {}", run_id, error);
+        }
+    }
+
+    let (result, usage) = {
+        let _session = session_manager
+            .acquire(&run_id, Some(project_id.clone()), None, agent_name)
+            .await?;
+        let turn = UsageRecord::for_turn(
+            Some(project_id.clone()),
+            None,
+            None,
+            model.clone(),
+            TokenUsage::default(),
+            &pricing,
+        );
+        // Planning has no chat thread, so this only keeps the usage of failed turns.
+        let handler = PersistingEventHandler::new(
+            data_service.inner(),
+            None,
+            model.clone(),
+            BudgetEventHandler::new(
+                &budget,
+                data_service.inner(),
+                session_manager.inner(),
+                &pricing,
+                turn,
+                session_manager
+                    .handler(TauriCodexEventHandler::with_channel(app.clone(), on_event)),
+            ),
+        );
+        let result = invoke_agent(agent, payload, &handler, &codex_service, &openai_service).await;
+        (result, handler.usage())
+    };
+
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+    let (status, error) = match &result {
+        Ok(_) => (ChatTurnStatus::Completed, None),
+        Err(error) => (ChatTurnStatus::Failed, Some(error.as_str())),
+    };
+    if let Err(error) = service.finish_prompt(&run_id, status, error) {
+        log::error!("failed to record outcome of prompt {}: {}", run_id, error);
+    }
+
+    // Failed and cancelled planning turns still cost what the agent reported.
+    let (thread_id, usage) = match &result {
+        Ok(outcome) => (outcome.thread_id.clone(), outcome.usage),
+        Err(_) => (None, usage),
+    };
+    let record = UsageRecord::for_turn(
+        Some(project_id.clone()),
+        thread_id,
+        None,
+        model,
+        usage,
+        &pricing,
+    );
+    if result.is_ok() || usage.total_tokens() > 0 {
+        if let Err(error) = service.save_usage_record(&record) {
+            log::error!("failed to save usage record: {}", error);
+        }
+    }
+    // The plan is kept even when it crossed the budget; the check before the
+    // next turn stops further spending. A turn cancelled over budget fails
+    // with the violation as its reason.
+    if let Some(violation) = check_after_turn(&budget, &service, &record) {
+        let notification =
+            BudgetExceededNotification::new(Some(project_id.clone()), None, None, None, violation);
+        let reason = stop_over_budget(&app, &service, notification);
+        if result.is_err() {
+            return Err(reason);
+        }
+    }
+    let outcome = result.map_err(|error| format!("failed to plan task: {error}"))?;
+
+    let output = outcome
+        .last_message
+        .ok_or_else(|| "agent returned no plan".to_string())?;
+    planner.parse(&project_id, &goal, &output)
+}
+
+/// Runs the verification commands the step declares in the project folder
+/// after its `attempt`-th run. The result carries the prompt for the next
+/// attempt while the configured number of attempts is not used up.
+#[tauri::command]
+pub async fn verify_step(
+    project_id: String,
+    task_id: String,
+    step_id: String,
+    attempt: u32,
+    app_service: State<'_, Mutex<AppService>>,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<StepVerification, String> {
+    let (max_attempts, timeout) = {
+        let service = app_service
+            .lock()
+            .map_err(|error| format!("failed to lock app service: {error}"))?;
+        (
+            service.get_verify_max_attempts(),
+            Duration::from_secs(service.get_verify_timeout_seconds()),
+        )
+    };
+    let (commands, cwd) = {
+        let service = data_service
+            .lock()
+            .map_err(|error| format!("failed to lock data service: {error}"))?;
+        let project = service
+            .load_project(&project_id)
+            .map_err(|error| format!("failed to load project: {error}"))?
+            .ok_or_else(|| format!("project not found: {project_id}"))?;
+        let task = project
+            .tasks
+            .iter()
+            .find(|task| task.id == task_id)
+            .ok_or_else(|| format!("task not found: {task_id}"))?;
+        let step = task
+            .presteps
+            .iter()
+            .chain(&task.steps)
+            .chain(&task.poststeps)
+            .find(|step| step.id == step_id)
+            .ok_or_else(|| format!("step not found: {step_id}"))?;
+        (step.verify.clone(), project.path)
+    };
+
+    let checks = tauri::async_runtime::spawn_blocking(move || {
+        CommandService::new().verify(&commands, &cwd, timeout)
+    })
+    .await
+    .map_err(|error| format!("failed to run verification: {error}"))?;
+
+    Ok(StepVerification::new(checks, attempt, max_attempts))
+}
+
+/// The task's main steps ordered by their dependencies; fails on unknown
+/// dependencies and cycles.
+#[tauri::command]
+pub fn task_graph(task: Task) -> Result<TaskGraph, String> {
+    TaskGraphService::new().build(&task)
+}
+
+/// The steps of a task run to start next given the statuses of the steps so
+/// far. Steps whose condition no longer holds are recorded as skipped.
+/// `ifChanged` globs are matched against the files changed since `since`
+/// while the project is watched, and hold otherwise.
+#[tauri::command]
+pub fn advance_task_run(
+    project_id: String,
+    task: Task,
+    task_run_id: String,
+    statuses: HashMap<String, TaskStatus>,
+    since: i64,
+    data_service: State<'_, Mutex<DataService>>,
+    watcher_service: State<'_, WatcherService>,
+) -> Result<TaskGraphAdvance, String> {
+    let service = TaskGraphService::new();
+    let graph = service.build(&task)?;
+    let watched = watcher_service.watched_project().as_deref() == Some(project_id.as_str());
+    let changed = watched.then(|| {
+        watcher_service
+            .changes_since(&project_id, since)
+            .into_iter()
+            .map(|change| change.path)
+            .collect::<Vec<_>>()
+    });
+    let advance = service.advance(&graph, &statuses, changed.as_deref())?;
+
+    if !advance.skipped.is_empty() {
+        let data = data_service
+            .lock()
+            .map_err(|error| format!("failed to lock data service: {error}"))?;
+        for step_id in &advance.skipped {
+            let step = TaskRunStep::new(
+                task_run_id.clone(),
+                task.id.clone(),
+                step_id.clone(),
+                TaskStatus::Skipped,
+                Some("condition not met".to_string()),
+            );
+            data.save_task_run_step(&step)
+                .map_err(|error| format!("failed to save task run step: {error}"))?;
+        }
+    }
+
+    Ok(advance)
+}
+
+#[tauri::command]
+pub fn save_task_run_step(
+    step: TaskRunStep,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<(), String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+    service
+        .save_task_run_step(&step)
+        .map_err(|error| format!("failed to save task run step: {error}"))
+}
+
+#[tauri::command]
+pub fn load_task_run_steps(
+    task_run_id: String,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<Vec<TaskRunStep>, String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+    service
+        .load_task_run_steps(&task_run_id)
+        .map_err(|error| format!("failed to load task run steps: {error}"))
+}
+
+/// Records the start of a task run whose turns go to `chat_thread_id`. A
+/// run the scheduler queued keeps its schedule.
+#[tauri::command]
+pub fn start_task_run(
+    project_id: String,
+    task_id: String,
+    task_run_id: String,
+    chat_thread_id: Option<String>,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<TaskRun, String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+    let schedule_id = service
+        .load_task_run(&task_run_id)
+        .map_err(|error| format!("failed to load task run: {error}"))?
+        .and_then(|run| run.schedule_id);
+
+    let now = current_timestamp_millis();
+    let run = TaskRun {
+        id: task_run_id,
+        project_id,
+        task_id,
+        status: TaskStatus::InProgress,
+        status_reason: None,
+        chat_thread_id,
+        schedule_id,
+        started_at: now,
+        updated_at: now,
+    };
+    service
+        .save_task_run(&run)
+        .map_err(|error| format!("failed to save task run: {error}"))?;
+    Ok(run)
+}
+
+#[tauri::command]
+pub fn set_task_run_status(
+    task_run_id: String,
+    status: TaskStatus,
+    status_reason: Option<String>,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<TaskRun, String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+    let mut run = service
+        .load_task_run(&task_run_id)
+        .map_err(|error| format!("failed to load task run: {error}"))?
+        .ok_or_else(|| format!("task run not found: {task_run_id}"))?;
+
+    run.status = status;
+    run.status_reason = status_reason;
+    run.updated_at = current_timestamp_millis();
+    service
+        .save_task_run(&run)
+        .map_err(|error| format!("failed to save task run: {error}"))?;
+    Ok(run)
+}
+
+#[tauri::command]
+pub fn load_latest_task_run(
+    task_id: String,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<Option<TaskRun>, String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+    service
+        .load_latest_task_run(&task_id)
+        .map_err(|error| format!("failed to load task run: {error}"))
+}
+
+/// The task's last `limit` runs, newest first, whether started by hand or by
+/// a schedule.
+#[tauri::command]
+pub fn load_task_run_history(
+    task_id: String,
+    limit: u32,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<Vec<TaskRun>, String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+    service
+        .load_task_run_history(&task_id, limit)
+        .map_err(|error| format!("failed to load task run history: {error}"))
+}
+
+/// Picks a task run up again from `from_step_id`, or without it from its
+/// failed and unfinished steps. The steps to run again are reset to pending;
+/// the returned statuses are those of the steps the run keeps.
+#[tauri::command]
+pub fn resume_task_run(
+    task: Task,
+    task_run_id: String,
+    from_step_id: Option<String>,
+    data_service: State<'_, Mutex<DataService>>,
+) -> Result<TaskRunResume, String> {
+    let service = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?;
+    let mut run = service
+        .load_task_run(&task_run_id)
+        .map_err(|error| format!("failed to load task run: {error}"))?
+        .filter(|run| run.task_id == task.id)
+        .ok_or_else(|| format!("task run not found: {task_run_id}"))?;
+    let checkpoints = service
+        .load_task_run_steps(&task_run_id)
+        .map_err(|error| format!("failed to load task run steps: {error}"))?;
+
+    let graph_service = TaskGraphService::new();
+    let graph = graph_service.build(&task)?;
+    let previous: HashMap<String, TaskStatus> = checkpoints
+        .into_iter()
+        .map(|step| (step.step_id, step.status))
+        .collect();
+    let statuses = graph_service.restart(&graph, &previous, from_step_id.as_deref())?;
+
+    for (step_id, status) in &previous {
+        if *status == TaskStatus::Pending || statuses.contains_key(step_id) {
+            continue;
+        }
+        let step = TaskRunStep::new(
+            task_run_id.clone(),
+            task.id.clone(),
+            step_id.clone(),
+            TaskStatus::Pending,
+            None,
+        );
+        service
+            .save_task_run_step(&step)
+            .map_err(|error| format!("failed to save task run step: {error}"))?;
+    }
+
+    run.status = TaskStatus::InProgress;
+    run.status_reason = None;
+    run.updated_at = current_timestamp_millis();
+    service
+        .save_task_run(&run)
+        .map_err(|error| format!("failed to save task run: {error}"))?;
+
+    let chat_thread = match &run.chat_thread_id {
+        Some(chat_thread_id) => service
+            .load_chat_thread(chat_thread_id)
+            .map_err(|error| format!("failed to load chat thread: {error}"))?,
+        None => None,
+    };
+
+    Ok(TaskRunResume {
+        run,
+        statuses,
+        chat_thread,
+    })
+}
@@ -0,0 +1,90 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use tauri::{Emitter, Manager, State};
+
+use crate::commands::project_commands::import_task_files;
+use crate::models::file_change::{FileChange, FilesChangedEvent};
+use crate::services::data_service::DataService;
+use crate::services::symbol_service::SymbolService;
+use crate::services::task_file_service::{TaskFileService, TASKS_SYNCED_EVENT};
+use crate::services::watcher_service::{WatcherService, FILES_CHANGED_EVENT};
+
+/// Watches the project's directory, replacing the previous watch; each
+/// batch of changes updates the symbol index and the tasks stored in
+/// `.vibeflow/tasks/`, and is emitted to the UI.
+#[tauri::command]
+pub fn watch_project(
+    project_id: String,
+    app: tauri::AppHandle,
+    data_service: State<'_, Mutex<DataService>>,
+    watcher_service: State<'_, WatcherService>,
+) -> Result<(), String> {
+    let project = data_service
+        .lock()
+        .map_err(|error| format!("failed to lock data service: {error}"))?
+        .load_project(&project_id)
+        .map_err(|error| format!("failed to load project: {error}"))?
+        .ok_or_else(|| format!("project not found: {project_id}"))?;
+    let root = PathBuf::from(&project.path);
+
+    watcher_service.watch(&project_id, Path::new(&project.path), move |event| {
+        refresh_symbols(&app, &root, event);
+        sync_task_files(&app, event);
+        if let Err(error) = app.emit(FILES_CHANGED_EVENT, event) {
+            log::error!("failed to emit file changes: {}", error);
+        }
+    })
+}
+
+#[tauri::command]
+pub fn unwatch_project(watcher_service: State<'_, WatcherService>) {
+    watcher_service.unwatch();
+}
+
+/// Files changed after `since` (epoch millis) while the project was watched.
+#[tauri::command]
+pub fn files_changed_since(
+    project_id: String,
+    since: i64,
+    watcher_service: State<'_, WatcherService>,
+) -> Vec<FileChange> {
+    watcher_service.changes_since(&project_id, since)
+}
+
+fn refresh_symbols(app: &tauri::AppHandle, root: &Path, event: &FilesChangedEvent) {
+    let data_service = app.state::<Mutex<DataService>>();
+    let Ok(service) = data_service.lock() else {
+        return;
+    };
+
+    if let Err(error) =
+        SymbolService::new().refresh_paths(&service, &event.project_id, root, &event.paths())
+    {
+        log::error!("failed to update symbol index: {}", error);
+    }
+}
+
+fn sync_task_files(app: &tauri::AppHandle, event: &FilesChangedEvent) {
+    if !event
+        .paths()
+        .iter()
+        .any(|path| TaskFileService::is_task_file(path))
+    {
+        return;
+    }
+
+    let data_service = app.state::<Mutex<DataService>>();
+    let Ok(service) = data_service.lock() else {
+        return;
+    };
+    let Ok(Some(mut project)) = service.load_project(&event.project_id) else {
+        return;
+    };
+
+    if import_task_files(&service, &mut project).changed_project() {
+        if let Err(error) = app.emit(TASKS_SYNCED_EVENT, &event.project_id) {
+            log::error!("failed to emit task changes: {}", error);
+        }
+    }
+}
@@ -2,3 +2,4 @@ pub mod app;
 pub mod commands;
 pub mod models;
 pub mod services;
+pub mod utils;
@@ -6,6 +6,73 @@ pub struct AgentRule {
     pub id: String,
     pub name: String,
     pub description: Option<String>,
+    /// Markdown body handed to the agent.
+    #[serde(default)]
+    pub content: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Rules are assembled in ascending priority.
+    #[serde(default)]
+    pub priority: i64,
+    /// File globs relative to the project root; empty applies everywhere.
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// File the rule is synced with, relative to the project root.
+    #[serde(default)]
+    pub source_path: Option<String>,
+    /// Content hash at the last sync, used to tell which side changed.
+    #[serde(default)]
+    pub synced_hash: Option<String>,
     pub created_at: i64,
     pub updated_at: i64,
 }
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Where a prompt is going to run, used to pick the rules that apply.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleContext {
+    /// Paths relative to the project root the turn works on: its working
+    /// directory below the root, its context files and its step's folders.
+    pub paths: Vec<String>,
+    /// When non-empty, tagged rules only apply if they share one of these tags.
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleConflictKind {
+    /// Both the rule and its file changed since the last sync.
+    BothChanged,
+    DeletedOnDisk,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleConflict {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub path: String,
+    pub kind: RuleConflictKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleConflictResolution {
+    KeepApp,
+    KeepDisk,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleSyncReport {
+    pub imported: Vec<String>,
+    pub exported: Vec<String>,
+    pub updated: Vec<String>,
+    pub conflicts: Vec<RuleConflict>,
+    pub rules: Vec<AgentRule>,
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetLimits {
+    pub max_tokens_per_turn: Option<u64>,
+    pub max_cost_per_turn: Option<f64>,
+    pub max_tokens_per_task_run: Option<u64>,
+    pub max_cost_per_task_run: Option<f64>,
+    pub max_tokens_per_project_per_day: Option<u64>,
+    pub max_cost_per_project_per_day: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetScope {
+    Turn,
+    TaskRun,
+    ProjectDay,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetUnit {
+    Tokens,
+    Cost,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetViolation {
+    pub scope: BudgetScope,
+    pub unit: BudgetUnit,
+    pub limit: f64,
+    pub actual: f64,
+}
+
+impl BudgetViolation {
+    pub fn reason(&self) -> String {
+        let scope = match self.scope {
+            BudgetScope::Turn => "turn",
+            BudgetScope::TaskRun => "task run",
+            BudgetScope::ProjectDay => "daily project",
+        };
+
+        match self.unit {
+            BudgetUnit::Tokens => format!(
+                "{scope} token budget exceeded: {} of {} tokens used",
+                self.actual as u64, self.limit as u64
+            ),
+            BudgetUnit::Cost => format!(
+                "{scope} cost budget exceeded: ${:.4} of ${:.4} spent",
+                self.actual, self.limit
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetExceededNotification {
+    pub project_id: Option<String>,
+    pub task_id: Option<String>,
+    pub step_id: Option<String>,
+    pub task_run_id: Option<String>,
+    pub violation: BudgetViolation,
+    pub reason: String,
+}
+
+impl BudgetExceededNotification {
+    pub fn new(
+        project_id: Option<String>,
+        task_id: Option<String>,
+        step_id: Option<String>,
+        task_run_id: Option<String>,
+        violation: BudgetViolation,
+    ) -> Self {
+        Self {
+            reason: violation.reason(),
+            project_id,
+            task_id,
+            step_id,
+            task_run_id,
+            violation,
+        }
+    }
+}
@@ -1,51 +1,152 @@
-use codex_sdk::ThreadItem;
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ChatMessage {
-    pub id: String,
-    pub thread_id: String,
-    pub role: String,
-    pub content: String,
-    pub model: String,
-    pub created_at: i64,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ChatThread {
-    pub id: String,
-    pub project_id: String,
-    pub title: String,
-    pub created_at: i64,
-    pub updated_at: i64,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ChatRequest {
-    pub content: String,
-    pub thread_id: Option<String>,
+use codex_sdk::ThreadItem;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::task::TaskSandbox;
+use super::usage::TokenUsage;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatMessage {
+    pub id: String,
+    pub thread_id: String,
+    pub role: String,
+    pub content: String,
+    pub model: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatTurnStatus {
+    Running,
+    Completed,
+    Failed,
+    Interrupted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatTurn {
+    pub run_id: String,
+    pub thread_id: String,
+    pub status: ChatTurnStatus,
+    pub error: Option<String>,
+    pub started_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatThread {
+    pub id: String,
+    pub project_id: String,
+    pub title: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    #[serde(default)]
+    pub agent_thread_id: Option<String>,
+    #[serde(default)]
+    pub agent_id: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+impl ChatThread {
+    pub fn resumable_thread_id(&self, agent_id: Option<&str>) -> Option<&str> {
+        let same_agent = match (self.agent_id.as_deref(), agent_id) {
+            (Some(stored), Some(requested)) => stored == requested,
+            _ => true,
+        };
+
+        self.agent_thread_id.as_deref().filter(|_| same_agent)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatRequest {
+    pub content: String,
+    pub thread_id: Option<String>,
     pub model: Option<String>,
+    /// Sandbox for this turn; Codex runs with full access when unset.
+    #[serde(default)]
+    pub sandbox: Option<TaskSandbox>,
     pub working_directory: Option<String>,
+    pub agent_id: Option<String>,
+    pub tools: Option<Vec<serde_json::Value>>,
+    /// JSON Schema the agent's final message has to match.
+    #[serde(default)]
+    pub output_schema: Option<serde_json::Value>,
+    pub project_id: Option<String>,
+    pub task_run_id: Option<String>,
+    pub task_id: Option<String>,
+    pub step_id: Option<String>,
+    pub run_id: Option<String>,
+    pub chat_thread_id: Option<String>,
+    /// Starts a new agent thread instead of resuming the chat's and leaves
+    /// the chat's thread as it was, e.g. for independent task steps.
+    #[serde(default)]
+    pub fresh_thread: bool,
+    /// Sends the content as is instead of rendering it as the step's
+    /// template, e.g. for the retries of a failed verification.
+    #[serde(default)]
+    pub verbatim: bool,
+    pub rule_tags: Option<Vec<String>>,
+    pub context_set_ids: Option<Vec<String>>,
+}
+
+impl ChatRequest {
+    pub fn ensure_run_id(&mut self) -> String {
+        self.run_id
+            .get_or_insert_with(|| Uuid::new_v4().to_string())
+            .clone()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnOutcome {
+    pub run_id: String,
+    pub thread_id: Option<String>,
+    pub usage: TokenUsage,
+    pub last_message: Option<String>,
 }
 
 #[derive(Serialize)]
 #[serde(tag = "type", content = "data")]
 #[serde(rename_all = "camelCase")]
 pub enum ChatResponse {
-    Token { text: String },
-    ThreadStarted { thread_id: String },
-    Message { role: String, content: String },
-    Done { total_tokens: u32 },
-    Error { message: String },
+    Token {
+        text: String,
+    },
+    ThreadStarted {
+        thread_id: String,
+    },
+    Message {
+        role: String,
+        content: String,
+    },
+    Done {
+        total_tokens: u64,
+        usage: TokenUsage,
+    },
+    Error {
+        message: String,
+    },
 }
 
 impl ChatResponse {
     pub fn to_json(&self) -> serde_json::Value {
         serde_json::to_value(self).unwrap()
     }
+
+    pub fn done(usage: TokenUsage) -> Self {
+        ChatResponse::Done {
+            total_tokens: usage.total_tokens(),
+            usage,
+        }
+    }
 }
 
 impl From<ThreadItem> for ChatResponse {
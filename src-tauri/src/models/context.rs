@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+/// Files and globs, relative to the project root, that are attached to
+/// prompts together under one name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextSet {
+    pub id: String,
+    pub project_id: String,
+    pub name: String,
+    pub patterns: Vec<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextFile {
+    pub path: String,
+    pub tokens: usize,
+}
+
+/// Rendered context and the files that did or did not fit the token budget.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextBlock {
+    pub content: String,
+    pub files: Vec<ContextFile>,
+    pub omitted: Vec<ContextFile>,
+    pub estimated_tokens: usize,
+    pub token_budget: usize,
+}
@@ -1,31 +1,179 @@
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use tauri::ipc::Channel;
 use tauri::Emitter;
 
+use crate::models::usage::TokenUsage;
+
 pub trait CodexEventHandler: Send + Sync {
-    fn on_item(&self, item: serde_json::Value);
-    fn on_done(&self, usage: serde_json::Value);
-    fn on_thread_started(&self, thread_info: serde_json::Value);
+    fn on_item(&self, run_id: &str, item: serde_json::Value);
+    fn on_done(&self, run_id: &str, usage: serde_json::Value);
+    fn on_thread_started(&self, run_id: &str, thread_info: serde_json::Value);
+
+    fn on_item_completed(&self, run_id: &str, item: serde_json::Value) {
+        self.on_item(run_id, item);
+    }
+
+    /// Token usage of the turn so far, reported as soon as the agent sends it,
+    /// including for turns that fail afterwards.
+    fn on_usage(&self, _run_id: &str, _usage: &TokenUsage) {}
+
+    /// Why the run was cancelled, if it was; agents stop streaming once this is set.
+    fn cancel_reason(&self, _run_id: &str) -> Option<String> {
+        None
+    }
+}
+
+impl<H: CodexEventHandler> CodexEventHandler for &H {
+    fn on_item(&self, run_id: &str, item: serde_json::Value) {
+        (*self).on_item(run_id, item);
+    }
+
+    fn on_done(&self, run_id: &str, usage: serde_json::Value) {
+        (*self).on_done(run_id, usage);
+    }
+
+    fn on_thread_started(&self, run_id: &str, thread_info: serde_json::Value) {
+        (*self).on_thread_started(run_id, thread_info);
+    }
+
+    fn on_item_completed(&self, run_id: &str, item: serde_json::Value) {
+        (*self).on_item_completed(run_id, item);
+    }
+
+    fn on_usage(&self, run_id: &str, usage: &TokenUsage) {
+        (*self).on_usage(run_id, usage);
+    }
+
+    fn cancel_reason(&self, run_id: &str) -> Option<String> {
+        (*self).cancel_reason(run_id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RunEventKind {
+    Item,
+    Done,
+    ThreadStarted,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunEvent {
+    pub run_id: String,
+    pub kind: RunEventKind,
+    pub payload: serde_json::Value,
+}
+
+impl RunEvent {
+    pub fn new(run_id: &str, kind: RunEventKind, payload: serde_json::Value) -> Self {
+        Self {
+            run_id: run_id.to_string(),
+            kind,
+            payload,
+        }
+    }
 }
 
 pub struct TauriCodexEventHandler {
     app: tauri::AppHandle,
+    channel: Option<Channel<RunEvent>>,
 }
 
 impl TauriCodexEventHandler {
     pub fn new(app: tauri::AppHandle) -> Self {
-        Self { app }
+        Self { app, channel: None }
+    }
+
+    pub fn with_channel(app: tauri::AppHandle, channel: Option<Channel<RunEvent>>) -> Self {
+        Self { app, channel }
+    }
+
+    fn dispatch(&self, event_name: &str, event: RunEvent) {
+        match &self.channel {
+            Some(channel) => {
+                if let Err(error) = channel.send(event) {
+                    log::error!("failed to send {} to run channel: {}", event_name, error);
+                }
+            }
+            None => {
+                let _ = self.app.emit(event_name, event);
+            }
+        }
     }
 }
 
 impl CodexEventHandler for TauriCodexEventHandler {
-    fn on_item(&self, item: serde_json::Value) {
-        let _ = self.app.emit("codex:message", item);
+    fn on_item(&self, run_id: &str, item: serde_json::Value) {
+        self.dispatch(
+            "codex:message",
+            RunEvent::new(run_id, RunEventKind::Item, item),
+        );
+    }
+
+    fn on_done(&self, run_id: &str, usage: serde_json::Value) {
+        self.dispatch(
+            "codex:done",
+            RunEvent::new(run_id, RunEventKind::Done, usage),
+        );
+    }
+
+    fn on_thread_started(&self, run_id: &str, thread_info: serde_json::Value) {
+        self.dispatch(
+            "codex:thread-started",
+            RunEvent::new(run_id, RunEventKind::ThreadStarted, thread_info),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RecordingEventHandler {
+    events: Arc<Mutex<Vec<RunEvent>>>,
+}
+
+impl RecordingEventHandler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn events(&self) -> Vec<RunEvent> {
+        self.events
+            .lock()
+            .map(|events| events.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn payloads(&self, kind: RunEventKind) -> Vec<serde_json::Value> {
+        self.events()
+            .into_iter()
+            .filter(|event| event.kind == kind)
+            .map(|event| event.payload)
+            .collect()
+    }
+
+    fn record(&self, event: RunEvent) {
+        if let Ok(mut events) = self.events.lock() {
+            events.push(event);
+        }
+    }
+}
+
+impl CodexEventHandler for RecordingEventHandler {
+    fn on_item(&self, run_id: &str, item: serde_json::Value) {
+        self.record(RunEvent::new(run_id, RunEventKind::Item, item));
     }
 
-    fn on_done(&self, usage: serde_json::Value) {
-        let _ = self.app.emit("codex:done", usage);
+    fn on_done(&self, run_id: &str, usage: serde_json::Value) {
+        self.record(RunEvent::new(run_id, RunEventKind::Done, usage));
     }
 
-    fn on_thread_started(&self, thread_info: serde_json::Value) {
-        let _ = self.app.emit("codex:thread-started", thread_info);
+    fn on_thread_started(&self, run_id: &str, thread_info: serde_json::Value) {
+        self.record(RunEvent::new(
+            run_id,
+            RunEventKind::ThreadStarted,
+            thread_info,
+        ));
     }
 }
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// Contents of `.vibeflow/project.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectManifest {
+    /// Layout of the `.vibeflow/` folder the manifest was written for.
+    pub version: u32,
+    pub name: String,
+    pub created_at: i64,
+    /// Whether tasks are kept as files under `.vibeflow/tasks/`, with the
+    /// database only caching them.
+    #[serde(default)]
+    pub store_in_repo: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MovedFile {
+    pub from: String,
+    pub to: String,
+}
+
+/// What scaffolding or repairing the `.vibeflow/` folder changed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScaffoldReport {
+    pub version: u32,
+    /// Layout found before the run; `None` when there was no manifest.
+    pub previous_version: Option<u32>,
+    pub created: Vec<String>,
+    pub moved: Vec<MovedFile>,
+}
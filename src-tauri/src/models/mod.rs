@@ -1,7 +1,22 @@
 pub mod agent_rule;
 pub mod app_config;
+pub mod budget;
 pub mod chat;
+pub mod context;
 pub mod event_handler;
+pub mod file_change;
+pub mod manifest;
 pub mod project;
+pub mod prompt;
+pub mod schedule;
+pub mod session;
+pub mod session_recording;
 pub mod setting;
+pub mod symbol;
 pub mod task;
+pub mod task_file;
+pub mod task_graph;
+pub mod task_import;
+pub mod task_plan;
+pub mod usage;
+pub mod verification;
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use super::agent_rule::AgentRule;
 use super::task::Task;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Project {
     pub id: String,
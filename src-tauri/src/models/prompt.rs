@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+use super::chat::ChatTurnStatus;
+use crate::utils::current_timestamp_millis;
+
+/// A prompt as it was sent to an agent, keyed by the run it started.
+///
+/// Pinned prompts form the library; `name` and `tags` describe them there.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptRecord {
+    pub id: String,
+    pub project_id: Option<String>,
+    pub thread_id: Option<String>,
+    pub task_run_id: Option<String>,
+    pub content: String,
+    pub outcome: ChatTurnStatus,
+    pub error: Option<String>,
+    pub starred: bool,
+    pub pinned: bool,
+    pub name: Option<String>,
+    pub tags: Vec<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl PromptRecord {
+    pub fn for_run(
+        run_id: String,
+        project_id: Option<String>,
+        thread_id: Option<String>,
+        task_run_id: Option<String>,
+        content: String,
+    ) -> Self {
+        let created_at = current_timestamp_millis();
+
+        Self {
+            id: run_id,
+            project_id,
+            thread_id,
+            task_run_id,
+            content,
+            outcome: ChatTurnStatus::Running,
+            error: None,
+            starred: false,
+            pinned: false,
+            name: None,
+            tags: Vec::new(),
+            created_at,
+            updated_at: created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptQuery {
+    /// Case-insensitive match against the content and library name.
+    pub text: Option<String>,
+    pub project_id: Option<String>,
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub starred_only: bool,
+    /// Only return prompts pinned to the library.
+    #[serde(default)]
+    pub pinned_only: bool,
+    pub limit: Option<u32>,
+}
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+/// What happens to runs that came due while the app was not running.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MissedRunPolicy {
+    /// Record the missed runs as skipped and wait for the next one.
+    #[default]
+    Skip,
+    /// Run once as soon as possible, however many runs were missed.
+    CatchUp,
+}
+
+/// A cron-style schedule that runs a task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskSchedule {
+    pub id: String,
+    pub project_id: String,
+    pub task_id: String,
+    /// Five fields: minute, hour, day of month, month and day of week, or
+    /// one of `@hourly`, `@daily` (or `@midnight`), `@weekly`, `@monthly`
+    /// and `@yearly` (or `@annually`).
+    pub cron: String,
+    pub enabled: bool,
+    #[serde(default)]
+    pub missed_run_policy: MissedRunPolicy,
+    /// IANA name of the time zone `cron` is read in, e.g. `Europe/Berlin`,
+    /// so runs keep their local time across daylight saving changes.
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    /// Fixed offset `cron` is read in when there is no `time_zone`, e.g.
+    /// 120 for UTC+2.
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
+    #[serde(default)]
+    pub next_run_at: Option<i64>,
+    #[serde(default)]
+    pub last_run_at: Option<i64>,
+    #[serde(default)]
+    pub last_run_id: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// What a schedule does when the scheduler looks at it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScheduleDecision {
+    /// The occurrence to run now, if any.
+    pub run_for: Option<i64>,
+    /// Occurrences that passed without a run.
+    pub missed: u32,
+    pub next_run_at: Option<i64>,
+}
+
+/// A due run the UI should start, queued until it takes it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledRunEvent {
+    pub schedule_id: String,
+    pub project_id: String,
+    pub task_id: String,
+    pub task_run_id: String,
+    pub scheduled_for: i64,
+    /// Runs before this one that were missed, e.g. while the app was closed.
+    pub missed: u32,
+}
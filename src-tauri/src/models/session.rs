@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    Queued,
+    Running,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveSession {
+    pub run_id: String,
+    pub project_id: Option<String>,
+    pub task_run_id: Option<String>,
+    pub thread_id: Option<String>,
+    pub agent: String,
+    pub status: SessionStatus,
+    pub queued_at: i64,
+    pub started_at: Option<i64>,
+    pub elapsed_ms: i64,
+    pub current_item: Option<String>,
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionRecording {
+    pub id: String,
+    pub path: String,
+    pub thread_id: Option<String>,
+    pub event_count: usize,
+    pub size_bytes: u64,
+    pub created_at: i64,
+}
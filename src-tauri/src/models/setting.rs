@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,7 +27,15 @@ pub struct SettingModel {
     pub value_type: SettingValueType,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum AgentApiStyle {
+    #[default]
+    ChatCompletions,
+    Responses,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AgentConfig {
     pub id: String,
@@ -36,4 +46,17 @@ pub struct AgentConfig {
     pub base_url: String,
     pub enabled: bool,
     pub is_default: bool,
+    #[serde(default)]
+    pub api_style: AgentApiStyle,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+impl AgentConfig {
+    pub const CODEX_CLI: &'static str = "codex-cli";
+    pub const OPENAI_COMPATIBLE: &'static str = "openai-compatible";
+
+    pub fn is_openai_compatible(&self) -> bool {
+        self.agent_type == Self::OPENAI_COMPATIBLE
+    }
 }
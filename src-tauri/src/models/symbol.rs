@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    Function,
+    Method,
+    Struct,
+    Enum,
+    Trait,
+    Class,
+    Interface,
+    Type,
+    Constant,
+    Module,
+    Macro,
+}
+
+/// A definition found in a project file; lines are 1-based and inclusive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub path: String,
+    pub language: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Enclosing type, trait, impl or class, if any.
+    pub container: Option<String>,
+    /// First line of the definition.
+    pub signature: String,
+}
+
+/// Size and modification time a file had when its symbols were extracted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolFile {
+    pub path: String,
+    pub modified_at: i64,
+    pub size: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolReference {
+    pub path: String,
+    pub line: usize,
+    pub text: String,
+}
+
+/// A symbol's definition with surrounding lines, plus where it is used.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolContext {
+    pub symbol: CodeSymbol,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub code: String,
+    pub references: Vec<SymbolReference>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolIndexReport {
+    pub indexed: Vec<String>,
+    pub removed: Vec<String>,
+    pub symbols: usize,
+}
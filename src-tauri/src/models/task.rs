@@ -1,23 +1,55 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::utils::current_timestamp_millis;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum TaskStatus {
+    #[default]
     Pending,
     InProgress,
     Completed,
     Failed,
+    /// Not run because its condition did not hold.
+    Skipped,
+    /// Stopped before finishing and can be resumed.
+    Paused,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TaskStepType {
+    #[default]
     Normal,
     Post,
     Pre,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// How much of the machine the agent may touch while running a step,
+/// matching the Codex sandbox modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskSandbox {
+    ReadOnly,
+    WorkspaceWrite,
+    DangerFullAccess,
+}
+
+/// When a step runs, judged by the steps it depends on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum StepRunWhen {
+    /// Every dependency completed.
+    #[default]
+    OnSuccess,
+    /// At least one dependency failed.
+    OnFailure,
+    /// Every dependency finished, however it ended.
+    Always,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskStep {
     pub id: String,
@@ -27,10 +59,117 @@ pub struct TaskStep {
     pub created_at: i64,
     pub updated_at: i64,
     pub r#type: TaskStepType,
+    #[serde(default)]
+    pub status_reason: Option<String>,
+    /// Model used for this step instead of the agent's default.
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub sandbox: Option<TaskSandbox>,
+    /// Shell commands that must succeed after the step for it to pass.
+    #[serde(default)]
+    pub verify: Vec<String>,
+    /// Ids of steps of the same task that must finish first.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    #[serde(default)]
+    pub run_when: StepRunWhen,
+    /// Globs; the step only runs when a matching file changed during the run.
+    #[serde(default)]
+    pub if_changed: Vec<String>,
+}
+
+/// User-defined value available to step prompts as `{{params.<name>}}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskParameter {
+    pub name: String,
+    pub value: String,
 }
 
+/// Outcome of a step within a task run, kept for the prompts of later steps.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+pub struct TaskStepResult {
+    pub task_run_id: String,
+    pub task_id: String,
+    pub step_id: String,
+    pub summary: String,
+    pub created_at: i64,
+}
+
+impl TaskStepResult {
+    pub fn new(task_run_id: String, task_id: String, step_id: String, summary: String) -> Self {
+        let created_at = current_timestamp_millis();
+
+        Self {
+            task_run_id,
+            task_id,
+            step_id,
+            summary,
+            created_at,
+        }
+    }
+}
+
+/// Status of a main step, with its pre and post steps, within one task run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRunStep {
+    pub task_run_id: String,
+    pub task_id: String,
+    pub step_id: String,
+    pub status: TaskStatus,
+    #[serde(default)]
+    pub status_reason: Option<String>,
+    pub updated_at: i64,
+}
+
+impl TaskRunStep {
+    pub fn new(
+        task_run_id: String,
+        task_id: String,
+        step_id: String,
+        status: TaskStatus,
+        status_reason: Option<String>,
+    ) -> Self {
+        let updated_at = current_timestamp_millis();
+
+        Self {
+            task_run_id,
+            task_id,
+            step_id,
+            status,
+            status_reason,
+            updated_at,
+        }
+    }
+}
+
+/// One run of a task. Its progress is checkpointed as `TaskRunStep`s so the
+/// run can be resumed, also after the app restarts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRun {
+    pub id: String,
+    pub project_id: String,
+    pub task_id: String,
+    pub status: TaskStatus,
+    #[serde(default)]
+    pub status_reason: Option<String>,
+    /// Chat thread the run's turns go to; a resumed run continues its agent
+    /// thread.
+    #[serde(default)]
+    pub chat_thread_id: Option<String>,
+    /// Schedule that started the run, if it was not started by hand.
+    #[serde(default)]
+    pub schedule_id: Option<String>,
+    pub started_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Task {
     pub id: String,
     pub project_id: String,
@@ -39,6 +178,11 @@ pub struct Task {
     pub steps: Vec<TaskStep>,
     pub presteps: Vec<TaskStep>,
     pub poststeps: Vec<TaskStep>,
+    #[serde(default)]
+    pub parameters: Vec<TaskParameter>,
+    /// Tagged project rules only apply to the task's steps when they share one of these.
+    #[serde(default)]
+    pub tags: Vec<String>,
     pub status: TaskStatus,
     pub created_at: i64,
     pub updated_at: i64,
@@ -0,0 +1,214 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::models::task::{
+    StepRunWhen, Task, TaskParameter, TaskSandbox, TaskStatus, TaskStep, TaskStepType,
+};
+
+/// A task as stored in `.vibeflow/tasks/<name>.yaml` or `<name>.json`. Only
+/// the definition is kept; run status and timestamps stay in each user's
+/// database.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[schemars(title = "VibeFlow task")]
+pub struct TaskFile {
+    /// Format of the file. VibeFlow refuses files newer than it knows and
+    /// writes the lowest version that holds the task.
+    pub version: u32,
+    /// Stable id of the task; derived from the file name when left out.
+    #[serde(default)]
+    pub id: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// Values step prompts can use as `{{params.<name>}}`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<TaskParameter>,
+    /// Tags that pick the project rules sent with the task's steps (version 2).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Steps run before each main step.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub presteps: Vec<TaskFileStep>,
+    #[serde(default)]
+    pub steps: Vec<TaskFileStep>,
+    /// Steps run after each main step.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub poststeps: Vec<TaskFileStep>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskFileStep {
+    /// Stable id of the step; derived from the task id and the step's
+    /// position when left out.
+    #[serde(default)]
+    pub id: String,
+    pub title: String,
+    /// The prompt sent to the agent.
+    #[serde(default)]
+    pub content: String,
+    /// Model used for this step instead of the agent's default (version 2).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Sandbox the agent runs this step in (version 2).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<TaskSandbox>,
+    /// Shell commands run in the project folder after the step; the step
+    /// only passes when all of them exit with 0 (version 2).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub verify: Vec<String>,
+    /// Ids of main steps of this task that must finish first; once any main
+    /// step declares them, steps no longer wait for the one before them
+    /// (version 2).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// Whether the step runs after its dependencies succeeded, failed or
+    /// either way (version 2).
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub run_when: StepRunWhen,
+    /// Globs; the step is skipped unless a matching file changed during the
+    /// run (version 2).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub if_changed: Vec<String>,
+}
+
+impl TaskFile {
+    /// Lowest format version that can hold this task, so files stay
+    /// readable by older VibeFlow builds unless they use newer fields.
+    pub fn required_version(&self) -> u32 {
+        let uses_step_options = self
+            .presteps
+            .iter()
+            .chain(&self.steps)
+            .chain(&self.poststeps)
+            .any(|step| {
+                step.model.is_some()
+                    || step.sandbox.is_some()
+                    || !step.verify.is_empty()
+                    || !step.depends_on.is_empty()
+                    || step.run_when != StepRunWhen::default()
+                    || !step.if_changed.is_empty()
+            });
+
+        if uses_step_options || !self.tags.is_empty() {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// A new pending task with this definition.
+    pub fn to_task(&self, project_id: &str, now: i64) -> Task {
+        let steps = |steps: &[TaskFileStep], step_type: TaskStepType| {
+            steps
+                .iter()
+                .map(|step| step.to_step(step_type.clone(), now))
+                .collect()
+        };
+
+        Task {
+            id: self.id.clone(),
+            project_id: project_id.to_string(),
+            title: self.title.clone(),
+            description: self.description.clone(),
+            steps: steps(&self.steps, TaskStepType::Normal),
+            presteps: steps(&self.presteps, TaskStepType::Pre),
+            poststeps: steps(&self.poststeps, TaskStepType::Post),
+            parameters: self.parameters.clone(),
+            tags: self.tags.clone(),
+            status: TaskStatus::Pending,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+impl From<&Task> for TaskFile {
+    fn from(task: &Task) -> Self {
+        let steps = |steps: &[TaskStep]| steps.iter().map(TaskFileStep::from).collect();
+        let mut file = TaskFile {
+            version: 0,
+            id: task.id.clone(),
+            title: task.title.clone(),
+            description: task.description.clone(),
+            parameters: task.parameters.clone(),
+            tags: task.tags.clone(),
+            presteps: steps(&task.presteps),
+            steps: steps(&task.steps),
+            poststeps: steps(&task.poststeps),
+        };
+        file.version = file.required_version();
+        file
+    }
+}
+
+impl TaskFileStep {
+    /// A new pending step with this definition.
+    pub fn to_step(&self, step_type: TaskStepType, now: i64) -> TaskStep {
+        TaskStep {
+            id: self.id.clone(),
+            title: self.title.clone(),
+            content: self.content.clone(),
+            status: TaskStatus::Pending,
+            created_at: now,
+            updated_at: now,
+            r#type: step_type,
+            status_reason: None,
+            model: self.model.clone(),
+            sandbox: self.sandbox,
+            verify: self.verify.clone(),
+            depends_on: self.depends_on.clone(),
+            run_when: self.run_when,
+            if_changed: self.if_changed.clone(),
+        }
+    }
+
+    /// Whether `step` has this definition, ignoring its run state.
+    pub fn matches(&self, step: &TaskStep) -> bool {
+        *self == TaskFileStep::from(step)
+    }
+}
+
+impl From<&TaskStep> for TaskFileStep {
+    fn from(step: &TaskStep) -> Self {
+        TaskFileStep {
+            id: step.id.clone(),
+            title: step.title.clone(),
+            content: step.content.clone(),
+            model: step.model.clone(),
+            sandbox: step.sandbox,
+            verify: step.verify.clone(),
+            depends_on: step.depends_on.clone(),
+            run_when: step.run_when,
+            if_changed: step.if_changed.clone(),
+        }
+    }
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+/// What syncing a project's tasks with `.vibeflow/tasks/` changed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskSyncReport {
+    /// Files whose task was added or updated in the project.
+    pub updated: Vec<String>,
+    /// Ids of tasks dropped because their file is gone.
+    pub removed: Vec<String>,
+    /// Files written from the project's tasks.
+    pub written: Vec<String>,
+    /// Files deleted along with their task.
+    pub deleted: Vec<String>,
+    /// Files that could not be read; tasks are never dropped while any exist.
+    pub errors: Vec<String>,
+}
+
+impl TaskSyncReport {
+    /// Whether the project's tasks changed and need saving.
+    pub fn changed_project(&self) -> bool {
+        !self.updated.is_empty() || !self.removed.is_empty()
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::chat::ChatThread;
+use crate::models::task::{StepRunWhen, TaskRun, TaskStatus};
+
+/// A main step of a task together with the steps it waits for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskGraphNode {
+    pub step_id: String,
+    pub title: String,
+    pub depends_on: Vec<String>,
+    pub run_when: StepRunWhen,
+    pub if_changed: Vec<String>,
+}
+
+/// The main steps of a task as a dependency graph, in an order where every
+/// node comes after its dependencies.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskGraph {
+    pub task_id: String,
+    /// Whether any step declares dependencies; otherwise each main step
+    /// depends on the one before it. Nodes still run one at a time.
+    pub declares_dependencies: bool,
+    pub nodes: Vec<TaskGraphNode>,
+}
+
+/// What to do next in a run of a task graph.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskGraphAdvance {
+    /// Nodes whose dependencies finished and whose condition holds.
+    pub ready: Vec<String>,
+    /// Nodes that will not run, including those only waiting on skipped ones.
+    pub skipped: Vec<String>,
+}
+
+/// A task run picked up again, with the statuses of the main steps it keeps.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRunResume {
+    pub run: TaskRun,
+    pub statuses: HashMap<String, TaskStatus>,
+    pub chat_thread: Option<ChatThread>,
+}
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::task::Task;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskImportKind {
+    New,
+    Updated,
+    Unchanged,
+}
+
+/// One Markdown task file and what importing it would do to the project.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskImport {
+    /// Relative to the project root when the file is inside it.
+    pub path: String,
+    pub kind: TaskImportKind,
+    /// The task as it would be saved, merged into the existing one if any.
+    pub task: Task,
+    /// Human-readable differences from the existing task.
+    pub changes: Vec<String>,
+}
@@ -0,0 +1,31 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The structured answer the agent gives when asked to plan a task. Every
+/// field is required and unknown fields are rejected so the schema can be
+/// used with strict structured output.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+#[schemars(title = "VibeFlow task plan")]
+pub struct TaskPlan {
+    /// Short imperative title of the task.
+    pub title: String,
+    /// What the task achieves and any constraints the steps must respect.
+    pub description: String,
+    /// Steps run before each main step, e.g. re-reading the relevant code.
+    pub presteps: Vec<PlannedStep>,
+    /// The work itself, in execution order.
+    pub steps: Vec<PlannedStep>,
+    /// Steps run after each main step, e.g. running the tests.
+    pub poststeps: Vec<PlannedStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PlannedStep {
+    pub title: String,
+    /// The prompt sent to the coding agent for this step.
+    pub content: String,
+    /// Shell commands that must exit with 0 once the step is done.
+    pub verify: Vec<String>,
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::utils::current_timestamp_millis;
+
+const TOKENS_PER_MILLION: f64 = 1_000_000.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub cached_input_tokens: u64,
+    pub output_tokens: u64,
+    /// The part of `output_tokens` spent on reasoning, when the API reports it.
+    pub reasoning_output_tokens: u64,
+}
+
+impl TokenUsage {
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.input_tokens += other.input_tokens;
+        self.cached_input_tokens += other.cached_input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.reasoning_output_tokens += other.reasoning_output_tokens;
+    }
+}
+
+impl From<&codex_sdk::Usage> for TokenUsage {
+    fn from(usage: &codex_sdk::Usage) -> Self {
+        Self {
+            input_tokens: usage.input_tokens as u64,
+            cached_input_tokens: usage.cached_input_tokens as u64,
+            output_tokens: usage.output_tokens as u64,
+            reasoning_output_tokens: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPricing {
+    pub model: String,
+    pub input_per_million: f64,
+    pub cached_input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+impl ModelPricing {
+    pub fn find<'a>(pricing: &'a [ModelPricing], model: &str) -> Option<&'a ModelPricing> {
+        pricing
+            .iter()
+            .find(|entry| entry.model == model)
+            .or_else(|| {
+                pricing
+                    .iter()
+                    .filter(|entry| !entry.model.is_empty() && model.starts_with(&entry.model))
+                    .max_by_key(|entry| entry.model.len())
+            })
+    }
+
+    pub fn estimate_cost(pricing: &[ModelPricing], model: &str, usage: &TokenUsage) -> f64 {
+        Self::find(pricing, model)
+            .map(|entry| entry.cost_of(usage))
+            .unwrap_or(0.0)
+    }
+
+    pub fn cost_of(&self, usage: &TokenUsage) -> f64 {
+        let cached_tokens = usage.cached_input_tokens.min(usage.input_tokens);
+        let uncached_tokens = usage.input_tokens - cached_tokens;
+
+        (uncached_tokens as f64 * self.input_per_million
+            + cached_tokens as f64 * self.cached_input_per_million
+            + usage.output_tokens as f64 * self.output_per_million)
+            / TOKENS_PER_MILLION
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageRecord {
+    pub id: String,
+    pub project_id: Option<String>,
+    pub thread_id: Option<String>,
+    pub task_run_id: Option<String>,
+    pub model: String,
+    pub usage: TokenUsage,
+    pub cost: f64,
+    pub created_at: i64,
+}
+
+impl UsageRecord {
+    pub fn for_turn(
+        project_id: Option<String>,
+        thread_id: Option<String>,
+        task_run_id: Option<String>,
+        model: String,
+        usage: TokenUsage,
+        pricing: &[ModelPricing],
+    ) -> Self {
+        let created_at = current_timestamp_millis();
+
+        Self {
+            id: Uuid::new_v4().to_string(),
+            cost: ModelPricing::estimate_cost(pricing, &model, &usage),
+            project_id,
+            thread_id,
+            task_run_id,
+            model,
+            usage,
+            created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReportQuery {
+    pub project_id: Option<String>,
+    pub thread_id: Option<String>,
+    pub task_run_id: Option<String>,
+    pub model: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReportRow {
+    pub key: String,
+    pub turns: u64,
+    pub usage: TokenUsage,
+    pub total_tokens: u64,
+    pub cost: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReport {
+    pub total: UsageReportRow,
+    pub by_day: Vec<UsageReportRow>,
+    pub by_project: Vec<UsageReportRow>,
+    pub by_model: Vec<UsageReportRow>,
+}
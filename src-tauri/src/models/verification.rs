@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+/// One verification command run after a step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandCheck {
+    pub command: String,
+    /// `None` when the command could not start or was killed.
+    pub exit_code: Option<i32>,
+    /// Combined stdout and stderr, keeping the end when it is long.
+    pub output: String,
+    pub timed_out: bool,
+    pub duration_ms: u64,
+}
+
+impl CommandCheck {
+    pub fn passed(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+
+    /// Short explanation of why the check failed.
+    pub fn failure(&self) -> String {
+        match self.exit_code {
+            _ if self.timed_out => format!("`{}` timed out", self.command),
+            Some(code) => format!("`{}` exited with {code}", self.command),
+            None => format!("`{}` could not run", self.command),
+        }
+    }
+}
+
+/// The outcome of a step's verification commands for one attempt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepVerification {
+    pub passed: bool,
+    /// Checks in order; the commands after the first failing one are skipped.
+    pub checks: Vec<CommandCheck>,
+    /// 1-based attempt this verification belongs to.
+    pub attempt: u32,
+    pub max_attempts: u32,
+    /// Why the step failed, for its status.
+    pub reason: Option<String>,
+    /// Prompt asking the agent to fix the failure; only set while attempts are left.
+    pub retry_prompt: Option<String>,
+}
+
+impl StepVerification {
+    pub fn new(checks: Vec<CommandCheck>, attempt: u32, max_attempts: u32) -> Self {
+        let failed = checks.iter().find(|check| !check.passed());
+        let reason = failed.map(|check| format!("verification failed: {}", check.failure()));
+        let retry_prompt = failed.filter(|_| attempt < max_attempts).map(|check| {
+            format!(
+                "The changes did not pass verification: {}. Output:\n\n```\n{}\n```\n\n\
+                 Fix the problem so the command succeeds. This was attempt {attempt} of {max_attempts}.",
+                check.failure(),
+                check.output.trim_end()
+            )
+        });
+
+        Self {
+            passed: failed.is_none(),
+            checks,
+            attempt,
+            max_attempts,
+            reason,
+            retry_prompt,
+        }
+    }
+}
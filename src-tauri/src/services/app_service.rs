@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use uuid::Uuid;
@@ -8,11 +9,34 @@ use tauri::{
 };
 
 use crate::models::app_config::{AppConfig, MainWindowConfig};
-use crate::models::setting::{AgentConfig, SettingModel, SettingValue, SettingValueType};
+use crate::models::budget::BudgetLimits;
+use crate::models::setting::{
+    AgentApiStyle, AgentConfig, SettingModel, SettingValue, SettingValueType,
+};
+use crate::models::usage::ModelPricing;
 
 const APP_FOLDER_NAME: &str = "vibeflow";
 const APP_CONFIG_FILE_NAME: &str = "app.config.json";
 const SETTINGS_FILE_NAME: &str = "settings.json";
+const CONFIGURED_AGENTS_KEY: &str = "configured.agents";
+pub const PROMPT_TEMPLATE_KEY: &str = "prompt.template";
+const RECORD_SESSIONS_KEY: &str = "codex.recordSessions";
+const GENERATE_VIBEFLOW_FOLDER_KEY: &str = "project.generateVibeflowFolder";
+const MODEL_PRICING_KEY: &str = "usage.modelPricing";
+const MAX_CONCURRENT_SESSIONS_KEY: &str = "codex.maxConcurrentSessions";
+const CONTEXT_TOKEN_BUDGET_KEY: &str = "context.tokenBudget";
+const VERIFY_MAX_ATTEMPTS_KEY: &str = "task.verifyMaxAttempts";
+const VERIFY_TIMEOUT_SECONDS_KEY: &str = "task.verifyTimeoutSeconds";
+const BUDGET_MAX_TOKENS_PER_TURN_KEY: &str = "budget.maxTokensPerTurn";
+const BUDGET_MAX_COST_PER_TURN_KEY: &str = "budget.maxCostPerTurn";
+const BUDGET_MAX_TOKENS_PER_TASK_RUN_KEY: &str = "budget.maxTokensPerTaskRun";
+const BUDGET_MAX_COST_PER_TASK_RUN_KEY: &str = "budget.maxCostPerTaskRun";
+const BUDGET_MAX_TOKENS_PER_PROJECT_PER_DAY_KEY: &str = "budget.maxTokensPerProjectPerDay";
+const BUDGET_MAX_COST_PER_PROJECT_PER_DAY_KEY: &str = "budget.maxCostPerProjectPerDay";
+const DEFAULT_MAX_CONCURRENT_SESSIONS: usize = 2;
+const DEFAULT_CONTEXT_TOKEN_BUDGET: usize = 8000;
+const DEFAULT_VERIFY_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_VERIFY_TIMEOUT_SECONDS: u64 = 600;
 const DEFAULT_WINDOW_WIDTH: u32 = 400;
 const DEFAULT_WINDOW_HEIGHT: u32 = 1000;
 
@@ -72,6 +96,127 @@ impl AppService {
         self.save_settings()
     }
 
+    pub fn get_agent_config(&self, agent_id: Option<&str>) -> Option<AgentConfig> {
+        let agents = self.get_agent_configs();
+
+        match agent_id.map(str::trim).filter(|id| !id.is_empty()) {
+            Some(id) => agents.into_iter().find(|agent| agent.id == id),
+            None => {
+                let enabled_agents = agents
+                    .into_iter()
+                    .filter(|agent| agent.enabled)
+                    .collect::<Vec<_>>();
+                enabled_agents
+                    .iter()
+                    .find(|agent| agent.is_default)
+                    .cloned()
+                    .or_else(|| enabled_agents.into_iter().next())
+            }
+        }
+    }
+
+    pub fn get_agent_configs(&self) -> Vec<AgentConfig> {
+        let Some(SettingValue::String(raw)) = self.get_setting_value(CONFIGURED_AGENTS_KEY) else {
+            return Vec::new();
+        };
+
+        serde_json::from_str::<Vec<AgentConfig>>(&raw).unwrap_or_else(|error| {
+            log::error!("failed to parse configured agents: {}", error);
+            Vec::new()
+        })
+    }
+
+    pub fn get_model_pricing(&self) -> Vec<ModelPricing> {
+        let Some(SettingValue::String(raw)) = self.get_setting_value(MODEL_PRICING_KEY) else {
+            return Vec::new();
+        };
+
+        serde_json::from_str::<Vec<ModelPricing>>(&raw).unwrap_or_else(|error| {
+            log::error!("failed to parse model pricing: {}", error);
+            Vec::new()
+        })
+    }
+
+    pub fn is_session_recording_enabled(&self) -> bool {
+        matches!(
+            self.get_setting_value(RECORD_SESSIONS_KEY),
+            Some(SettingValue::Boolean(true))
+        )
+    }
+
+    /// Whether opening a project scaffolds its `.vibeflow/` folder.
+    pub fn should_generate_vibeflow_folder(&self) -> bool {
+        matches!(
+            self.get_setting_value(GENERATE_VIBEFLOW_FOLDER_KEY),
+            Some(SettingValue::Boolean(true))
+        )
+    }
+
+    pub fn get_max_concurrent_sessions(&self) -> usize {
+        match self.get_setting_value(MAX_CONCURRENT_SESSIONS_KEY) {
+            Some(SettingValue::Number(limit)) if limit >= 1.0 => limit as usize,
+            _ => DEFAULT_MAX_CONCURRENT_SESSIONS,
+        }
+    }
+
+    /// Upper bound on the estimated tokens of the context block added to prompts.
+    pub fn get_context_token_budget(&self) -> usize {
+        match self.get_setting_value(CONTEXT_TOKEN_BUDGET_KEY) {
+            Some(SettingValue::Number(budget)) if budget >= 1.0 => budget as usize,
+            _ => DEFAULT_CONTEXT_TOKEN_BUDGET,
+        }
+    }
+
+    /// How often a step is run before failing verification fails the step.
+    pub fn get_verify_max_attempts(&self) -> u32 {
+        match self.get_setting_value(VERIFY_MAX_ATTEMPTS_KEY) {
+            Some(SettingValue::Number(attempts)) if attempts >= 1.0 => attempts as u32,
+            _ => DEFAULT_VERIFY_MAX_ATTEMPTS,
+        }
+    }
+
+    pub fn get_verify_timeout_seconds(&self) -> u64 {
+        match self.get_setting_value(VERIFY_TIMEOUT_SECONDS_KEY) {
+            Some(SettingValue::Number(seconds)) if seconds >= 1.0 => seconds as u64,
+            _ => DEFAULT_VERIFY_TIMEOUT_SECONDS,
+        }
+    }
+
+    pub fn get_budget_limits(&self) -> BudgetLimits {
+        BudgetLimits {
+            max_tokens_per_turn: self
+                .get_budget_limit(BUDGET_MAX_TOKENS_PER_TURN_KEY)
+                .map(|limit| limit as u64),
+            max_cost_per_turn: self.get_budget_limit(BUDGET_MAX_COST_PER_TURN_KEY),
+            max_tokens_per_task_run: self
+                .get_budget_limit(BUDGET_MAX_TOKENS_PER_TASK_RUN_KEY)
+                .map(|limit| limit as u64),
+            max_cost_per_task_run: self.get_budget_limit(BUDGET_MAX_COST_PER_TASK_RUN_KEY),
+            max_tokens_per_project_per_day: self
+                .get_budget_limit(BUDGET_MAX_TOKENS_PER_PROJECT_PER_DAY_KEY)
+                .map(|limit| limit as u64),
+            max_cost_per_project_per_day: self
+                .get_budget_limit(BUDGET_MAX_COST_PER_PROJECT_PER_DAY_KEY),
+        }
+    }
+
+    pub fn get_setting_value(&self, key: &str) -> Option<SettingValue> {
+        self.settings
+            .iter()
+            .find(|setting| setting.key == key)
+            .map(|setting| setting.value.clone())
+    }
+
+    fn get_budget_limit(&self, key: &str) -> Option<f64> {
+        match self.get_setting_value(key) {
+            Some(SettingValue::Number(limit)) if limit > 0.0 => Some(limit),
+            Some(SettingValue::String(raw)) => {
+                raw.trim().parse::<f64>().ok().filter(|limit| *limit > 0.0)
+            }
+            _ => None,
+        }
+    }
+
     pub fn restore_main_window<R: Runtime>(&self, window: &WebviewWindow<R>) {
         let Some(main_window) = &self.app_config.main_window else {
             return;
@@ -214,13 +359,13 @@ impl AppService {
         vec![
             SettingModel {
                 id: "setting-configured-agents".to_string(),
-                key: "configured.agents".to_string(),
+                key: CONFIGURED_AGENTS_KEY.to_string(),
                 value: SettingValue::String(Self::default_agents()),
                 value_type: SettingValueType::String,
             },
             SettingModel {
                 id: "setting-default-prompt-template".to_string(),
-                key: "prompt.template".to_string(),
+                key: PROMPT_TEMPLATE_KEY.to_string(),
                 value: SettingValue::String(
                     "You are Codex working inside VibeFlow.\nFollow project context and rules, keep outputs concise, and produce actionable steps."
                         .to_string(),
@@ -229,28 +374,94 @@ impl AppService {
             },
             SettingModel {
                 id: "setting-generate-folder".to_string(),
-                key: "project.generateVibeflowFolder".to_string(),
+                key: GENERATE_VIBEFLOW_FOLDER_KEY.to_string(),
                 value: SettingValue::Boolean(true),
                 value_type: SettingValueType::Boolean,
             },
+            SettingModel {
+                id: "setting-record-sessions".to_string(),
+                key: RECORD_SESSIONS_KEY.to_string(),
+                value: SettingValue::Boolean(false),
+                value_type: SettingValueType::Boolean,
+            },
+            SettingModel {
+                id: "setting-max-concurrent-sessions".to_string(),
+                key: MAX_CONCURRENT_SESSIONS_KEY.to_string(),
+                value: SettingValue::Number(DEFAULT_MAX_CONCURRENT_SESSIONS as f64),
+                value_type: SettingValueType::Number,
+            },
+            SettingModel {
+                id: "setting-context-token-budget".to_string(),
+                key: CONTEXT_TOKEN_BUDGET_KEY.to_string(),
+                value: SettingValue::Number(DEFAULT_CONTEXT_TOKEN_BUDGET as f64),
+                value_type: SettingValueType::Number,
+            },
+            SettingModel {
+                id: "setting-model-pricing".to_string(),
+                key: MODEL_PRICING_KEY.to_string(),
+                value: SettingValue::String(Self::default_model_pricing()),
+                value_type: SettingValueType::String,
+            },
+            Self::budget_setting("setting-budget-turn-tokens", BUDGET_MAX_TOKENS_PER_TURN_KEY),
+            Self::budget_setting("setting-budget-turn-cost", BUDGET_MAX_COST_PER_TURN_KEY),
+            Self::budget_setting("setting-budget-run-tokens", BUDGET_MAX_TOKENS_PER_TASK_RUN_KEY),
+            Self::budget_setting("setting-budget-run-cost", BUDGET_MAX_COST_PER_TASK_RUN_KEY),
+            Self::budget_setting(
+                "setting-budget-project-day-tokens",
+                BUDGET_MAX_TOKENS_PER_PROJECT_PER_DAY_KEY,
+            ),
+            Self::budget_setting(
+                "setting-budget-project-day-cost",
+                BUDGET_MAX_COST_PER_PROJECT_PER_DAY_KEY,
+            ),
         ]
     }
 
+    fn budget_setting(id: &str, key: &str) -> SettingModel {
+        SettingModel {
+            id: id.to_string(),
+            key: key.to_string(),
+            value: SettingValue::Number(0.0),
+            value_type: SettingValueType::Number,
+        }
+    }
+
     fn default_agents() -> String {
         let agents = vec![AgentConfig {
             id: Uuid::new_v4().to_string(),
             name: "Codex CLI Agent".to_string(),
-            agent_type: "codex-cli".to_string(),
+            agent_type: AgentConfig::CODEX_CLI.to_string(),
             model: "gpt-5-codex".to_string(),
             api_key: "".to_string(),
             base_url: "".to_string(),
             enabled: true,
             is_default: true,
+            api_style: AgentApiStyle::default(),
+            headers: HashMap::new(),
         }];
 
         serde_json::to_string(&agents).expect("Failed to serialize agent configs")
     }
 
+    fn default_model_pricing() -> String {
+        let pricing = vec![
+            ModelPricing {
+                model: "gpt-5-codex".to_string(),
+                input_per_million: 1.25,
+                cached_input_per_million: 0.125,
+                output_per_million: 10.0,
+            },
+            ModelPricing {
+                model: "gpt-5".to_string(),
+                input_per_million: 1.25,
+                cached_input_per_million: 0.125,
+                output_per_million: 10.0,
+            },
+        ];
+
+        serde_json::to_string(&pricing).expect("Failed to serialize model pricing")
+    }
+
     fn clamp_position_to_primary_monitor(
         saved_x: i32,
         saved_y: i32,
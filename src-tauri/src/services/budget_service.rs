@@ -0,0 +1,253 @@
+use std::sync::Mutex;
+
+use serde_json::Value;
+
+use crate::models::budget::{BudgetLimits, BudgetScope, BudgetUnit, BudgetViolation};
+use crate::models::event_handler::CodexEventHandler;
+use crate::models::usage::{
+    ModelPricing, TokenUsage, UsageRecord, UsageReportQuery, UsageReportRow,
+};
+use crate::services::data_service::DataService;
+use crate::services::session_manager::SessionManager;
+
+pub struct BudgetService {
+    limits: BudgetLimits,
+}
+
+/// Checks the budget whenever the agent reports usage and cancels the run's
+/// session as soon as a limit is crossed, instead of waiting for the turn to end.
+pub struct BudgetEventHandler<'a, H: CodexEventHandler> {
+    budget: &'a BudgetService,
+    data_service: &'a Mutex<DataService>,
+    sessions: &'a SessionManager,
+    pricing: &'a [ModelPricing],
+    turn: UsageRecord,
+    inner: H,
+}
+
+impl BudgetService {
+    pub fn new(limits: BudgetLimits) -> Self {
+        Self { limits }
+    }
+
+    pub fn limits(&self) -> &BudgetLimits {
+        &self.limits
+    }
+
+    pub fn check_before_turn(
+        &self,
+        data_service: &DataService,
+        project_id: Option<&str>,
+        task_run_id: Option<&str>,
+    ) -> Result<Option<BudgetViolation>, rusqlite::Error> {
+        self.check_spent(data_service, project_id, task_run_id, None)
+    }
+
+    pub fn check_after_turn(
+        &self,
+        data_service: &DataService,
+        record: &UsageRecord,
+    ) -> Result<Option<BudgetViolation>, rusqlite::Error> {
+        let turn_violation = Self::exceeded(
+            BudgetScope::Turn,
+            record.usage.total_tokens(),
+            record.cost,
+            self.limits.max_tokens_per_turn,
+            self.limits.max_cost_per_turn,
+        );
+        if turn_violation.is_some() {
+            return Ok(turn_violation);
+        }
+
+        self.check_spent(
+            data_service,
+            record.project_id.as_deref(),
+            record.task_run_id.as_deref(),
+            None,
+        )
+    }
+
+    /// Like `check_after_turn`, for a turn whose usage is not saved yet.
+    pub fn check_during_turn(
+        &self,
+        data_service: &DataService,
+        record: &UsageRecord,
+    ) -> Result<Option<BudgetViolation>, rusqlite::Error> {
+        let turn_violation = Self::exceeded(
+            BudgetScope::Turn,
+            record.usage.total_tokens(),
+            record.cost,
+            self.limits.max_tokens_per_turn,
+            self.limits.max_cost_per_turn,
+        );
+        if turn_violation.is_some() {
+            return Ok(turn_violation);
+        }
+
+        self.check_spent(
+            data_service,
+            record.project_id.as_deref(),
+            record.task_run_id.as_deref(),
+            Some(record),
+        )
+    }
+
+    fn check_spent(
+        &self,
+        data_service: &DataService,
+        project_id: Option<&str>,
+        task_run_id: Option<&str>,
+        pending: Option<&UsageRecord>,
+    ) -> Result<Option<BudgetViolation>, rusqlite::Error> {
+        let pending_tokens = pending.map_or(0, |record| record.usage.total_tokens());
+        let pending_cost = pending.map_or(0.0, |record| record.cost);
+
+        let run_limited = self.limits.max_tokens_per_task_run.is_some()
+            || self.limits.max_cost_per_task_run.is_some();
+        if let (Some(task_run_id), true) = (task_run_id, run_limited) {
+            let spent = Self::spent(
+                data_service,
+                UsageReportQuery {
+                    task_run_id: Some(task_run_id.to_string()),
+                    ..UsageReportQuery::default()
+                },
+            )?;
+            let violation = Self::exceeded(
+                BudgetScope::TaskRun,
+                spent.total_tokens + pending_tokens,
+                spent.cost + pending_cost,
+                self.limits.max_tokens_per_task_run,
+                self.limits.max_cost_per_task_run,
+            );
+            if violation.is_some() {
+                return Ok(violation);
+            }
+        }
+
+        let project_limited = self.limits.max_tokens_per_project_per_day.is_some()
+            || self.limits.max_cost_per_project_per_day.is_some();
+        if let (Some(project_id), true) = (project_id, project_limited) {
+            let spent = Self::spent(
+                data_service,
+                UsageReportQuery {
+                    project_id: Some(project_id.to_string()),
+                    from: Some(data_service.current_day_start_millis()?),
+                    ..UsageReportQuery::default()
+                },
+            )?;
+            return Ok(Self::exceeded(
+                BudgetScope::ProjectDay,
+                spent.total_tokens + pending_tokens,
+                spent.cost + pending_cost,
+                self.limits.max_tokens_per_project_per_day,
+                self.limits.max_cost_per_project_per_day,
+            ));
+        }
+
+        Ok(None)
+    }
+
+    fn spent(
+        data_service: &DataService,
+        query: UsageReportQuery,
+    ) -> Result<UsageReportRow, rusqlite::Error> {
+        Ok(data_service.load_usage_report(&query)?.total)
+    }
+
+    fn exceeded(
+        scope: BudgetScope,
+        tokens: u64,
+        cost: f64,
+        max_tokens: Option<u64>,
+        max_cost: Option<f64>,
+    ) -> Option<BudgetViolation> {
+        if let Some(limit) = max_tokens.filter(|limit| tokens >= *limit) {
+            return Some(BudgetViolation {
+                scope,
+                unit: BudgetUnit::Tokens,
+                limit: limit as f64,
+                actual: tokens as f64,
+            });
+        }
+
+        max_cost
+            .filter(|limit| cost >= *limit)
+            .map(|limit| BudgetViolation {
+                scope,
+                unit: BudgetUnit::Cost,
+                limit,
+                actual: cost,
+            })
+    }
+}
+
+impl<'a, H: CodexEventHandler> BudgetEventHandler<'a, H> {
+    pub fn new(
+        budget: &'a BudgetService,
+        data_service: &'a Mutex<DataService>,
+        sessions: &'a SessionManager,
+        pricing: &'a [ModelPricing],
+        turn: UsageRecord,
+        inner: H,
+    ) -> Self {
+        Self {
+            budget,
+            data_service,
+            sessions,
+            pricing,
+            turn,
+            inner,
+        }
+    }
+
+    fn check(&self, run_id: &str, usage: &TokenUsage) {
+        let record = UsageRecord {
+            usage: *usage,
+            cost: ModelPricing::estimate_cost(self.pricing, &self.turn.model, usage),
+            ..self.turn.clone()
+        };
+
+        let result = match self.data_service.lock() {
+            Ok(service) => self
+                .budget
+                .check_during_turn(&service, &record)
+                .map_err(|error| error.to_string()),
+            Err(error) => Err(error.to_string()),
+        };
+
+        match result {
+            Ok(Some(violation)) => {
+                self.sessions.cancel(run_id, &violation.reason());
+            }
+            Ok(None) => {}
+            Err(error) => log::error!("failed to check budget of run {}: {}", run_id, error),
+        }
+    }
+}
+
+impl<H: CodexEventHandler> CodexEventHandler for BudgetEventHandler<'_, H> {
+    fn on_item(&self, run_id: &str, item: Value) {
+        self.inner.on_item(run_id, item);
+    }
+
+    fn on_item_completed(&self, run_id: &str, item: Value) {
+        self.inner.on_item_completed(run_id, item);
+    }
+
+    fn on_done(&self, run_id: &str, usage: Value) {
+        self.inner.on_done(run_id, usage);
+    }
+
+    fn on_usage(&self, run_id: &str, usage: &TokenUsage) {
+        self.check(run_id, usage);
+        self.inner.on_usage(run_id, usage);
+    }
+
+    fn on_thread_started(&self, run_id: &str, thread_info: Value) {
+        self.inner.on_thread_started(run_id, thread_info);
+    }
+
+    fn cancel_reason(&self, run_id: &str) -> Option<String> {
+        self.inner.cancel_reason(run_id)
+    }
+}
@@ -0,0 +1,111 @@
+use std::sync::Mutex;
+
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::models::chat::ChatMessage;
+use crate::models::event_handler::CodexEventHandler;
+use crate::models::usage::TokenUsage;
+use crate::services::data_service::DataService;
+use crate::utils::current_timestamp_millis;
+
+const USER_ROLE: &str = "user";
+const AGENT_ROLE: &str = "agent";
+
+pub struct PersistingEventHandler<'a, H: CodexEventHandler> {
+    data_service: &'a Mutex<DataService>,
+    thread_id: Option<String>,
+    model: String,
+    usage: Mutex<TokenUsage>,
+    inner: H,
+}
+
+impl<'a, H: CodexEventHandler> PersistingEventHandler<'a, H> {
+    pub fn new(
+        data_service: &'a Mutex<DataService>,
+        thread_id: Option<String>,
+        model: String,
+        inner: H,
+    ) -> Self {
+        Self {
+            data_service,
+            thread_id,
+            model,
+            usage: Mutex::new(TokenUsage::default()),
+            inner,
+        }
+    }
+
+    /// The latest usage the agent reported, kept so failed turns can still be accounted for.
+    pub fn usage(&self) -> TokenUsage {
+        self.usage.lock().map(|usage| *usage).unwrap_or_default()
+    }
+
+    pub fn save_prompt(&self, content: &str) {
+        self.save_message(USER_ROLE, content);
+    }
+
+    fn save_message(&self, role: &str, content: &str) {
+        let Some(thread_id) = &self.thread_id else {
+            return;
+        };
+
+        let message = ChatMessage {
+            id: Uuid::new_v4().to_string(),
+            thread_id: thread_id.clone(),
+            role: role.to_string(),
+            content: content.to_string(),
+            model: self.model.clone(),
+            created_at: current_timestamp_millis(),
+        };
+
+        let result = match self.data_service.lock() {
+            Ok(service) => service
+                .save_chat_message(&message)
+                .map_err(|error| error.to_string()),
+            Err(error) => Err(error.to_string()),
+        };
+
+        if let Err(error) = result {
+            log::error!(
+                "failed to persist chat message for {}: {}",
+                thread_id,
+                error
+            );
+        }
+    }
+}
+
+impl<H: CodexEventHandler> CodexEventHandler for PersistingEventHandler<'_, H> {
+    fn on_item(&self, run_id: &str, item: Value) {
+        self.inner.on_item(run_id, item);
+    }
+
+    fn on_item_completed(&self, run_id: &str, item: Value) {
+        if item["type"] == "message" {
+            if let Some(content) = item["data"]["content"].as_str() {
+                self.save_message(AGENT_ROLE, content);
+            }
+        }
+        self.inner.on_item_completed(run_id, item);
+    }
+
+    fn on_done(&self, run_id: &str, usage: Value) {
+        self.inner.on_done(run_id, usage);
+    }
+
+    fn on_usage(&self, run_id: &str, usage: &TokenUsage) {
+        if let Ok(mut latest) = self.usage.lock() {
+            *latest = *usage;
+        }
+        self.inner.on_usage(run_id, usage);
+    }
+
+    fn cancel_reason(&self, run_id: &str) -> Option<String> {
+        self.inner.cancel_reason(run_id)
+    }
+
+    fn on_thread_started(&self, run_id: &str, thread_info: Value) {
+        self.inner.on_thread_started(run_id, thread_info);
+    }
+}
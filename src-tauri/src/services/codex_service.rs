@@ -1,29 +1,48 @@
-use crate::models::chat::{ChatRequest, ChatResponse};
+use crate::models::chat::{ChatRequest, ChatResponse, TurnOutcome};
 use crate::models::event_handler::CodexEventHandler;
+use crate::models::task::TaskSandbox;
+use crate::models::usage::TokenUsage;
+use crate::services::recording_service::RecordingService;
 use codex_sdk::{
-    ApprovalMode, Codex, CodexOptions, SandboxMode, ThreadEvent, ThreadOptions, TurnOptions,
-    WebSearchMode,
+    ApprovalMode, Codex, CodexOptions, SandboxMode, ThreadEvent, ThreadItem, ThreadOptions,
+    TurnOptions, WebSearchMode,
 };
-use futures::StreamExt;
+use futures::{Stream, StreamExt};
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 pub struct CodexService {
     codex: Codex,
+    recording_service: RecordingService,
+    recording_enabled: AtomicBool,
 }
 
 impl CodexService {
-    pub fn new() -> Self {
+    pub fn new(app_data_dir: PathBuf) -> Self {
         let options = CodexOptions::default();
         Self {
             codex: Codex::new(options)
                 .expect("failed to initialize codex-sdk in CodexService::new"),
+            recording_service: RecordingService::new(app_data_dir),
+            recording_enabled: AtomicBool::new(false),
         }
     }
 
+    pub fn recording_service(&self) -> &RecordingService {
+        &self.recording_service
+    }
+
+    pub fn set_recording_enabled(&self, enabled: bool) {
+        self.recording_enabled.store(enabled, Ordering::Relaxed);
+    }
+
     pub async fn invoke_stream<H: CodexEventHandler>(
         &self,
-        payload: ChatRequest,
+        mut payload: ChatRequest,
         handler: H,
-    ) -> Result<(), String> {
+    ) -> Result<TurnOutcome, String> {
+        let run_id = payload.ensure_run_id();
         let trimmed_prompt = payload.content.trim();
 
         if trimmed_prompt.is_empty() {
@@ -35,7 +54,11 @@ impl CodexService {
             working_directory: payload.working_directory.clone(),
             network_access_enabled: Some(true),
             approval_policy: Some(ApprovalMode::Never),
-            sandbox_mode: Some(SandboxMode::DangerFullAccess),
+            sandbox_mode: Some(match payload.sandbox {
+                Some(TaskSandbox::ReadOnly) => SandboxMode::ReadOnly,
+                Some(TaskSandbox::WorkspaceWrite) => SandboxMode::WorkspaceWrite,
+                Some(TaskSandbox::DangerFullAccess) | None => SandboxMode::DangerFullAccess,
+            }),
             web_search_mode: Some(WebSearchMode::Cached),
             ..ThreadOptions::default()
         };
@@ -47,6 +70,10 @@ impl CodexService {
         );
         log::info!("Thread options: {}", thread_options);
 
+        let turn_options = TurnOptions {
+            output_schema: payload.output_schema.take(),
+        };
+
         let thread = if let Some(id) = payload.thread_id {
             self.codex.resume_thread(id, thread_options)
         } else {
@@ -54,40 +81,82 @@ impl CodexService {
         };
 
         let streamed = thread
-            .run_streamed(trimmed_prompt.into(), TurnOptions::default())
+            .run_streamed(trimmed_prompt.into(), turn_options)
             .map_err(|e| e.to_string())?;
-        let mut events = streamed.events;
+
+        let mut recorder = if self.recording_enabled.load(Ordering::Relaxed) {
+            match self.recording_service.start_recording() {
+                Ok(recorder) => Some(recorder),
+                Err(error) => {
+                    log::error!("session recording disabled for turn: {}", error);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let events = streamed.events.inspect(|event| {
+            if let (Some(recorder), Ok(event)) = (recorder.as_mut(), event) {
+                recorder.record(event);
+            }
+        });
+
+        Self::handle_events(events, &run_id, &handler).await
+    }
+
+    pub async fn handle_events<S, E, H>(
+        mut events: S,
+        run_id: &str,
+        handler: &H,
+    ) -> Result<TurnOutcome, String>
+    where
+        S: Stream<Item = Result<ThreadEvent, E>> + Unpin,
+        E: Display,
+        H: CodexEventHandler,
+    {
+        let mut outcome = TurnOutcome {
+            run_id: run_id.to_string(),
+            ..TurnOutcome::default()
+        };
 
         while let Some(event) = events.next().await {
             match event.map_err(|e| e.to_string())? {
                 ThreadEvent::ThreadStarted { thread_id } => {
                     log::info!(" Turn started with thread ID: {}", thread_id);
+                    outcome.thread_id = Some(thread_id.clone());
                     let response = ChatResponse::ThreadStarted { thread_id };
-                    handler.on_thread_started(response.to_json());
+                    handler.on_thread_started(run_id, response.to_json());
                 }
                 ThreadEvent::ItemUpdated { item } => {
                     log::info!("Received item update: {:?}", item);
                     let response = ChatResponse::from(item);
-                    handler.on_item(response.to_json());
+                    handler.on_item(run_id, response.to_json());
                 }
                 ThreadEvent::ItemCompleted { item } => {
                     log::info!("Received item: {:?}", item);
+                    if let ThreadItem::AgentMessage { text, .. } = &item {
+                        outcome.last_message = Some(text.clone());
+                    }
                     let response = ChatResponse::from(item);
-                    handler.on_item(response.to_json());
+                    handler.on_item_completed(run_id, response.to_json());
                 }
                 ThreadEvent::TurnCompleted { usage } => {
                     log::info!("Turn completed with usage: {:?}", usage);
-                    let response = ChatResponse::Done {
-                        total_tokens: usage.output_tokens as u32,
-                    };
-                    handler.on_done(response.to_json());
+                    outcome.usage = TokenUsage::from(&usage);
+                    handler.on_usage(run_id, &outcome.usage);
+                    if let Some(reason) = Self::cancelled(run_id, handler) {
+                        return Err(reason);
+                    }
+                    let response = ChatResponse::done(outcome.usage);
+                    handler.on_done(run_id, response.to_json());
                 }
                 ThreadEvent::TurnFailed { error } => {
                     log::error!("Turn failed: {:?}", error);
                     let response = ChatResponse::Error {
                         message: error.message.clone(),
                     };
-                    handler.on_done(response.to_json());
+                    handler.on_done(run_id, response.to_json());
                     return Err(error.message);
                 }
                 ThreadEvent::ThreadErrorEvent { message } => {
@@ -95,13 +164,28 @@ impl CodexService {
                     let response = ChatResponse::Error {
                         message: message.clone(),
                     };
-                    handler.on_done(response.to_json());
+                    handler.on_done(run_id, response.to_json());
                     return Err(message);
                 }
                 _ => {}
             }
+
+            if let Some(reason) = Self::cancelled(run_id, handler) {
+                return Err(reason);
+            }
         }
 
-        Ok(())
+        Ok(outcome)
+    }
+
+    /// Ends the turn with an error event when its run was cancelled.
+    fn cancelled<H: CodexEventHandler>(run_id: &str, handler: &H) -> Option<String> {
+        let reason = handler.cancel_reason(run_id)?;
+        log::info!("Turn cancelled: {}", reason);
+        let response = ChatResponse::Error {
+            message: reason.clone(),
+        };
+        handler.on_done(run_id, response.to_json());
+        Some(reason)
     }
 }
@@ -1,8 +1,30 @@
-use std::io::Write;
-use std::process::{Command, Stdio};
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::models::verification::CommandCheck;
+
+/// Longest output kept per check; the end is kept since that is where
+/// build and test failures are reported.
+const MAX_CHECK_OUTPUT_CHARS: usize = 8000;
+const CHECK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+type PipeReader = (Arc<Mutex<Vec<u8>>>, JoinHandle<()>);
 
 pub struct CommandService;
 
+/// A check's shell and every process it starts, so a timeout stops them
+/// all: its own process group on Unix, a job object on Windows. Processes
+/// still running when the shell exits are stopped too.
+struct ProcessGroup {
+    #[cfg(unix)]
+    id: libc::pid_t,
+    #[cfg(windows)]
+    job: windows_sys::Win32::Foundation::HANDLE,
+}
+
 impl CommandService {
     pub fn new() -> Self {
         Self
@@ -66,4 +88,244 @@ impl CommandService {
 
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
+
+    /// Runs each command through the shell in `cwd`, stopping at the first
+    /// one that fails.
+    pub fn verify(&self, commands: &[String], cwd: &str, timeout: Duration) -> Vec<CommandCheck> {
+        let mut checks = Vec::new();
+        for command in commands
+            .iter()
+            .map(|command| command.trim())
+            .filter(|command| !command.is_empty())
+        {
+            let check = self.run_check(command, cwd, timeout);
+            let passed = check.passed();
+            checks.push(check);
+            if !passed {
+                break;
+            }
+        }
+        checks
+    }
+
+    /// Runs a shell command line, killing it once `timeout` has passed.
+    pub fn run_check(&self, command: &str, cwd: &str, timeout: Duration) -> CommandCheck {
+        log::info!("Running check: {command} in directory: {cwd}");
+        let started = Instant::now();
+        let mut check = CommandCheck {
+            command: command.to_string(),
+            exit_code: None,
+            output: String::new(),
+            timed_out: false,
+            duration_ms: 0,
+        };
+
+        let mut shell = if cfg!(target_os = "windows") {
+            let mut cmd = Command::new("cmd");
+            cmd.args(["/C", command]);
+            cmd
+        } else {
+            let mut cmd = Command::new("sh");
+            cmd.args(["-c", command]);
+            cmd
+        };
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut shell, 0);
+        // The shell starts suspended so it joins the job before it can start
+        // anything; `ProcessGroup::new` resumes it.
+        #[cfg(windows)]
+        std::os::windows::process::CommandExt::creation_flags(
+            &mut shell,
+            windows_sys::Win32::System::Threading::CREATE_SUSPENDED,
+        );
+        let mut child = match shell
+            .current_dir(cwd)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(error) => {
+                check.output = format!("Failed to spawn process: {error}");
+                return check;
+            }
+        };
+        let group = ProcessGroup::new(&child);
+        let deadline = started + timeout;
+
+        let stdout = Self::drain(child.stdout.take());
+        let stderr = Self::drain(child.stderr.take());
+        let (exit_code, timed_out) = Self::wait(&mut child, &group, deadline);
+        group.kill();
+
+        // Processes that left the group may still hold the pipes open, so
+        // the readers are only waited for until the deadline.
+        let mut output = Self::collect(stdout, deadline);
+        let errors = Self::collect(stderr, deadline);
+        if !errors.is_empty() {
+            if !output.is_empty() && !output.ends_with('\n') {
+                output.push('\n');
+            }
+            output.push_str(&errors);
+        }
+
+        check.exit_code = exit_code;
+        check.timed_out = timed_out;
+        check.output = Self::tail(&output, MAX_CHECK_OUTPUT_CHARS);
+        check.duration_ms = started.elapsed().as_millis() as u64;
+        check
+    }
+
+    fn wait(child: &mut Child, group: &ProcessGroup, deadline: Instant) -> (Option<i32>, bool) {
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) => return (status.code(), false),
+                Ok(None) if Instant::now() >= deadline => {
+                    group.kill();
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return (None, true);
+                }
+                Ok(None) => thread::sleep(CHECK_POLL_INTERVAL),
+                Err(error) => {
+                    log::error!("failed to wait for check: {}", error);
+                    return (None, false);
+                }
+            }
+        }
+    }
+
+    fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> Option<PipeReader> {
+        pipe.map(|mut pipe| {
+            let buffer = Arc::new(Mutex::new(Vec::new()));
+            let shared = buffer.clone();
+            let handle = thread::spawn(move || {
+                let mut chunk = [0u8; 4096];
+                while let Ok(read) = pipe.read(&mut chunk) {
+                    if read == 0 {
+                        break;
+                    }
+                    if let Ok(mut buffer) = shared.lock() {
+                        buffer.extend_from_slice(&chunk[..read]);
+                    }
+                }
+            });
+            (buffer, handle)
+        })
+    }
+
+    fn collect(reader: Option<PipeReader>, deadline: Instant) -> String {
+        let Some((buffer, handle)) = reader else {
+            return String::new();
+        };
+        while !handle.is_finished() && Instant::now() < deadline {
+            thread::sleep(CHECK_POLL_INTERVAL);
+        }
+        if handle.is_finished() {
+            let _ = handle.join();
+        }
+
+        buffer
+            .lock()
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .unwrap_or_default()
+    }
+
+    fn tail(text: &str, max_chars: usize) -> String {
+        let count = text.chars().count();
+        if count <= max_chars {
+            return text.to_string();
+        }
+
+        let kept: String = text.chars().skip(count - max_chars).collect();
+        format!("... {} characters omitted ...\n{kept}", count - max_chars)
+    }
+}
+
+impl ProcessGroup {
+    #[cfg(unix)]
+    fn new(child: &Child) -> Self {
+        // The shell was spawned as the leader of a new group.
+        Self {
+            id: child.id() as libc::pid_t,
+        }
+    }
+
+    #[cfg(windows)]
+    fn new(child: &Child) -> Self {
+        use std::os::windows::io::AsRawHandle;
+        use windows_sys::Win32::System::JobObjects::{AssignProcessToJobObject, CreateJobObjectW};
+
+        // Without a job only the shell itself can be killed.
+        unsafe {
+            let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+            if !job.is_null() && AssignProcessToJobObject(job, child.as_raw_handle()) == 0 {
+                log::error!("failed to add check {} to a job object", child.id());
+            }
+            Self::resume(child.id());
+            Self { job }
+        }
+    }
+
+    /// Resumes the threads of a process that was created suspended.
+    #[cfg(windows)]
+    unsafe fn resume(process_id: u32) {
+        use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+        use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+            CreateToolhelp32Snapshot, Thread32First, Thread32Next, TH32CS_SNAPTHREAD, THREADENTRY32,
+        };
+        use windows_sys::Win32::System::Threading::{
+            OpenThread, ResumeThread, THREAD_SUSPEND_RESUME,
+        };
+
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0);
+        if snapshot == INVALID_HANDLE_VALUE {
+            log::error!("failed to resume check {}", process_id);
+            return;
+        }
+        let mut entry: THREADENTRY32 = std::mem::zeroed();
+        entry.dwSize = std::mem::size_of::<THREADENTRY32>() as u32;
+        let mut found = Thread32First(snapshot, &mut entry) != 0;
+        while found {
+            if entry.th32OwnerProcessID == process_id {
+                let thread = OpenThread(THREAD_SUSPEND_RESUME, 0, entry.th32ThreadID);
+                if !thread.is_null() {
+                    ResumeThread(thread);
+                    CloseHandle(thread);
+                }
+            }
+            found = Thread32Next(snapshot, &mut entry) != 0;
+        }
+        CloseHandle(snapshot);
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn new(_child: &Child) -> Self {
+        Self {}
+    }
+
+    fn kill(&self) {
+        #[cfg(unix)]
+        unsafe {
+            libc::kill(-self.id, libc::SIGKILL);
+        }
+        #[cfg(windows)]
+        if !self.job.is_null() {
+            unsafe {
+                windows_sys::Win32::System::JobObjects::TerminateJobObject(self.job, 1);
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        if !self.job.is_null() {
+            unsafe {
+                windows_sys::Win32::Foundation::CloseHandle(self.job);
+            }
+        }
+    }
 }
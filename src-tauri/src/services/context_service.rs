@@ -0,0 +1,291 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use globset::{GlobBuilder, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::WalkBuilder;
+
+use crate::models::context::{ContextBlock, ContextFile, ContextSet};
+
+/// Project-level ignore file, in gitignore syntax, applied on top of `.gitignore`.
+pub const IGNORE_FILE: &str = ".vibeflow/ignore";
+/// Rough average for English text and code; good enough to budget with.
+const CHARS_PER_TOKEN: usize = 4;
+const MAX_FILE_BYTES: u64 = 256 * 1024;
+
+pub struct ContextService;
+
+/// The rules `list_files` skips paths by, for checking single paths without
+/// walking the project. Ignore files are read once per directory.
+pub struct IgnoreRules {
+    root: PathBuf,
+    project: Gitignore,
+    directories: HashMap<PathBuf, Option<Gitignore>>,
+}
+
+impl ContextService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Every file under `root` that is not ignored, relative and `/`-separated.
+    pub fn list_files(&self, root: &Path) -> Result<Vec<String>, String> {
+        Self::walk(root, root)
+    }
+
+    /// Like `list_files`, limited to the directory `relative` below `root`.
+    pub fn list_files_under(&self, root: &Path, relative: &str) -> Result<Vec<String>, String> {
+        Self::walk(root, &root.join(relative))
+    }
+
+    /// Files matching any of `patterns`; a pattern is a path or a glob.
+    pub fn resolve_patterns(
+        &self,
+        root: &Path,
+        patterns: &[String],
+    ) -> Result<Vec<String>, String> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let pattern = pattern.trim().trim_start_matches("./");
+            if pattern.is_empty() {
+                continue;
+            }
+            let glob = GlobBuilder::new(pattern.trim_end_matches('/'))
+                .literal_separator(true)
+                .build()
+                .map_err(|error| format!("invalid context pattern '{pattern}': {error}"))?;
+            builder.add(glob);
+            if !pattern.contains(['*', '?', '[', '{']) {
+                // A plain directory name pulls in everything below it.
+                let directory = format!("{}/**", pattern.trim_end_matches('/'));
+                builder.add(
+                    GlobBuilder::new(&directory)
+                        .literal_separator(true)
+                        .build()
+                        .map_err(|error| format!("invalid context pattern '{pattern}': {error}"))?,
+                );
+            }
+        }
+        let globs = builder
+            .build()
+            .map_err(|error| format!("invalid context patterns: {error}"))?;
+
+        Ok(self
+            .list_files(root)?
+            .into_iter()
+            .filter(|path| globs.is_match(path))
+            .collect())
+    }
+
+    pub fn estimate_tokens(text: &str) -> usize {
+        text.chars().count().div_ceil(CHARS_PER_TOKEN)
+    }
+
+    /// Renders the files of `sets` in order, skipping binary files and any
+    /// file that would take the block over `token_budget`.
+    pub fn build_block(
+        &self,
+        root: &Path,
+        sets: &[ContextSet],
+        token_budget: usize,
+    ) -> Result<ContextBlock, String> {
+        let mut seen = BTreeSet::new();
+        let mut paths = Vec::new();
+        for set in sets {
+            for path in self.resolve_patterns(root, &set.patterns)? {
+                if seen.insert(path.clone()) {
+                    paths.push(path);
+                }
+            }
+        }
+
+        let mut block = ContextBlock {
+            content: "# Project context\n".to_string(),
+            token_budget,
+            ..ContextBlock::default()
+        };
+        let mut used = Self::estimate_tokens(&block.content);
+        for path in paths {
+            let Some(text) = Self::read_text(&root.join(&path)) else {
+                continue;
+            };
+            let section = Self::render_file(&path, &text);
+            let tokens = Self::estimate_tokens(&section);
+            let file = ContextFile { path, tokens };
+            if used + tokens > token_budget {
+                block.omitted.push(file);
+                continue;
+            }
+
+            used += tokens;
+            block.content.push_str(&section);
+            block.files.push(file);
+        }
+
+        block.estimated_tokens = used;
+        Ok(block)
+    }
+
+    pub fn prepend(block: &ContextBlock, prompt: &str) -> String {
+        if block.files.is_empty() {
+            return prompt.to_string();
+        }
+
+        format!("{}\n---\n\n{prompt}", block.content)
+    }
+
+    fn walk(root: &Path, start: &Path) -> Result<Vec<String>, String> {
+        let ignore = Self::project_ignore(root)?;
+        let walk_root = root.to_path_buf();
+        let walker = WalkBuilder::new(start)
+            .hidden(false)
+            .require_git(false)
+            .filter_entry(move |entry| {
+                if entry.file_name() == ".git" {
+                    return false;
+                }
+                let Ok(relative) = entry.path().strip_prefix(&walk_root) else {
+                    return true;
+                };
+                let is_dir = entry
+                    .file_type()
+                    .is_some_and(|file_type| file_type.is_dir());
+                relative.as_os_str().is_empty() || !ignore.matched(relative, is_dir).is_ignore()
+            })
+            .build();
+
+        let mut files = Vec::new();
+        for entry in walker {
+            let entry = entry.map_err(|error| format!("failed to scan project: {error}"))?;
+            if !entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_file())
+            {
+                continue;
+            }
+            if let Ok(relative) = entry.path().strip_prefix(root) {
+                files.push(relative.to_string_lossy().replace('\\', "/"));
+            }
+        }
+
+        files.sort();
+        Ok(files)
+    }
+
+    fn project_ignore(root: &Path) -> Result<Gitignore, String> {
+        let mut builder = GitignoreBuilder::new(root);
+        let path = root.join(IGNORE_FILE);
+        if path.is_file() {
+            if let Some(error) = builder.add(&path) {
+                return Err(format!("failed to read {IGNORE_FILE}: {error}"));
+            }
+        }
+
+        builder
+            .build()
+            .map_err(|error| format!("failed to read {IGNORE_FILE}: {error}"))
+    }
+
+    fn read_text(path: &Path) -> Option<String> {
+        let metadata = fs::metadata(path).ok()?;
+        if metadata.len() > MAX_FILE_BYTES {
+            return None;
+        }
+
+        let bytes = fs::read(path).ok()?;
+        if bytes.contains(&0) {
+            return None;
+        }
+        String::from_utf8(bytes).ok()
+    }
+
+    fn render_file(path: &str, text: &str) -> String {
+        let language = Path::new(path)
+            .extension()
+            .map(|extension| extension.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let fence = if text.contains("```") { "````" } else { "```" };
+        let newline = if text.ends_with('\n') { "" } else { "\n" };
+
+        format!("\n## {path}\n\n{fence}{language}\n{text}{newline}{fence}\n")
+    }
+}
+
+impl IgnoreRules {
+    pub fn new(root: &Path) -> Result<Self, String> {
+        Ok(Self {
+            root: root.to_path_buf(),
+            project: ContextService::project_ignore(root)?,
+            directories: HashMap::new(),
+        })
+    }
+
+    /// Whether an ignore file sets rules, so changing it can show or hide
+    /// files anywhere below its directory.
+    pub fn is_ignore_file(relative: &str) -> bool {
+        let file_name = Path::new(relative)
+            .file_name()
+            .and_then(|name| name.to_str());
+        relative == IGNORE_FILE || matches!(file_name, Some(".gitignore" | ".ignore"))
+    }
+
+    /// Whether `relative` or one of its parent directories is skipped by
+    /// `.git`, the project ignore file or a `.gitignore`/`.ignore` on the way.
+    pub fn is_ignored(&mut self, relative: &str, is_dir: bool) -> bool {
+        let relative = Path::new(relative);
+        if relative
+            .components()
+            .any(|component| component.as_os_str() == ".git")
+        {
+            return true;
+        }
+        if self
+            .project
+            .matched_path_or_any_parents(relative, is_dir)
+            .is_ignore()
+        {
+            return true;
+        }
+
+        let path = self.root.join(relative);
+        let mut directory = self.root.clone();
+        let mut ignored = self.directory_matched(&directory, &path, is_dir);
+        for component in relative.parent().into_iter().flat_map(Path::components) {
+            directory.push(component);
+            // Rules in deeper directories override those above them.
+            ignored = self
+                .directory_matched(&directory, &path, is_dir)
+                .or(ignored);
+        }
+        ignored.unwrap_or(false)
+    }
+
+    fn directory_matched(&mut self, directory: &Path, path: &Path, is_dir: bool) -> Option<bool> {
+        let root = &self.root;
+        let rules = self
+            .directories
+            .entry(directory.to_path_buf())
+            .or_insert_with(|| {
+                let mut builder = GitignoreBuilder::new(directory);
+                let mut files = vec![directory.join(".gitignore"), directory.join(".ignore")];
+                if directory == root {
+                    files.push(directory.join(".git/info/exclude"));
+                }
+                for file in files.into_iter().filter(|file| file.is_file()) {
+                    builder.add(file);
+                }
+                builder.build().ok()
+            })
+            .as_ref()?;
+
+        let matched = rules.matched_path_or_any_parents(path, is_dir);
+        if matched.is_ignore() {
+            Some(true)
+        } else if matched.is_whitelist() {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
@@ -1,14 +1,31 @@
 use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
 
-use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Transaction};
 
 use crate::models::agent_rule::AgentRule;
-use crate::models::chat::{ChatMessage, ChatThread};
+use crate::models::chat::{ChatMessage, ChatThread, ChatTurn, ChatTurnStatus};
+use crate::models::context::ContextSet;
 use crate::models::project::Project;
-use crate::models::task::{Task, TaskStatus, TaskStep, TaskStepType};
+use crate::models::prompt::{PromptQuery, PromptRecord};
+use crate::models::schedule::{MissedRunPolicy, TaskSchedule};
+use crate::models::symbol::{CodeSymbol, SymbolFile, SymbolKind};
+use crate::models::task::{
+    StepRunWhen, Task, TaskRun, TaskRunStep, TaskSandbox, TaskStatus, TaskStep, TaskStepResult,
+    TaskStepType,
+};
+use crate::models::usage::{
+    TokenUsage, UsageRecord, UsageReport, UsageReportQuery, UsageReportRow,
+};
+use crate::utils::current_timestamp_millis;
 
 const PROJECT_DATABASE_FILE_NAME: &str = "projects.db.sqlite";
+const INTERRUPTED_TURN_MESSAGE: &str =
+    "Turn interrupted: the app exited before the agent finished.";
+const INTERRUPTED_TASK_RUN_MESSAGE: &str = "Run paused: the app exited before the task finished.";
+const UNSTARTED_SCHEDULED_RUN_MESSAGE: &str =
+    "Run skipped: the app exited before the scheduled run started.";
+const DEFAULT_PROMPT_LIMIT: u32 = 100;
 
 pub struct DataService {
     db_path: PathBuf,
@@ -98,7 +115,7 @@ impl DataService {
             return Ok(existing_project);
         }
 
-        let now = Self::current_timestamp_millis();
+        let now = current_timestamp_millis();
         let project = Project {
             id: format!("project-{now}"),
             name: Self::project_name_from_path(project_path),
@@ -146,26 +163,398 @@ impl DataService {
         Ok(())
     }
 
-    pub fn save_chat_thread(&self, thread: &ChatThread) -> Result<(), rusqlite::Error> {
+    pub fn save_context_set(&self, context_set: &ContextSet) -> Result<(), rusqlite::Error> {
         let connection = self.open_connection()?;
         connection.execute(
-            "INSERT INTO chat_threads (id, project_id, title, created_at, updated_at)
+            "INSERT INTO context_sets (id, project_id, name, patterns, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(id) DO UPDATE SET
+               name = excluded.name,
+               patterns = excluded.patterns,
+               updated_at = excluded.updated_at",
+            params![
+                context_set.id,
+                context_set.project_id,
+                context_set.name,
+                serde_json::to_string(&context_set.patterns).unwrap_or_else(|_| "[]".into()),
+                context_set.created_at,
+                context_set.updated_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn load_context_sets(&self, project_id: &str) -> Result<Vec<ContextSet>, rusqlite::Error> {
+        let connection = self.open_connection()?;
+        let mut statement = connection.prepare(
+            "SELECT id, project_id, name, patterns, created_at, updated_at
+             FROM context_sets
+             WHERE project_id = ?1
+             ORDER BY name ASC",
+        )?;
+
+        let context_sets = statement
+            .query_map(params![project_id], |row| {
+                Ok(ContextSet {
+                    id: row.get(0)?,
+                    project_id: row.get(1)?,
+                    name: row.get(2)?,
+                    patterns: Self::parse_string_list(&row.get::<usize, String>(3)?),
+                    created_at: row.get(4)?,
+                    updated_at: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(context_sets)
+    }
+
+    pub fn delete_context_set(&self, context_set_id: &str) -> Result<(), rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection.execute(
+            "DELETE FROM context_sets WHERE id = ?1",
+            params![context_set_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn load_symbol_files(&self, project_id: &str) -> Result<Vec<SymbolFile>, rusqlite::Error> {
+        let connection = self.open_connection()?;
+        let mut statement = connection.prepare(
+            "SELECT path, modified_at, size
+             FROM symbol_files
+             WHERE project_id = ?1
+             ORDER BY path ASC",
+        )?;
+
+        let files = statement
+            .query_map(params![project_id], |row| {
+                Ok(SymbolFile {
+                    path: row.get(0)?,
+                    modified_at: row.get(1)?,
+                    size: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(files)
+    }
+
+    /// Replaces everything indexed for `file` with `symbols`.
+    pub fn replace_file_symbols(
+        &self,
+        project_id: &str,
+        file: &SymbolFile,
+        symbols: &[CodeSymbol],
+    ) -> Result<(), rusqlite::Error> {
+        let mut connection = self.open_connection()?;
+        let transaction = connection.transaction()?;
+
+        transaction.execute(
+            "DELETE FROM symbols WHERE project_id = ?1 AND path = ?2",
+            params![project_id, file.path],
+        )?;
+        transaction.execute(
+            "INSERT INTO symbol_files (project_id, path, modified_at, size, indexed_at)
              VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(project_id, path) DO UPDATE SET
+               modified_at = excluded.modified_at,
+               size = excluded.size,
+               indexed_at = excluded.indexed_at",
+            params![
+                project_id,
+                file.path,
+                file.modified_at,
+                file.size,
+                current_timestamp_millis()
+            ],
+        )?;
+        for symbol in symbols {
+            transaction.execute(
+                "INSERT INTO symbols (
+                    project_id, path, name, kind, language, start_line, end_line, container, signature
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    project_id,
+                    file.path,
+                    symbol.name,
+                    Self::symbol_kind_as_str(symbol.kind),
+                    symbol.language,
+                    symbol.start_line as i64,
+                    symbol.end_line as i64,
+                    symbol.container,
+                    symbol.signature
+                ],
+            )?;
+        }
+
+        transaction.commit()
+    }
+
+    pub fn delete_symbol_files(
+        &self,
+        project_id: &str,
+        paths: &[String],
+    ) -> Result<(), rusqlite::Error> {
+        let mut connection = self.open_connection()?;
+        let transaction = connection.transaction()?;
+        for path in paths {
+            transaction.execute(
+                "DELETE FROM symbols WHERE project_id = ?1 AND path = ?2",
+                params![project_id, path],
+            )?;
+            transaction.execute(
+                "DELETE FROM symbol_files WHERE project_id = ?1 AND path = ?2",
+                params![project_id, path],
+            )?;
+        }
+        transaction.commit()
+    }
+
+    /// Symbols whose name matches `query`: exact matches first, then
+    /// prefixes, then substrings, each case-insensitive.
+    pub fn search_symbols(
+        &self,
+        project_id: &str,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<CodeSymbol>, rusqlite::Error> {
+        let connection = self.open_connection()?;
+        let query = query.trim().to_lowercase();
+        let escaped = query
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let mut statement = connection.prepare(
+            "SELECT path, name, kind, language, start_line, end_line, container, signature
+             FROM symbols
+             WHERE project_id = ?1 AND lower(name) LIKE ?3 ESCAPE '\\'
+             ORDER BY
+               CASE
+                 WHEN lower(name) = ?2 THEN 0
+                 WHEN lower(name) LIKE ?4 ESCAPE '\\' THEN 1
+                 ELSE 2
+               END,
+               length(name) ASC,
+               name ASC,
+               path ASC,
+               start_line ASC
+             LIMIT ?5",
+        )?;
+
+        let symbols = statement
+            .query_map(
+                params![
+                    project_id,
+                    query,
+                    format!("%{escaped}%"),
+                    format!("{escaped}%"),
+                    limit as i64
+                ],
+                Self::map_symbol,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(symbols)
+    }
+
+    /// Definitions named exactly `name`, optionally only those in `path`.
+    pub fn find_symbols(
+        &self,
+        project_id: &str,
+        name: &str,
+        path: Option<&str>,
+    ) -> Result<Vec<CodeSymbol>, rusqlite::Error> {
+        let connection = self.open_connection()?;
+        let mut statement = connection.prepare(
+            "SELECT path, name, kind, language, start_line, end_line, container, signature
+             FROM symbols
+             WHERE project_id = ?1 AND name = ?2 AND (?3 IS NULL OR path = ?3)
+             ORDER BY path ASC, start_line ASC",
+        )?;
+
+        let symbols = statement
+            .query_map(params![project_id, name, path], Self::map_symbol)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(symbols)
+    }
+
+    pub fn save_chat_thread(&self, thread: &ChatThread) -> Result<(), rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection.execute(
+            "INSERT INTO chat_threads (id, project_id, title, created_at, updated_at, agent_thread_id, agent_id, model)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
              ON CONFLICT(id) DO UPDATE SET
                project_id = excluded.project_id,
                title = excluded.title,
-               updated_at = excluded.updated_at",
+               updated_at = excluded.updated_at,
+               agent_thread_id = COALESCE(excluded.agent_thread_id, chat_threads.agent_thread_id),
+               agent_id = COALESCE(excluded.agent_id, chat_threads.agent_id),
+               model = COALESCE(excluded.model, chat_threads.model)",
             params![
                 thread.id,
                 thread.project_id,
                 thread.title,
                 thread.created_at,
-                thread.updated_at
+                thread.updated_at,
+                thread.agent_thread_id,
+                thread.agent_id,
+                thread.model
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn update_chat_thread_agent(
+        &self,
+        thread_id: &str,
+        agent_thread_id: &str,
+        agent_id: Option<&str>,
+        model: Option<&str>,
+    ) -> Result<(), rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection.execute(
+            "UPDATE chat_threads
+             SET agent_thread_id = ?2,
+               agent_id = COALESCE(?3, agent_id),
+               model = COALESCE(?4, model)
+             WHERE id = ?1",
+            params![thread_id, agent_thread_id, agent_id, model],
+        )?;
+        Ok(())
+    }
+
+    pub fn load_chat_thread(&self, thread_id: &str) -> Result<Option<ChatThread>, rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection
+            .query_row(
+                "SELECT id, project_id, title, created_at, updated_at, agent_thread_id, agent_id, model
+                 FROM chat_threads
+                 WHERE id = ?1",
+                params![thread_id],
+                Self::map_chat_thread,
+            )
+            .optional()
+    }
+
+    pub fn start_chat_turn(&self, run_id: &str, thread_id: &str) -> Result<(), rusqlite::Error> {
+        let connection = self.open_connection()?;
+        let now = current_timestamp_millis();
+        connection.execute(
+            "INSERT INTO chat_turns (run_id, thread_id, status, error, started_at, updated_at)
+             VALUES (?1, ?2, ?3, NULL, ?4, ?4)
+             ON CONFLICT(run_id) DO UPDATE SET
+               status = excluded.status,
+               error = NULL,
+               updated_at = excluded.updated_at",
+            params![
+                run_id,
+                thread_id,
+                Self::chat_turn_status_as_str(ChatTurnStatus::Running),
+                now
             ],
         )?;
         Ok(())
     }
 
+    pub fn finish_chat_turn(
+        &self,
+        run_id: &str,
+        status: ChatTurnStatus,
+        error: Option<&str>,
+    ) -> Result<(), rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection.execute(
+            "UPDATE chat_turns SET status = ?2, error = ?3, updated_at = ?4 WHERE run_id = ?1",
+            params![
+                run_id,
+                Self::chat_turn_status_as_str(status),
+                error,
+                current_timestamp_millis()
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn load_chat_turns_by_thread(
+        &self,
+        thread_id: &str,
+    ) -> Result<Vec<ChatTurn>, rusqlite::Error> {
+        let connection = self.open_connection()?;
+        let mut statement = connection.prepare(
+            "SELECT run_id, thread_id, status, error, started_at, updated_at
+             FROM chat_turns
+             WHERE thread_id = ?1
+             ORDER BY started_at ASC",
+        )?;
+
+        let turns = statement
+            .query_map(params![thread_id], |row| {
+                Ok(ChatTurn {
+                    run_id: row.get(0)?,
+                    thread_id: row.get(1)?,
+                    status: Self::parse_chat_turn_status(&row.get::<usize, String>(2)?),
+                    error: row.get(3)?,
+                    started_at: row.get(4)?,
+                    updated_at: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(turns)
+    }
+
+    pub fn recover_interrupted_turns(&self) -> Result<usize, rusqlite::Error> {
+        let mut connection = self.open_connection()?;
+        let transaction = connection.transaction()?;
+        let now = current_timestamp_millis();
+
+        let running = transaction
+            .prepare("SELECT run_id, thread_id FROM chat_turns WHERE status = ?1")?
+            .query_map(
+                params![Self::chat_turn_status_as_str(ChatTurnStatus::Running)],
+                |row| Ok((row.get::<usize, String>(0)?, row.get::<usize, String>(1)?)),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (run_id, thread_id) in &running {
+            transaction.execute(
+                "UPDATE chat_turns SET status = ?2, error = ?3, updated_at = ?4 WHERE run_id = ?1",
+                params![
+                    run_id,
+                    Self::chat_turn_status_as_str(ChatTurnStatus::Interrupted),
+                    INTERRUPTED_TURN_MESSAGE,
+                    now
+                ],
+            )?;
+            transaction.execute(
+                "INSERT INTO chat_messages (id, thread_id, role, content, model, created_at)
+                 SELECT ?1, ?2, 'system', ?3, '', ?4
+                 WHERE EXISTS (SELECT 1 FROM chat_threads WHERE id = ?2)",
+                params![
+                    format!("{run_id}-interrupted"),
+                    thread_id,
+                    INTERRUPTED_TURN_MESSAGE,
+                    now
+                ],
+            )?;
+        }
+
+        transaction.execute(
+            "UPDATE prompts SET outcome = ?2, error = ?3, updated_at = ?4 WHERE outcome = ?1",
+            params![
+                Self::chat_turn_status_as_str(ChatTurnStatus::Running),
+                Self::chat_turn_status_as_str(ChatTurnStatus::Interrupted),
+                INTERRUPTED_TURN_MESSAGE,
+                now
+            ],
+        )?;
+
+        transaction.commit()?;
+        Ok(running.len())
+    }
+
     pub fn save_chat_message(&self, message: &ChatMessage) -> Result<(), rusqlite::Error> {
         let connection = self.open_connection()?;
         connection.execute(
@@ -200,68 +589,578 @@ impl DataService {
         Ok(())
     }
 
-    pub fn load_chat_threads_by_project(
+    pub fn load_chat_threads_by_project(
+        &self,
+        project_id: &str,
+        count: usize,
+    ) -> Result<Vec<ChatThread>, rusqlite::Error> {
+        let connection = self.open_connection()?;
+        let mut statement = connection.prepare(
+            "SELECT t.id, t.project_id, t.title, t.created_at, t.updated_at,
+                    t.agent_thread_id, t.agent_id, t.model
+             FROM chat_threads t
+             WHERE t.project_id = ?1
+               AND EXISTS (
+                 SELECT 1
+                 FROM chat_messages m
+                 WHERE m.thread_id = t.id
+               )
+             ORDER BY t.updated_at DESC
+             LIMIT ?2",
+        )?;
+
+        log::info!("Loading  threads: {}, count: {}", project_id, count);
+
+        let threads = statement
+            .query_map(params![project_id, count as i64], Self::map_chat_thread)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(threads)
+    }
+
+    pub fn load_chat_messages_by_thread(
+        &self,
+        thread_id: &str,
+    ) -> Result<Vec<ChatMessage>, rusqlite::Error> {
+        let connection = self.open_connection()?;
+        let mut statement = connection.prepare(
+            "SELECT id, thread_id, role, content, model, created_at
+             FROM chat_messages
+             WHERE thread_id = ?1
+             ORDER BY created_at ASC",
+        )?;
+
+        let messages = statement
+            .query_map(params![thread_id], |row| {
+                Ok(ChatMessage {
+                    id: row.get(0)?,
+                    thread_id: row.get(1)?,
+                    role: row.get(2)?,
+                    content: row.get(3)?,
+                    model: row.get(4)?,
+                    created_at: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(messages)
+    }
+
+    pub fn save_usage_record(&self, record: &UsageRecord) -> Result<(), rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection.execute(
+            "INSERT INTO usage_records (id, project_id, thread_id, task_run_id, model, input_tokens, cached_input_tokens, output_tokens, reasoning_output_tokens, cost, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                record.id,
+                record.project_id,
+                record.thread_id,
+                record.task_run_id,
+                record.model,
+                record.usage.input_tokens as i64,
+                record.usage.cached_input_tokens as i64,
+                record.usage.output_tokens as i64,
+                record.usage.reasoning_output_tokens as i64,
+                record.cost,
+                record.created_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn load_usage_report(
+        &self,
+        query: &UsageReportQuery,
+    ) -> Result<UsageReport, rusqlite::Error> {
+        let connection = self.open_connection()?;
+        let (filter, values) = Self::usage_filter(query);
+
+        let total = self
+            .load_usage_rows(&connection, "'total'", &filter, &values)?
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| UsageReportRow {
+                key: "total".to_string(),
+                ..UsageReportRow::default()
+            });
+
+        Ok(UsageReport {
+            total,
+            by_day: self.load_usage_rows(
+                &connection,
+                "strftime('%Y-%m-%d', created_at / 1000, 'unixepoch', 'localtime')",
+                &filter,
+                &values,
+            )?,
+            by_project: self.load_usage_rows(
+                &connection,
+                "COALESCE(project_id, '')",
+                &filter,
+                &values,
+            )?,
+            by_model: self.load_usage_rows(&connection, "model", &filter, &values)?,
+        })
+    }
+
+    pub fn update_task_step_status(
+        &self,
+        task_id: &str,
+        step_id: &str,
+        status: &TaskStatus,
+        status_reason: Option<&str>,
+    ) -> Result<(), rusqlite::Error> {
+        let connection = self.open_connection()?;
+        let updated_at = current_timestamp_millis();
+
+        connection.execute(
+            "UPDATE task_steps SET status = ?1, status_reason = ?2, updated_at = ?3
+             WHERE task_id = ?4 AND id = ?5",
+            params![
+                Self::task_status_as_str(status),
+                status_reason,
+                updated_at,
+                task_id,
+                step_id
+            ],
+        )?;
+
+        if matches!(status, TaskStatus::Failed) {
+            connection.execute(
+                "UPDATE tasks SET status = ?1, updated_at = ?2 WHERE id = ?3",
+                params![Self::task_status_as_str(status), updated_at, task_id],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn save_task_step_result(&self, result: &TaskStepResult) -> Result<(), rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection.execute(
+            "INSERT INTO task_step_results (task_run_id, task_id, step_id, summary, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(task_run_id, step_id) DO UPDATE SET
+               summary = excluded.summary,
+               created_at = excluded.created_at",
+            params![
+                result.task_run_id,
+                result.task_id,
+                result.step_id,
+                result.summary,
+                result.created_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Latest result of the run recorded for a step other than `step_id`.
+    pub fn load_previous_step_result(
+        &self,
+        task_run_id: &str,
+        step_id: &str,
+    ) -> Result<Option<TaskStepResult>, rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection
+            .query_row(
+                "SELECT task_run_id, task_id, step_id, summary, created_at
+                 FROM task_step_results
+                 WHERE task_run_id = ?1 AND step_id <> ?2
+                 ORDER BY created_at DESC, rowid DESC
+                 LIMIT 1",
+                params![task_run_id, step_id],
+                |row| {
+                    Ok(TaskStepResult {
+                        task_run_id: row.get(0)?,
+                        task_id: row.get(1)?,
+                        step_id: row.get(2)?,
+                        summary: row.get(3)?,
+                        created_at: row.get(4)?,
+                    })
+                },
+            )
+            .optional()
+    }
+
+    pub fn save_task_run(&self, run: &TaskRun) -> Result<(), rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection.execute(
+            "INSERT INTO task_runs (id, project_id, task_id, status, status_reason, chat_thread_id, schedule_id, started_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+             ON CONFLICT(id) DO UPDATE SET
+               status = excluded.status,
+               status_reason = excluded.status_reason,
+               chat_thread_id = excluded.chat_thread_id,
+               updated_at = excluded.updated_at",
+            params![
+                run.id,
+                run.project_id,
+                run.task_id,
+                Self::task_status_as_str(&run.status),
+                run.status_reason,
+                run.chat_thread_id,
+                run.schedule_id,
+                run.started_at,
+                run.updated_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn load_task_run(&self, task_run_id: &str) -> Result<Option<TaskRun>, rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection
+            .query_row(
+                "SELECT id, project_id, task_id, status, status_reason, chat_thread_id, schedule_id, started_at, updated_at
+                 FROM task_runs
+                 WHERE id = ?1",
+                params![task_run_id],
+                Self::map_task_run,
+            )
+            .optional()
+    }
+
+    pub fn load_latest_task_run(&self, task_id: &str) -> Result<Option<TaskRun>, rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection
+            .query_row(
+                "SELECT id, project_id, task_id, status, status_reason, chat_thread_id, schedule_id, started_at, updated_at
+                 FROM task_runs
+                 WHERE task_id = ?1
+                 ORDER BY started_at DESC, rowid DESC
+                 LIMIT 1",
+                params![task_id],
+                Self::map_task_run,
+            )
+            .optional()
+    }
+
+    /// Pauses the task runs the app was in the middle of when it exited, so
+    /// they can be resumed from their last finished step. Scheduled runs
+    /// that had started are paused the same way.
+    pub fn recover_interrupted_task_runs(&self) -> Result<usize, rusqlite::Error> {
+        let mut connection = self.open_connection()?;
+        let transaction = connection.transaction()?;
+        let now = current_timestamp_millis();
+        let in_progress = Self::task_status_as_str(&TaskStatus::InProgress);
+
+        transaction.execute(
+            "UPDATE task_run_steps SET status = ?2, updated_at = ?3 WHERE status = ?1",
+            params![
+                in_progress,
+                Self::task_status_as_str(&TaskStatus::Pending),
+                now
+            ],
+        )?;
+        let count = transaction.execute(
+            "UPDATE task_runs SET status = ?2, status_reason = ?3, updated_at = ?4 WHERE status = ?1",
+            params![
+                in_progress,
+                Self::task_status_as_str(&TaskStatus::Paused),
+                INTERRUPTED_TASK_RUN_MESSAGE,
+                now
+            ],
+        )?;
+
+        transaction.commit()?;
+        Ok(count)
+    }
+
+    /// Skips the scheduled runs that were still waiting for the UI to start
+    /// them when the app exited. Their queue lived in memory, so nothing
+    /// would start them now; the schedule's missed-run policy covers them.
+    pub fn skip_unstarted_scheduled_runs(&self) -> Result<usize, rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection.execute(
+            "UPDATE task_runs SET status = ?2, status_reason = ?3, updated_at = ?4
+             WHERE status = ?1 AND schedule_id IS NOT NULL",
+            params![
+                Self::task_status_as_str(&TaskStatus::Pending),
+                Self::task_status_as_str(&TaskStatus::Skipped),
+                UNSTARTED_SCHEDULED_RUN_MESSAGE,
+                current_timestamp_millis()
+            ],
+        )
+    }
+
+    /// The task's most recent runs, newest first.
+    pub fn load_task_run_history(
+        &self,
+        task_id: &str,
+        limit: u32,
+    ) -> Result<Vec<TaskRun>, rusqlite::Error> {
+        let connection = self.open_connection()?;
+        let mut statement = connection.prepare(
+            "SELECT id, project_id, task_id, status, status_reason, chat_thread_id, schedule_id, started_at, updated_at
+             FROM task_runs
+             WHERE task_id = ?1
+             ORDER BY started_at DESC, rowid DESC
+             LIMIT ?2",
+        )?;
+
+        let runs = statement
+            .query_map(params![task_id, limit], Self::map_task_run)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(runs)
+    }
+
+    pub fn save_task_schedule(&self, schedule: &TaskSchedule) -> Result<(), rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection.execute(
+            "INSERT INTO task_schedules (id, project_id, task_id, cron, enabled, missed_run_policy, time_zone, utc_offset_minutes, next_run_at, last_run_at, last_run_id, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+             ON CONFLICT(id) DO UPDATE SET
+               cron = excluded.cron,
+               enabled = excluded.enabled,
+               missed_run_policy = excluded.missed_run_policy,
+               time_zone = excluded.time_zone,
+               utc_offset_minutes = excluded.utc_offset_minutes,
+               next_run_at = excluded.next_run_at,
+               last_run_at = excluded.last_run_at,
+               last_run_id = excluded.last_run_id,
+               updated_at = excluded.updated_at",
+            params![
+                schedule.id,
+                schedule.project_id,
+                schedule.task_id,
+                schedule.cron,
+                schedule.enabled,
+                Self::missed_run_policy_as_str(schedule.missed_run_policy),
+                schedule.time_zone,
+                schedule.utc_offset_minutes,
+                schedule.next_run_at,
+                schedule.last_run_at,
+                schedule.last_run_id,
+                schedule.created_at,
+                schedule.updated_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn load_task_schedule(
+        &self,
+        schedule_id: &str,
+    ) -> Result<Option<TaskSchedule>, rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection
+            .query_row(
+                "SELECT id, project_id, task_id, cron, enabled, missed_run_policy, time_zone, utc_offset_minutes, next_run_at, last_run_at, last_run_id, created_at, updated_at
+                 FROM task_schedules
+                 WHERE id = ?1",
+                params![schedule_id],
+                Self::map_task_schedule,
+            )
+            .optional()
+    }
+
+    /// Schedules of one project, or of every project when `project_id` is
+    /// `None`.
+    pub fn load_task_schedules(
+        &self,
+        project_id: Option<&str>,
+    ) -> Result<Vec<TaskSchedule>, rusqlite::Error> {
+        let connection = self.open_connection()?;
+        let mut statement = connection.prepare(
+            "SELECT id, project_id, task_id, cron, enabled, missed_run_policy, time_zone, utc_offset_minutes, next_run_at, last_run_at, last_run_id, created_at, updated_at
+             FROM task_schedules
+             WHERE ?1 IS NULL OR project_id = ?1
+             ORDER BY created_at ASC",
+        )?;
+
+        let schedules = statement
+            .query_map(params![project_id], Self::map_task_schedule)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(schedules)
+    }
+
+    pub fn delete_task_schedule(&self, schedule_id: &str) -> Result<(), rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection.execute(
+            "DELETE FROM task_schedules WHERE id = ?1",
+            params![schedule_id],
+        )?;
+        Ok(())
+    }
+
+    /// Records how a step node ended, or is doing, within a task run.
+    pub fn save_task_run_step(&self, step: &TaskRunStep) -> Result<(), rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection.execute(
+            "INSERT INTO task_run_steps (task_run_id, task_id, step_id, status, status_reason, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(task_run_id, step_id) DO UPDATE SET
+               status = excluded.status,
+               status_reason = excluded.status_reason,
+               updated_at = excluded.updated_at",
+            params![
+                step.task_run_id,
+                step.task_id,
+                step.step_id,
+                Self::task_status_as_str(&step.status),
+                step.status_reason,
+                step.updated_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn load_task_run_steps(
+        &self,
+        task_run_id: &str,
+    ) -> Result<Vec<TaskRunStep>, rusqlite::Error> {
+        let connection = self.open_connection()?;
+        let mut statement = connection.prepare(
+            "SELECT task_run_id, task_id, step_id, status, status_reason, updated_at
+             FROM task_run_steps
+             WHERE task_run_id = ?1
+             ORDER BY updated_at ASC, rowid ASC",
+        )?;
+        let steps = statement
+            .query_map(params![task_run_id], |row| {
+                Ok(TaskRunStep {
+                    task_run_id: row.get(0)?,
+                    task_id: row.get(1)?,
+                    step_id: row.get(2)?,
+                    status: Self::parse_task_status(&row.get::<usize, String>(3)?),
+                    status_reason: row.get(4)?,
+                    updated_at: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(steps)
+    }
+
+    pub fn save_prompt(&self, prompt: &PromptRecord) -> Result<(), rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection.execute(
+            "INSERT INTO prompts (id, project_id, thread_id, task_run_id, content, outcome, error, starred, pinned, name, tags, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+             ON CONFLICT(id) DO UPDATE SET
+               content = excluded.content,
+               outcome = excluded.outcome,
+               error = excluded.error,
+               updated_at = excluded.updated_at",
+            params![
+                prompt.id,
+                prompt.project_id,
+                prompt.thread_id,
+                prompt.task_run_id,
+                prompt.content,
+                Self::chat_turn_status_as_str(prompt.outcome),
+                prompt.error,
+                prompt.starred,
+                prompt.pinned,
+                prompt.name,
+                serde_json::to_string(&prompt.tags).unwrap_or_else(|_| "[]".into()),
+                prompt.created_at,
+                prompt.updated_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn finish_prompt(
+        &self,
+        prompt_id: &str,
+        outcome: ChatTurnStatus,
+        error: Option<&str>,
+    ) -> Result<(), rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection.execute(
+            "UPDATE prompts SET outcome = ?2, error = ?3, updated_at = ?4 WHERE id = ?1",
+            params![
+                prompt_id,
+                Self::chat_turn_status_as_str(outcome),
+                error,
+                current_timestamp_millis()
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn load_prompt(&self, prompt_id: &str) -> Result<Option<PromptRecord>, rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection
+            .query_row(
+                "SELECT id, project_id, thread_id, task_run_id, content, outcome, error, starred, pinned, name, tags, created_at, updated_at
+                 FROM prompts
+                 WHERE id = ?1",
+                params![prompt_id],
+                Self::map_prompt,
+            )
+            .optional()
+    }
+
+    /// Newest first; pinned prompts that match come back like any other.
+    pub fn search_prompts(
         &self,
-        project_id: &str,
-        count: usize,
-    ) -> Result<Vec<ChatThread>, rusqlite::Error> {
+        query: &PromptQuery,
+    ) -> Result<Vec<PromptRecord>, rusqlite::Error> {
         let connection = self.open_connection()?;
-        let mut statement = connection.prepare(
-            "SELECT t.id, t.project_id, t.title, t.created_at, t.updated_at
-             FROM chat_threads t
-             WHERE t.project_id = ?1
-               AND EXISTS (
-                 SELECT 1
-                 FROM chat_messages m
-                 WHERE m.thread_id = t.id
-               )
-             ORDER BY t.updated_at DESC
-             LIMIT ?2",
-        )?;
-
-        log::info!("Loading  threads: {}, count: {}", project_id, count);
-
-        let threads = statement
-            .query_map(params![project_id, count as i64], |row| {
-                Ok(ChatThread {
-                    id: row.get(0)?,
-                    project_id: row.get(1)?,
-                    title: row.get(2)?,
-                    created_at: row.get(3)?,
-                    updated_at: row.get(4)?,
-                })
-            })?
+        let (filter, mut values) = Self::prompt_filter(query);
+        values.push(SqlValue::Integer(
+            query.limit.unwrap_or(DEFAULT_PROMPT_LIMIT).into(),
+        ));
+
+        let mut statement = connection.prepare(&format!(
+            "SELECT id, project_id, thread_id, task_run_id, content, outcome, error, starred, pinned, name, tags, created_at, updated_at
+             FROM prompts
+             {filter}
+             ORDER BY created_at DESC, rowid DESC
+             LIMIT ?{}",
+            values.len()
+        ))?;
+
+        let prompts = statement
+            .query_map(params_from_iter(values.iter()), Self::map_prompt)?
             .collect::<Result<Vec<_>, _>>()?;
 
-        Ok(threads)
+        Ok(prompts)
     }
 
-    pub fn load_chat_messages_by_thread(
+    pub fn set_prompt_starred(
         &self,
-        thread_id: &str,
-    ) -> Result<Vec<ChatMessage>, rusqlite::Error> {
+        prompt_id: &str,
+        starred: bool,
+    ) -> Result<(), rusqlite::Error> {
         let connection = self.open_connection()?;
-        let mut statement = connection.prepare(
-            "SELECT id, thread_id, role, content, model, created_at
-             FROM chat_messages
-             WHERE thread_id = ?1
-             ORDER BY created_at ASC",
+        connection.execute(
+            "UPDATE prompts SET starred = ?2, updated_at = ?3 WHERE id = ?1",
+            params![prompt_id, starred, current_timestamp_millis()],
         )?;
+        Ok(())
+    }
 
-        let messages = statement
-            .query_map(params![thread_id], |row| {
-                Ok(ChatMessage {
-                    id: row.get(0)?,
-                    thread_id: row.get(1)?,
-                    role: row.get(2)?,
-                    content: row.get(3)?,
-                    model: row.get(4)?,
-                    created_at: row.get(5)?,
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
+    /// Pins a prompt to the library under `name`, or unpins it when `name` is `None`.
+    pub fn set_prompt_pinned(
+        &self,
+        prompt_id: &str,
+        name: Option<&str>,
+        tags: &[String],
+    ) -> Result<(), rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection.execute(
+            "UPDATE prompts SET pinned = ?2, name = ?3, tags = ?4, updated_at = ?5 WHERE id = ?1",
+            params![
+                prompt_id,
+                name.is_some(),
+                name,
+                serde_json::to_string(tags).unwrap_or_else(|_| "[]".into()),
+                current_timestamp_millis()
+            ],
+        )?;
+        Ok(())
+    }
 
-        Ok(messages)
+    pub fn current_day_start_millis(&self) -> Result<i64, rusqlite::Error> {
+        let connection = self.open_connection()?;
+        connection.query_row(
+            "SELECT CAST(strftime('%s', 'now', 'localtime', 'start of day', 'utc') AS INTEGER) * 1000",
+            [],
+            |row| row.get::<usize, i64>(0),
+        )
     }
 
     pub fn db_path(&self) -> &PathBuf {
@@ -291,6 +1190,13 @@ impl DataService {
                 project_id TEXT NOT NULL,
                 name TEXT NOT NULL,
                 description TEXT,
+                content TEXT NOT NULL DEFAULT '',
+                enabled INTEGER NOT NULL DEFAULT 1,
+                priority INTEGER NOT NULL DEFAULT 0,
+                scopes TEXT NOT NULL DEFAULT '[]',
+                tags TEXT NOT NULL DEFAULT '[]',
+                source_path TEXT,
+                synced_hash TEXT,
                 created_at INTEGER NOT NULL,
                 updated_at INTEGER NOT NULL,
                 FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
@@ -302,6 +1208,8 @@ impl DataService {
                 title TEXT NOT NULL,
                 description TEXT NOT NULL,
                 status TEXT NOT NULL,
+                parameters TEXT NOT NULL DEFAULT '[]',
+                tags TEXT NOT NULL DEFAULT '[]',
                 created_at INTEGER NOT NULL,
                 updated_at INTEGER NOT NULL,
                 FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
@@ -318,15 +1226,58 @@ impl DataService {
                 type TEXT NOT NULL,
                 step_kind TEXT NOT NULL,
                 sort_order INTEGER NOT NULL,
+                status_reason TEXT,
+                model TEXT,
+                sandbox TEXT,
+                verify TEXT NOT NULL DEFAULT '[]',
+                depends_on TEXT NOT NULL DEFAULT '[]',
+                run_when TEXT NOT NULL DEFAULT 'on-success',
+                if_changed TEXT NOT NULL DEFAULT '[]',
                 FOREIGN KEY(task_id) REFERENCES tasks(id) ON DELETE CASCADE
             );
 
+            CREATE TABLE IF NOT EXISTS context_sets (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                patterns TEXT NOT NULL DEFAULT '[]',
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+            );
+
+            CREATE TABLE IF NOT EXISTS symbol_files (
+                project_id TEXT NOT NULL,
+                path TEXT NOT NULL,
+                modified_at INTEGER NOT NULL,
+                size INTEGER NOT NULL,
+                indexed_at INTEGER NOT NULL,
+                PRIMARY KEY(project_id, path),
+                FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+            );
+
+            CREATE TABLE IF NOT EXISTS symbols (
+                project_id TEXT NOT NULL,
+                path TEXT NOT NULL,
+                name TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                language TEXT NOT NULL,
+                start_line INTEGER NOT NULL,
+                end_line INTEGER NOT NULL,
+                container TEXT,
+                signature TEXT NOT NULL,
+                FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+            );
+
             CREATE TABLE IF NOT EXISTS chat_threads (
                 id TEXT PRIMARY KEY,
                 project_id TEXT NOT NULL,
                 title TEXT NOT NULL,
                 created_at INTEGER NOT NULL,
                 updated_at INTEGER NOT NULL,
+                agent_thread_id TEXT,
+                agent_id TEXT,
+                model TEXT,
                 FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
             );
 
@@ -340,18 +1291,157 @@ impl DataService {
                 FOREIGN KEY(thread_id) REFERENCES chat_threads(id) ON DELETE CASCADE
             );
 
+            CREATE TABLE IF NOT EXISTS chat_turns (
+                run_id TEXT PRIMARY KEY,
+                thread_id TEXT NOT NULL,
+                status TEXT NOT NULL,
+                error TEXT,
+                started_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                FOREIGN KEY(thread_id) REFERENCES chat_threads(id) ON DELETE CASCADE
+            );
+
+            CREATE TABLE IF NOT EXISTS task_step_results (
+                task_run_id TEXT NOT NULL,
+                task_id TEXT NOT NULL,
+                step_id TEXT NOT NULL,
+                summary TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                PRIMARY KEY(task_run_id, step_id)
+            );
+
+            CREATE TABLE IF NOT EXISTS task_runs (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL,
+                task_id TEXT NOT NULL,
+                status TEXT NOT NULL,
+                status_reason TEXT,
+                chat_thread_id TEXT,
+                schedule_id TEXT,
+                started_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS task_schedules (
+                id TEXT PRIMARY KEY,
+                project_id TEXT NOT NULL,
+                task_id TEXT NOT NULL,
+                cron TEXT NOT NULL,
+                enabled INTEGER NOT NULL,
+                missed_run_policy TEXT NOT NULL,
+                time_zone TEXT,
+                utc_offset_minutes INTEGER NOT NULL,
+                next_run_at INTEGER,
+                last_run_at INTEGER,
+                last_run_id TEXT,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS task_run_steps (
+                task_run_id TEXT NOT NULL,
+                task_id TEXT NOT NULL,
+                step_id TEXT NOT NULL,
+                status TEXT NOT NULL,
+                status_reason TEXT,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY(task_run_id, step_id)
+            );
+
+            CREATE TABLE IF NOT EXISTS prompts (
+                id TEXT PRIMARY KEY,
+                project_id TEXT,
+                thread_id TEXT,
+                task_run_id TEXT,
+                content TEXT NOT NULL,
+                outcome TEXT NOT NULL,
+                error TEXT,
+                starred INTEGER NOT NULL DEFAULT 0,
+                pinned INTEGER NOT NULL DEFAULT 0,
+                name TEXT,
+                tags TEXT NOT NULL DEFAULT '[]',
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+            );
+
+            CREATE TABLE IF NOT EXISTS usage_records (
+                id TEXT PRIMARY KEY,
+                project_id TEXT,
+                thread_id TEXT,
+                task_run_id TEXT,
+                model TEXT NOT NULL,
+                input_tokens INTEGER NOT NULL,
+                cached_input_tokens INTEGER NOT NULL,
+                output_tokens INTEGER NOT NULL,
+                reasoning_output_tokens INTEGER NOT NULL,
+                cost REAL NOT NULL,
+                created_at INTEGER NOT NULL,
+                FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+            );
+
             CREATE INDEX IF NOT EXISTS idx_agent_rules_project_id ON agent_rules(project_id);
             CREATE INDEX IF NOT EXISTS idx_tasks_project_id ON tasks(project_id);
             CREATE INDEX IF NOT EXISTS idx_task_steps_task_id ON task_steps(task_id);
+            CREATE INDEX IF NOT EXISTS idx_task_step_results_created_at ON task_step_results(task_run_id, created_at);
             CREATE INDEX IF NOT EXISTS idx_projects_path ON projects(path);
+            CREATE INDEX IF NOT EXISTS idx_context_sets_project_id ON context_sets(project_id);
+            CREATE INDEX IF NOT EXISTS idx_symbols_project_name ON symbols(project_id, name);
+            CREATE INDEX IF NOT EXISTS idx_symbols_project_path ON symbols(project_id, path);
             CREATE INDEX IF NOT EXISTS idx_chat_threads_project_id ON chat_threads(project_id);
             CREATE INDEX IF NOT EXISTS idx_chat_threads_updated_at ON chat_threads(updated_at);
             CREATE INDEX IF NOT EXISTS idx_chat_messages_thread_id ON chat_messages(thread_id);
             CREATE INDEX IF NOT EXISTS idx_chat_messages_created_at ON chat_messages(created_at);
+            CREATE INDEX IF NOT EXISTS idx_chat_turns_thread_id ON chat_turns(thread_id);
+            CREATE INDEX IF NOT EXISTS idx_chat_turns_status ON chat_turns(status);
+            CREATE INDEX IF NOT EXISTS idx_prompts_project_id ON prompts(project_id);
+            CREATE INDEX IF NOT EXISTS idx_prompts_created_at ON prompts(created_at);
+            CREATE INDEX IF NOT EXISTS idx_usage_records_project_id ON usage_records(project_id);
+            CREATE INDEX IF NOT EXISTS idx_usage_records_thread_id ON usage_records(thread_id);
+            CREATE INDEX IF NOT EXISTS idx_usage_records_task_run_id ON usage_records(task_run_id);
+            CREATE INDEX IF NOT EXISTS idx_usage_records_created_at ON usage_records(created_at);
+            CREATE INDEX IF NOT EXISTS idx_task_runs_task_id ON task_runs(task_id, started_at);
             ",
         )?;
 
+        self.ensure_agent_rule_columns(&connection)?;
         self.ensure_chat_thread_columns(&connection)?;
+        self.ensure_task_columns(&connection)?;
+        self.ensure_task_step_columns(&connection)?;
+
+        Ok(())
+    }
+
+    fn ensure_agent_rule_columns(&self, connection: &Connection) -> Result<(), rusqlite::Error> {
+        let mut statement = connection.prepare("PRAGMA table_info(agent_rules)")?;
+        let columns = statement
+            .query_map([], |row| row.get::<usize, String>(1))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (column, definition) in [
+            ("content", "TEXT NOT NULL DEFAULT ''"),
+            ("enabled", "INTEGER NOT NULL DEFAULT 1"),
+            ("priority", "INTEGER NOT NULL DEFAULT 0"),
+            ("scopes", "TEXT NOT NULL DEFAULT '[]'"),
+            ("tags", "TEXT NOT NULL DEFAULT '[]'"),
+            ("source_path", "TEXT"),
+            ("synced_hash", "TEXT"),
+        ] {
+            if !columns.iter().any(|c| c == column) {
+                connection.execute(
+                    &format!("ALTER TABLE agent_rules ADD COLUMN {column} {definition}"),
+                    [],
+                )?;
+            }
+        }
+
+        // Rules used to keep their markdown body in `description`.
+        if !columns.iter().any(|c| c == "content") {
+            connection.execute(
+                "UPDATE agent_rules SET content = COALESCE(description, ''), description = NULL",
+                [],
+            )?;
+        }
 
         Ok(())
     }
@@ -376,6 +1466,15 @@ impl DataService {
             )?;
         }
 
+        for column in ["agent_thread_id", "agent_id", "model"] {
+            if !columns.iter().any(|c| c == column) {
+                connection.execute(
+                    &format!("ALTER TABLE chat_threads ADD COLUMN {column} TEXT"),
+                    [],
+                )?;
+            }
+        }
+
         connection.execute(
             "UPDATE chat_threads
              SET created_at = (CAST(strftime('%s','now') AS INTEGER) * 1000)
@@ -393,6 +1492,95 @@ impl DataService {
         Ok(())
     }
 
+    fn ensure_task_columns(&self, connection: &Connection) -> Result<(), rusqlite::Error> {
+        let mut statement = connection.prepare("PRAGMA table_info(tasks)")?;
+        let columns = statement
+            .query_map([], |row| row.get::<usize, String>(1))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for column in ["parameters", "tags"] {
+            if !columns.iter().any(|c| c == column) {
+                connection.execute(
+                    &format!("ALTER TABLE tasks ADD COLUMN {column} TEXT NOT NULL DEFAULT '[]'"),
+                    [],
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn ensure_task_step_columns(&self, connection: &Connection) -> Result<(), rusqlite::Error> {
+        let mut statement = connection.prepare("PRAGMA table_info(task_steps)")?;
+        let columns = statement
+            .query_map([], |row| row.get::<usize, String>(1))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (column, definition) in [
+            ("status_reason", "TEXT"),
+            ("model", "TEXT"),
+            ("sandbox", "TEXT"),
+            ("verify", "TEXT NOT NULL DEFAULT '[]'"),
+            ("depends_on", "TEXT NOT NULL DEFAULT '[]'"),
+            ("run_when", "TEXT NOT NULL DEFAULT 'on-success'"),
+            ("if_changed", "TEXT NOT NULL DEFAULT '[]'"),
+        ] {
+            if !columns.iter().any(|c| c == column) {
+                connection.execute(
+                    &format!("ALTER TABLE task_steps ADD COLUMN {column} {definition}"),
+                    [],
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn map_chat_thread(row: &rusqlite::Row<'_>) -> Result<ChatThread, rusqlite::Error> {
+        Ok(ChatThread {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            title: row.get(2)?,
+            created_at: row.get(3)?,
+            updated_at: row.get(4)?,
+            agent_thread_id: row.get(5)?,
+            agent_id: row.get(6)?,
+            model: row.get(7)?,
+        })
+    }
+
+    fn map_task_run(row: &rusqlite::Row<'_>) -> Result<TaskRun, rusqlite::Error> {
+        Ok(TaskRun {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            task_id: row.get(2)?,
+            status: Self::parse_task_status(&row.get::<usize, String>(3)?),
+            status_reason: row.get(4)?,
+            chat_thread_id: row.get(5)?,
+            schedule_id: row.get(6)?,
+            started_at: row.get(7)?,
+            updated_at: row.get(8)?,
+        })
+    }
+
+    fn map_task_schedule(row: &rusqlite::Row<'_>) -> Result<TaskSchedule, rusqlite::Error> {
+        Ok(TaskSchedule {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            task_id: row.get(2)?,
+            cron: row.get(3)?,
+            enabled: row.get(4)?,
+            missed_run_policy: Self::parse_missed_run_policy(&row.get::<usize, String>(5)?),
+            time_zone: row.get(6)?,
+            utc_offset_minutes: row.get(7)?,
+            next_run_at: row.get(8)?,
+            last_run_at: row.get(9)?,
+            last_run_id: row.get(10)?,
+            created_at: row.get(11)?,
+            updated_at: row.get(12)?,
+        })
+    }
+
     fn load_project_with_connection(
         &self,
         connection: &Connection,
@@ -438,10 +1626,11 @@ impl DataService {
         project_id: &str,
     ) -> Result<Vec<AgentRule>, rusqlite::Error> {
         let mut statement = connection.prepare(
-            "SELECT id, name, description, created_at, updated_at
+            "SELECT id, name, description, content, enabled, priority, scopes, tags,
+                    source_path, synced_hash, created_at, updated_at
              FROM agent_rules
              WHERE project_id = ?1
-             ORDER BY updated_at DESC",
+             ORDER BY priority ASC, updated_at DESC",
         )?;
 
         let rules = statement
@@ -450,8 +1639,15 @@ impl DataService {
                     id: row.get(0)?,
                     name: row.get(1)?,
                     description: row.get(2)?,
-                    created_at: row.get(3)?,
-                    updated_at: row.get(4)?,
+                    content: row.get(3)?,
+                    enabled: row.get(4)?,
+                    priority: row.get(5)?,
+                    scopes: Self::parse_string_list(&row.get::<_, String>(6)?),
+                    tags: Self::parse_string_list(&row.get::<_, String>(7)?),
+                    source_path: row.get(8)?,
+                    synced_hash: row.get(9)?,
+                    created_at: row.get(10)?,
+                    updated_at: row.get(11)?,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
@@ -465,7 +1661,7 @@ impl DataService {
         project_id: &str,
     ) -> Result<Vec<Task>, rusqlite::Error> {
         let mut statement = connection.prepare(
-            "SELECT id, title, description, status, created_at, updated_at
+            "SELECT id, title, description, status, parameters, tags, created_at, updated_at
              FROM tasks
              WHERE project_id = ?1
              ORDER BY updated_at DESC",
@@ -478,14 +1674,17 @@ impl DataService {
                     row.get::<usize, String>(1)?,
                     row.get::<usize, String>(2)?,
                     row.get::<usize, String>(3)?,
-                    row.get::<usize, i64>(4)?,
-                    row.get::<usize, i64>(5)?,
+                    row.get::<usize, String>(4)?,
+                    row.get::<usize, String>(5)?,
+                    row.get::<usize, i64>(6)?,
+                    row.get::<usize, i64>(7)?,
                 ))
             })?
             .collect::<Result<Vec<_>, _>>()?;
 
         let mut tasks = Vec::with_capacity(task_rows.len());
-        for (id, title, description, status, created_at, updated_at) in task_rows {
+        for (id, title, description, status, parameters, tags, created_at, updated_at) in task_rows
+        {
             let (presteps, steps, poststeps) = self.load_task_steps(connection, &id)?;
             tasks.push(Task {
                 id,
@@ -495,6 +1694,8 @@ impl DataService {
                 presteps,
                 steps,
                 poststeps,
+                parameters: serde_json::from_str(&parameters).unwrap_or_default(),
+                tags: serde_json::from_str(&tags).unwrap_or_default(),
                 status: Self::parse_task_status(&status),
                 created_at,
                 updated_at,
@@ -510,7 +1711,8 @@ impl DataService {
         task_id: &str,
     ) -> Result<(Vec<TaskStep>, Vec<TaskStep>, Vec<TaskStep>), rusqlite::Error> {
         let mut statement = connection.prepare(
-            "SELECT id, title, content, status, created_at, updated_at, type, step_kind
+            "SELECT id, title, content, status, created_at, updated_at, type, step_kind, status_reason,
+                    model, sandbox, verify, depends_on, run_when, if_changed
              FROM task_steps
              WHERE task_id = ?1
              ORDER BY sort_order ASC, updated_at ASC",
@@ -518,16 +1720,26 @@ impl DataService {
 
         let step_rows = statement
             .query_map(params![task_id], |row| {
-                Ok((
-                    row.get::<usize, String>(0)?,
-                    row.get::<usize, String>(1)?,
-                    row.get::<usize, String>(2)?,
-                    row.get::<usize, String>(3)?,
-                    row.get::<usize, i64>(4)?,
-                    row.get::<usize, i64>(5)?,
-                    row.get::<usize, String>(6)?,
-                    row.get::<usize, String>(7)?,
-                ))
+                let step = TaskStep {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    content: row.get(2)?,
+                    status: Self::parse_task_status(&row.get::<usize, String>(3)?),
+                    created_at: row.get(4)?,
+                    updated_at: row.get(5)?,
+                    r#type: Self::parse_task_step_type(&row.get::<usize, String>(6)?),
+                    status_reason: row.get(8)?,
+                    model: row.get(9)?,
+                    sandbox: row
+                        .get::<usize, Option<String>>(10)?
+                        .as_deref()
+                        .and_then(Self::parse_task_sandbox),
+                    verify: Self::parse_string_list(&row.get::<usize, String>(11)?),
+                    depends_on: Self::parse_string_list(&row.get::<usize, String>(12)?),
+                    run_when: Self::parse_step_run_when(&row.get::<usize, String>(13)?),
+                    if_changed: Self::parse_string_list(&row.get::<usize, String>(14)?),
+                };
+                Ok((row.get::<usize, String>(7)?, step))
             })?
             .collect::<Result<Vec<_>, _>>()?;
 
@@ -535,22 +1747,11 @@ impl DataService {
         let mut steps = Vec::new();
         let mut poststeps = Vec::new();
 
-        for (id, title, content, status, created_at, updated_at, step_type, step_kind) in step_rows
-        {
-            let mapped_step = TaskStep {
-                id,
-                title,
-                content,
-                status: Self::parse_task_status(&status),
-                created_at,
-                updated_at,
-                r#type: Self::parse_task_step_type(&step_type),
-            };
-
+        for (step_kind, step) in step_rows {
             match step_kind.as_str() {
-                "pre" => presteps.push(mapped_step),
-                "post" => poststeps.push(mapped_step),
-                _ => steps.push(mapped_step),
+                "pre" => presteps.push(step),
+                "post" => poststeps.push(step),
+                _ => steps.push(step),
             }
         }
 
@@ -569,13 +1770,23 @@ impl DataService {
 
         for rule in &project.rules {
             transaction.execute(
-                "INSERT INTO agent_rules (id, project_id, name, description, created_at, updated_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                "INSERT INTO agent_rules (
+                    id, project_id, name, description, content, enabled, priority, scopes, tags,
+                    source_path, synced_hash, created_at, updated_at
+                 )
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
                 params![
                     rule.id,
                     project.id,
                     rule.name,
                     rule.description,
+                    rule.content,
+                    rule.enabled,
+                    rule.priority,
+                    serde_json::to_string(&rule.scopes).unwrap_or_else(|_| "[]".into()),
+                    serde_json::to_string(&rule.tags).unwrap_or_else(|_| "[]".into()),
+                    rule.source_path,
+                    rule.synced_hash,
                     rule.created_at,
                     rule.updated_at
                 ],
@@ -597,14 +1808,16 @@ impl DataService {
 
         for task in &project.tasks {
             transaction.execute(
-                "INSERT INTO tasks (id, project_id, title, description, status, created_at, updated_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                "INSERT INTO tasks (id, project_id, title, description, status, parameters, tags, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
                 params![
                     task.id,
                     project.id,
                     task.title,
                     task.description,
                     Self::task_status_as_str(&task.status),
+                    serde_json::to_string(&task.parameters).unwrap_or_else(|_| "[]".into()),
+                    serde_json::to_string(&task.tags).unwrap_or_else(|_| "[]".into()),
                     task.created_at,
                     task.updated_at
                 ],
@@ -627,8 +1840,9 @@ impl DataService {
     ) -> Result<(), rusqlite::Error> {
         for (index, step) in steps.iter().enumerate() {
             transaction.execute(
-                "INSERT INTO task_steps (id, task_id, title, content, status, created_at, updated_at, type, step_kind, sort_order)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                "INSERT INTO task_steps (id, task_id, title, content, status, created_at, updated_at, type, step_kind, sort_order, status_reason,
+                                         model, sandbox, verify, depends_on, run_when, if_changed)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
                 params![
                     step.id,
                     task_id,
@@ -639,7 +1853,14 @@ impl DataService {
                     step.updated_at,
                     Self::task_step_type_as_str(&step.r#type),
                     step_kind,
-                    index as i64
+                    index as i64,
+                    step.status_reason,
+                    step.model,
+                    step.sandbox.map(Self::task_sandbox_as_str),
+                    serde_json::to_string(&step.verify).unwrap_or_else(|_| "[]".into()),
+                    serde_json::to_string(&step.depends_on).unwrap_or_else(|_| "[]".into()),
+                    Self::step_run_when_as_str(step.run_when),
+                    serde_json::to_string(&step.if_changed).unwrap_or_else(|_| "[]".into())
                 ],
             )?;
         }
@@ -647,12 +1868,154 @@ impl DataService {
         Ok(())
     }
 
+    fn load_usage_rows(
+        &self,
+        connection: &Connection,
+        key_expression: &str,
+        filter: &str,
+        values: &[SqlValue],
+    ) -> Result<Vec<UsageReportRow>, rusqlite::Error> {
+        let mut statement = connection.prepare(&format!(
+            "SELECT {key_expression} AS usage_key,
+                    COUNT(*),
+                    COALESCE(SUM(input_tokens), 0),
+                    COALESCE(SUM(cached_input_tokens), 0),
+                    COALESCE(SUM(output_tokens), 0),
+                    COALESCE(SUM(reasoning_output_tokens), 0),
+                    COALESCE(SUM(cost), 0)
+             FROM usage_records
+             {filter}
+             GROUP BY usage_key
+             ORDER BY usage_key ASC"
+        ))?;
+
+        let rows = statement
+            .query_map(params_from_iter(values.iter()), |row| {
+                let usage = TokenUsage {
+                    input_tokens: row.get::<usize, i64>(2)? as u64,
+                    cached_input_tokens: row.get::<usize, i64>(3)? as u64,
+                    output_tokens: row.get::<usize, i64>(4)? as u64,
+                    reasoning_output_tokens: row.get::<usize, i64>(5)? as u64,
+                };
+
+                Ok(UsageReportRow {
+                    key: row.get(0)?,
+                    turns: row.get::<usize, i64>(1)? as u64,
+                    total_tokens: usage.total_tokens(),
+                    usage,
+                    cost: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rows)
+    }
+
+    fn usage_filter(query: &UsageReportQuery) -> (String, Vec<SqlValue>) {
+        let mut clauses = Vec::new();
+        let mut values = Vec::new();
+
+        let text_filters = [
+            ("project_id", &query.project_id),
+            ("thread_id", &query.thread_id),
+            ("task_run_id", &query.task_run_id),
+            ("model", &query.model),
+        ];
+        for (column, value) in text_filters {
+            if let Some(value) = value {
+                values.push(SqlValue::Text(value.clone()));
+                clauses.push(format!("{column} = ?{}", values.len()));
+            }
+        }
+
+        if let Some(from) = query.from {
+            values.push(SqlValue::Integer(from));
+            clauses.push(format!("created_at >= ?{}", values.len()));
+        }
+
+        if let Some(to) = query.to {
+            values.push(SqlValue::Integer(to));
+            clauses.push(format!("created_at < ?{}", values.len()));
+        }
+
+        if clauses.is_empty() {
+            (String::new(), values)
+        } else {
+            (format!("WHERE {}", clauses.join(" AND ")), values)
+        }
+    }
+
+    fn prompt_filter(query: &PromptQuery) -> (String, Vec<SqlValue>) {
+        let mut clauses = Vec::new();
+        let mut values = Vec::new();
+
+        if let Some(project_id) = &query.project_id {
+            values.push(SqlValue::Text(project_id.clone()));
+            clauses.push(format!("project_id = ?{}", values.len()));
+        }
+
+        if let Some(text) = query
+            .text
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+        {
+            values.push(SqlValue::Text(text.to_lowercase()));
+            clauses.push(format!(
+                "(instr(lower(content), ?{0}) > 0 OR instr(lower(COALESCE(name, '')), ?{0}) > 0)",
+                values.len()
+            ));
+        }
+
+        if let Some(tag) = &query.tag {
+            values.push(SqlValue::Text(tag.clone()));
+            clauses.push(format!(
+                "EXISTS (SELECT 1 FROM json_each(prompts.tags) WHERE json_each.value = ?{})",
+                values.len()
+            ));
+        }
+
+        if query.starred_only {
+            clauses.push("starred = 1".to_string());
+        }
+
+        if query.pinned_only {
+            clauses.push("pinned = 1".to_string());
+        }
+
+        if clauses.is_empty() {
+            (String::new(), values)
+        } else {
+            (format!("WHERE {}", clauses.join(" AND ")), values)
+        }
+    }
+
+    fn map_prompt(row: &rusqlite::Row<'_>) -> Result<PromptRecord, rusqlite::Error> {
+        Ok(PromptRecord {
+            id: row.get(0)?,
+            project_id: row.get(1)?,
+            thread_id: row.get(2)?,
+            task_run_id: row.get(3)?,
+            content: row.get(4)?,
+            outcome: Self::parse_chat_turn_status(&row.get::<usize, String>(5)?),
+            error: row.get(6)?,
+            starred: row.get(7)?,
+            pinned: row.get(8)?,
+            name: row.get(9)?,
+            tags: Self::parse_string_list(&row.get::<usize, String>(10)?),
+            created_at: row.get(11)?,
+            updated_at: row.get(12)?,
+        })
+    }
+
     fn task_status_as_str(status: &TaskStatus) -> &'static str {
         match status {
             TaskStatus::Pending => "pending",
             TaskStatus::InProgress => "in_progress",
             TaskStatus::Completed => "completed",
             TaskStatus::Failed => "failed",
+            TaskStatus::Skipped => "skipped",
+            TaskStatus::Paused => "paused",
         }
     }
 
@@ -661,10 +2024,44 @@ impl DataService {
             "in_progress" => TaskStatus::InProgress,
             "completed" => TaskStatus::Completed,
             "failed" => TaskStatus::Failed,
+            "skipped" => TaskStatus::Skipped,
+            "paused" => TaskStatus::Paused,
             _ => TaskStatus::Pending,
         }
     }
 
+    fn missed_run_policy_as_str(policy: MissedRunPolicy) -> &'static str {
+        match policy {
+            MissedRunPolicy::Skip => "skip",
+            MissedRunPolicy::CatchUp => "catch-up",
+        }
+    }
+
+    fn parse_missed_run_policy(value: &str) -> MissedRunPolicy {
+        match value {
+            "catch-up" => MissedRunPolicy::CatchUp,
+            _ => MissedRunPolicy::Skip,
+        }
+    }
+
+    fn chat_turn_status_as_str(status: ChatTurnStatus) -> &'static str {
+        match status {
+            ChatTurnStatus::Running => "running",
+            ChatTurnStatus::Completed => "completed",
+            ChatTurnStatus::Failed => "failed",
+            ChatTurnStatus::Interrupted => "interrupted",
+        }
+    }
+
+    fn parse_chat_turn_status(value: &str) -> ChatTurnStatus {
+        match value {
+            "completed" => ChatTurnStatus::Completed,
+            "failed" => ChatTurnStatus::Failed,
+            "interrupted" => ChatTurnStatus::Interrupted,
+            _ => ChatTurnStatus::Running,
+        }
+    }
+
     fn task_step_type_as_str(step_type: &TaskStepType) -> &'static str {
         match step_type {
             TaskStepType::Normal => "normal",
@@ -681,13 +2078,88 @@ impl DataService {
         }
     }
 
-    fn current_timestamp_millis() -> i64 {
-        match SystemTime::now().duration_since(UNIX_EPOCH) {
-            Ok(duration) => duration.as_millis() as i64,
-            Err(_) => 0,
+    fn task_sandbox_as_str(sandbox: TaskSandbox) -> &'static str {
+        match sandbox {
+            TaskSandbox::ReadOnly => "read-only",
+            TaskSandbox::WorkspaceWrite => "workspace-write",
+            TaskSandbox::DangerFullAccess => "danger-full-access",
+        }
+    }
+
+    fn parse_task_sandbox(value: &str) -> Option<TaskSandbox> {
+        match value {
+            "read-only" => Some(TaskSandbox::ReadOnly),
+            "workspace-write" => Some(TaskSandbox::WorkspaceWrite),
+            "danger-full-access" => Some(TaskSandbox::DangerFullAccess),
+            _ => None,
+        }
+    }
+
+    fn step_run_when_as_str(run_when: StepRunWhen) -> &'static str {
+        match run_when {
+            StepRunWhen::OnSuccess => "on-success",
+            StepRunWhen::OnFailure => "on-failure",
+            StepRunWhen::Always => "always",
+        }
+    }
+
+    fn parse_step_run_when(value: &str) -> StepRunWhen {
+        match value {
+            "on-failure" => StepRunWhen::OnFailure,
+            "always" => StepRunWhen::Always,
+            _ => StepRunWhen::OnSuccess,
+        }
+    }
+
+    fn map_symbol(row: &rusqlite::Row<'_>) -> Result<CodeSymbol, rusqlite::Error> {
+        Ok(CodeSymbol {
+            path: row.get(0)?,
+            name: row.get(1)?,
+            kind: Self::parse_symbol_kind(&row.get::<usize, String>(2)?),
+            language: row.get(3)?,
+            start_line: row.get::<usize, i64>(4)? as usize,
+            end_line: row.get::<usize, i64>(5)? as usize,
+            container: row.get(6)?,
+            signature: row.get(7)?,
+        })
+    }
+
+    fn symbol_kind_as_str(kind: SymbolKind) -> &'static str {
+        match kind {
+            SymbolKind::Function => "function",
+            SymbolKind::Method => "method",
+            SymbolKind::Struct => "struct",
+            SymbolKind::Enum => "enum",
+            SymbolKind::Trait => "trait",
+            SymbolKind::Class => "class",
+            SymbolKind::Interface => "interface",
+            SymbolKind::Type => "type",
+            SymbolKind::Constant => "constant",
+            SymbolKind::Module => "module",
+            SymbolKind::Macro => "macro",
+        }
+    }
+
+    fn parse_symbol_kind(value: &str) -> SymbolKind {
+        match value {
+            "method" => SymbolKind::Method,
+            "struct" => SymbolKind::Struct,
+            "enum" => SymbolKind::Enum,
+            "trait" => SymbolKind::Trait,
+            "class" => SymbolKind::Class,
+            "interface" => SymbolKind::Interface,
+            "type" => SymbolKind::Type,
+            "constant" => SymbolKind::Constant,
+            "module" => SymbolKind::Module,
+            "macro" => SymbolKind::Macro,
+            _ => SymbolKind::Function,
         }
     }
 
+    fn parse_string_list(value: &str) -> Vec<String> {
+        serde_json::from_str(value).unwrap_or_default()
+    }
+
     fn project_name_from_path(path: &str) -> String {
         let normalized_path = path.replace('\\', "/");
         let segments = normalized_path
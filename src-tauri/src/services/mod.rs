@@ -1,4 +1,22 @@
 pub mod app_service;
+pub mod budget_service;
+pub mod chat_persistence;
 pub mod codex_service;
 pub mod command_service;
+pub mod context_service;
 pub mod data_service;
+pub mod openai_service;
+pub mod project_folder_service;
+pub mod recording_service;
+pub mod rule_service;
+pub mod rule_sync_service;
+pub mod scheduler_service;
+pub mod scripted_agent;
+pub mod session_manager;
+pub mod symbol_service;
+pub mod task_file_service;
+pub mod task_graph_service;
+pub mod task_import_service;
+pub mod task_planner_service;
+pub mod template_service;
+pub mod watcher_service;
@@ -0,0 +1,537 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use futures::StreamExt;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, AUTHORIZATION};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::models::chat::{ChatRequest, ChatResponse, TurnOutcome};
+use crate::models::event_handler::CodexEventHandler;
+use crate::models::setting::{AgentApiStyle, AgentConfig};
+use crate::models::usage::TokenUsage;
+
+const DONE_MARKER: &str = "[DONE]";
+
+pub struct OpenAiService {
+    client: reqwest::Client,
+    conversations: Mutex<HashMap<String, Vec<Value>>>,
+}
+
+#[derive(Debug, Default)]
+struct ToolCall {
+    name: String,
+    arguments: String,
+}
+
+#[derive(Debug, Default)]
+struct StreamState {
+    run_id: String,
+    text: String,
+    tool_calls: BTreeMap<usize, ToolCall>,
+    usage: TokenUsage,
+    completed: bool,
+}
+
+impl OpenAiService {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            conversations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn invoke_stream<H: CodexEventHandler>(
+        &self,
+        mut payload: ChatRequest,
+        agent: &AgentConfig,
+        handler: H,
+    ) -> Result<TurnOutcome, String> {
+        let run_id = payload.ensure_run_id();
+        let trimmed_prompt = payload.content.trim();
+
+        if trimmed_prompt.is_empty() {
+            return Err("prompt cannot be empty".to_string());
+        }
+
+        let base_url = agent.base_url.trim().trim_end_matches('/');
+        if base_url.is_empty() {
+            return Err(format!("agent '{}' has no base url configured", agent.name));
+        }
+
+        let model = payload
+            .model
+            .clone()
+            .filter(|model| !model.trim().is_empty())
+            .unwrap_or_else(|| agent.model.clone());
+        let thread_id = payload
+            .thread_id
+            .clone()
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+        log::info!(
+            "Starting {:?} request against '{}' with model '{}' on thread {}",
+            agent.api_style,
+            base_url,
+            model,
+            thread_id
+        );
+
+        handler.on_thread_started(
+            &run_id,
+            ChatResponse::ThreadStarted {
+                thread_id: thread_id.clone(),
+            }
+            .to_json(),
+        );
+
+        let mut messages = self.conversation(&thread_id);
+        messages.push(json!({ "role": "user", "content": trimmed_prompt }));
+
+        let (endpoint, body) = match agent.api_style {
+            AgentApiStyle::ChatCompletions => (
+                format!("{base_url}/chat/completions"),
+                json!({
+                    "model": model,
+                    "messages": messages,
+                    "stream": true,
+                    "stream_options": { "include_usage": true },
+                }),
+            ),
+            AgentApiStyle::Responses => (
+                format!("{base_url}/responses"),
+                json!({
+                    "model": model,
+                    "input": messages,
+                    "stream": true,
+                }),
+            ),
+        };
+        let body = Self::with_tools(body, payload.tools.as_deref());
+        let body = Self::with_output_schema(body, &agent.api_style, payload.output_schema.take());
+
+        let state = match self
+            .send_and_stream(agent, &run_id, &endpoint, &body, &handler)
+            .await
+        {
+            Ok(state) => state,
+            Err(message) => {
+                log::error!("OpenAI-compatible request failed: {}", message);
+                let response = ChatResponse::Error {
+                    message: message.clone(),
+                };
+                handler.on_done(&run_id, response.to_json());
+                return Err(message);
+            }
+        };
+
+        if !state.text.is_empty() {
+            let response = ChatResponse::Message {
+                role: "assistant".into(),
+                content: state.text.clone(),
+            };
+            handler.on_item_completed(&run_id, response.to_json());
+        }
+
+        for call in state.tool_calls.values() {
+            let response = ChatResponse::Message {
+                role: "tool".into(),
+                content: format!("Tool {} call:\n{}", call.name, call.arguments),
+            };
+            handler.on_item_completed(&run_id, response.to_json());
+        }
+
+        messages.push(Self::assistant_message(&state));
+        self.store_conversation(thread_id.clone(), messages);
+
+        let response = ChatResponse::done(state.usage);
+        handler.on_done(&run_id, response.to_json());
+
+        Ok(TurnOutcome {
+            run_id,
+            thread_id: Some(thread_id),
+            usage: state.usage,
+            last_message: Some(state.text).filter(|text| !text.is_empty()),
+        })
+    }
+
+    async fn send_and_stream<H: CodexEventHandler>(
+        &self,
+        agent: &AgentConfig,
+        run_id: &str,
+        endpoint: &str,
+        body: &Value,
+        handler: &H,
+    ) -> Result<StreamState, String> {
+        let response = self
+            .client
+            .post(endpoint)
+            .headers(Self::build_headers(agent)?)
+            .json(body)
+            .send()
+            .await
+            .map_err(|error| format!("failed to reach agent endpoint {endpoint}: {error}"))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let detail = response.text().await.unwrap_or_default();
+            return Err(format!(
+                "agent endpoint returned {status}: {}",
+                detail.trim()
+            ));
+        }
+
+        let mut decoder = SseDecoder::new();
+        let mut state = StreamState {
+            run_id: run_id.to_string(),
+            ..StreamState::default()
+        };
+        let mut chunks = response.bytes_stream();
+
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk.map_err(|error| format!("failed to read agent stream: {error}"))?;
+            for data in decoder.push(&chunk) {
+                Self::apply_event(agent.api_style, &data, &mut state, handler)?;
+            }
+
+            if let Some(reason) = handler.cancel_reason(run_id) {
+                return Err(reason);
+            }
+
+            if state.completed {
+                return Ok(state);
+            }
+        }
+
+        for data in decoder.finish() {
+            Self::apply_event(agent.api_style, &data, &mut state, handler)?;
+        }
+
+        Ok(state)
+    }
+
+    fn apply_event<H: CodexEventHandler>(
+        api_style: AgentApiStyle,
+        data: &str,
+        state: &mut StreamState,
+        handler: &H,
+    ) -> Result<(), String> {
+        if data.trim() == DONE_MARKER {
+            state.completed = true;
+            return Ok(());
+        }
+
+        let event: Value = serde_json::from_str(data)
+            .map_err(|error| format!("invalid event from agent stream: {error}"))?;
+
+        match api_style {
+            AgentApiStyle::ChatCompletions => Self::apply_chat_chunk(&event, state, handler),
+            AgentApiStyle::Responses => Self::apply_response_event(&event, state, handler),
+        }
+    }
+
+    fn apply_chat_chunk<H: CodexEventHandler>(
+        chunk: &Value,
+        state: &mut StreamState,
+        handler: &H,
+    ) -> Result<(), String> {
+        if let Some(error) = chunk.get("error") {
+            return Err(Self::error_message(error));
+        }
+
+        if let Some(usage) = chunk.get("usage").filter(|usage| usage.is_object()) {
+            state.usage = TokenUsage {
+                input_tokens: Self::token_count(&usage["prompt_tokens"]),
+                cached_input_tokens: Self::token_count(
+                    &usage["prompt_tokens_details"]["cached_tokens"],
+                ),
+                output_tokens: Self::token_count(&usage["completion_tokens"]),
+                reasoning_output_tokens: Self::token_count(
+                    &usage["completion_tokens_details"]["reasoning_tokens"],
+                ),
+            };
+            handler.on_usage(&state.run_id, &state.usage);
+        }
+
+        let Some(choices) = chunk.get("choices").and_then(Value::as_array) else {
+            return Ok(());
+        };
+
+        for choice in choices {
+            let delta = &choice["delta"];
+
+            if let Some(text) = delta.get("content").and_then(Value::as_str) {
+                Self::append_text(text, state, handler);
+            }
+
+            let Some(calls) = delta.get("tool_calls").and_then(Value::as_array) else {
+                continue;
+            };
+
+            for call in calls {
+                let index = call.get("index").and_then(Value::as_u64).unwrap_or(0) as usize;
+                let entry = state.tool_calls.entry(index).or_default();
+
+                if let Some(function) = call.get("function") {
+                    if let Some(name) = function.get("name").and_then(Value::as_str) {
+                        entry.name.push_str(name);
+                    }
+                    if let Some(arguments) = function.get("arguments").and_then(Value::as_str) {
+                        entry.arguments.push_str(arguments);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply_response_event<H: CodexEventHandler>(
+        event: &Value,
+        state: &mut StreamState,
+        handler: &H,
+    ) -> Result<(), String> {
+        match event
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+        {
+            "response.output_text.delta" => {
+                if let Some(text) = event.get("delta").and_then(Value::as_str) {
+                    Self::append_text(text, state, handler);
+                }
+            }
+            "response.output_item.done" => {
+                let item = &event["item"];
+                if item.get("type").and_then(Value::as_str) == Some("function_call") {
+                    let index = state.tool_calls.len();
+                    state.tool_calls.insert(
+                        index,
+                        ToolCall {
+                            name: item["name"].as_str().unwrap_or_default().to_string(),
+                            arguments: item["arguments"].as_str().unwrap_or_default().to_string(),
+                        },
+                    );
+                }
+            }
+            "response.completed" => {
+                Self::apply_response_usage(&event["response"]["usage"], state, handler);
+                state.completed = true;
+            }
+            "response.failed" => {
+                Self::apply_response_usage(&event["response"]["usage"], state, handler);
+                return Err(Self::error_message(&event["response"]["error"]));
+            }
+            "error" => {
+                return Err(Self::error_message(event));
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn apply_response_usage<H: CodexEventHandler>(
+        usage: &Value,
+        state: &mut StreamState,
+        handler: &H,
+    ) {
+        if !usage.is_object() {
+            return;
+        }
+
+        state.usage = TokenUsage {
+            input_tokens: Self::token_count(&usage["input_tokens"]),
+            cached_input_tokens: Self::token_count(&usage["input_tokens_details"]["cached_tokens"]),
+            output_tokens: Self::token_count(&usage["output_tokens"]),
+            reasoning_output_tokens: Self::token_count(
+                &usage["output_tokens_details"]["reasoning_tokens"],
+            ),
+        };
+        handler.on_usage(&state.run_id, &state.usage);
+    }
+
+    fn append_text<H: CodexEventHandler>(text: &str, state: &mut StreamState, handler: &H) {
+        if text.is_empty() {
+            return;
+        }
+
+        state.text.push_str(text);
+        let response = ChatResponse::Token {
+            text: text.to_string(),
+        };
+        handler.on_item(&state.run_id, response.to_json());
+    }
+
+    /// Tool calls are shown but not executed, so the history keeps them as
+    /// text: a `tool_calls` entry without `tool` results would make the API
+    /// reject the thread's next turn.
+    fn assistant_message(state: &StreamState) -> Value {
+        let mut content = state.text.clone();
+        for call in state.tool_calls.values() {
+            if !content.is_empty() {
+                content.push_str("\n\n");
+            }
+            content.push_str(&format!(
+                "Requested tool {} with {}",
+                call.name, call.arguments
+            ));
+        }
+
+        json!({ "role": "assistant", "content": content })
+    }
+
+    fn with_tools(mut body: Value, tools: Option<&[Value]>) -> Value {
+        if let Some(tools) = tools.filter(|tools| !tools.is_empty()) {
+            body["tools"] = Value::Array(tools.to_vec());
+        }
+        body
+    }
+
+    fn with_output_schema(
+        mut body: Value,
+        api_style: &AgentApiStyle,
+        schema: Option<Value>,
+    ) -> Value {
+        let Some(schema) = schema else {
+            return body;
+        };
+
+        match api_style {
+            AgentApiStyle::ChatCompletions => {
+                body["response_format"] = json!({
+                    "type": "json_schema",
+                    "json_schema": { "name": "output", "schema": schema, "strict": true },
+                });
+            }
+            AgentApiStyle::Responses => {
+                body["text"] = json!({
+                    "format": {
+                        "type": "json_schema",
+                        "name": "output",
+                        "schema": schema,
+                        "strict": true,
+                    },
+                });
+            }
+        }
+        body
+    }
+
+    fn build_headers(agent: &AgentConfig) -> Result<HeaderMap, String> {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static("text/event-stream"));
+
+        let api_key = agent.api_key.trim();
+        if !api_key.is_empty() {
+            let value = HeaderValue::from_str(&format!("Bearer {api_key}"))
+                .map_err(|error| format!("invalid api key for agent '{}': {error}", agent.name))?;
+            headers.insert(AUTHORIZATION, value);
+        }
+
+        for (name, value) in &agent.headers {
+            let header_name = HeaderName::from_bytes(name.trim().as_bytes())
+                .map_err(|error| format!("invalid header name '{name}': {error}"))?;
+            let header_value = HeaderValue::from_str(value.trim())
+                .map_err(|error| format!("invalid value for header '{name}': {error}"))?;
+            headers.insert(header_name, header_value);
+        }
+
+        Ok(headers)
+    }
+
+    fn token_count(value: &Value) -> u64 {
+        value.as_u64().unwrap_or(0)
+    }
+
+    fn error_message(error: &Value) -> String {
+        error
+            .get("message")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .or_else(|| error.as_str().map(str::to_string))
+            .unwrap_or_else(|| error.to_string())
+    }
+
+    fn conversation(&self, thread_id: &str) -> Vec<Value> {
+        match self.conversations.lock() {
+            Ok(conversations) => conversations.get(thread_id).cloned().unwrap_or_default(),
+            Err(error) => {
+                log::error!("failed to lock conversations: {}", error);
+                Vec::new()
+            }
+        }
+    }
+
+    fn store_conversation(&self, thread_id: String, messages: Vec<Value>) {
+        match self.conversations.lock() {
+            Ok(mut conversations) => {
+                conversations.insert(thread_id, messages);
+            }
+            Err(error) => log::error!("failed to lock conversations: {}", error),
+        }
+    }
+}
+
+pub struct SseDecoder {
+    buffer: Vec<u8>,
+    data_lines: Vec<String>,
+}
+
+impl SseDecoder {
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::new(),
+            data_lines: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        while let Some(position) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line = self.buffer.drain(..=position).collect::<Vec<_>>();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if let Some(event) = self.apply_line(line) {
+                events.push(event);
+            }
+        }
+
+        events
+    }
+
+    pub fn finish(&mut self) -> Vec<String> {
+        let remaining = std::mem::take(&mut self.buffer);
+        let line = String::from_utf8_lossy(&remaining);
+        let line = line.trim_end_matches(['\n', '\r']);
+
+        let mut events = Vec::new();
+        if let Some(event) = self.apply_line(line) {
+            events.push(event);
+        }
+        if let Some(event) = self.apply_line("") {
+            events.push(event);
+        }
+
+        events
+    }
+
+    fn apply_line(&mut self, line: &str) -> Option<String> {
+        if line.is_empty() {
+            if self.data_lines.is_empty() {
+                return None;
+            }
+            return Some(std::mem::take(&mut self.data_lines).join("\n"));
+        }
+
+        if let Some(data) = line.strip_prefix("data:") {
+            self.data_lines
+                .push(data.strip_prefix(' ').unwrap_or(data).to_string());
+        }
+
+        None
+    }
+}
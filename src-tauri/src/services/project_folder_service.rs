@@ -0,0 +1,214 @@
+use std::fs;
+use std::path::Path;
+
+use crate::models::manifest::{MovedFile, ProjectManifest, ScaffoldReport};
+use crate::models::project::Project;
+use crate::services::context_service::IGNORE_FILE;
+use crate::utils::current_timestamp_millis;
+
+pub const VIBEFLOW_FOLDER: &str = ".vibeflow";
+pub const MANIFEST_FILE: &str = ".vibeflow/project.json";
+pub const README_FILE: &str = ".vibeflow/README.md";
+pub const RULES_FOLDER: &str = ".vibeflow/rules";
+pub const TASKS_FOLDER: &str = ".vibeflow/tasks";
+/// Bumped whenever the folder layout changes; `repair` upgrades older ones.
+pub const LAYOUT_VERSION: u32 = 1;
+/// Where rules and exported tasks were written before `.vibeflow/` existed.
+const LEGACY_FOLDER: &str = ".codex";
+/// Headings the frontend wrote into exported task files.
+const TASK_SECTIONS: [&str; 3] = ["## Pre Steps", "## Main Steps", "## Post Steps"];
+
+const README_TEMPLATE: &str = include_str!("../../templates/vibeflow/README.md");
+const IGNORE_TEMPLATE: &str = include_str!("../../templates/vibeflow/ignore");
+
+pub struct ProjectFolderService;
+
+impl ProjectFolderService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn has_manifest(root: &Path) -> bool {
+        root.join(MANIFEST_FILE).is_file()
+    }
+
+    pub fn read_manifest(root: &Path) -> Result<Option<ProjectManifest>, String> {
+        let content = match fs::read_to_string(root.join(MANIFEST_FILE)) {
+            Ok(content) => content,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(format!("failed to read {MANIFEST_FILE}: {error}")),
+        };
+
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|error| format!("invalid {MANIFEST_FILE}: {error}"))
+    }
+
+    pub fn write_manifest(root: &Path, manifest: &ProjectManifest) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(manifest)
+            .map_err(|error| format!("failed to serialize manifest: {error}"))?;
+        Self::write(root, MANIFEST_FILE, &format!("{content}\n"))
+    }
+
+    /// Creates `.vibeflow/` for a project that has no manifest yet; returns
+    /// `None` and leaves the folder alone otherwise. Only missing files are
+    /// written: legacy `.codex` files stay where they are until `repair`.
+    pub fn scaffold(&self, project: &Project) -> Result<Option<ScaffoldReport>, String> {
+        let root = Path::new(&project.path);
+        if Self::has_manifest(root) {
+            return Ok(None);
+        }
+
+        let mut report = ScaffoldReport {
+            version: LAYOUT_VERSION,
+            ..ScaffoldReport::default()
+        };
+        Self::create_missing(root, project, None, &mut report)?;
+        Ok(Some(report))
+    }
+
+    /// Brings `.vibeflow/` up to [`LAYOUT_VERSION`]: moves legacy `.codex`
+    /// rule and task files, recreates missing template files and rewrites
+    /// the manifest. Existing files are never overwritten.
+    ///
+    /// Rules linked to moved files are re-linked; rules that had been
+    /// imported from exported task files are dropped from `project`.
+    pub fn repair(&self, project: &mut Project) -> Result<ScaffoldReport, String> {
+        let root = Path::new(&project.path).to_path_buf();
+        let manifest = Self::read_manifest(&root)?;
+        if let Some(manifest) = &manifest {
+            if manifest.version > LAYOUT_VERSION {
+                return Err(format!(
+                    "{MANIFEST_FILE} uses layout {} but this VibeFlow only knows layout {LAYOUT_VERSION}",
+                    manifest.version
+                ));
+            }
+        }
+
+        let mut report = ScaffoldReport {
+            version: LAYOUT_VERSION,
+            previous_version: manifest.as_ref().map(|manifest| manifest.version),
+            ..ScaffoldReport::default()
+        };
+        Self::migrate_legacy(&root, project, &mut report)?;
+        Self::create_missing(&root, project, manifest, &mut report)?;
+        Ok(report)
+    }
+
+    /// Writes the folders and template files that do not exist yet and the
+    /// manifest when it differs from `manifest`.
+    fn create_missing(
+        root: &Path,
+        project: &Project,
+        manifest: Option<ProjectManifest>,
+        report: &mut ScaffoldReport,
+    ) -> Result<(), String> {
+        for folder in [RULES_FOLDER, TASKS_FOLDER] {
+            if !root.join(folder).is_dir() {
+                fs::create_dir_all(root.join(folder))
+                    .map_err(|error| format!("failed to create {folder}: {error}"))?;
+                report.created.push(folder.to_string());
+            }
+        }
+        for (path, content) in [
+            (README_FILE, README_TEMPLATE),
+            (IGNORE_FILE, IGNORE_TEMPLATE),
+        ] {
+            if !root.join(path).exists() {
+                Self::write(root, path, content)?;
+                report.created.push(path.to_string());
+            }
+        }
+
+        let upgraded = ProjectManifest {
+            version: LAYOUT_VERSION,
+            name: manifest
+                .as_ref()
+                .map(|manifest| manifest.name.clone())
+                .unwrap_or_else(|| project.name.clone()),
+            created_at: manifest
+                .as_ref()
+                .map(|manifest| manifest.created_at)
+                .unwrap_or_else(current_timestamp_millis),
+            store_in_repo: manifest
+                .as_ref()
+                .is_some_and(|manifest| manifest.store_in_repo),
+        };
+        if manifest.as_ref() != Some(&upgraded) {
+            Self::write_manifest(root, &upgraded)?;
+            if manifest.is_none() {
+                report.created.push(MANIFEST_FILE.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the Markdown files directly in `.codex/` into `rules/` or, when
+    /// they look like exported tasks, `tasks/`.
+    fn migrate_legacy(
+        root: &Path,
+        project: &mut Project,
+        report: &mut ScaffoldReport,
+    ) -> Result<(), String> {
+        let Ok(entries) = fs::read_dir(root.join(LEGACY_FOLDER)) else {
+            return Ok(());
+        };
+        let mut files = entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_file()))
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .filter(|name| name.ends_with(".md"))
+            .collect::<Vec<_>>();
+        files.sort();
+
+        for name in files {
+            let from = format!("{LEGACY_FOLDER}/{name}");
+            let content = fs::read_to_string(root.join(&from))
+                .map_err(|error| format!("failed to read {from}: {error}"))?;
+            let is_task = content
+                .lines()
+                .any(|line| TASK_SECTIONS.contains(&line.trim_end()));
+            let folder = if is_task { TASKS_FOLDER } else { RULES_FOLDER };
+            let to = format!("{folder}/{name}");
+            if root.join(&to).exists() {
+                log::info!("Left {} in place, {} already exists", from, to);
+                continue;
+            }
+
+            fs::create_dir_all(root.join(folder))
+                .map_err(|error| format!("failed to create {folder}: {error}"))?;
+            fs::rename(root.join(&from), root.join(&to))
+                .map_err(|error| format!("failed to move {from} to {to}: {error}"))?;
+
+            if is_task {
+                project
+                    .rules
+                    .retain(|rule| rule.source_path.as_deref() != Some(from.as_str()));
+            } else {
+                for rule in project
+                    .rules
+                    .iter_mut()
+                    .filter(|rule| rule.source_path.as_deref() == Some(from.as_str()))
+                {
+                    rule.source_path = Some(to.clone());
+                }
+            }
+            report.moved.push(MovedFile { from, to });
+        }
+
+        // Only succeeds once nothing else is left in the folder.
+        let _ = fs::remove_dir(root.join(LEGACY_FOLDER));
+        Ok(())
+    }
+
+    fn write(root: &Path, path: &str, content: &str) -> Result<(), String> {
+        let file_path = root.join(path);
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent).map_err(|error| {
+                format!("failed to create directory '{}': {error}", parent.display())
+            })?;
+        }
+
+        fs::write(&file_path, content).map_err(|error| format!("failed to write {path}: {error}"))
+    }
+}
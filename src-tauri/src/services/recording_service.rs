@@ -0,0 +1,161 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use codex_sdk::ThreadEvent;
+
+use crate::models::session_recording::SessionRecording;
+use crate::services::scripted_agent::ScriptedAgent;
+use crate::utils::current_timestamp_millis;
+
+const RECORDINGS_FOLDER_NAME: &str = "recordings";
+const RECORDING_EXTENSION: &str = "jsonl";
+
+pub struct RecordingService {
+    recordings_dir: PathBuf,
+}
+
+pub struct SessionRecorder {
+    file: File,
+    path: PathBuf,
+}
+
+impl RecordingService {
+    pub fn new(app_data_dir: PathBuf) -> Self {
+        Self {
+            recordings_dir: app_data_dir.join(RECORDINGS_FOLDER_NAME),
+        }
+    }
+
+    pub fn recordings_dir(&self) -> &PathBuf {
+        &self.recordings_dir
+    }
+
+    pub fn start_recording(&self) -> Result<SessionRecorder, String> {
+        fs::create_dir_all(&self.recordings_dir).map_err(|error| {
+            format!(
+                "failed to create recordings directory '{}': {error}",
+                self.recordings_dir.display()
+            )
+        })?;
+
+        let id = format!(
+            "session-{}-{}",
+            current_timestamp_millis(),
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        );
+        let path = self.recording_path(&id)?;
+
+        SessionRecorder::create(path)
+    }
+
+    pub fn list_recordings(&self) -> Result<Vec<SessionRecording>, String> {
+        if !self.recordings_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let entries = fs::read_dir(&self.recordings_dir)
+            .map_err(|error| format!("failed to read recordings directory: {error}"))?;
+
+        let mut recordings = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension().and_then(|ext| ext.to_str()) == Some(RECORDING_EXTENSION)
+            })
+            .filter_map(|path| Self::describe_recording(&path))
+            .collect::<Vec<_>>();
+
+        recordings.sort_by_key(|recording| std::cmp::Reverse(recording.created_at));
+        Ok(recordings)
+    }
+
+    pub fn load_recording(&self, recording_id: &str) -> Result<ScriptedAgent, String> {
+        let path = self.recording_path(recording_id)?;
+        if !path.exists() {
+            return Err(format!("recording not found: {recording_id}"));
+        }
+
+        ScriptedAgent::from_jsonl_file(path)
+    }
+
+    fn recording_path(&self, recording_id: &str) -> Result<PathBuf, String> {
+        let trimmed = recording_id.trim();
+        let is_plain_name = !trimmed.is_empty()
+            && trimmed
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+        if !is_plain_name {
+            return Err(format!("invalid recording id: {recording_id}"));
+        }
+
+        Ok(self
+            .recordings_dir
+            .join(format!("{trimmed}.{RECORDING_EXTENSION}")))
+    }
+
+    fn describe_recording(path: &Path) -> Option<SessionRecording> {
+        let id = path.file_stem()?.to_str()?.to_string();
+        let metadata = fs::metadata(path).ok()?;
+        let created_at = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_millis() as i64)
+            .unwrap_or(0);
+
+        let agent = ScriptedAgent::from_jsonl_file(path).ok();
+        let thread_id = agent.as_ref().and_then(|agent| {
+            agent.events().iter().find_map(|event| match event {
+                ThreadEvent::ThreadStarted { thread_id } => Some(thread_id.clone()),
+                _ => None,
+            })
+        });
+
+        Some(SessionRecording {
+            id,
+            path: path.to_string_lossy().to_string(),
+            thread_id,
+            event_count: agent.map(|agent| agent.events().len()).unwrap_or(0),
+            size_bytes: metadata.len(),
+            created_at,
+        })
+    }
+}
+
+impl SessionRecorder {
+    fn create(path: PathBuf) -> Result<Self, String> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|error| format!("failed to create recording '{}': {error}", path.display()))?;
+
+        log::info!("Recording session events to {}", path.display());
+        Ok(Self { file, path })
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn record(&mut self, event: &ThreadEvent) {
+        let line = match serde_json::to_string(event) {
+            Ok(line) => line,
+            Err(error) => {
+                log::error!("failed to serialize thread event for recording: {}", error);
+                return;
+            }
+        };
+
+        if let Err(error) = writeln!(self.file, "{line}") {
+            log::error!(
+                "failed to write recording '{}': {}",
+                self.path.display(),
+                error
+            );
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::path::Path;
+
+use globset::{GlobBuilder, GlobMatcher};
+
+use crate::models::agent_rule::{AgentRule, RuleContext};
+use crate::models::chat::ChatRequest;
+use crate::models::project::Project;
+
+pub const AGENTS_FILE_NAME: &str = "AGENTS.md";
+
+pub struct RuleService;
+
+impl RuleService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// The context of a turn in `project`: its working directory, the
+    /// `files` it is given, the folders its step watches and the tags of its task.
+    pub fn context_for(
+        &self,
+        project: &Project,
+        request: &ChatRequest,
+        files: &[String],
+    ) -> RuleContext {
+        let mut paths = request
+            .working_directory
+            .as_deref()
+            .and_then(|directory| Path::new(directory).strip_prefix(&project.path).ok())
+            .map(|path| path.to_string_lossy().replace('\\', "/"))
+            .filter(|path| !path.is_empty())
+            .into_iter()
+            .chain(files.iter().cloned())
+            .collect::<Vec<_>>();
+        let mut tags = request.rule_tags.clone().unwrap_or_default();
+
+        let task = request
+            .task_id
+            .as_deref()
+            .and_then(|task_id| project.tasks.iter().find(|task| task.id == task_id));
+        if let Some(task) = task {
+            tags.extend(task.tags.iter().cloned());
+
+            let step = request.step_id.as_deref().and_then(|step_id| {
+                task.presteps
+                    .iter()
+                    .chain(&task.steps)
+                    .chain(&task.poststeps)
+                    .find(|step| step.id == step_id)
+            });
+            if let Some(step) = step {
+                paths.extend(
+                    step.if_changed
+                        .iter()
+                        .map(|glob| Self::scope_base(glob))
+                        .filter(|base| !base.is_empty()),
+                );
+            }
+        }
+
+        tags.sort();
+        tags.dedup();
+        paths.sort();
+        paths.dedup();
+        RuleContext { paths, tags }
+    }
+
+    pub fn applicable_rules<'a>(
+        &self,
+        rules: &'a [AgentRule],
+        context: &RuleContext,
+    ) -> Vec<&'a AgentRule> {
+        let mut applicable = rules
+            .iter()
+            .filter(|rule| rule.enabled)
+            .filter(|rule| Self::matches_tags(rule, &context.tags))
+            .filter(|rule| Self::matches_scope(rule, &context.paths))
+            .collect::<Vec<_>>();
+        applicable.sort_by(|left, right| {
+            left.priority
+                .cmp(&right.priority)
+                .then_with(|| left.name.cmp(&right.name))
+        });
+        applicable
+    }
+
+    /// Prepends the applicable rules to `prompt`, leaving it untouched when none apply.
+    pub fn assemble_prompt(
+        &self,
+        rules: &[AgentRule],
+        context: &RuleContext,
+        prompt: &str,
+    ) -> String {
+        let applicable = self.applicable_rules(rules, context);
+        if applicable.is_empty() {
+            return prompt.to_string();
+        }
+
+        format!("{}\n---\n\n{}", Self::render_rules(&applicable), prompt)
+    }
+
+    fn render_rules(rules: &[&AgentRule]) -> String {
+        let mut rendered = String::from("# Project rules\n");
+        for rule in rules {
+            rendered.push_str(&format!("\n## {}\n", rule.name));
+            if let Some(description) = rule
+                .description
+                .as_deref()
+                .filter(|description| !description.trim().is_empty())
+            {
+                rendered.push_str(&format!("\n_{}_\n", description.trim()));
+            }
+            if !rule.content.trim().is_empty() {
+                rendered.push_str(&format!("\n{}\n", rule.content.trim()));
+            }
+        }
+        rendered
+    }
+
+    fn matches_tags(rule: &AgentRule, tags: &[String]) -> bool {
+        tags.is_empty() || rule.tags.is_empty() || rule.tags.iter().any(|tag| tags.contains(tag))
+    }
+
+    /// A scoped rule applies when one of its globs matches one of the turn's
+    /// paths or one of their ancestors.
+    fn matches_scope(rule: &AgentRule, paths: &[String]) -> bool {
+        if rule.scopes.is_empty() {
+            return true;
+        }
+        if paths.is_empty() {
+            return false;
+        }
+
+        let matchers = rule
+            .scopes
+            .iter()
+            .filter_map(|scope| Self::compile_scope(&rule.name, scope))
+            .collect::<Vec<_>>();
+
+        paths.iter().any(|path| {
+            Path::new(path)
+                .ancestors()
+                .filter(|path| !path.as_os_str().is_empty())
+                .any(|path| matchers.iter().any(|matcher| matcher.is_match(path)))
+        })
+    }
+
+    fn compile_scope(rule_name: &str, scope: &str) -> Option<GlobMatcher> {
+        match GlobBuilder::new(scope.trim_end_matches('/'))
+            .literal_separator(true)
+            .build()
+        {
+            Ok(glob) => Some(glob.compile_matcher()),
+            Err(error) => {
+                log::error!("invalid scope '{}' on rule {}: {}", scope, rule_name, error);
+                None
+            }
+        }
+    }
+
+    fn scope_base(scope: &str) -> String {
+        scope
+            .split('/')
+            .take_while(|segment| !segment.contains(['*', '?', '[', '{']))
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use ignore::WalkBuilder;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::models::agent_rule::{
+    AgentRule, RuleConflict, RuleConflictKind, RuleConflictResolution, RuleSyncReport,
+};
+use crate::models::project::Project;
+use crate::services::project_folder_service::{ProjectFolderService, RULES_FOLDER};
+use crate::services::rule_service::AGENTS_FILE_NAME;
+use crate::utils::current_timestamp_millis;
+
+/// Where rule files not tied to a directory of the repo live in projects
+/// without a `.vibeflow/` manifest.
+pub const LEGACY_RULES_FOLDER: &str = ".codex";
+
+pub struct RuleSyncService;
+
+impl RuleSyncService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Reconciles `project.rules` with the rule files in the project folder.
+    ///
+    /// Each linked rule is compared with its file against the hash recorded at
+    /// the last sync: whichever side changed wins, and a change on both sides
+    /// is reported as a conflict and left untouched.
+    pub fn sync(&self, project: &mut Project) -> Result<RuleSyncReport, String> {
+        let root = Path::new(&project.path);
+        let folder = rules_folder(root);
+        let mut disk_files = self.discover(root, folder)?;
+        let mut report = RuleSyncReport::default();
+
+        for rule in project.rules.iter_mut() {
+            let path = match rule.source_path.clone() {
+                Some(path) => path,
+                None => Self::default_path(rule, folder),
+            };
+
+            let Some(disk_content) = disk_files.remove(&path) else {
+                if rule.source_path.is_some() {
+                    report.conflicts.push(Self::conflict(
+                        rule,
+                        &path,
+                        RuleConflictKind::DeletedOnDisk,
+                    ));
+                } else {
+                    Self::write(root, &path, &rule.content)?;
+                    Self::link(rule, &path);
+                    report.exported.push(path);
+                }
+                continue;
+            };
+
+            let disk_hash = content_hash(&disk_content);
+            let app_hash = content_hash(&rule.content);
+            if disk_hash == app_hash {
+                Self::link(rule, &path);
+                continue;
+            }
+
+            let base = rule.synced_hash.as_deref();
+            if base == Some(app_hash.as_str()) {
+                rule.content = disk_content;
+                rule.updated_at = current_timestamp_millis();
+                Self::link(rule, &path);
+                report.updated.push(path);
+            } else if base == Some(disk_hash.as_str()) {
+                Self::write(root, &path, &rule.content)?;
+                Self::link(rule, &path);
+                report.exported.push(path);
+            } else {
+                rule.source_path = Some(path.clone());
+                report
+                    .conflicts
+                    .push(Self::conflict(rule, &path, RuleConflictKind::BothChanged));
+            }
+        }
+
+        for (path, content) in disk_files {
+            let rule = Self::import(&path, content, folder);
+            log::info!("Imported rule {} from {}", rule.name, path);
+            project.rules.push(rule);
+            report.imported.push(path);
+        }
+
+        report.rules = project.rules.clone();
+        Ok(report)
+    }
+
+    pub fn resolve_conflict(
+        &self,
+        project: &mut Project,
+        rule_id: &str,
+        resolution: RuleConflictResolution,
+    ) -> Result<(), String> {
+        let root = Path::new(&project.path);
+        let index = project
+            .rules
+            .iter()
+            .position(|rule| rule.id == rule_id)
+            .ok_or_else(|| format!("rule not found: {rule_id}"))?;
+        let path = {
+            let rule = &project.rules[index];
+            rule.source_path
+                .clone()
+                .unwrap_or_else(|| Self::default_path(rule, rules_folder(root)))
+        };
+
+        match resolution {
+            RuleConflictResolution::KeepApp => {
+                let rule = &mut project.rules[index];
+                Self::write(root, &path, &rule.content)?;
+                Self::link(rule, &path);
+            }
+            RuleConflictResolution::KeepDisk => match fs::read_to_string(root.join(&path)) {
+                Ok(content) => {
+                    let rule = &mut project.rules[index];
+                    rule.content = content;
+                    rule.updated_at = current_timestamp_millis();
+                    Self::link(rule, &path);
+                }
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                    project.rules.remove(index);
+                }
+                Err(error) => return Err(format!("failed to read rule file '{path}': {error}")),
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Finds every `AGENTS.md` in the repo plus the markdown files directly
+    /// in `folder`, keyed by path relative to `root`.
+    fn discover(&self, root: &Path, folder: &str) -> Result<BTreeMap<String, String>, String> {
+        let mut files = BTreeMap::new();
+        let walker = WalkBuilder::new(root)
+            .hidden(false)
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .build();
+
+        for entry in walker {
+            let entry = entry.map_err(|error| format!("failed to scan project: {error}"))?;
+            if !entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_file())
+            {
+                continue;
+            }
+
+            let Ok(relative) = entry.path().strip_prefix(root) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            let is_agents_file = entry.file_name() == AGENTS_FILE_NAME;
+            let is_rule_file = relative.starts_with(&format!("{folder}/"))
+                && relative.ends_with(".md")
+                && !relative[folder.len() + 1..].contains('/');
+            if !is_agents_file && !is_rule_file {
+                continue;
+            }
+
+            let content = fs::read_to_string(entry.path())
+                .map_err(|error| format!("failed to read rule file '{relative}': {error}"))?;
+            files.insert(relative, content);
+        }
+
+        Ok(files)
+    }
+
+    fn import(path: &str, content: String, folder: &str) -> AgentRule {
+        let now = current_timestamp_millis();
+        let (name, scopes) = match path.strip_suffix(&format!("/{AGENTS_FILE_NAME}")) {
+            Some(directory) if directory != folder => {
+                (path.to_string(), vec![directory.to_string()])
+            }
+            _ if path.ends_with(AGENTS_FILE_NAME) => (AGENTS_FILE_NAME.to_string(), Vec::new()),
+            _ => (
+                Path::new(path)
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| path.to_string()),
+                Vec::new(),
+            ),
+        };
+
+        AgentRule {
+            id: Uuid::new_v4().to_string(),
+            name,
+            description: None,
+            synced_hash: Some(content_hash(&content)),
+            content,
+            enabled: true,
+            priority: 0,
+            scopes,
+            tags: Vec::new(),
+            source_path: Some(path.to_string()),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Where a rule that was never synced is written.
+    fn default_path(rule: &AgentRule, folder: &str) -> String {
+        if rule.name.eq_ignore_ascii_case(AGENTS_FILE_NAME) {
+            return format!("{folder}/{AGENTS_FILE_NAME}");
+        }
+
+        format!("{folder}/{}.md", safe_file_name(&rule.name, "rule"))
+    }
+
+    fn link(rule: &mut AgentRule, path: &str) {
+        rule.source_path = Some(path.to_string());
+        rule.synced_hash = Some(content_hash(&rule.content));
+    }
+
+    fn conflict(rule: &AgentRule, path: &str, kind: RuleConflictKind) -> RuleConflict {
+        log::info!("Rule {} conflicts with {}: {:?}", rule.name, path, kind);
+        RuleConflict {
+            rule_id: rule.id.clone(),
+            rule_name: rule.name.clone(),
+            path: path.to_string(),
+            kind,
+        }
+    }
+
+    fn write(root: &Path, path: &str, content: &str) -> Result<(), String> {
+        let file_path = root.join(path);
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent).map_err(|error| {
+                format!("failed to create directory '{}': {error}", parent.display())
+            })?;
+        }
+
+        fs::write(&file_path, content)
+            .map_err(|error| format!("failed to write rule file '{path}': {error}"))
+    }
+}
+
+/// `.vibeflow/rules` once the project folder is scaffolded, else the
+/// folder the frontend used to export rules to.
+pub fn rules_folder(root: &Path) -> &'static str {
+    if ProjectFolderService::has_manifest(root) {
+        RULES_FOLDER
+    } else {
+        LEGACY_RULES_FOLDER
+    }
+}
+
+pub fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// Lower-cased, dash-separated form of `value` usable as a file name.
+pub(crate) fn safe_file_name(value: &str, fallback: &str) -> String {
+    let sanitized = value
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_control() || "<>:\"/\\|?*".contains(c) || c.is_whitespace() {
+                '-'
+            } else {
+                c
+            }
+        })
+        .collect::<String>()
+        .split('-')
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    if sanitized.is_empty() {
+        fallback.to_string()
+    } else {
+        sanitized
+    }
+}
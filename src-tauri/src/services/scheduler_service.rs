@@ -0,0 +1,520 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{
+    DateTime, Datelike, LocalResult, NaiveDate, NaiveDateTime, Offset, TimeDelta, TimeZone,
+    Timelike,
+};
+use chrono_tz::Tz;
+use uuid::Uuid;
+
+use crate::models::schedule::{MissedRunPolicy, ScheduleDecision, ScheduledRunEvent, TaskSchedule};
+use crate::models::task::{TaskRun, TaskStatus};
+use crate::services::data_service::DataService;
+
+pub const TASK_RUN_DUE_EVENT: &str = "task-schedule:due";
+
+/// How often the scheduler looks for due runs.
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(30);
+/// A run started this late still counts as on time rather than missed.
+const ON_TIME_GRACE_MS: i64 = 5 * 60 * 1000;
+const MINUTE_MS: i64 = 60_000;
+/// Long enough to reach the next 29 February from any date.
+const MAX_SEARCH_DAYS: usize = 366 * 8;
+const MAX_COUNTED_MISSED_RUNS: u32 = 1000;
+
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// A parsed five-field cron expression. Each field is a bit set of the
+/// values it matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = expression.trim();
+        let expanded = match expression.to_ascii_lowercase().as_str() {
+            "@hourly" => "0 * * * *".to_string(),
+            "@daily" | "@midnight" => "0 0 * * *".to_string(),
+            "@weekly" => "0 0 * * 0".to_string(),
+            "@monthly" => "0 0 1 * *".to_string(),
+            "@yearly" | "@annually" => "0 0 1 1 *".to_string(),
+            _ => expression.to_string(),
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "cron expression '{expression}' needs five fields: minute, hour, day of month, month and day of week"
+            ));
+        };
+
+        let mut weekdays = parse_field(weekday, "day of week", 0, 7, &WEEKDAY_NAMES)?;
+        // Both 0 and 7 are Sunday.
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        let schedule = Self {
+            minutes: parse_field(minute, "minute", 0, 59, &[])?,
+            hours: parse_field(hour, "hour", 0, 23, &[])?,
+            days: parse_field(day, "day of month", 1, 31, &[])?,
+            months: parse_field(month, "month", 1, 12, &MONTH_NAMES)?,
+            weekdays,
+            any_day: day.starts_with('*'),
+            any_weekday: weekday.starts_with('*'),
+        };
+
+        if schedule.next_after(0, &CronTimeZone::Fixed(0)).is_none() {
+            return Err(format!(
+                "cron expression '{expression}' never matches a date"
+            ));
+        }
+        Ok(schedule)
+    }
+
+    /// The first matching minute after `after` (epoch millis), with the
+    /// fields read in `zone`. The search starts at the local minute of
+    /// `after` because around a daylight saving change later local minutes
+    /// can come earlier in time.
+    pub fn next_after(&self, after: i64, zone: &CronTimeZone) -> Option<i64> {
+        let start = zone.local_time_of(after)?;
+        let first_day = start.date();
+
+        first_day
+            .iter_days()
+            .take(MAX_SEARCH_DAYS)
+            .filter(|day| self.matches_day(*day))
+            .find_map(|day| {
+                let mut from_minute = if day == first_day {
+                    i64::from(start.hour() * 60 + start.minute())
+                } else {
+                    0
+                };
+                while let Some(minute) = self.first_minute_from(from_minute) {
+                    let local = day.and_hms_opt((minute / 60) as u32, (minute % 60) as u32, 0)?;
+                    let at = zone.instant_of(local);
+                    if at > after {
+                        return Some(at);
+                    }
+                    from_minute = minute + 1;
+                }
+                None
+            })
+    }
+
+    /// Like cron, a day matches either field when both day of month and
+    /// day of week are restricted.
+    fn matches_day(&self, day: NaiveDate) -> bool {
+        if !has(self.months, i64::from(day.month())) {
+            return false;
+        }
+
+        let day_matches = has(self.days, i64::from(day.day()));
+        let weekday_matches = has(
+            self.weekdays,
+            i64::from(day.weekday().num_days_from_sunday()),
+        );
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday_matches,
+            (false, true) => day_matches,
+            (false, false) => day_matches || weekday_matches,
+        }
+    }
+
+    fn first_minute_from(&self, from_minute: i64) -> Option<i64> {
+        (from_minute / 60..24)
+            .filter(|hour| has(self.hours, *hour))
+            .find_map(|hour| {
+                let first = if hour == from_minute / 60 {
+                    from_minute % 60
+                } else {
+                    0
+                };
+                (first..60)
+                    .find(|minute| has(self.minutes, *minute))
+                    .map(|minute| hour * 60 + minute)
+            })
+    }
+}
+
+/// The time zone a cron expression is read in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CronTimeZone {
+    /// Minutes east of UTC, all year round.
+    Fixed(i32),
+    /// A zone whose offset follows daylight saving time.
+    Named(Tz),
+}
+
+impl CronTimeZone {
+    /// The named zone when there is one, else the fixed offset.
+    pub fn new(time_zone: Option<&str>, utc_offset_minutes: i32) -> Result<Self, String> {
+        match time_zone.map(str::trim).filter(|name| !name.is_empty()) {
+            Some(name) => name
+                .parse::<Tz>()
+                .map(Self::Named)
+                .map_err(|_| format!("unknown time zone '{name}'")),
+            None => Ok(Self::Fixed(utc_offset_minutes)),
+        }
+    }
+
+    pub fn of(schedule: &TaskSchedule) -> Result<Self, String> {
+        Self::new(schedule.time_zone.as_deref(), schedule.utc_offset_minutes)
+    }
+
+    /// The local date and time at the instant `at` (epoch millis).
+    fn local_time_of(&self, at: i64) -> Option<NaiveDateTime> {
+        let utc = DateTime::from_timestamp_millis(at)?;
+        Some(match self {
+            Self::Fixed(offset) => utc.naive_utc() + TimeDelta::minutes(i64::from(*offset)),
+            Self::Named(zone) => utc.with_timezone(zone).naive_local(),
+        })
+    }
+
+    /// The instant (epoch millis) of a local time. A time that happens
+    /// twice when clocks go back counts the first time; one skipped when
+    /// clocks go forward is read with the offset from before the change, so
+    /// it lands just after it.
+    fn instant_of(&self, local: NaiveDateTime) -> i64 {
+        let zone = match self {
+            Self::Fixed(offset) => {
+                return (local - TimeDelta::minutes(i64::from(*offset)))
+                    .and_utc()
+                    .timestamp_millis()
+            }
+            Self::Named(zone) => zone,
+        };
+
+        match zone.from_local_datetime(&local) {
+            LocalResult::Single(at) | LocalResult::Ambiguous(at, _) => at.timestamp_millis(),
+            LocalResult::None => {
+                let before = zone
+                    .offset_from_utc_datetime(&(local - TimeDelta::days(1)))
+                    .fix();
+                (local - TimeDelta::seconds(i64::from(before.local_minus_utc())))
+                    .and_utc()
+                    .timestamp_millis()
+            }
+        }
+    }
+}
+
+pub struct SchedulerService {
+    running: Arc<AtomicBool>,
+    /// Due runs the UI has not taken yet. It starts them, so runs that come
+    /// due before it loads wait here instead of being lost.
+    queued: Mutex<Vec<ScheduledRunEvent>>,
+}
+
+impl SchedulerService {
+    pub fn new() -> Self {
+        Self {
+            running: Arc::new(AtomicBool::new(false)),
+            queued: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Calls `tick` on a background thread every half minute until the
+    /// service is dropped. Later calls do nothing while it runs.
+    pub fn start<F>(&self, mut tick: F)
+    where
+        F: FnMut() + Send + 'static,
+    {
+        if self.running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let running = Arc::clone(&self.running);
+        thread::spawn(move || {
+            while running.load(Ordering::SeqCst) {
+                tick();
+                thread::sleep(SCHEDULER_INTERVAL);
+            }
+        });
+    }
+
+    pub fn queue(&self, events: Vec<ScheduledRunEvent>) {
+        if let Ok(mut queued) = self.queued.lock() {
+            queued.extend(events);
+        }
+    }
+
+    /// The queued due runs, oldest first, leaving the queue empty.
+    pub fn take_queued(&self) -> Vec<ScheduledRunEvent> {
+        self.queued
+            .lock()
+            .map(|mut queued| std::mem::take(&mut *queued))
+            .unwrap_or_default()
+    }
+
+    /// Checks the schedule's cron expression and sets its next run, which
+    /// a disabled schedule does not have.
+    pub fn reschedule(&self, schedule: &mut TaskSchedule, now: i64) -> Result<(), String> {
+        let cron = CronSchedule::parse(&schedule.cron)?;
+        let zone = CronTimeZone::of(schedule)?;
+        schedule.next_run_at = if schedule.enabled {
+            cron.next_after(now, &zone)
+        } else {
+            None
+        };
+        schedule.updated_at = now;
+        Ok(())
+    }
+
+    /// The next `count` times `cron` matches after `now`.
+    pub fn preview(
+        &self,
+        cron: &str,
+        zone: &CronTimeZone,
+        now: i64,
+        count: usize,
+    ) -> Result<Vec<i64>, String> {
+        let cron = CronSchedule::parse(cron)?;
+        let mut times = Vec::with_capacity(count);
+        let mut after = now;
+        while times.len() < count {
+            let Some(next) = cron.next_after(after, zone) else {
+                break;
+            };
+            times.push(next);
+            after = next;
+        }
+        Ok(times)
+    }
+
+    /// Whether `schedule` runs at `now` and when it runs next. A run more
+    /// than a few minutes late was missed, as when the app was closed, and
+    /// `missed_run_policy` decides whether to still run once.
+    pub fn plan(&self, schedule: &TaskSchedule, now: i64) -> Result<ScheduleDecision, String> {
+        let cron = CronSchedule::parse(&schedule.cron)?;
+        let zone = CronTimeZone::of(schedule)?;
+        let next_run_at = cron.next_after(now, &zone);
+        let Some(due) = schedule.next_run_at.filter(|due| *due <= now) else {
+            return Ok(ScheduleDecision {
+                run_for: None,
+                missed: 0,
+                next_run_at: schedule.next_run_at.or(next_run_at),
+            });
+        };
+
+        let mut latest = due;
+        let mut count = 1;
+        while count < MAX_COUNTED_MISSED_RUNS {
+            match cron.next_after(latest, &zone) {
+                Some(following) if following <= now => {
+                    latest = following;
+                    count += 1;
+                }
+                _ => break,
+            }
+        }
+
+        let on_time = now - latest <= ON_TIME_GRACE_MS;
+        let (run_for, missed) = match (on_time, schedule.missed_run_policy) {
+            (true, _) | (false, MissedRunPolicy::CatchUp) => (Some(latest), count - 1),
+            (false, MissedRunPolicy::Skip) => (None, count),
+        };
+        Ok(ScheduleDecision {
+            run_for,
+            missed,
+            next_run_at,
+        })
+    }
+
+    /// Advances every enabled schedule to `now` and returns the runs to
+    /// start. Each run and each batch of missed runs is recorded in the
+    /// task's run history.
+    pub fn due_runs(
+        &self,
+        service: &DataService,
+        now: i64,
+    ) -> Result<Vec<ScheduledRunEvent>, String> {
+        let schedules = service
+            .load_task_schedules(None)
+            .map_err(|error| format!("failed to load task schedules: {error}"))?;
+
+        let mut events = Vec::new();
+        for mut schedule in schedules.into_iter().filter(|schedule| schedule.enabled) {
+            let task_exists = service
+                .load_project(&schedule.project_id)
+                .map_err(|error| format!("failed to load project: {error}"))?
+                .is_some_and(|project| {
+                    project.tasks.iter().any(|task| task.id == schedule.task_id)
+                });
+            if !task_exists {
+                log::info!("removing schedule {} of a deleted task", schedule.id);
+                service
+                    .delete_task_schedule(&schedule.id)
+                    .map_err(|error| format!("failed to delete task schedule: {error}"))?;
+                continue;
+            }
+
+            let decision = match self.plan(&schedule, now) {
+                Ok(decision) => decision,
+                Err(error) => {
+                    log::error!("skipping schedule {}: {}", schedule.id, error);
+                    continue;
+                }
+            };
+            if decision.run_for.is_none()
+                && decision.missed == 0
+                && decision.next_run_at == schedule.next_run_at
+            {
+                continue;
+            }
+
+            if decision.missed > 0 {
+                // The app may have been closed, or open without the
+                // scheduler getting to the runs in time.
+                let runs = if decision.missed == 1 {
+                    "1 scheduled run".to_string()
+                } else {
+                    format!("{} scheduled runs", decision.missed)
+                };
+                let reason = format!(
+                    "missed {runs} that did not start within {} minutes of their time",
+                    ON_TIME_GRACE_MS / MINUTE_MS
+                );
+                let run = scheduled_run(&schedule, TaskStatus::Skipped, Some(reason), now);
+                service
+                    .save_task_run(&run)
+                    .map_err(|error| format!("failed to save task run: {error}"))?;
+            }
+
+            if let Some(scheduled_for) = decision.run_for {
+                let run = scheduled_run(&schedule, TaskStatus::Pending, None, now);
+                service
+                    .save_task_run(&run)
+                    .map_err(|error| format!("failed to save task run: {error}"))?;
+                schedule.last_run_at = Some(scheduled_for);
+                schedule.last_run_id = Some(run.id.clone());
+                events.push(ScheduledRunEvent {
+                    schedule_id: schedule.id.clone(),
+                    project_id: schedule.project_id.clone(),
+                    task_id: schedule.task_id.clone(),
+                    task_run_id: run.id,
+                    scheduled_for,
+                    missed: decision.missed,
+                });
+            }
+
+            schedule.next_run_at = decision.next_run_at;
+            schedule.updated_at = now;
+            service
+                .save_task_schedule(&schedule)
+                .map_err(|error| format!("failed to save task schedule: {error}"))?;
+        }
+
+        Ok(events)
+    }
+}
+
+impl Drop for SchedulerService {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
+
+fn scheduled_run(
+    schedule: &TaskSchedule,
+    status: TaskStatus,
+    status_reason: Option<String>,
+    now: i64,
+) -> TaskRun {
+    TaskRun {
+        id: Uuid::new_v4().to_string(),
+        project_id: schedule.project_id.clone(),
+        task_id: schedule.task_id.clone(),
+        status,
+        status_reason,
+        chat_thread_id: None,
+        schedule_id: Some(schedule.id.clone()),
+        started_at: now,
+        updated_at: now,
+    }
+}
+
+/// Parses one field, e.g. `*/15`, `1-5` or `mon,wed,fri`, into a bit set.
+fn parse_field(
+    field: &str,
+    label: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+) -> Result<u64, String> {
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("invalid step '{step}' in {label} field '{field}'"))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                parse_value(start, label, min, max, names)?,
+                parse_value(end, label, min, max, names)?,
+            )
+        } else {
+            let start = parse_value(range, label, min, max, names)?;
+            // `5/10` means from 5 to the end in steps of 10.
+            (start, if step > 1 { max } else { start })
+        };
+        if start > end {
+            return Err(format!(
+                "invalid range '{range}' in {label} field '{field}'"
+            ));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+
+    Ok(bits)
+}
+
+fn parse_value(
+    value: &str,
+    label: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+) -> Result<u32, String> {
+    let lower = value.to_ascii_lowercase();
+    if let Some(index) = names.iter().position(|name| *name == lower) {
+        // Month names start at 1, weekday names at 0.
+        return Ok(index as u32 + min);
+    }
+
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|number| (min..=max).contains(number))
+        .ok_or_else(|| format!("invalid {label} '{value}'; expected {min}-{max}"))
+}
+
+fn has(bits: u64, value: i64) -> bool {
+    bits & (1 << value) != 0
+}
@@ -0,0 +1,73 @@
+use std::convert::Infallible;
+use std::fs;
+use std::path::Path;
+
+use codex_sdk::ThreadEvent;
+use futures::stream;
+
+use crate::models::chat::{ChatRequest, TurnOutcome};
+use crate::models::event_handler::CodexEventHandler;
+use crate::services::codex_service::CodexService;
+
+pub struct ScriptedAgent {
+    events: Vec<ThreadEvent>,
+}
+
+impl ScriptedAgent {
+    pub fn new(events: Vec<ThreadEvent>) -> Self {
+        Self { events }
+    }
+
+    pub fn from_jsonl_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|error| format!("failed to read script '{}': {error}", path.display()))?;
+        Self::from_jsonl_str(&content)
+    }
+
+    pub fn from_jsonl_str(content: &str) -> Result<Self, String> {
+        let events = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str::<ThreadEvent>(line)
+                    .map_err(|error| format!("invalid event on line {}: {error}", index + 1))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self::new(events))
+    }
+
+    pub fn events(&self) -> &[ThreadEvent] {
+        &self.events
+    }
+
+    pub async fn invoke_stream<H: CodexEventHandler>(
+        &self,
+        mut payload: ChatRequest,
+        handler: H,
+    ) -> Result<TurnOutcome, String> {
+        if payload.content.trim().is_empty() {
+            return Err("prompt cannot be empty".to_string());
+        }
+
+        self.replay(&payload.ensure_run_id(), handler).await
+    }
+
+    pub async fn replay<H: CodexEventHandler>(
+        &self,
+        run_id: &str,
+        handler: H,
+    ) -> Result<TurnOutcome, String> {
+        log::info!("Replaying {} scripted events", self.events.len());
+
+        let events = stream::iter(
+            self.events
+                .iter()
+                .cloned()
+                .map(Ok::<ThreadEvent, Infallible>),
+        );
+        CodexService::handle_events(events, run_id, &handler).await
+    }
+}
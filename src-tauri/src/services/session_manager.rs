@@ -0,0 +1,290 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use futures::channel::oneshot;
+use serde_json::Value;
+
+use crate::models::event_handler::CodexEventHandler;
+use crate::models::session::{ActiveSession, SessionStatus};
+use crate::models::usage::TokenUsage;
+use crate::utils::current_timestamp_millis;
+
+const DEFAULT_MAX_CONCURRENT_SESSIONS: usize = 2;
+const CURRENT_ITEM_MAX_CHARS: usize = 120;
+
+pub struct SessionManager {
+    state: Arc<Mutex<SessionState>>,
+}
+
+pub struct SessionGuard {
+    run_id: String,
+    state: Arc<Mutex<SessionState>>,
+}
+
+pub struct SessionEventHandler<'a, H: CodexEventHandler> {
+    sessions: &'a SessionManager,
+    inner: H,
+}
+
+struct SessionState {
+    max_concurrent: usize,
+    sessions: HashMap<String, ActiveSession>,
+    queue: VecDeque<(String, oneshot::Sender<()>)>,
+    cancellations: HashMap<String, String>,
+}
+
+impl SessionManager {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(SessionState {
+                max_concurrent: DEFAULT_MAX_CONCURRENT_SESSIONS,
+                sessions: HashMap::new(),
+                queue: VecDeque::new(),
+                cancellations: HashMap::new(),
+            })),
+        }
+    }
+
+    pub fn set_max_concurrent(&self, max_concurrent: usize) {
+        if let Ok(mut state) = self.state.lock() {
+            state.max_concurrent = max_concurrent.max(1);
+            state.promote_queued();
+        }
+    }
+
+    pub fn max_concurrent(&self) -> usize {
+        self.state
+            .lock()
+            .map(|state| state.max_concurrent)
+            .unwrap_or(DEFAULT_MAX_CONCURRENT_SESSIONS)
+    }
+
+    pub async fn acquire(
+        &self,
+        run_id: &str,
+        project_id: Option<String>,
+        task_run_id: Option<String>,
+        agent: String,
+    ) -> Result<SessionGuard, String> {
+        let waiting = {
+            let mut state = self
+                .state
+                .lock()
+                .map_err(|error| format!("failed to lock session manager: {error}"))?;
+
+            if state.sessions.contains_key(run_id) {
+                return Err(format!("run {run_id} is already active"));
+            }
+
+            let now = current_timestamp_millis();
+            let mut session = ActiveSession {
+                run_id: run_id.to_string(),
+                project_id,
+                task_run_id,
+                thread_id: None,
+                agent,
+                status: SessionStatus::Queued,
+                queued_at: now,
+                started_at: None,
+                elapsed_ms: 0,
+                current_item: None,
+            };
+
+            if state.queue.is_empty() && state.running_count() < state.max_concurrent {
+                session.status = SessionStatus::Running;
+                session.started_at = Some(now);
+                state.sessions.insert(run_id.to_string(), session);
+                None
+            } else {
+                let (sender, receiver) = oneshot::channel();
+                state.sessions.insert(run_id.to_string(), session);
+                state.queue.push_back((run_id.to_string(), sender));
+                log::info!(
+                    "Queued run {} behind {} running sessions",
+                    run_id,
+                    state.running_count()
+                );
+                Some(receiver)
+            }
+        };
+
+        let guard = SessionGuard {
+            run_id: run_id.to_string(),
+            state: self.state.clone(),
+        };
+
+        if let Some(receiver) = waiting {
+            receiver
+                .await
+                .map_err(|_| format!("run {run_id} was dropped from the session queue"))?;
+        }
+
+        Ok(guard)
+    }
+
+    pub fn list_sessions(&self) -> Vec<ActiveSession> {
+        let Ok(state) = self.state.lock() else {
+            return Vec::new();
+        };
+
+        let now = current_timestamp_millis();
+        let mut sessions = state
+            .sessions
+            .values()
+            .cloned()
+            .map(|mut session| {
+                session.elapsed_ms = now - session.started_at.unwrap_or(session.queued_at);
+                session
+            })
+            .collect::<Vec<_>>();
+
+        sessions
+            .sort_by_key(|session| (session.status == SessionStatus::Queued, session.queued_at));
+        sessions
+    }
+
+    /// Asks the agent of an active run to stop; false when the run is not active.
+    pub fn cancel(&self, run_id: &str, reason: &str) -> bool {
+        let Ok(mut state) = self.state.lock() else {
+            return false;
+        };
+
+        if !state.sessions.contains_key(run_id) {
+            return false;
+        }
+
+        log::info!("Cancelling run {}: {}", run_id, reason);
+        state
+            .cancellations
+            .entry(run_id.to_string())
+            .or_insert_with(|| reason.to_string());
+        true
+    }
+
+    pub fn cancel_reason(&self, run_id: &str) -> Option<String> {
+        self.state
+            .lock()
+            .ok()
+            .and_then(|state| state.cancellations.get(run_id).cloned())
+    }
+
+    pub fn handler<H: CodexEventHandler>(&self, inner: H) -> SessionEventHandler<'_, H> {
+        SessionEventHandler {
+            sessions: self,
+            inner,
+        }
+    }
+
+    fn update(&self, run_id: &str, apply: impl FnOnce(&mut ActiveSession)) {
+        if let Ok(mut state) = self.state.lock() {
+            if let Some(session) = state.sessions.get_mut(run_id) {
+                apply(session);
+            }
+        }
+    }
+
+    fn describe_item(item: &Value) -> Option<String> {
+        let data = &item["data"];
+        let description = match item["type"].as_str()? {
+            "message" => format!(
+                "{}: {}",
+                data["role"].as_str().unwrap_or("agent"),
+                data["content"].as_str().unwrap_or_default()
+            ),
+            "token" => format!("reasoning: {}", data["text"].as_str().unwrap_or_default()),
+            _ => return None,
+        };
+
+        let first_line = description.lines().next().unwrap_or_default();
+        Some(first_line.chars().take(CURRENT_ITEM_MAX_CHARS).collect())
+    }
+}
+
+impl SessionState {
+    fn running_count(&self) -> usize {
+        self.sessions
+            .values()
+            .filter(|session| session.status == SessionStatus::Running)
+            .count()
+    }
+
+    fn promote_queued(&mut self) {
+        while self.running_count() < self.max_concurrent {
+            let Some((run_id, sender)) = self.queue.pop_front() else {
+                return;
+            };
+
+            if sender.send(()).is_err() {
+                self.sessions.remove(&run_id);
+                continue;
+            }
+
+            if let Some(session) = self.sessions.get_mut(&run_id) {
+                session.status = SessionStatus::Running;
+                session.started_at = Some(current_timestamp_millis());
+            }
+            log::info!("Starting queued run {}", run_id);
+        }
+    }
+}
+
+impl SessionGuard {
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.sessions.remove(&self.run_id);
+            state.cancellations.remove(&self.run_id);
+            state.queue.retain(|(run_id, _)| run_id != &self.run_id);
+            state.promote_queued();
+        }
+    }
+}
+
+impl<H: CodexEventHandler> SessionEventHandler<'_, H> {
+    fn track_item(&self, run_id: &str, item: &Value) {
+        if let Some(description) = SessionManager::describe_item(item) {
+            self.sessions
+                .update(run_id, |session| session.current_item = Some(description));
+        }
+    }
+}
+
+impl<H: CodexEventHandler> CodexEventHandler for SessionEventHandler<'_, H> {
+    fn on_item(&self, run_id: &str, item: Value) {
+        self.track_item(run_id, &item);
+        self.inner.on_item(run_id, item);
+    }
+
+    fn on_item_completed(&self, run_id: &str, item: Value) {
+        self.track_item(run_id, &item);
+        self.inner.on_item_completed(run_id, item);
+    }
+
+    fn on_done(&self, run_id: &str, usage: Value) {
+        self.inner.on_done(run_id, usage);
+    }
+
+    fn on_usage(&self, run_id: &str, usage: &TokenUsage) {
+        self.inner.on_usage(run_id, usage);
+    }
+
+    fn cancel_reason(&self, run_id: &str) -> Option<String> {
+        self.sessions
+            .cancel_reason(run_id)
+            .or_else(|| self.inner.cancel_reason(run_id))
+    }
+
+    fn on_thread_started(&self, run_id: &str, thread_info: Value) {
+        if let Some(thread_id) = thread_info["data"]["thread_id"].as_str() {
+            self.sessions.update(run_id, |session| {
+                session.thread_id = Some(thread_id.to_string())
+            });
+        }
+        self.inner.on_thread_started(run_id, thread_info);
+    }
+}
@@ -0,0 +1,441 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use tree_sitter::{Language, Node, Parser};
+
+use crate::models::symbol::{
+    CodeSymbol, SymbolContext, SymbolFile, SymbolIndexReport, SymbolKind, SymbolReference,
+};
+use crate::services::context_service::ContextService;
+use crate::services::data_service::DataService;
+
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+const MAX_REFERENCES: usize = 20;
+const MAX_SIGNATURE_CHARS: usize = 200;
+
+/// Grammar a file is parsed with, picked from its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Grammar {
+    Rust,
+    TypeScript,
+    Tsx,
+    Python,
+}
+
+impl Grammar {
+    fn for_path(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?;
+        match extension {
+            "rs" => Some(Self::Rust),
+            "ts" | "mts" | "cts" => Some(Self::TypeScript),
+            "tsx" => Some(Self::Tsx),
+            "py" | "pyi" => Some(Self::Python),
+            _ => None,
+        }
+    }
+
+    fn language(self) -> Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::TypeScript | Self::Tsx => "typescript",
+            Self::Python => "python",
+        }
+    }
+}
+
+/// The definition a node opens for its children, if any.
+struct Scope<'a> {
+    name: &'a str,
+    /// Functions directly inside a type scope are methods.
+    is_type: bool,
+}
+
+pub struct SymbolService;
+
+impl SymbolService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Whether symbols are extracted from `path` at all.
+    pub fn is_indexable(path: &str) -> bool {
+        Grammar::for_path(path).is_some()
+    }
+
+    /// Definitions in `source`, in document order.
+    pub fn extract(&self, path: &str, source: &str) -> Result<Vec<CodeSymbol>, String> {
+        let Some(grammar) = Grammar::for_path(path) else {
+            return Ok(Vec::new());
+        };
+
+        let mut parser = Parser::new();
+        parser
+            .set_language(&grammar.language())
+            .map_err(|error| format!("failed to load {} grammar: {error}", grammar.name()))?;
+        let tree = parser
+            .parse(source, None)
+            .ok_or_else(|| format!("failed to parse {path}"))?;
+
+        let mut symbols = Vec::new();
+        Self::visit(tree.root_node(), source, path, grammar, None, &mut symbols);
+        Ok(symbols)
+    }
+
+    /// Re-indexes every supported file whose size or modification time
+    /// changed since the last run and drops files that no longer exist.
+    pub fn refresh(
+        &self,
+        data_service: &DataService,
+        project_id: &str,
+        root: &Path,
+    ) -> Result<SymbolIndexReport, String> {
+        let paths = ContextService::new()
+            .list_files(root)?
+            .into_iter()
+            .filter(|path| Self::is_indexable(path))
+            .collect::<BTreeSet<_>>();
+        let indexed = Self::indexed_files(data_service, project_id)?;
+
+        let mut report = SymbolIndexReport {
+            removed: indexed
+                .keys()
+                .filter(|path| !paths.contains(*path))
+                .cloned()
+                .collect(),
+            ..SymbolIndexReport::default()
+        };
+        data_service
+            .delete_symbol_files(project_id, &report.removed)
+            .map_err(|error| format!("failed to update symbol index: {error}"))?;
+
+        for path in paths {
+            self.index_file(data_service, project_id, root, &path, &indexed, &mut report)?;
+        }
+        Ok(report)
+    }
+
+    /// Re-indexes just `paths`, e.g. after a file watcher reported them;
+    /// paths that are gone or unsupported are removed from the index.
+    pub fn refresh_paths(
+        &self,
+        data_service: &DataService,
+        project_id: &str,
+        root: &Path,
+        paths: &[String],
+    ) -> Result<SymbolIndexReport, String> {
+        let indexed = Self::indexed_files(data_service, project_id)?;
+        let mut report = SymbolIndexReport::default();
+        for path in paths {
+            if Self::is_indexable(path) && root.join(path).is_file() {
+                self.index_file(data_service, project_id, root, path, &indexed, &mut report)?;
+            } else if indexed.contains_key(path) {
+                report.removed.push(path.clone());
+            }
+        }
+
+        data_service
+            .delete_symbol_files(project_id, &report.removed)
+            .map_err(|error| format!("failed to update symbol index: {error}"))?;
+        Ok(report)
+    }
+
+    /// Every definition of `name`, with `context_lines` lines around it and
+    /// the lines in other indexed files that mention it.
+    pub fn symbol_context(
+        &self,
+        data_service: &DataService,
+        project_id: &str,
+        root: &Path,
+        name: &str,
+        path: Option<&str>,
+        context_lines: usize,
+    ) -> Result<Vec<SymbolContext>, String> {
+        let symbols = data_service
+            .find_symbols(project_id, name, path)
+            .map_err(|error| format!("failed to load symbols: {error}"))?;
+        if symbols.is_empty() {
+            return Ok(Vec::new());
+        }
+        let references = Self::find_references(data_service, project_id, root, &symbols)?;
+
+        let mut contexts = Vec::new();
+        for symbol in symbols {
+            let Some(source) = Self::read_source(&root.join(&symbol.path)) else {
+                continue;
+            };
+            let lines = source.lines().collect::<Vec<_>>();
+            let start_line = symbol.start_line.saturating_sub(context_lines).max(1);
+            let end_line = (symbol.end_line + context_lines).min(lines.len());
+            let code = lines
+                .get(start_line - 1..end_line)
+                .unwrap_or_default()
+                .join("\n");
+
+            contexts.push(SymbolContext {
+                symbol,
+                start_line,
+                end_line,
+                code,
+                references: references.clone(),
+            });
+        }
+        Ok(contexts)
+    }
+
+    fn visit(
+        node: Node<'_>,
+        source: &str,
+        path: &str,
+        grammar: Grammar,
+        scope: Option<&Scope<'_>>,
+        symbols: &mut Vec<CodeSymbol>,
+    ) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            let definition = Self::definition(child, source, grammar, scope);
+            if let Some((kind, name)) = definition {
+                symbols.push(CodeSymbol {
+                    name: name.to_string(),
+                    kind,
+                    path: path.to_string(),
+                    language: grammar.name().to_string(),
+                    start_line: child.start_position().row + 1,
+                    end_line: child.end_position().row + 1,
+                    container: scope.map(|scope| scope.name.to_string()),
+                    signature: Self::signature(source, child.start_position().row),
+                });
+            }
+
+            if Self::is_function(child.kind()) {
+                continue;
+            }
+            match Self::opened_scope(child, source, grammar, definition) {
+                Some(inner) => {
+                    Self::visit(child, source, path, grammar, Some(&inner), symbols);
+                }
+                None => Self::visit(child, source, path, grammar, scope, symbols),
+            }
+        }
+    }
+
+    fn definition<'a>(
+        node: Node<'_>,
+        source: &'a str,
+        grammar: Grammar,
+        scope: Option<&Scope<'_>>,
+    ) -> Option<(SymbolKind, &'a str)> {
+        let in_type = scope.is_some_and(|scope| scope.is_type);
+        let kind = match (grammar, node.kind()) {
+            (Grammar::Rust, "function_item" | "function_signature_item") if in_type => {
+                SymbolKind::Method
+            }
+            (Grammar::Rust, "function_item") => SymbolKind::Function,
+            (Grammar::Rust, "struct_item" | "union_item") => SymbolKind::Struct,
+            (Grammar::Rust, "enum_item") => SymbolKind::Enum,
+            (Grammar::Rust, "trait_item") => SymbolKind::Trait,
+            (Grammar::Rust, "type_item") => SymbolKind::Type,
+            (Grammar::Rust, "const_item" | "static_item") => SymbolKind::Constant,
+            (Grammar::Rust, "mod_item") => SymbolKind::Module,
+            (Grammar::Rust, "macro_definition") => SymbolKind::Macro,
+            (
+                Grammar::TypeScript | Grammar::Tsx,
+                "function_declaration" | "generator_function_declaration",
+            ) => SymbolKind::Function,
+            (
+                Grammar::TypeScript | Grammar::Tsx,
+                "class_declaration" | "abstract_class_declaration",
+            ) => SymbolKind::Class,
+            (Grammar::TypeScript | Grammar::Tsx, "interface_declaration") => SymbolKind::Interface,
+            (Grammar::TypeScript | Grammar::Tsx, "type_alias_declaration") => SymbolKind::Type,
+            (Grammar::TypeScript | Grammar::Tsx, "enum_declaration") => SymbolKind::Enum,
+            (Grammar::TypeScript | Grammar::Tsx, "internal_module") => SymbolKind::Module,
+            (Grammar::TypeScript | Grammar::Tsx, "method_definition") => SymbolKind::Method,
+            (Grammar::TypeScript | Grammar::Tsx, "variable_declarator") => {
+                let value = node.child_by_field_name("value")?;
+                if !matches!(value.kind(), "arrow_function" | "function_expression") {
+                    return None;
+                }
+                SymbolKind::Function
+            }
+            (Grammar::Python, "function_definition") if in_type => SymbolKind::Method,
+            (Grammar::Python, "function_definition") => SymbolKind::Function,
+            (Grammar::Python, "class_definition") => SymbolKind::Class,
+            _ => return None,
+        };
+
+        let name = node.child_by_field_name("name")?;
+        Some((kind, name.utf8_text(source.as_bytes()).ok()?))
+    }
+
+    /// Function bodies are not indexed; their locals are not worth a lookup.
+    fn is_function(kind: &str) -> bool {
+        matches!(
+            kind,
+            "function_item"
+                | "function_declaration"
+                | "generator_function_declaration"
+                | "method_definition"
+                | "variable_declarator"
+                | "function_definition"
+        )
+    }
+
+    fn opened_scope<'a>(
+        node: Node<'_>,
+        source: &'a str,
+        grammar: Grammar,
+        definition: Option<(SymbolKind, &'a str)>,
+    ) -> Option<Scope<'a>> {
+        if grammar == Grammar::Rust && node.kind() == "impl_item" {
+            let implemented = node
+                .child_by_field_name("type")?
+                .utf8_text(source.as_bytes())
+                .ok()?;
+            let name = implemented.split('<').next().unwrap_or(implemented).trim();
+            return Some(Scope {
+                name,
+                is_type: true,
+            });
+        }
+
+        let (kind, name) = definition?;
+        match kind {
+            SymbolKind::Trait | SymbolKind::Class => Some(Scope {
+                name,
+                is_type: true,
+            }),
+            SymbolKind::Module => Some(Scope {
+                name,
+                is_type: false,
+            }),
+            _ => None,
+        }
+    }
+
+    fn signature(source: &str, row: usize) -> String {
+        let line = source.lines().nth(row).unwrap_or_default().trim();
+        match line.char_indices().nth(MAX_SIGNATURE_CHARS) {
+            Some((index, _)) => format!("{}…", &line[..index]),
+            None => line.to_string(),
+        }
+    }
+
+    fn index_file(
+        &self,
+        data_service: &DataService,
+        project_id: &str,
+        root: &Path,
+        path: &str,
+        indexed: &BTreeMap<String, SymbolFile>,
+        report: &mut SymbolIndexReport,
+    ) -> Result<(), String> {
+        let full_path = root.join(path);
+        let Ok(metadata) = fs::metadata(&full_path) else {
+            return Ok(());
+        };
+        let file = SymbolFile {
+            path: path.to_string(),
+            modified_at: metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_millis() as i64)
+                .unwrap_or_default(),
+            size: metadata.len() as i64,
+        };
+        if indexed.get(path) == Some(&file) {
+            return Ok(());
+        }
+
+        // Unreadable or oversized files are recorded without symbols so
+        // they are not retried until they change again.
+        let symbols = match Self::read_source(&full_path) {
+            Some(source) => self.extract(path, &source)?,
+            None => Vec::new(),
+        };
+        data_service
+            .replace_file_symbols(project_id, &file, &symbols)
+            .map_err(|error| format!("failed to update symbol index: {error}"))?;
+
+        report.symbols += symbols.len();
+        report.indexed.push(file.path);
+        Ok(())
+    }
+
+    fn indexed_files(
+        data_service: &DataService,
+        project_id: &str,
+    ) -> Result<BTreeMap<String, SymbolFile>, String> {
+        Ok(data_service
+            .load_symbol_files(project_id)
+            .map_err(|error| format!("failed to load symbol index: {error}"))?
+            .into_iter()
+            .map(|file| (file.path.clone(), file))
+            .collect())
+    }
+
+    /// Lines in indexed files that use the name as a whole word, outside
+    /// the definitions themselves.
+    fn find_references(
+        data_service: &DataService,
+        project_id: &str,
+        root: &Path,
+        symbols: &[CodeSymbol],
+    ) -> Result<Vec<SymbolReference>, String> {
+        let name = &symbols[0].name;
+        let mut references = Vec::new();
+        for file in Self::indexed_files(data_service, project_id)?.into_values() {
+            let Some(source) = Self::read_source(&root.join(&file.path)) else {
+                continue;
+            };
+            for (index, line) in source.lines().enumerate() {
+                let line_number = index + 1;
+                let in_definition = symbols.iter().any(|symbol| {
+                    symbol.path == file.path
+                        && (symbol.start_line..=symbol.end_line).contains(&line_number)
+                });
+                if in_definition || !Self::contains_word(line, name) {
+                    continue;
+                }
+
+                references.push(SymbolReference {
+                    path: file.path.clone(),
+                    line: line_number,
+                    text: line.trim().to_string(),
+                });
+                if references.len() == MAX_REFERENCES {
+                    return Ok(references);
+                }
+            }
+        }
+        Ok(references)
+    }
+
+    fn contains_word(line: &str, word: &str) -> bool {
+        let is_word = |character: char| character.is_alphanumeric() || character == '_';
+        line.match_indices(word).any(|(index, _)| {
+            let before = line[..index].chars().next_back();
+            let after = line[index + word.len()..].chars().next();
+            !before.is_some_and(is_word) && !after.is_some_and(is_word)
+        })
+    }
+
+    fn read_source(path: &Path) -> Option<String> {
+        let metadata = fs::metadata(path).ok()?;
+        if metadata.len() > MAX_FILE_BYTES {
+            return None;
+        }
+        fs::read_to_string(path).ok()
+    }
+}
@@ -0,0 +1,357 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::models::project::Project;
+use crate::models::task::{Task, TaskStep, TaskStepType};
+use crate::models::task_file::{TaskFile, TaskFileStep, TaskSyncReport};
+use crate::services::project_folder_service::{ProjectFolderService, TASKS_FOLDER};
+use crate::services::rule_sync_service::safe_file_name;
+use crate::utils::current_timestamp_millis;
+
+/// Emitted with the project id after task files changed the project's tasks.
+pub const TASKS_SYNCED_EVENT: &str = "project:tasks-synced";
+/// Newest task file format this build reads and writes; see [`TaskFile`].
+pub const TASK_FILE_VERSION: u32 = 2;
+/// JSON Schema of [`TaskFile`], written next to the task folder for editors.
+pub const TASK_SCHEMA_FILE: &str = ".vibeflow/task.schema.json";
+const TASK_FILE_EXTENSIONS: [&str; 3] = ["yaml", "yml", "json"];
+/// Points YAML language servers at the schema, relative to the task file.
+const YAML_SCHEMA_COMMENT: &str = "# yaml-language-server: $schema=../task.schema.json\n";
+
+/// Keeps a project's tasks in `.vibeflow/tasks/`, one YAML or JSON file per task,
+/// once the manifest opts in with `storeInRepo`. The files are the source of
+/// truth; the database keeps the definitions plus each user's run state.
+pub struct TaskFileService;
+
+impl TaskFileService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn is_enabled(root: &Path) -> bool {
+        ProjectFolderService::read_manifest(root)
+            .ok()
+            .flatten()
+            .is_some_and(|manifest| manifest.store_in_repo)
+    }
+
+    /// Whether a project-relative path is one `import` reads.
+    pub fn is_task_file(path: &str) -> bool {
+        path.strip_prefix(TASKS_FOLDER)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .is_some_and(|name| !name.contains('/') && Self::has_task_extension(name))
+    }
+
+    /// Turns storing tasks in the repository on or off. Turning it on merges
+    /// existing task files into the project and writes every task out.
+    pub fn set_enabled(
+        &self,
+        project: &mut Project,
+        enabled: bool,
+    ) -> Result<TaskSyncReport, String> {
+        let root = Path::new(&project.path).to_path_buf();
+        if enabled {
+            ProjectFolderService::new().scaffold(project)?;
+        }
+        let Some(mut manifest) = ProjectFolderService::read_manifest(&root)? else {
+            return Ok(TaskSyncReport::default());
+        };
+        if manifest.store_in_repo != enabled {
+            manifest.store_in_repo = enabled;
+            ProjectFolderService::write_manifest(&root, &manifest)?;
+        }
+        if !enabled {
+            return Ok(TaskSyncReport::default());
+        }
+
+        let mut report = self.apply(project, false)?;
+        let exported = self.export(project, &[])?;
+        report.written = exported.written;
+        Ok(report)
+    }
+
+    /// Updates `project.tasks` from the task files: changed definitions are
+    /// taken over while step status is kept, new files add tasks and tasks
+    /// without a file are removed.
+    pub fn import(&self, project: &mut Project) -> Result<TaskSyncReport, String> {
+        self.apply(project, true)
+    }
+
+    /// Writes each task whose file is missing or defines it differently, and
+    /// deletes the files of `removed_ids`. Files keep their name when a task
+    /// is renamed.
+    pub fn export(
+        &self,
+        project: &Project,
+        removed_ids: &[String],
+    ) -> Result<TaskSyncReport, String> {
+        let root = Path::new(&project.path);
+        let files = self.discover(root)?;
+        let mut files_by_id = HashMap::new();
+        let mut used_paths = HashSet::new();
+        for (path, file) in &files {
+            if let Ok(file) = file {
+                files_by_id.entry(file.id.clone()).or_insert((path, file));
+            }
+            used_paths.insert(path.clone());
+        }
+
+        let mut report = TaskSyncReport::default();
+        let schema = Self::schema();
+        if fs::read_to_string(root.join(TASK_SCHEMA_FILE))
+            .ok()
+            .as_deref()
+            != Some(schema.as_str())
+        {
+            Self::write(root, TASK_SCHEMA_FILE, &schema)?;
+        }
+
+        for task in &project.tasks {
+            let expected = TaskFile::from(task);
+            let path = match files_by_id.get(&task.id) {
+                // Hand-written files are left as they are while they still match.
+                Some((_, file))
+                    if same_definition(file, &expected) && file.version >= expected.version =>
+                {
+                    continue
+                }
+                Some((path, _)) => path.to_string(),
+                None => {
+                    let path = Self::unused_path(&task.title, &used_paths);
+                    used_paths.insert(path.clone());
+                    path
+                }
+            };
+            let content = Self::render(task, &path)?;
+            Self::write(root, &path, &content)?;
+            report.written.push(path);
+        }
+
+        for id in removed_ids {
+            let Some((path, _)) = files_by_id.get(id) else {
+                continue;
+            };
+            fs::remove_file(root.join(path))
+                .map_err(|error| format!("failed to delete {path}: {error}"))?;
+            report.deleted.push(path.to_string());
+        }
+
+        Ok(report)
+    }
+
+    /// Serializes `task` as JSON for `.json` paths and as YAML otherwise.
+    pub fn render(task: &Task, path: &str) -> Result<String, String> {
+        let file = TaskFile::from(task);
+        let content = if path.ends_with(".json") {
+            serde_json::to_string_pretty(&file).map_err(|error| error.to_string())
+        } else {
+            serde_yaml::to_string(&file)
+                .map(|content| format!("{YAML_SCHEMA_COMMENT}{content}"))
+                .map_err(|error| error.to_string())
+        };
+
+        content
+            .map(|content| format!("{}\n", content.trim_end()))
+            .map_err(|error| format!("failed to serialize task {}: {error}", task.title))
+    }
+
+    /// Parses a YAML or JSON task file, filling in ids the author left out.
+    pub fn parse(path: &str, content: &str) -> Result<TaskFile, String> {
+        let parsed = if path.ends_with(".json") {
+            serde_json::from_str::<TaskFile>(content).map_err(|error| error.to_string())
+        } else {
+            serde_yaml::from_str::<TaskFile>(content).map_err(|error| error.to_string())
+        };
+        let mut file = parsed.map_err(|error| format!("invalid {path}: {error}"))?;
+        if file.version > TASK_FILE_VERSION {
+            return Err(format!(
+                "{path} uses task file version {} but this VibeFlow only knows version {TASK_FILE_VERSION}",
+                file.version
+            ));
+        }
+
+        if file.id.trim().is_empty() {
+            let name = path.rsplit('/').next().unwrap_or(path);
+            file.id = name
+                .rsplit_once('.')
+                .map_or(name, |(stem, _)| stem)
+                .to_string();
+        }
+        for (section, steps) in [
+            ("pre", &mut file.presteps),
+            ("main", &mut file.steps),
+            ("post", &mut file.poststeps),
+        ] {
+            for (index, step) in steps.iter_mut().enumerate() {
+                if step.id.trim().is_empty() {
+                    step.id = format!("{}-{section}-{}", file.id, index + 1);
+                }
+            }
+        }
+
+        Ok(file)
+    }
+
+    /// The JSON Schema task files are validated against.
+    pub fn schema() -> String {
+        let schema = schemars::schema_for!(TaskFile);
+        let content = serde_json::to_string_pretty(&schema).unwrap_or_else(|_| "{}".into());
+        format!("{content}\n")
+    }
+
+    fn apply(&self, project: &mut Project, prune: bool) -> Result<TaskSyncReport, String> {
+        let root = Path::new(&project.path).to_path_buf();
+        let now = current_timestamp_millis();
+        let mut report = TaskSyncReport::default();
+        let mut seen = HashSet::new();
+
+        for (path, file) in self.discover(&root)? {
+            let file = match file {
+                Ok(file) => file,
+                Err(error) => {
+                    log::error!("{}", error);
+                    report.errors.push(error);
+                    continue;
+                }
+            };
+            if !seen.insert(file.id.clone()) {
+                report
+                    .errors
+                    .push(format!("{path} repeats task id {}", file.id));
+                continue;
+            }
+
+            match project.tasks.iter_mut().find(|task| task.id == file.id) {
+                Some(task) if same_definition(&TaskFile::from(&*task), &file) => {}
+                Some(task) => {
+                    Self::update(task, file, now);
+                    report.updated.push(path);
+                }
+                None => {
+                    log::info!("Imported task {} from {}", file.title, path);
+                    project.tasks.push(file.to_task(&project.id, now));
+                    report.updated.push(path);
+                }
+            }
+        }
+
+        // A file that failed to parse may still hold one of the tasks.
+        if prune && report.errors.is_empty() {
+            project.tasks.retain(|task| {
+                let keep = seen.contains(&task.id);
+                if !keep {
+                    report.removed.push(task.id.clone());
+                }
+                keep
+            });
+        }
+
+        Ok(report)
+    }
+
+    /// Task files directly in `.vibeflow/tasks/`, keyed by relative path.
+    fn discover(&self, root: &Path) -> Result<BTreeMap<String, Result<TaskFile, String>>, String> {
+        let mut files = BTreeMap::new();
+        let entries = match fs::read_dir(root.join(TASKS_FOLDER)) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(files),
+            Err(error) => return Err(format!("failed to read {TASKS_FOLDER}: {error}")),
+        };
+
+        for entry in entries.filter_map(Result::ok) {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !entry.file_type().is_ok_and(|file_type| file_type.is_file())
+                || !Self::has_task_extension(&name)
+            {
+                continue;
+            }
+
+            let path = format!("{TASKS_FOLDER}/{name}");
+            let file = fs::read_to_string(entry.path())
+                .map_err(|error| format!("failed to read {path}: {error}"))
+                .and_then(|content| Self::parse(&path, &content));
+            files.insert(path, file);
+        }
+
+        Ok(files)
+    }
+
+    /// Takes over the definition in `file`, keeping the run state of steps
+    /// that are still there.
+    fn update(task: &mut Task, file: TaskFile, now: i64) {
+        let mut previous = task
+            .presteps
+            .drain(..)
+            .chain(task.steps.drain(..))
+            .chain(task.poststeps.drain(..))
+            .map(|step| (step.id.clone(), step))
+            .collect::<HashMap<_, _>>();
+        let mut steps = |steps: Vec<TaskFileStep>, step_type: TaskStepType| {
+            steps
+                .into_iter()
+                .map(|step| match previous.remove(&step.id) {
+                    Some(existing) if step.matches(&existing) => TaskStep {
+                        r#type: step_type.clone(),
+                        ..existing
+                    },
+                    Some(existing) => TaskStep {
+                        status: existing.status,
+                        created_at: existing.created_at,
+                        status_reason: existing.status_reason,
+                        ..step.to_step(step_type.clone(), now)
+                    },
+                    None => step.to_step(step_type.clone(), now),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        task.presteps = steps(file.presteps, TaskStepType::Pre);
+        task.steps = steps(file.steps, TaskStepType::Normal);
+        task.poststeps = steps(file.poststeps, TaskStepType::Post);
+        task.title = file.title;
+        task.description = file.description;
+        task.parameters = file.parameters;
+        task.tags = file.tags;
+        task.updated_at = now;
+    }
+
+    fn unused_path(title: &str, used_paths: &HashSet<String>) -> String {
+        let name = safe_file_name(title, "task");
+        let mut path = format!("{TASKS_FOLDER}/{name}.yaml");
+        let mut suffix = 2;
+        while used_paths.contains(&path) {
+            path = format!("{TASKS_FOLDER}/{name}-{suffix}.yaml");
+            suffix += 1;
+        }
+        path
+    }
+
+    fn has_task_extension(name: &str) -> bool {
+        name.rsplit_once('.')
+            .is_some_and(|(_, extension)| TASK_FILE_EXTENSIONS.contains(&extension))
+    }
+
+    fn write(root: &Path, path: &str, content: &str) -> Result<(), String> {
+        let file_path = root.join(path);
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent).map_err(|error| {
+                format!("failed to create directory '{}': {error}", parent.display())
+            })?;
+        }
+
+        fs::write(&file_path, content)
+            .map_err(|error| format!("failed to write task file '{path}': {error}"))
+    }
+}
+
+/// Whether two files define the same task, whatever version they declare.
+fn same_definition(left: &TaskFile, right: &TaskFile) -> bool {
+    TaskFile {
+        version: 0,
+        ..left.clone()
+    } == TaskFile {
+        version: 0,
+        ..right.clone()
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::{HashMap, HashSet};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use crate::models::task::{StepRunWhen, Task, TaskStatus};
+use crate::models::task_graph::{TaskGraph, TaskGraphAdvance, TaskGraphNode};
+
+pub struct TaskGraphService;
+
+impl TaskGraphService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Orders the task's main steps by their dependencies. Without any
+    /// declared dependencies the steps keep running one after another.
+    pub fn build(&self, task: &Task) -> Result<TaskGraph, String> {
+        let declares_dependencies = task.steps.iter().any(|step| !step.depends_on.is_empty());
+        let mut ids = HashSet::new();
+        for step in &task.steps {
+            if !ids.insert(step.id.as_str()) {
+                return Err(format!("more than one step has the id \"{}\"", step.id));
+            }
+            Self::globs(&step.if_changed)
+                .map_err(|error| format!("step \"{}\" {error}", step.title))?;
+        }
+
+        let mut nodes = Vec::with_capacity(task.steps.len());
+        for (index, step) in task.steps.iter().enumerate() {
+            let depends_on = if declares_dependencies {
+                let mut depends_on: Vec<String> = Vec::new();
+                for dependency in &step.depends_on {
+                    if dependency == &step.id {
+                        return Err(format!("step \"{}\" depends on itself", step.title));
+                    }
+                    if !ids.contains(dependency.as_str()) {
+                        let is_wrapper = task
+                            .presteps
+                            .iter()
+                            .chain(&task.poststeps)
+                            .any(|other| &other.id == dependency);
+                        return Err(if is_wrapper {
+                            format!(
+                                "step \"{}\" depends on the pre or post step \"{dependency}\"; only main steps can be dependencies",
+                                step.title
+                            )
+                        } else {
+                            format!(
+                                "step \"{}\" depends on unknown step \"{dependency}\"",
+                                step.title
+                            )
+                        });
+                    }
+                    if !depends_on.contains(dependency) {
+                        depends_on.push(dependency.clone());
+                    }
+                }
+                depends_on
+            } else {
+                index
+                    .checked_sub(1)
+                    .map(|previous| vec![task.steps[previous].id.clone()])
+                    .unwrap_or_default()
+            };
+
+            nodes.push(TaskGraphNode {
+                step_id: step.id.clone(),
+                title: step.title.clone(),
+                depends_on,
+                run_when: step.run_when,
+                if_changed: step.if_changed.clone(),
+            });
+        }
+
+        Ok(TaskGraph {
+            task_id: task.id.clone(),
+            declares_dependencies,
+            nodes: Self::sort(nodes)?,
+        })
+    }
+
+    /// The nodes to start and to skip given the run's node statuses; nodes
+    /// missing from `statuses` are pending. `changed` holds the files changed
+    /// during the run, or `None` when that is unknown and `if_changed`
+    /// conditions are taken to hold.
+    pub fn advance(
+        &self,
+        graph: &TaskGraph,
+        statuses: &HashMap<String, TaskStatus>,
+        changed: Option<&[String]>,
+    ) -> Result<TaskGraphAdvance, String> {
+        let mut statuses = statuses.clone();
+        let mut advance = TaskGraphAdvance::default();
+
+        // Skipping a node can settle the nodes waiting on it, so repeat
+        // until nothing more is skipped.
+        loop {
+            let mut skipped_any = false;
+            for node in &graph.nodes {
+                if !Self::is_waiting(node, &statuses) {
+                    continue;
+                }
+                if !Self::should_run(node, &statuses, changed)? {
+                    statuses.insert(node.step_id.clone(), TaskStatus::Skipped);
+                    advance.skipped.push(node.step_id.clone());
+                    skipped_any = true;
+                }
+            }
+            if !skipped_any {
+                break;
+            }
+        }
+
+        advance.ready = graph
+            .nodes
+            .iter()
+            .filter(|node| Self::is_waiting(node, &statuses))
+            .map(|node| node.step_id.clone())
+            .collect();
+        Ok(advance)
+    }
+
+    /// The statuses a resumed run keeps: `from` and every node after it are
+    /// run again, or without `from` the failed and unfinished nodes and the
+    /// nodes after them.
+    pub fn restart(
+        &self,
+        graph: &TaskGraph,
+        statuses: &HashMap<String, TaskStatus>,
+        from: Option<&str>,
+    ) -> Result<HashMap<String, TaskStatus>, String> {
+        let mut rerun: HashSet<&str> = match from {
+            Some(step_id) => {
+                if !graph.nodes.iter().any(|node| node.step_id == step_id) {
+                    return Err(format!("task has no main step \"{step_id}\""));
+                }
+                HashSet::from([step_id])
+            }
+            None => graph
+                .nodes
+                .iter()
+                .filter(|node| {
+                    matches!(
+                        statuses.get(&node.step_id),
+                        Some(TaskStatus::Failed | TaskStatus::InProgress | TaskStatus::Paused)
+                    )
+                })
+                .map(|node| node.step_id.as_str())
+                .collect(),
+        };
+
+        // Nodes come after their dependencies, so one pass reaches every
+        // node downstream.
+        for node in &graph.nodes {
+            if node
+                .depends_on
+                .iter()
+                .any(|dependency| rerun.contains(dependency.as_str()))
+            {
+                rerun.insert(&node.step_id);
+            }
+        }
+
+        Ok(statuses
+            .iter()
+            .filter(|(step_id, status)| {
+                !rerun.contains(step_id.as_str()) && **status != TaskStatus::Pending
+            })
+            .map(|(step_id, status)| (step_id.clone(), status.clone()))
+            .collect())
+    }
+
+    /// Kahn's algorithm, keeping the authored order among independent nodes.
+    fn sort(mut pending: Vec<TaskGraphNode>) -> Result<Vec<TaskGraphNode>, String> {
+        let mut sorted: Vec<TaskGraphNode> = Vec::with_capacity(pending.len());
+        let mut placed = HashSet::new();
+
+        while !pending.is_empty() {
+            let Some(index) = pending.iter().position(|node| {
+                node.depends_on
+                    .iter()
+                    .all(|dependency| placed.contains(dependency))
+            }) else {
+                let titles = pending
+                    .iter()
+                    .map(|node| format!("\"{}\"", node.title))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                return Err(format!("steps {titles} depend on each other"));
+            };
+
+            let node = pending.remove(index);
+            placed.insert(node.step_id.clone());
+            sorted.push(node);
+        }
+
+        Ok(sorted)
+    }
+
+    /// Pending with every dependency finished.
+    fn is_waiting(node: &TaskGraphNode, statuses: &HashMap<String, TaskStatus>) -> bool {
+        let pending = matches!(
+            statuses.get(&node.step_id),
+            None | Some(TaskStatus::Pending)
+        );
+
+        pending
+            && node.depends_on.iter().all(|dependency| {
+                matches!(
+                    statuses.get(dependency),
+                    Some(TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Skipped)
+                )
+            })
+    }
+
+    fn should_run(
+        node: &TaskGraphNode,
+        statuses: &HashMap<String, TaskStatus>,
+        changed: Option<&[String]>,
+    ) -> Result<bool, String> {
+        let mut dependencies = node
+            .depends_on
+            .iter()
+            .filter_map(|dependency| statuses.get(dependency));
+        let condition_holds = match node.run_when {
+            StepRunWhen::OnSuccess => dependencies.all(|status| *status == TaskStatus::Completed),
+            StepRunWhen::OnFailure => dependencies.any(|status| *status == TaskStatus::Failed),
+            StepRunWhen::Always => true,
+        };
+        if !condition_holds || node.if_changed.is_empty() {
+            return Ok(condition_holds);
+        }
+
+        let Some(changed) = changed else {
+            return Ok(true);
+        };
+        let globs = Self::globs(&node.if_changed)?;
+        Ok(changed.iter().any(|path| globs.is_match(path)))
+    }
+
+    fn globs(patterns: &[String]) -> Result<GlobSet, String> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let pattern = pattern.trim().trim_start_matches("./");
+            if pattern.is_empty() {
+                continue;
+            }
+            builder.add(
+                GlobBuilder::new(pattern)
+                    .literal_separator(true)
+                    .build()
+                    .map_err(|error| {
+                        format!("has an invalid ifChanged glob '{pattern}': {error}")
+                    })?,
+            );
+        }
+        builder
+            .build()
+            .map_err(|error| format!("has invalid ifChanged globs: {error}"))
+    }
+}
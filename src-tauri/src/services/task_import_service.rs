@@ -0,0 +1,483 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::models::project::Project;
+use crate::models::task::{StepRunWhen, Task, TaskStatus, TaskStep, TaskStepType};
+use crate::models::task_import::{TaskImport, TaskImportKind};
+use crate::services::project_folder_service::TASKS_FOLDER;
+use crate::services::rule_sync_service::{content_hash, safe_file_name, LEGACY_RULES_FOLDER};
+use crate::utils::current_timestamp_millis;
+
+/// Placeholders the frontend writes for empty sections and steps.
+const PLACEHOLDERS: [&str; 2] = ["_None_", "_No content_"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Pre,
+    Main,
+    Post,
+}
+
+impl Section {
+    const ALL: [Section; 3] = [Section::Pre, Section::Main, Section::Post];
+
+    /// Reads `## Pre Steps`, `## Main Steps`, `## Post Steps` and looser
+    /// headings such as `## Steps`, `## Checklist` or `## Post-steps`.
+    fn from_heading(heading: &str) -> Option<Self> {
+        let heading = heading.trim().to_lowercase();
+        if heading.starts_with("pre") {
+            Some(Section::Pre)
+        } else if heading.starts_with("post") {
+            Some(Section::Post)
+        } else if ["main", "step", "checklist", "todo", "tasks"]
+            .iter()
+            .any(|word| heading.starts_with(word))
+        {
+            Some(Section::Main)
+        } else {
+            None
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Section::Pre => "pre",
+            Section::Main => "main",
+            Section::Post => "post",
+        }
+    }
+
+    fn step_type(self) -> TaskStepType {
+        match self {
+            Section::Pre => TaskStepType::Pre,
+            Section::Main => TaskStepType::Normal,
+            Section::Post => TaskStepType::Post,
+        }
+    }
+
+    fn steps(self, task: &Task) -> &[TaskStep] {
+        match self {
+            Section::Pre => &task.presteps,
+            Section::Main => &task.steps,
+            Section::Post => &task.poststeps,
+        }
+    }
+
+    fn steps_mut(self, task: &mut Task) -> &mut Vec<TaskStep> {
+        match self {
+            Section::Pre => &mut task.presteps,
+            Section::Main => &mut task.steps,
+            Section::Post => &mut task.poststeps,
+        }
+    }
+}
+
+struct DraftStep {
+    section: Section,
+    title: String,
+    lines: Vec<String>,
+    done: bool,
+    /// `### ` steps own the lists below them; list-item steps end at the
+    /// next item.
+    from_heading: bool,
+}
+
+/// Reads tasks back from the Markdown the frontend exports, and from
+/// hand-written files that list steps as checklists.
+pub struct TaskImportService;
+
+impl TaskImportService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Markdown files in `.vibeflow/tasks/`, plus those in the legacy
+    /// `.codex/` folder that have step sections.
+    pub fn discover(&self, root: &Path) -> Result<Vec<String>, String> {
+        let mut paths = Vec::new();
+        for folder in [TASKS_FOLDER, LEGACY_RULES_FOLDER] {
+            let entries = match fs::read_dir(root.join(folder)) {
+                Ok(entries) => entries,
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(error) => return Err(format!("failed to read {folder}: {error}")),
+            };
+
+            for entry in entries.filter_map(Result::ok) {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if !name.ends_with(".md")
+                    || !entry.file_type().is_ok_and(|file_type| file_type.is_file())
+                {
+                    continue;
+                }
+                if folder == LEGACY_RULES_FOLDER
+                    && !fs::read_to_string(entry.path()).is_ok_and(|content| {
+                        content.lines().any(|line| {
+                            line.strip_prefix("## ")
+                                .is_some_and(|heading| Section::from_heading(heading).is_some())
+                        })
+                    })
+                {
+                    continue;
+                }
+                paths.push(format!("{folder}/{name}"));
+            }
+        }
+
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Parses each file and merges it into the matching task of `project`,
+    /// without changing `project`. Files are matched to tasks by the id an
+    /// earlier import gave them, then by title.
+    pub fn preview(&self, project: &Project, paths: &[String]) -> Result<Vec<TaskImport>, String> {
+        let root = Path::new(&project.path);
+        let now = current_timestamp_millis();
+        let mut matched = HashSet::new();
+        let mut imports = Vec::new();
+
+        for path in paths {
+            let file_path = root.join(path);
+            let path = file_path
+                .strip_prefix(root)
+                .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+                .unwrap_or_else(|_| path.clone());
+            let content = fs::read_to_string(&file_path)
+                .map_err(|error| format!("failed to read {path}: {error}"))?;
+            let parsed = self.parse(&project.id, &path, &content);
+
+            let existing = project
+                .tasks
+                .iter()
+                .filter(|task| !matched.contains(&task.id))
+                .find(|task| task.id == parsed.id)
+                .or_else(|| {
+                    project
+                        .tasks
+                        .iter()
+                        .filter(|task| !matched.contains(&task.id))
+                        .find(|task| task.title.eq_ignore_ascii_case(&parsed.title))
+                });
+            let import = match existing {
+                Some(existing) => {
+                    matched.insert(existing.id.clone());
+                    Self::merge(existing, parsed, path, now)
+                }
+                None => TaskImport {
+                    path,
+                    kind: TaskImportKind::New,
+                    task: parsed,
+                    changes: Vec::new(),
+                },
+            };
+            imports.push(import);
+        }
+
+        Ok(imports)
+    }
+
+    /// Saves the new and updated tasks of a preview into `project`.
+    pub fn apply(&self, project: &mut Project, imports: &[TaskImport]) {
+        for import in imports {
+            if import.kind == TaskImportKind::Unchanged {
+                continue;
+            }
+
+            match project
+                .tasks
+                .iter_mut()
+                .find(|task| task.id == import.task.id)
+            {
+                Some(task) => *task = import.task.clone(),
+                None => project.tasks.push(import.task.clone()),
+            }
+        }
+    }
+
+    /// Turns one Markdown file into a pending task. Ids are derived from the
+    /// path and step titles, so importing the same file again yields the
+    /// same ids.
+    pub fn parse(&self, project_id: &str, path: &str, content: &str) -> Task {
+        let mut title = None;
+        let mut description = Vec::new();
+        let mut steps: Vec<DraftStep> = Vec::new();
+        let mut section = None;
+        let mut current: Option<usize> = None;
+        let mut in_fence = false;
+
+        for line in content.lines() {
+            let trimmed = line.trim();
+            let is_fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+            if is_fence {
+                in_fence = !in_fence;
+            } else if !in_fence {
+                if let Some(heading) = line.strip_prefix("# ") {
+                    if title.is_none() && section.is_none() {
+                        title = Some(heading.trim().to_string());
+                        continue;
+                    }
+                }
+                if let Some(heading) = line.strip_prefix("## ") {
+                    if let Some(next) = Section::from_heading(heading) {
+                        section = Some(next);
+                        current = None;
+                        continue;
+                    }
+                }
+                if let (Some(heading), Some(step_section)) = (line.strip_prefix("### "), section) {
+                    let (done, step_title) = checkbox(strip_number(heading.trim()));
+                    steps.push(DraftStep {
+                        section: step_section,
+                        title: step_title.to_string(),
+                        lines: Vec::new(),
+                        done,
+                        from_heading: true,
+                    });
+                    current = Some(steps.len() - 1);
+                    continue;
+                }
+                if let Some((checked, item)) = list_item(line) {
+                    let in_heading_step = current.is_some_and(|index| steps[index].from_heading);
+                    // Outside the step sections only checklists are steps.
+                    if !in_heading_step && (section.is_some() || checked.is_some()) {
+                        steps.push(DraftStep {
+                            section: section.unwrap_or(Section::Main),
+                            title: item.to_string(),
+                            lines: Vec::new(),
+                            done: checked == Some(true),
+                            from_heading: false,
+                        });
+                        current = Some(steps.len() - 1);
+                        continue;
+                    }
+                }
+                if PLACEHOLDERS.contains(&trimmed) {
+                    continue;
+                }
+            }
+
+            match current {
+                // Unindented text after a list item ends that step.
+                Some(index)
+                    if steps[index].from_heading
+                        || in_fence
+                        || is_fence
+                        || trimmed.is_empty()
+                        || line.starts_with([' ', '\t']) =>
+                {
+                    steps[index].lines.push(strip_indent(line, 4).to_string());
+                }
+                _ => {
+                    current = None;
+                    description.push(line.to_string());
+                }
+            }
+        }
+
+        let title = title
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| title_from_path(path));
+        let task_id = format!("task-{}", &content_hash(path)[..12]);
+        let now = current_timestamp_millis();
+        let mut task = Task {
+            id: task_id.clone(),
+            project_id: project_id.to_string(),
+            title,
+            description: description.join("\n").trim().to_string(),
+            steps: Vec::new(),
+            presteps: Vec::new(),
+            poststeps: Vec::new(),
+            parameters: Vec::new(),
+            tags: Vec::new(),
+            status: TaskStatus::Pending,
+            created_at: now,
+            updated_at: now,
+        };
+
+        let mut used_ids = HashSet::new();
+        for draft in steps {
+            let mut content = draft.lines.join("\n").trim().to_string();
+            if content.is_empty() && !draft.from_heading {
+                content = draft.title.clone();
+            }
+            let base_id = format!(
+                "{task_id}-{}-{}",
+                draft.section.label(),
+                safe_file_name(&draft.title, "step")
+            );
+            let mut id = base_id.clone();
+            let mut suffix = 2;
+            while !used_ids.insert(id.clone()) {
+                id = format!("{base_id}-{suffix}");
+                suffix += 1;
+            }
+
+            draft.section.steps_mut(&mut task).push(TaskStep {
+                id,
+                title: draft.title,
+                content,
+                status: if draft.done {
+                    TaskStatus::Completed
+                } else {
+                    TaskStatus::Pending
+                },
+                created_at: now,
+                updated_at: now,
+                r#type: draft.section.step_type(),
+                status_reason: None,
+                model: None,
+                sandbox: None,
+                verify: Vec::new(),
+                depends_on: Vec::new(),
+                run_when: StepRunWhen::OnSuccess,
+                if_changed: Vec::new(),
+            });
+        }
+
+        task
+    }
+
+    /// Takes the file's title, description and steps over `existing`,
+    /// keeping ids, parameters and run status of the steps that match by
+    /// title.
+    fn merge(existing: &Task, parsed: Task, path: String, now: i64) -> TaskImport {
+        let mut changes = Vec::new();
+        if existing.title != parsed.title {
+            changes.push(format!(
+                "title \"{}\" becomes \"{}\"",
+                existing.title, parsed.title
+            ));
+        }
+        if existing.description.trim() != parsed.description {
+            changes.push("description changed".to_string());
+        }
+
+        let mut task = Task {
+            id: existing.id.clone(),
+            project_id: existing.project_id.clone(),
+            title: parsed.title.clone(),
+            description: parsed.description.clone(),
+            steps: Vec::new(),
+            presteps: Vec::new(),
+            poststeps: Vec::new(),
+            parameters: existing.parameters.clone(),
+            tags: existing.tags.clone(),
+            status: existing.status.clone(),
+            created_at: existing.created_at,
+            updated_at: existing.updated_at,
+        };
+
+        for section in Section::ALL {
+            let label = section.label();
+            let old_steps = section.steps(existing);
+            let mut used = vec![false; old_steps.len()];
+            let mut order = Vec::new();
+            let mut steps = Vec::new();
+
+            for step in section.steps(&parsed) {
+                let index = (0..old_steps.len())
+                    .find(|&index| !used[index] && old_steps[index].title == step.title);
+                let Some(index) = index else {
+                    changes.push(format!("adds {label} step \"{}\"", step.title));
+                    steps.push(step.clone());
+                    continue;
+                };
+
+                used[index] = true;
+                order.push(index);
+                let old = &old_steps[index];
+                let mut merged = old.clone();
+                merged.r#type = section.step_type();
+                if old.content.trim() != step.content {
+                    changes.push(format!("changes {label} step \"{}\"", step.title));
+                    merged.content = step.content.clone();
+                    merged.updated_at = now;
+                }
+                if matches!(step.status, TaskStatus::Completed)
+                    && !matches!(old.status, TaskStatus::Completed)
+                {
+                    changes.push(format!("completes {label} step \"{}\"", step.title));
+                    merged.status = TaskStatus::Completed;
+                    merged.updated_at = now;
+                }
+                steps.push(merged);
+            }
+
+            for (index, old) in old_steps.iter().enumerate() {
+                if !used[index] {
+                    changes.push(format!("removes {label} step \"{}\"", old.title));
+                }
+            }
+            if order.windows(2).any(|pair| pair[0] > pair[1]) {
+                changes.push(format!("reorders {label} steps"));
+            }
+            *section.steps_mut(&mut task) = steps;
+        }
+
+        let kind = if changes.is_empty() {
+            TaskImportKind::Unchanged
+        } else {
+            task.updated_at = now;
+            TaskImportKind::Updated
+        };
+
+        TaskImport {
+            path,
+            kind,
+            task,
+            changes,
+        }
+    }
+}
+
+/// Splits `- [ ] text`, `* [x] text` and `- text` list items; the flag is
+/// `None` for items without a checkbox.
+fn list_item(line: &str) -> Option<(Option<bool>, &str)> {
+    let item = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .or_else(|| line.strip_prefix("+ "))?
+        .trim();
+    let (done, text) = checkbox(item);
+    if text == item {
+        Some((None, item))
+    } else {
+        Some((Some(done), text))
+    }
+}
+
+fn checkbox(text: &str) -> (bool, &str) {
+    for (prefix, done) in [("[ ]", false), ("[x]", true), ("[X]", true)] {
+        if let Some(rest) = text.strip_prefix(prefix) {
+            return (done, rest.trim());
+        }
+    }
+    (false, text)
+}
+
+/// Drops the `1. ` the frontend numbers step headings with.
+fn strip_number(text: &str) -> &str {
+    let rest = text.trim_start_matches(|c: char| c.is_ascii_digit());
+    if rest.len() == text.len() {
+        return text;
+    }
+
+    rest.strip_prefix(['.', ')'])
+        .map(str::trim_start)
+        .unwrap_or(text)
+}
+
+fn strip_indent(line: &str, width: usize) -> &str {
+    let indent = line.chars().take(width).take_while(|c| *c == ' ').count();
+    match line.strip_prefix('\t') {
+        Some(rest) if indent == 0 => rest,
+        _ => &line[indent..],
+    }
+}
+
+fn title_from_path(path: &str) -> String {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let stem = name.strip_suffix(".md").unwrap_or(name);
+    stem.replace(['-', '_'], " ")
+}
@@ -0,0 +1,191 @@
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::models::project::Project;
+use crate::models::task::{StepRunWhen, Task, TaskStatus, TaskStep, TaskStepType};
+use crate::models::task_plan::{PlannedStep, TaskPlan};
+use crate::services::context_service::ContextService;
+use crate::services::template_service::TemplateService;
+use crate::utils::current_timestamp_millis;
+
+/// Files listed in the planning prompt; agents with repository access can
+/// look further themselves.
+const MAX_PLAN_FILES: usize = 300;
+const MAX_README_CHARS: usize = 4000;
+const MAX_PLAN_STEPS: usize = 30;
+
+pub struct TaskPlannerService;
+
+impl TaskPlannerService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// JSON Schema the agent's answer must match.
+    pub fn output_schema() -> Value {
+        serde_json::to_value(schemars::schema_for!(TaskPlan)).unwrap_or_default()
+    }
+
+    /// The planning prompt: the goal plus what the agent needs to know about
+    /// the project to split it into steps.
+    pub fn build_prompt(&self, goal: &str, project: &Project) -> Result<String, String> {
+        let goal = goal.trim();
+        if goal.is_empty() {
+            return Err("goal cannot be empty".to_string());
+        }
+
+        let root = Path::new(&project.path);
+        let mut prompt = format!(
+            "Plan a task for the coding agent working in the project \"{}\".\n\n\
+             Goal:\n{goal}\n\n\
+             Answer with JSON matching the given schema. Main steps run in order and each \
+             one is a self-contained prompt for the agent. Pre-steps run before and \
+             post-steps after every main step; leave them empty unless each main step \
+             needs them. Add shell commands to `verify` when a step's result can be \
+             checked, e.g. by building or running tests. Do not change any files while \
+             planning and do not use `{{{{` in step content.\n",
+            project.name
+        );
+
+        if !project.tasks.is_empty() {
+            prompt.push_str("\nExisting tasks:\n");
+            for task in &project.tasks {
+                prompt.push_str(&format!("- {}\n", task.title));
+            }
+        }
+
+        if root.is_dir() {
+            let files = ContextService::new().list_files(root)?;
+            prompt.push_str("\nProject files:\n");
+            for file in files.iter().take(MAX_PLAN_FILES) {
+                prompt.push_str(&format!("- {file}\n"));
+            }
+            if files.len() > MAX_PLAN_FILES {
+                prompt.push_str(&format!(
+                    "- ... and {} more\n",
+                    files.len() - MAX_PLAN_FILES
+                ));
+            }
+
+            if let Ok(readme) = fs::read_to_string(root.join("README.md")) {
+                let readme: String = readme.chars().take(MAX_README_CHARS).collect();
+                prompt.push_str(&format!("\nREADME.md:\n{}\n", readme.trim_end()));
+            }
+        }
+
+        Ok(prompt)
+    }
+
+    /// Validates the agent's answer and turns it into a draft task; nothing
+    /// is saved.
+    pub fn parse(&self, project_id: &str, goal: &str, output: &str) -> Result<Task, String> {
+        let plan: TaskPlan = serde_json::from_str(extract_json(output))
+            .map_err(|error| format!("agent returned an invalid plan: {error}"))?;
+
+        let title = plan.title.trim();
+        if title.is_empty() {
+            return Err("agent returned a plan without a title".to_string());
+        }
+        if plan.steps.is_empty() {
+            return Err("agent returned a plan without steps".to_string());
+        }
+        let count = plan.presteps.len() + plan.steps.len() + plan.poststeps.len();
+        if count > MAX_PLAN_STEPS {
+            return Err(format!(
+                "agent returned {count} steps; plans are limited to {MAX_PLAN_STEPS}"
+            ));
+        }
+
+        let now = current_timestamp_millis();
+        let description = match plan.description.trim() {
+            "" => goal.trim().to_string(),
+            description => description.to_string(),
+        };
+
+        Ok(Task {
+            id: format!("task-{}", Uuid::new_v4()),
+            project_id: project_id.to_string(),
+            title: title.to_string(),
+            description,
+            presteps: to_steps(&plan.presteps, TaskStepType::Pre, now)?,
+            steps: to_steps(&plan.steps, TaskStepType::Normal, now)?,
+            poststeps: to_steps(&plan.poststeps, TaskStepType::Post, now)?,
+            parameters: Vec::new(),
+            tags: Vec::new(),
+            status: TaskStatus::Pending,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+}
+
+fn to_steps(
+    steps: &[PlannedStep],
+    step_type: TaskStepType,
+    now: i64,
+) -> Result<Vec<TaskStep>, String> {
+    let templates = TemplateService::new();
+
+    steps
+        .iter()
+        .enumerate()
+        .map(|(index, step)| {
+            let title = step.title.trim();
+            let content = step.content.trim();
+            if title.is_empty() || content.is_empty() {
+                return Err(format!(
+                    "agent returned an empty {} step at position {}",
+                    step_type_label(&step_type),
+                    index + 1
+                ));
+            }
+            templates
+                .validate(content, &[])
+                .map_err(|error| format!("step \"{title}\" is not a valid prompt: {error}"))?;
+
+            Ok(TaskStep {
+                id: Uuid::new_v4().to_string(),
+                title: title.to_string(),
+                content: content.to_string(),
+                status: TaskStatus::Pending,
+                created_at: now,
+                updated_at: now,
+                r#type: step_type.clone(),
+                status_reason: None,
+                model: None,
+                sandbox: None,
+                verify: step
+                    .verify
+                    .iter()
+                    .map(|command| command.trim())
+                    .filter(|command| !command.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+                depends_on: Vec::new(),
+                run_when: StepRunWhen::OnSuccess,
+                if_changed: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+fn step_type_label(step_type: &TaskStepType) -> &'static str {
+    match step_type {
+        TaskStepType::Pre => "pre",
+        TaskStepType::Normal => "main",
+        TaskStepType::Post => "post",
+    }
+}
+
+/// The JSON object in `output`, tolerating agents that wrap it in a code
+/// fence or a sentence despite the schema.
+fn extract_json(output: &str) -> &str {
+    let output = output.trim();
+    match (output.find('{'), output.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &output[start..=end],
+        _ => output,
+    }
+}
@@ -0,0 +1,343 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::process::Command;
+
+use serde::Serialize;
+
+use crate::models::project::Project;
+use crate::models::task::{Task, TaskStep, TaskStepResult};
+
+/// Variables every step prompt can use; task parameters come on top as
+/// `params.<name>`.
+pub const TEMPLATE_VARIABLES: &[&str] = &[
+    "project.name",
+    "project.path",
+    "task.title",
+    "task.description",
+    "step.index",
+    "step.title",
+    "git.branch",
+    "previous_step.summary",
+];
+pub const PARAMETER_PREFIX: &str = "params.";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Variable {
+        name: String,
+        raw: String,
+        line: usize,
+        column: usize,
+    },
+}
+
+/// A prompt with `{{name}}` placeholders; `\{{` yields a literal `{{`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTemplate {
+    segments: Vec<Segment>,
+}
+
+impl PromptTemplate {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        Self::parse_with(source, false)
+    }
+
+    /// Parses `source` keeping malformed placeholders, such as a literal `{{`
+    /// in a code sample, as text instead of failing.
+    pub fn parse_lenient(source: &str) -> Self {
+        Self::parse_with(source, true).unwrap_or_else(|_| Self {
+            segments: vec![Segment::Text(source.to_string())],
+        })
+    }
+
+    fn parse_with(source: &str, lenient: bool) -> Result<Self, TemplateError> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut rest = source;
+
+        while let Some(start) = rest.find("{{") {
+            if rest[..start].ends_with('\\') {
+                text.push_str(&rest[..start - 1]);
+                text.push_str("{{");
+                rest = &rest[start + 2..];
+                continue;
+            }
+
+            text.push_str(&rest[..start]);
+            let (line, column) = Self::position(source, source.len() - rest.len() + start);
+            let after_open = &rest[start + 2..];
+            let end = match after_open.find("}}") {
+                None => Err("unclosed '{{'".to_string()),
+                Some(end) if after_open[..end].trim().is_empty() => {
+                    Err("empty variable".to_string())
+                }
+                Some(end) if !Self::is_valid_name(after_open[..end].trim()) => Err(format!(
+                    "invalid variable name '{}'",
+                    after_open[..end].trim()
+                )),
+                Some(end) => Ok(end),
+            };
+            let end = match end {
+                Ok(end) => end,
+                Err(_) if lenient => {
+                    text.push_str("{{");
+                    rest = after_open;
+                    continue;
+                }
+                Err(message) => {
+                    return Err(TemplateError {
+                        line,
+                        column,
+                        message,
+                    })
+                }
+            };
+
+            if !text.is_empty() {
+                segments.push(Segment::Text(std::mem::take(&mut text)));
+            }
+            segments.push(Segment::Variable {
+                name: after_open[..end].trim().to_string(),
+                raw: rest[start..start + end + 4].to_string(),
+                line,
+                column,
+            });
+            rest = &after_open[end + 2..];
+        }
+
+        text.push_str(rest);
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Rejects variables that are neither built in nor declared parameters.
+    pub fn validate(&self, parameters: &[String]) -> Result<(), TemplateError> {
+        for segment in &self.segments {
+            let Segment::Variable {
+                name, line, column, ..
+            } = segment
+            else {
+                continue;
+            };
+
+            let known = match name.strip_prefix(PARAMETER_PREFIX) {
+                Some(parameter) => parameters.iter().any(|declared| declared == parameter),
+                None => TEMPLATE_VARIABLES.contains(&name.as_str()),
+            };
+            if !known {
+                return Err(TemplateError {
+                    line: *line,
+                    column: *column,
+                    message: format!("unknown variable '{name}'"),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn render(&self, values: &BTreeMap<String, String>) -> Result<String, TemplateError> {
+        let mut rendered = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => rendered.push_str(text),
+                Segment::Variable {
+                    name, line, column, ..
+                } => {
+                    let value = values.get(name).ok_or_else(|| TemplateError {
+                        line: *line,
+                        column: *column,
+                        message: format!("no value for '{name}'"),
+                    })?;
+                    rendered.push_str(value);
+                }
+            }
+        }
+
+        Ok(rendered)
+    }
+
+    /// Renders the variables `values` has and leaves any other placeholder as written.
+    pub fn render_known(&self, values: &BTreeMap<String, String>) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.as_str(),
+                Segment::Variable { name, raw, .. } => {
+                    values.get(name).map_or(raw.as_str(), String::as_str)
+                }
+            })
+            .collect()
+    }
+
+    fn is_valid_name(name: &str) -> bool {
+        name.split('.').all(|part| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        })
+    }
+
+    fn position(source: &str, offset: usize) -> (usize, usize) {
+        let before = &source[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rfind('\n')
+            .map_or(before.chars().count(), |newline| {
+                before[newline + 1..].chars().count()
+            })
+            + 1;
+        (line, column)
+    }
+}
+
+pub struct TemplateService;
+
+impl TemplateService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn validate(&self, source: &str, parameters: &[String]) -> Result<(), TemplateError> {
+        PromptTemplate::parse(source)?.validate(parameters)
+    }
+
+    /// Checks the step prompts that differ from `previous`, the stored
+    /// project, so a typo is caught while the step is edited; prompts saved
+    /// before are left alone and render leniently.
+    pub fn validate_project(
+        &self,
+        project: &Project,
+        previous: Option<&Project>,
+    ) -> Result<(), String> {
+        for task in &project.tasks {
+            let parameters = Self::parameter_names(task);
+            let stored = previous
+                .and_then(|previous| previous.tasks.iter().find(|stored| stored.id == task.id));
+            let edited = task
+                .presteps
+                .iter()
+                .chain(&task.steps)
+                .chain(&task.poststeps)
+                .filter(|step| {
+                    !stored.is_some_and(|stored| {
+                        stored
+                            .presteps
+                            .iter()
+                            .chain(&stored.steps)
+                            .chain(&stored.poststeps)
+                            .any(|saved| saved.id == step.id && saved.content == step.content)
+                    })
+                });
+            for step in edited {
+                self.validate(&step.content, &parameters).map_err(|error| {
+                    format!(
+                        "step '{}' of task '{}' has an invalid prompt: {error}",
+                        step.title, task.title
+                    )
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Renders `source`, the prompt sent for `step`, with the step's variables.
+    /// Placeholders that are not variables of the step are sent as written.
+    pub fn render_step(
+        &self,
+        source: &str,
+        project: &Project,
+        task: &Task,
+        step: &TaskStep,
+        previous: Option<&TaskStepResult>,
+    ) -> String {
+        PromptTemplate::parse_lenient(source)
+            .render_known(&self.step_variables(project, task, step, previous))
+    }
+
+    pub fn step_variables(
+        &self,
+        project: &Project,
+        task: &Task,
+        step: &TaskStep,
+        previous: Option<&TaskStepResult>,
+    ) -> BTreeMap<String, String> {
+        let index = [&task.presteps, &task.steps, &task.poststeps]
+            .into_iter()
+            .find_map(|steps| steps.iter().position(|candidate| candidate.id == step.id))
+            .map_or(1, |position| position + 1);
+
+        let mut values = BTreeMap::from([
+            ("project.name".to_string(), project.name.clone()),
+            ("project.path".to_string(), project.path.clone()),
+            ("task.title".to_string(), task.title.clone()),
+            ("task.description".to_string(), task.description.clone()),
+            ("step.index".to_string(), index.to_string()),
+            ("step.title".to_string(), step.title.clone()),
+            ("git.branch".to_string(), Self::git_branch(&project.path)),
+            (
+                "previous_step.summary".to_string(),
+                previous
+                    .map(|result| result.summary.clone())
+                    .unwrap_or_default(),
+            ),
+        ]);
+        for parameter in &task.parameters {
+            values.insert(
+                format!("{PARAMETER_PREFIX}{}", parameter.name),
+                parameter.value.clone(),
+            );
+        }
+
+        values
+    }
+
+    fn parameter_names(task: &Task) -> Vec<String> {
+        task.parameters
+            .iter()
+            .map(|parameter| parameter.name.clone())
+            .collect()
+    }
+
+    fn git_branch(project_path: &str) -> String {
+        if project_path.trim().is_empty() || !Path::new(project_path).exists() {
+            return String::new();
+        }
+
+        match Command::new("git")
+            .args(["rev-parse", "--abbrev-ref", "HEAD"])
+            .current_dir(project_path)
+            .output()
+        {
+            Ok(output) if output.status.success() => {
+                String::from_utf8_lossy(&output.stdout).trim().to_string()
+            }
+            _ => String::new(),
+        }
+    }
+}
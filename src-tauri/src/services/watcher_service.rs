@@ -0,0 +1,316 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::models::file_change::{FileChange, FileChangeKind, FilesChangedEvent};
+use crate::services::context_service::{ContextService, IgnoreRules};
+use crate::utils::current_timestamp_millis;
+
+pub const FILES_CHANGED_EVENT: &str = "project:files-changed";
+/// Quiet period after the last file system event before a batch is reported.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+const MAX_HISTORY_PER_PROJECT: usize = 5000;
+
+/// Watches the open project and keeps a short in-memory history of what
+/// changed, so callers can ask what happened since a point in time.
+pub struct WatcherService {
+    state: Arc<Mutex<WatcherState>>,
+}
+
+struct WatcherState {
+    active: Option<ActiveWatch>,
+    history: HashMap<String, VecDeque<FileChange>>,
+}
+
+struct ActiveWatch {
+    project_id: String,
+    root: PathBuf,
+    // Dropping the watcher closes the channel, which ends the debounce thread.
+    _watcher: RecommendedWatcher,
+}
+
+impl WatcherService {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(WatcherState {
+                active: None,
+                history: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Starts watching `root` for `project_id`, replacing any previous watch.
+    /// `on_change` runs on the watcher thread once per debounced batch.
+    pub fn watch<F>(&self, project_id: &str, root: &Path, on_change: F) -> Result<(), String>
+    where
+        F: Fn(&FilesChangedEvent) + Send + 'static,
+    {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|error| format!("failed to lock file watcher: {error}"))?;
+        if state
+            .active
+            .as_ref()
+            .is_some_and(|active| active.project_id == project_id && active.root == root)
+        {
+            return Ok(());
+        }
+        state.active = None;
+
+        let snapshot = ContextService::new()
+            .list_files(root)?
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+        })
+        .map_err(|error| format!("failed to start file watcher: {error}"))?;
+        watcher
+            .watch(root, RecursiveMode::Recursive)
+            .map_err(|error| format!("failed to watch {}: {error}", root.display()))?;
+
+        let batch = Batch {
+            project_id: project_id.to_string(),
+            root: root.to_path_buf(),
+            canonical_root: fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf()),
+            ignore: IgnoreRules::new(root)?,
+            snapshot,
+        };
+        let history = Arc::clone(&self.state);
+        thread::spawn(move || batch.run(receiver, history, on_change));
+
+        state.active = Some(ActiveWatch {
+            project_id: project_id.to_string(),
+            root: root.to_path_buf(),
+            _watcher: watcher,
+        });
+        Ok(())
+    }
+
+    pub fn unwatch(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.active = None;
+        }
+    }
+
+    pub fn watched_project(&self) -> Option<String> {
+        self.state
+            .lock()
+            .ok()?
+            .active
+            .as_ref()
+            .map(|active| active.project_id.clone())
+    }
+
+    /// One entry per path changed after `since` (epoch millis), net of
+    /// everything that happened to it: a file added and then removed is
+    /// left out, one removed and then re-created counts as modified.
+    pub fn changes_since(&self, project_id: &str, since: i64) -> Vec<FileChange> {
+        let Ok(state) = self.state.lock() else {
+            return Vec::new();
+        };
+        let Some(history) = state.history.get(project_id) else {
+            return Vec::new();
+        };
+
+        let mut net = HashMap::<&str, (FileChangeKind, FileChange)>::new();
+        for change in history.iter().filter(|change| change.changed_at > since) {
+            net.entry(&change.path)
+                .and_modify(|(_, last)| *last = change.clone())
+                .or_insert_with(|| (change.kind, change.clone()));
+        }
+
+        let mut changes = net
+            .into_values()
+            .filter_map(|(first, last)| {
+                let kind = match (first, last.kind) {
+                    (FileChangeKind::Added, FileChangeKind::Removed) => return None,
+                    (FileChangeKind::Added, _) => FileChangeKind::Added,
+                    (FileChangeKind::Removed, FileChangeKind::Removed) => FileChangeKind::Removed,
+                    (FileChangeKind::Removed, _) => FileChangeKind::Modified,
+                    (_, kind) => kind,
+                };
+                Some(FileChange { kind, ..last })
+            })
+            .collect::<Vec<_>>();
+        changes.sort_by(|left, right| left.path.cmp(&right.path));
+        changes
+    }
+
+    fn record(state: &Mutex<WatcherState>, event: &FilesChangedEvent) {
+        let Ok(mut state) = state.lock() else {
+            return;
+        };
+        let history = state.history.entry(event.project_id.clone()).or_default();
+        let changes = [
+            (&event.added, FileChangeKind::Added),
+            (&event.modified, FileChangeKind::Modified),
+            (&event.removed, FileChangeKind::Removed),
+        ];
+        for (paths, kind) in changes {
+            for path in paths {
+                history.push_back(FileChange {
+                    path: path.clone(),
+                    kind,
+                    changed_at: event.changed_at,
+                });
+            }
+        }
+        while history.len() > MAX_HISTORY_PER_PROJECT {
+            history.pop_front();
+        }
+    }
+}
+
+/// State owned by the debounce thread of one watch.
+struct Batch {
+    project_id: String,
+    root: PathBuf,
+    canonical_root: PathBuf,
+    ignore: IgnoreRules,
+    /// Non-ignored files as of the last batch.
+    snapshot: BTreeSet<String>,
+}
+
+impl Batch {
+    fn run<F>(
+        mut self,
+        receiver: Receiver<notify::Result<Event>>,
+        state: Arc<Mutex<WatcherState>>,
+        on_change: F,
+    ) where
+        F: Fn(&FilesChangedEvent),
+    {
+        while let Ok(first) = receiver.recv() {
+            let mut candidates = BTreeSet::new();
+            self.collect(first, &mut candidates);
+            loop {
+                match receiver.recv_timeout(DEBOUNCE) {
+                    Ok(event) => self.collect(event, &mut candidates),
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+            if candidates.is_empty() {
+                continue;
+            }
+
+            let event = match self.diff(&candidates) {
+                Ok(event) => event,
+                Err(error) => {
+                    log::error!("failed to scan changed files: {}", error);
+                    continue;
+                }
+            };
+            if event.is_empty() {
+                continue;
+            }
+
+            WatcherService::record(&state, &event);
+            on_change(&event);
+        }
+    }
+
+    /// Adds the paths of `event` that are not ignored to `candidates`.
+    fn collect(&mut self, event: notify::Result<Event>, candidates: &mut BTreeSet<String>) {
+        let Ok(event) = event else {
+            return;
+        };
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+
+        for path in event.paths {
+            let relative = path
+                .strip_prefix(&self.root)
+                .or_else(|_| path.strip_prefix(&self.canonical_root));
+            if let Ok(relative) = relative {
+                let relative = relative.to_string_lossy().replace('\\', "/");
+                if !relative.is_empty() && !self.ignore.is_ignored(&relative, path.is_dir()) {
+                    candidates.insert(relative);
+                }
+            }
+        }
+    }
+
+    /// Classifies the files at or below each candidate path against the
+    /// snapshot, looking only at those paths. A changed ignore file can show
+    /// or hide files anywhere, so it rescans and diffs the whole project.
+    fn diff(&mut self, candidates: &BTreeSet<String>) -> Result<FilesChangedEvent, String> {
+        let rescan = candidates
+            .iter()
+            .any(|path| IgnoreRules::is_ignore_file(path));
+        let mut current = BTreeSet::new();
+        if rescan {
+            self.ignore = IgnoreRules::new(&self.root)?;
+            current.extend(ContextService::new().list_files(&self.root)?);
+        } else {
+            for candidate in candidates {
+                current.extend(self.scan(candidate)?);
+            }
+        }
+        // After a rescan every file may have been shown or hidden.
+        let touches = |path: &String| {
+            rescan
+                || candidates.iter().any(|candidate| {
+                    path == candidate
+                        || path
+                            .strip_prefix(candidate.as_str())
+                            .is_some_and(|rest| rest.starts_with('/'))
+                })
+        };
+
+        let mut event = FilesChangedEvent {
+            project_id: self.project_id.clone(),
+            changed_at: current_timestamp_millis(),
+            ..FilesChangedEvent::default()
+        };
+        for path in current.iter().filter(|path| touches(path)) {
+            if !self.snapshot.contains(path) {
+                event.added.push(path.clone());
+            } else if candidates.contains(path) {
+                // Files below a touched directory only count when they
+                // reported their own event.
+                event.modified.push(path.clone());
+            }
+        }
+        event.removed = self
+            .snapshot
+            .iter()
+            .filter(|path| !current.contains(*path) && touches(path))
+            .cloned()
+            .collect();
+
+        if rescan {
+            self.snapshot = current;
+        } else {
+            for path in &event.removed {
+                self.snapshot.remove(path);
+            }
+            self.snapshot.extend(current);
+        }
+        Ok(event)
+    }
+
+    /// The non-ignored files at or below `relative` as they are now.
+    fn scan(&self, relative: &str) -> Result<Vec<String>, String> {
+        let Ok(metadata) = fs::symlink_metadata(self.root.join(relative)) else {
+            return Ok(Vec::new());
+        };
+        if metadata.is_file() {
+            Ok(vec![relative.to_string()])
+        } else if metadata.is_dir() {
+            ContextService::new().list_files_under(&self.root, relative)
+        } else {
+            Ok(Vec::new())
+        }
+    }
+}
@@ -0,0 +1,222 @@
+mod common;
+
+use std::sync::Mutex;
+
+use vibeflow_lib::models::budget::{BudgetLimits, BudgetScope, BudgetUnit};
+use vibeflow_lib::models::event_handler::{RecordingEventHandler, RunEventKind};
+use vibeflow_lib::models::project::Project;
+use vibeflow_lib::models::task::{Task, TaskStatus, TaskStep};
+use vibeflow_lib::models::usage::{ModelPricing, TokenUsage, UsageRecord};
+use vibeflow_lib::services::budget_service::{BudgetEventHandler, BudgetService};
+use vibeflow_lib::services::scripted_agent::ScriptedAgent;
+use vibeflow_lib::services::session_manager::SessionManager;
+
+use common::{data_service_with_project, fixture};
+
+fn pricing() -> Vec<ModelPricing> {
+    vec![ModelPricing {
+        model: "gpt-5".into(),
+        input_per_million: 1.0,
+        cached_input_per_million: 0.1,
+        output_per_million: 10.0,
+    }]
+}
+
+fn turn(project_id: &str, task_run_id: &str, input: u64, output: u64) -> UsageRecord {
+    UsageRecord::for_turn(
+        Some(project_id.into()),
+        Some("thread-1".into()),
+        Some(task_run_id.into()),
+        "gpt-5".into(),
+        TokenUsage {
+            input_tokens: input,
+            output_tokens: output,
+            ..TokenUsage::default()
+        },
+        &pricing(),
+    )
+}
+
+fn step(id: &str) -> TaskStep {
+    TaskStep {
+        id: id.into(),
+        title: id.into(),
+        content: "do the thing".into(),
+        status: TaskStatus::InProgress,
+        created_at: 1,
+        updated_at: 1,
+        ..Default::default()
+    }
+}
+
+fn project() -> Project {
+    Project {
+        tasks: vec![Task {
+            id: "task-1".into(),
+            project_id: "project-1".into(),
+            title: "task".into(),
+            steps: vec![step("step-1")],
+            status: TaskStatus::InProgress,
+            created_at: 1,
+            updated_at: 1,
+            ..Default::default()
+        }],
+        ..common::project("/tmp/demo")
+    }
+}
+
+#[test]
+fn unlimited_budget_never_stops_a_run() {
+    let (_dir, data_service) = data_service_with_project(&project());
+    let budget = BudgetService::new(BudgetLimits::default());
+    let record = turn("project-1", "run-1", 5_000_000, 1_000_000);
+    data_service.save_usage_record(&record).unwrap();
+
+    assert!(budget
+        .check_after_turn(&data_service, &record)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn single_turn_over_limit_is_reported() {
+    let (_dir, data_service) = data_service_with_project(&project());
+    let budget = BudgetService::new(BudgetLimits {
+        max_tokens_per_turn: Some(1_000),
+        ..BudgetLimits::default()
+    });
+
+    let violation = budget
+        .check_after_turn(&data_service, &turn("project-1", "run-1", 900, 200))
+        .unwrap()
+        .expect("turn should exceed its budget");
+
+    assert_eq!(violation.scope, BudgetScope::Turn);
+    assert_eq!(violation.unit, BudgetUnit::Tokens);
+    assert_eq!(
+        violation.reason(),
+        "turn token budget exceeded: 1100 of 1000 tokens used"
+    );
+}
+
+#[test]
+fn task_run_budget_accumulates_across_turns_and_blocks_next_turn() {
+    let (_dir, data_service) = data_service_with_project(&project());
+    let budget = BudgetService::new(BudgetLimits {
+        max_cost_per_task_run: Some(0.02),
+        ..BudgetLimits::default()
+    });
+
+    let first = turn("project-1", "run-1", 1_000, 1_000);
+    data_service.save_usage_record(&first).unwrap();
+    assert!(budget
+        .check_after_turn(&data_service, &first)
+        .unwrap()
+        .is_none());
+
+    let second = turn("project-1", "run-1", 1_000, 1_000);
+    data_service.save_usage_record(&second).unwrap();
+    let violation = budget
+        .check_after_turn(&data_service, &second)
+        .unwrap()
+        .expect("run should exceed its budget");
+    assert_eq!(violation.scope, BudgetScope::TaskRun);
+    assert_eq!(violation.unit, BudgetUnit::Cost);
+
+    assert!(budget
+        .check_before_turn(&data_service, Some("project-1"), Some("run-1"))
+        .unwrap()
+        .is_some());
+    assert!(budget
+        .check_before_turn(&data_service, Some("project-1"), Some("run-2"))
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn daily_project_budget_counts_all_runs_of_the_day() {
+    let (_dir, data_service) = data_service_with_project(&project());
+    let budget = BudgetService::new(BudgetLimits {
+        max_tokens_per_project_per_day: Some(3_000),
+        ..BudgetLimits::default()
+    });
+
+    data_service
+        .save_usage_record(&turn("project-1", "run-1", 1_000, 500))
+        .unwrap();
+    data_service
+        .save_usage_record(&turn("project-1", "run-2", 1_000, 500))
+        .unwrap();
+
+    let violation = budget
+        .check_before_turn(&data_service, Some("project-1"), None)
+        .unwrap()
+        .expect("project should exceed its daily budget");
+    assert_eq!(violation.scope, BudgetScope::ProjectDay);
+}
+
+#[test]
+fn failed_step_keeps_budget_reason() {
+    let (_dir, data_service) = data_service_with_project(&project());
+
+    data_service
+        .update_task_step_status(
+            "task-1",
+            "step-1",
+            &TaskStatus::Failed,
+            Some("task run token budget exceeded"),
+        )
+        .unwrap();
+
+    let project = data_service.load_project("project-1").unwrap().unwrap();
+    let task = &project.tasks[0];
+    assert!(matches!(task.status, TaskStatus::Failed));
+    assert!(matches!(task.steps[0].status, TaskStatus::Failed));
+    assert_eq!(
+        task.steps[0].status_reason.as_deref(),
+        Some("task run token budget exceeded")
+    );
+}
+
+#[tokio::test]
+async fn cancels_a_scripted_turn_once_streamed_usage_crosses_the_budget() {
+    let (_dir, data_service) = data_service_with_project(&project());
+    let data_service = Mutex::new(data_service);
+    let sessions = SessionManager::new();
+    let _session = sessions
+        .acquire("run-1", Some("project-1".into()), None, "codex".into())
+        .await
+        .unwrap();
+    let budget = BudgetService::new(BudgetLimits {
+        max_tokens_per_turn: Some(1_000),
+        ..BudgetLimits::default()
+    });
+    let pricing = pricing();
+    let recorder = RecordingEventHandler::new();
+    let handler = BudgetEventHandler::new(
+        &budget,
+        &data_service,
+        &sessions,
+        &pricing,
+        turn("project-1", "task-run-1", 0, 0),
+        sessions.handler(recorder.clone()),
+    );
+
+    let agent = ScriptedAgent::from_jsonl_file(fixture("runaway_turn.jsonl")).unwrap();
+    let result = agent.replay("run-1", &handler).await;
+
+    let reason = "turn token budget exceeded: 1200 of 1000 tokens used";
+    assert_eq!(result.unwrap_err(), reason);
+    assert_eq!(sessions.cancel_reason("run-1").as_deref(), Some(reason));
+
+    let messages = recorder
+        .payloads(RunEventKind::Item)
+        .into_iter()
+        .filter(|item| item["type"] == "message")
+        .map(|item| item["data"]["content"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(messages, vec!["Rewriting the module".to_string()]);
+    let done = recorder.payloads(RunEventKind::Done);
+    assert_eq!(done.len(), 1);
+    assert_eq!(done[0]["type"], "error");
+}
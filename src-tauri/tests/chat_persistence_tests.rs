@@ -0,0 +1,106 @@
+mod common;
+
+use std::sync::Mutex;
+
+use vibeflow_lib::models::chat::{ChatRequest, ChatThread, ChatTurnStatus};
+use vibeflow_lib::models::event_handler::{RecordingEventHandler, RunEventKind};
+use vibeflow_lib::services::chat_persistence::PersistingEventHandler;
+use vibeflow_lib::services::data_service::DataService;
+use vibeflow_lib::services::scripted_agent::ScriptedAgent;
+
+use common::fixture;
+
+fn data_service_with_thread(data_dir: &tempfile::TempDir) -> DataService {
+    let data_service = DataService::new(data_dir.path().to_path_buf()).unwrap();
+    data_service
+        .upsert_project(&common::project("/tmp/demo"))
+        .unwrap();
+    data_service
+        .save_chat_thread(&ChatThread {
+            id: "chat-1".into(),
+            project_id: "project-1".into(),
+            title: "run the tests".into(),
+            created_at: 1,
+            updated_at: 1,
+            agent_thread_id: None,
+            agent_id: None,
+            model: None,
+        })
+        .unwrap();
+    data_service
+}
+
+#[tokio::test]
+async fn persists_prompt_and_completed_items_without_the_frontend() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let data_service = Mutex::new(data_service_with_thread(&data_dir));
+    let recorder = RecordingEventHandler::new();
+    let handler = PersistingEventHandler::new(
+        &data_service,
+        Some("chat-1".into()),
+        "gpt-5-codex".into(),
+        recorder.clone(),
+    );
+
+    handler.save_prompt("run the tests");
+    ScriptedAgent::from_jsonl_file(fixture("successful_turn.jsonl"))
+        .unwrap()
+        .invoke_stream(
+            ChatRequest {
+                content: "run the tests".into(),
+                ..ChatRequest::default()
+            },
+            handler,
+        )
+        .await
+        .unwrap();
+
+    let messages = data_service
+        .lock()
+        .unwrap()
+        .load_chat_messages_by_thread("chat-1")
+        .unwrap();
+    let roles = messages
+        .iter()
+        .map(|message| message.role.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(roles, vec!["user", "agent", "agent"]);
+    assert_eq!(messages[0].content, "run the tests");
+    assert!(messages[1].content.starts_with("Command: cargo test\n"));
+    assert_eq!(messages[2].content, "All tests pass.");
+    assert!(messages
+        .iter()
+        .all(|message| message.model == "gpt-5-codex"));
+
+    assert_eq!(recorder.payloads(RunEventKind::Item).len(), 3);
+}
+
+#[test]
+fn marks_turns_left_running_as_interrupted_on_restart() {
+    let data_dir = tempfile::tempdir().unwrap();
+    {
+        let data_service = data_service_with_thread(&data_dir);
+        data_service.start_chat_turn("run-done", "chat-1").unwrap();
+        data_service
+            .finish_chat_turn("run-done", ChatTurnStatus::Completed, None)
+            .unwrap();
+        data_service.start_chat_turn("run-lost", "chat-1").unwrap();
+    }
+
+    let data_service = DataService::new(data_dir.path().to_path_buf()).unwrap();
+    assert_eq!(data_service.recover_interrupted_turns().unwrap(), 1);
+    assert_eq!(data_service.recover_interrupted_turns().unwrap(), 0);
+
+    let statuses = data_service
+        .load_chat_turns_by_thread("chat-1")
+        .unwrap()
+        .into_iter()
+        .map(|turn| (turn.run_id, turn.status))
+        .collect::<Vec<_>>();
+    assert!(statuses.contains(&("run-done".to_string(), ChatTurnStatus::Completed)));
+    assert!(statuses.contains(&("run-lost".to_string(), ChatTurnStatus::Interrupted)));
+
+    let messages = data_service.load_chat_messages_by_thread("chat-1").unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].role, "system");
+}
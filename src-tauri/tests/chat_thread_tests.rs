@@ -0,0 +1,65 @@
+mod common;
+
+use vibeflow_lib::models::chat::ChatThread;
+
+use common::{data_service_with_project, project};
+
+fn thread(id: &str) -> ChatThread {
+    ChatThread {
+        id: id.into(),
+        project_id: "project-1".into(),
+        title: "refactor the parser".into(),
+        created_at: 1,
+        updated_at: 1,
+        agent_thread_id: None,
+        agent_id: None,
+        model: None,
+    }
+}
+
+#[test]
+fn stores_agent_thread_and_keeps_it_across_frontend_saves() {
+    let (_dir, data_service) = data_service_with_project(&project("/tmp/demo"));
+    data_service.save_chat_thread(&thread("chat-1")).unwrap();
+
+    data_service
+        .update_chat_thread_agent("chat-1", "codex-thread-1", Some("agent-1"), Some("gpt-5"))
+        .unwrap();
+    data_service
+        .save_chat_thread(&ChatThread {
+            title: "renamed".into(),
+            updated_at: 5,
+            ..thread("chat-1")
+        })
+        .unwrap();
+
+    let stored = data_service.load_chat_thread("chat-1").unwrap().unwrap();
+    assert_eq!(stored.title, "renamed");
+    assert_eq!(stored.agent_thread_id.as_deref(), Some("codex-thread-1"));
+    assert_eq!(stored.agent_id.as_deref(), Some("agent-1"));
+    assert_eq!(stored.model.as_deref(), Some("gpt-5"));
+}
+
+#[test]
+fn missing_chat_thread_loads_as_none() {
+    let (_dir, data_service) = data_service_with_project(&project("/tmp/demo"));
+
+    assert!(data_service.load_chat_thread("missing").unwrap().is_none());
+}
+
+#[test]
+fn resumes_only_with_the_agent_that_started_the_thread() {
+    let stored = ChatThread {
+        agent_thread_id: Some("codex-thread-1".into()),
+        agent_id: Some("agent-1".into()),
+        ..thread("chat-1")
+    };
+
+    assert_eq!(
+        stored.resumable_thread_id(Some("agent-1")),
+        Some("codex-thread-1")
+    );
+    assert_eq!(stored.resumable_thread_id(None), Some("codex-thread-1"));
+    assert_eq!(stored.resumable_thread_id(Some("agent-2")), None);
+    assert_eq!(thread("chat-2").resumable_thread_id(Some("agent-1")), None);
+}
@@ -0,0 +1,226 @@
+mod common;
+
+use std::sync::Mutex;
+
+use serde_json::Value;
+use vibeflow_lib::models::chat::{ChatRequest, ChatThread, TurnOutcome};
+use vibeflow_lib::models::event_handler::{RecordingEventHandler, RunEventKind};
+use vibeflow_lib::services::chat_persistence::PersistingEventHandler;
+use vibeflow_lib::services::scripted_agent::ScriptedAgent;
+
+use common::fixture;
+
+fn request(content: &str) -> ChatRequest {
+    ChatRequest {
+        content: content.into(),
+        ..ChatRequest::default()
+    }
+}
+
+async fn replay(name: &str) -> (Result<TurnOutcome, String>, RecordingEventHandler) {
+    let agent = ScriptedAgent::from_jsonl_file(fixture(name)).expect("fixture should parse");
+    let handler = RecordingEventHandler::new();
+    let result = agent
+        .invoke_stream(request("run the tests"), handler.clone())
+        .await;
+    (result, handler)
+}
+
+fn message_items(handler: &RecordingEventHandler) -> Vec<Value> {
+    handler
+        .payloads(RunEventKind::Item)
+        .into_iter()
+        .filter(|item| item["type"] == "message")
+        .collect()
+}
+
+#[tokio::test]
+async fn translates_successful_turn_into_chat_responses() {
+    let (result, handler) = replay("successful_turn.jsonl").await;
+    let outcome = result.expect("successful turn should not fail");
+    assert_eq!(outcome.thread_id.as_deref(), Some("thread-abc"));
+    assert_eq!(outcome.last_message.as_deref(), Some("All tests pass."));
+    assert_eq!(outcome.usage.input_tokens, 1200);
+    assert_eq!(outcome.usage.cached_input_tokens, 200);
+    assert_eq!(outcome.usage.output_tokens, 42);
+
+    let kinds = handler
+        .events()
+        .iter()
+        .map(|event| event.kind)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            RunEventKind::ThreadStarted,
+            RunEventKind::Item,
+            RunEventKind::Item,
+            RunEventKind::Item,
+            RunEventKind::Done,
+        ]
+    );
+
+    let started = &handler.payloads(RunEventKind::ThreadStarted)[0];
+    assert_eq!(started["type"], "threadStarted");
+    assert_eq!(started["data"]["thread_id"], "thread-abc");
+
+    let items = handler.payloads(RunEventKind::Item);
+    assert_eq!(items[0]["type"], "token");
+    assert_eq!(items[0]["data"]["text"], "Looking at the project layout");
+
+    let messages = message_items(&handler);
+    assert_eq!(messages[0]["data"]["role"], "tool");
+    let tool_output = messages[0]["data"]["content"].as_str().unwrap();
+    assert!(tool_output.starts_with("Command: cargo test\n"));
+    assert!(tool_output.ends_with("test result: ok"));
+    assert_eq!(messages[1]["data"]["role"], "assistant");
+    assert_eq!(messages[1]["data"]["content"], "All tests pass.");
+
+    let done = &handler.payloads(RunEventKind::Done)[0];
+    assert_eq!(done["type"], "done");
+    assert_eq!(done["data"]["total_tokens"], 1242);
+    assert_eq!(done["data"]["usage"]["cachedInputTokens"], 200);
+}
+
+#[tokio::test]
+async fn turn_failed_reports_error_and_stops_stream() {
+    let (result, handler) = replay("failed_turn.jsonl").await;
+
+    assert_eq!(result, Err("model overloaded".to_string()));
+
+    let done = handler.payloads(RunEventKind::Done);
+    assert_eq!(done.len(), 1);
+    assert_eq!(done[0]["type"], "error");
+    assert_eq!(done[0]["data"]["message"], "model overloaded");
+
+    let contents = message_items(&handler)
+        .iter()
+        .map(|item| item["data"]["content"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(contents, vec!["Starting work".to_string()]);
+}
+
+#[tokio::test]
+async fn thread_error_event_fails_the_turn() {
+    let (result, handler) = replay("thread_error.jsonl").await;
+
+    assert_eq!(result, Err("stream disconnected".to_string()));
+    let done = handler.payloads(RunEventKind::Done);
+    assert_eq!(done[0]["type"], "error");
+    assert_eq!(done[0]["data"]["message"], "stream disconnected");
+}
+
+#[tokio::test]
+async fn rejects_empty_prompt_before_replaying() {
+    let agent = ScriptedAgent::from_jsonl_file(fixture("successful_turn.jsonl")).unwrap();
+    let handler = RecordingEventHandler::new();
+
+    let result = agent.invoke_stream(request("   "), handler.clone()).await;
+
+    assert_eq!(result, Err("prompt cannot be empty".to_string()));
+    assert!(handler.events().is_empty());
+}
+
+#[tokio::test]
+async fn concurrent_runs_tag_every_event_with_their_run_id() {
+    let agent = ScriptedAgent::from_jsonl_file(fixture("successful_turn.jsonl")).unwrap();
+    let handler = RecordingEventHandler::new();
+    let first = ChatRequest {
+        run_id: Some("run-a".into()),
+        ..request("first chat")
+    };
+    let second = ChatRequest {
+        run_id: Some("run-b".into()),
+        ..request("second chat")
+    };
+
+    let (first, second) = tokio::join!(
+        agent.invoke_stream(first, handler.clone()),
+        agent.invoke_stream(second, handler.clone())
+    );
+    assert_eq!(first.unwrap().run_id, "run-a");
+    assert_eq!(second.unwrap().run_id, "run-b");
+
+    let events = handler.events();
+    for run_id in ["run-a", "run-b"] {
+        let kinds = events
+            .iter()
+            .filter(|event| event.run_id == run_id)
+            .map(|event| event.kind)
+            .collect::<Vec<_>>();
+        assert_eq!(kinds.first(), Some(&RunEventKind::ThreadStarted));
+        assert_eq!(kinds.last(), Some(&RunEventKind::Done));
+        assert_eq!(kinds.len(), 5);
+    }
+}
+
+#[tokio::test]
+async fn generates_run_id_when_caller_does_not_supply_one() {
+    let (result, handler) = replay("successful_turn.jsonl").await;
+    let run_id = result.unwrap().run_id;
+
+    assert!(!run_id.is_empty());
+    assert!(handler.events().iter().all(|event| event.run_id == run_id));
+}
+
+#[test]
+fn reports_invalid_script_lines() {
+    let script = "{\"type\":\"thread.started\",\"thread_id\":\"t\"}\n\nnot json\n";
+
+    let error = ScriptedAgent::from_jsonl_str(script)
+        .err()
+        .expect("invalid line should be rejected");
+
+    assert!(error.starts_with("invalid event on line 3"));
+}
+
+#[tokio::test]
+async fn persists_chat_results_from_a_scripted_turn() {
+    let project = common::project("/tmp/demo");
+    let (_data_dir, data_service) = common::data_service_with_project(&project);
+    data_service
+        .save_chat_thread(&ChatThread {
+            id: "chat-1".into(),
+            project_id: project.id.clone(),
+            title: "run the tests".into(),
+            created_at: 1,
+            updated_at: 1,
+            agent_thread_id: None,
+            agent_id: None,
+            model: None,
+        })
+        .unwrap();
+
+    let data_service = Mutex::new(data_service);
+    let recorder = RecordingEventHandler::new();
+    let handler = PersistingEventHandler::new(
+        &data_service,
+        Some("chat-1".into()),
+        "gpt-5-codex".into(),
+        recorder.clone(),
+    );
+    ScriptedAgent::from_jsonl_file(fixture("successful_turn.jsonl"))
+        .unwrap()
+        .invoke_stream(request("run the tests"), handler)
+        .await
+        .unwrap();
+
+    let service = data_service.lock().unwrap();
+    let messages = service.load_chat_messages_by_thread("chat-1").unwrap();
+    let contents = messages
+        .iter()
+        .map(|message| message.content.as_str())
+        .collect::<Vec<_>>();
+    let sent = message_items(&recorder)
+        .iter()
+        .map(|item| item["data"]["content"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(contents, sent);
+    assert_eq!(contents.last(), Some(&"All tests pass."));
+
+    let threads = service
+        .load_chat_threads_by_project(&project.id, 10)
+        .unwrap();
+    assert_eq!(threads.len(), 1);
+    assert_eq!(threads[0].updated_at, messages.last().unwrap().created_at);
+}
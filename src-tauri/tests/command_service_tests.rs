@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+use vibeflow_lib::models::verification::StepVerification;
+use vibeflow_lib::services::command_service::CommandService;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+fn cwd() -> String {
+    std::env::temp_dir().to_string_lossy().into_owned()
+}
+
+#[test]
+fn verification_stops_at_the_first_failing_command() {
+    let commands = vec![
+        "echo built".to_string(),
+        " ".to_string(),
+        "echo broken >&2 && exit 3".to_string(),
+        "echo never".to_string(),
+    ];
+    let checks = CommandService::new().verify(&commands, &cwd(), TIMEOUT);
+
+    assert_eq!(checks.len(), 2);
+    assert!(checks[0].passed());
+    assert_eq!(checks[0].output.trim(), "built");
+    assert_eq!(checks[1].exit_code, Some(3));
+    assert!(checks[1].output.contains("broken"));
+
+    let verification = StepVerification::new(checks.clone(), 1, 2);
+    assert!(!verification.passed);
+    assert_eq!(
+        verification.reason.as_deref(),
+        Some("verification failed: `echo broken >&2 && exit 3` exited with 3")
+    );
+    let retry = verification.retry_prompt.unwrap();
+    assert!(retry.contains("broken"));
+    assert!(retry.contains("attempt 1 of 2"));
+
+    let last = StepVerification::new(checks, 2, 2);
+    assert!(last.reason.is_some());
+    assert!(last.retry_prompt.is_none());
+}
+
+#[test]
+fn passing_verification_has_nothing_to_retry() {
+    let checks = CommandService::new().verify(&["exit 0".to_string()], &cwd(), TIMEOUT);
+    let verification = StepVerification::new(checks, 1, 3);
+
+    assert!(verification.passed);
+    assert!(verification.reason.is_none());
+    assert!(verification.retry_prompt.is_none());
+    assert!(StepVerification::new(Vec::new(), 1, 3).passed);
+}
+
+#[cfg(unix)]
+#[test]
+fn slow_commands_time_out() {
+    let check = CommandService::new().run_check(
+        "echo started && sleep 5",
+        &cwd(),
+        Duration::from_millis(300),
+    );
+
+    assert!(check.timed_out);
+    assert!(!check.passed());
+    assert!(check.output.contains("started"));
+    assert!(check.failure().contains("timed out"));
+}
+
+#[cfg(unix)]
+#[test]
+fn timeouts_kill_the_processes_a_command_started() {
+    let dir = tempfile::tempdir().unwrap();
+    let check = CommandService::new().run_check(
+        "sleep 30 & echo $! > sleep.pid; wait",
+        &dir.path().to_string_lossy(),
+        Duration::from_millis(300),
+    );
+    assert!(check.timed_out);
+
+    let pid = std::fs::read_to_string(dir.path().join("sleep.pid")).unwrap();
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    let alive = || {
+        std::process::Command::new("kill")
+            .args(["-0", pid.trim()])
+            .stderr(std::process::Stdio::null())
+            .status()
+            .unwrap()
+            .success()
+    };
+    while alive() && std::time::Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    assert!(!alive());
+}
+
+#[cfg(unix)]
+#[test]
+fn background_processes_do_not_hold_a_finished_check() {
+    let started = std::time::Instant::now();
+    let check = CommandService::new().run_check("sleep 30 & echo ok", &cwd(), TIMEOUT);
+
+    assert!(check.passed());
+    assert!(!check.timed_out);
+    assert_eq!(check.output.trim(), "ok");
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
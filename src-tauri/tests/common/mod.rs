@@ -0,0 +1,37 @@
+//! Helpers shared by the integration tests. Each test crate uses only some
+//! of them.
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use tempfile::TempDir;
+use vibeflow_lib::models::project::Project;
+use vibeflow_lib::services::data_service::DataService;
+
+pub fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+/// The project the tests work with, at `path`.
+pub fn project(path: &str) -> Project {
+    Project {
+        id: "project-1".into(),
+        name: "demo".into(),
+        path: path.into(),
+        created_at: 1,
+        updated_at: 1,
+        ..Default::default()
+    }
+}
+
+/// A data service in a fresh directory, holding `project`. The directory
+/// is removed when the returned guard is dropped.
+pub fn data_service_with_project(project: &Project) -> (TempDir, DataService) {
+    let data_dir = tempfile::tempdir().unwrap();
+    let data_service = DataService::new(data_dir.path().to_path_buf()).unwrap();
+    data_service.upsert_project(project).unwrap();
+    (data_dir, data_service)
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use vibeflow_lib::models::context::ContextSet;
+use vibeflow_lib::services::context_service::ContextService;
+
+fn write(root: &Path, path: &str, content: &str) {
+    let path = root.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+fn context_set(name: &str, patterns: &[&str]) -> ContextSet {
+    ContextSet {
+        id: format!("set-{name}"),
+        project_id: "project-1".into(),
+        name: name.into(),
+        patterns: patterns.iter().map(|pattern| pattern.to_string()).collect(),
+        created_at: 1,
+        updated_at: 1,
+    }
+}
+
+#[test]
+fn lists_files_honoring_gitignore_and_vibeflow_ignore() {
+    let root = tempfile::tempdir().unwrap();
+    write(root.path(), ".gitignore", "target/\n*.log\n");
+    write(root.path(), ".vibeflow/ignore", "/docs\n");
+    write(root.path(), "src/main.rs", "fn main() {}");
+    write(
+        root.path(),
+        "src/docs/guide.md",
+        "kept, only the root docs are ignored",
+    );
+    write(root.path(), "docs/readme.md", "ignored");
+    write(root.path(), "target/debug/app", "ignored");
+    write(root.path(), "build.log", "ignored");
+
+    let files = ContextService::new().list_files(root.path()).unwrap();
+
+    assert_eq!(
+        files,
+        [
+            ".gitignore",
+            ".vibeflow/ignore",
+            "src/docs/guide.md",
+            "src/main.rs"
+        ]
+    );
+}
+
+#[test]
+fn resolves_paths_directories_and_globs() {
+    let root = tempfile::tempdir().unwrap();
+    write(root.path(), "src/main.rs", "");
+    write(root.path(), "src/lib.rs", "");
+    write(root.path(), "src/services/data.rs", "");
+    write(root.path(), "README.md", "");
+    let service = ContextService::new();
+
+    let patterns = ["src/*.rs".to_string(), "README.md".to_string()];
+    assert_eq!(
+        service.resolve_patterns(root.path(), &patterns).unwrap(),
+        ["README.md", "src/lib.rs", "src/main.rs"]
+    );
+    assert_eq!(
+        service
+            .resolve_patterns(root.path(), &["src/services/".to_string()])
+            .unwrap(),
+        ["src/services/data.rs"]
+    );
+    assert!(service
+        .resolve_patterns(root.path(), &["src/[".to_string()])
+        .is_err());
+}
+
+#[test]
+fn renders_files_within_the_token_budget() {
+    let root = tempfile::tempdir().unwrap();
+    write(root.path(), "a.rs", "fn a() {}\n");
+    write(root.path(), "big.txt", &"word ".repeat(400));
+    write(root.path(), "c.bin", "\0\0binary");
+    let service = ContextService::new();
+    let sets = [
+        context_set("code", &["a.rs", "c.bin"]),
+        context_set("all", &["*"]),
+    ];
+
+    let block = service.build_block(root.path(), &sets, 200).unwrap();
+
+    assert_eq!(
+        block
+            .files
+            .iter()
+            .map(|file| file.path.as_str())
+            .collect::<Vec<_>>(),
+        ["a.rs"]
+    );
+    assert_eq!(block.omitted[0].path, "big.txt");
+    assert!(block.estimated_tokens <= 200);
+    assert_eq!(
+        block.content,
+        "# Project context\n\n## a.rs\n\n```rs\nfn a() {}\n```\n"
+    );
+    assert_eq!(
+        ContextService::prepend(&block, "explain a"),
+        format!("{}\n---\n\nexplain a", block.content)
+    );
+    assert_eq!(ContextService::estimate_tokens("abcde"), 2);
+}
+
+#[test]
+fn context_sets_are_stored_per_project() {
+    let (_data_dir, data_service) =
+        common::data_service_with_project(&common::project("/tmp/demo"));
+
+    data_service
+        .save_context_set(&context_set("backend", &["src-tauri/**"]))
+        .unwrap();
+    data_service
+        .save_context_set(&context_set("api", &["src/app/services/*.ts"]))
+        .unwrap();
+
+    let sets = data_service.load_context_sets("project-1").unwrap();
+    assert_eq!(
+        sets.iter().map(|set| set.name.as_str()).collect::<Vec<_>>(),
+        ["api", "backend"]
+    );
+    assert_eq!(sets[1].patterns, ["src-tauri/**"]);
+
+    data_service.delete_context_set("set-api").unwrap();
+    assert_eq!(
+        data_service.load_context_sets("project-1").unwrap().len(),
+        1
+    );
+}
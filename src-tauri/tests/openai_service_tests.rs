@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use serde_json::Value;
+use vibeflow_lib::models::chat::ChatRequest;
+use vibeflow_lib::models::event_handler::{RecordingEventHandler, RunEventKind};
+use vibeflow_lib::models::setting::{AgentApiStyle, AgentConfig};
+use vibeflow_lib::services::chat_persistence::PersistingEventHandler;
+use vibeflow_lib::services::data_service::DataService;
+use vibeflow_lib::services::openai_service::{OpenAiService, SseDecoder};
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn messages(handler: &RecordingEventHandler, role: &str) -> Vec<String> {
+    handler
+        .payloads(RunEventKind::Item)
+        .into_iter()
+        .filter(|event| event["type"] == "message" && event["data"]["role"] == role)
+        .map(|event| event["data"]["content"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn done(handler: &RecordingEventHandler) -> Value {
+    handler
+        .payloads(RunEventKind::Done)
+        .pop()
+        .expect("no done event received")
+}
+
+fn agent(base_url: String, api_style: AgentApiStyle) -> AgentConfig {
+    AgentConfig {
+        id: "local".into(),
+        name: "Local model".into(),
+        agent_type: AgentConfig::OPENAI_COMPATIBLE.into(),
+        model: "llama3".into(),
+        api_key: "secret".into(),
+        base_url,
+        enabled: true,
+        is_default: true,
+        api_style,
+        headers: HashMap::from([("X-Workspace".to_string(), "vibeflow".to_string())]),
+    }
+}
+
+fn request(content: &str) -> ChatRequest {
+    ChatRequest {
+        content: content.into(),
+        ..ChatRequest::default()
+    }
+}
+
+fn sse(events: &[&str]) -> String {
+    events
+        .iter()
+        .map(|event| format!("data: {event}\n\n"))
+        .collect::<String>()
+}
+
+#[tokio::test]
+async fn streams_chat_completion_text_and_usage() {
+    let server = MockServer::start().await;
+    let body = sse(&[
+        r#"{"choices":[{"index":0,"delta":{"role":"assistant","content":"Hello"}}]}"#,
+        r#"{"choices":[{"index":0,"delta":{"content":" world"}}]}"#,
+        r#"{"choices":[],"usage":{"prompt_tokens":5,"completion_tokens":7,"completion_tokens_details":{"reasoning_tokens":3},"total_tokens":12}}"#,
+        "[DONE]",
+    ]);
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .and(header("authorization", "Bearer secret"))
+        .and(header("x-workspace", "vibeflow"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let service = OpenAiService::new();
+    let handler = RecordingEventHandler::new();
+    let agent = agent(
+        format!("{}/v1/", server.uri()),
+        AgentApiStyle::ChatCompletions,
+    );
+
+    let outcome = service
+        .invoke_stream(request("Say hello"), &agent, handler.clone())
+        .await
+        .expect("chat completion should succeed");
+    assert_eq!(outcome.usage.input_tokens, 5);
+    assert_eq!(outcome.usage.output_tokens, 7);
+    assert_eq!(outcome.usage.reasoning_output_tokens, 3);
+    assert_eq!(outcome.last_message.as_deref(), Some("Hello world"));
+
+    let events = handler.events();
+    assert_eq!(events[0].kind, RunEventKind::ThreadStarted);
+    assert_eq!(events[0].payload["type"], "threadStarted");
+    assert_eq!(
+        messages(&handler, "assistant"),
+        vec!["Hello world".to_string()]
+    );
+    assert_eq!(done(&handler)["type"], "done");
+    assert_eq!(done(&handler)["data"]["total_tokens"], 12);
+}
+
+#[tokio::test]
+async fn accumulates_streamed_tool_calls() {
+    let server = MockServer::start().await;
+    let body = sse(&[
+        r#"{"choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"read_file","arguments":""}}]}}]}"#,
+        r#"{"choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"path\":"}}]}}]}"#,
+        r#"{"choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"README.md\"}"}}]}}]}"#,
+        "[DONE]",
+    ]);
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .mount(&server)
+        .await;
+
+    let service = OpenAiService::new();
+    let handler = RecordingEventHandler::new();
+    let agent = agent(server.uri(), AgentApiStyle::ChatCompletions);
+    for (content, handler) in [
+        ("Read the readme", handler.clone()),
+        ("And again", RecordingEventHandler::new()),
+    ] {
+        let mut payload = request(content);
+        payload.thread_id = Some("thread-1".into());
+        payload.tools = Some(vec![serde_json::json!({
+            "type": "function",
+            "function": { "name": "read_file", "parameters": { "type": "object" } }
+        })]);
+        service
+            .invoke_stream(payload, &agent, handler)
+            .await
+            .expect("tool call stream should succeed");
+    }
+
+    assert_eq!(
+        messages(&handler, "tool"),
+        vec!["Tool read_file call:\n{\"path\":\"README.md\"}".to_string()]
+    );
+
+    let received = server.received_requests().await.unwrap();
+    let sent: Value = serde_json::from_slice(&received[0].body).unwrap();
+    assert_eq!(sent["tools"][0]["function"]["name"], "read_file");
+    assert_eq!(sent["stream"], true);
+
+    // The calls were not executed, so the next turn must not send them back
+    // as `tool_calls` waiting for results.
+    let sent: Value = serde_json::from_slice(&received[1].body).unwrap();
+    let history = sent["messages"].as_array().unwrap();
+    assert_eq!(history.len(), 3);
+    assert!(history
+        .iter()
+        .all(|message| message.get("tool_calls").is_none()));
+    assert_eq!(
+        history[1]["content"],
+        "Requested tool read_file with {\"path\":\"README.md\"}"
+    );
+}
+
+#[tokio::test]
+async fn streams_responses_api_events() {
+    let server = MockServer::start().await;
+    let body = sse(&[
+        r#"{"type":"response.output_text.delta","delta":"Local "}"#,
+        r#"{"type":"response.output_text.delta","delta":"reply"}"#,
+        r#"{"type":"response.output_item.done","item":{"type":"function_call","call_id":"c1","name":"search","arguments":"{}"}}"#,
+        r#"{"type":"response.completed","response":{"usage":{"input_tokens":3,"output_tokens":4,"output_tokens_details":{"reasoning_tokens":2},"total_tokens":7}}}"#,
+    ]);
+    Mock::given(method("POST"))
+        .and(path("/responses"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .mount(&server)
+        .await;
+
+    let service = OpenAiService::new();
+    let handler = RecordingEventHandler::new();
+
+    let outcome = service
+        .invoke_stream(
+            request("Hi"),
+            &agent(server.uri(), AgentApiStyle::Responses),
+            handler.clone(),
+        )
+        .await
+        .expect("responses stream should succeed");
+    assert_eq!(outcome.usage.output_tokens, 4);
+    assert_eq!(outcome.usage.reasoning_output_tokens, 2);
+
+    assert_eq!(
+        messages(&handler, "assistant"),
+        vec!["Local reply".to_string()]
+    );
+    assert_eq!(
+        messages(&handler, "tool"),
+        vec!["Tool search call:\n{}".to_string()]
+    );
+    assert_eq!(done(&handler)["data"]["total_tokens"], 7);
+}
+
+#[tokio::test]
+async fn resumed_thread_sends_conversation_history() {
+    let server = MockServer::start().await;
+    let body = sse(&[
+        r#"{"choices":[{"index":0,"delta":{"content":"ok"}}]}"#,
+        "[DONE]",
+    ]);
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .mount(&server)
+        .await;
+
+    let service = OpenAiService::new();
+    let agent = agent(server.uri(), AgentApiStyle::ChatCompletions);
+    let mut payload = request("first");
+    payload.thread_id = Some("thread-1".into());
+    service
+        .invoke_stream(payload, &agent, RecordingEventHandler::new())
+        .await
+        .unwrap();
+
+    let mut payload = request("second");
+    payload.thread_id = Some("thread-1".into());
+    service
+        .invoke_stream(payload, &agent, RecordingEventHandler::new())
+        .await
+        .unwrap();
+
+    let received = server.received_requests().await.unwrap();
+    let sent: Value = serde_json::from_slice(&received[1].body).unwrap();
+    let roles = sent["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|message| message["role"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(roles, vec!["user", "assistant", "user"]);
+}
+
+#[tokio::test]
+async fn requests_structured_output_for_both_api_styles() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(sse(&["[DONE]"]), "text/event-stream"),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/responses"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            sse(&[r#"{"type":"response.completed","response":{}}"#]),
+            "text/event-stream",
+        ))
+        .mount(&server)
+        .await;
+
+    let service = OpenAiService::new();
+    let schema = serde_json::json!({ "type": "object" });
+    for api_style in [AgentApiStyle::ChatCompletions, AgentApiStyle::Responses] {
+        let mut payload = request("Plan it");
+        payload.output_schema = Some(schema.clone());
+        service
+            .invoke_stream(
+                payload,
+                &agent(server.uri(), api_style),
+                RecordingEventHandler::new(),
+            )
+            .await
+            .unwrap();
+    }
+
+    let received = server.received_requests().await.unwrap();
+    let chat: Value = serde_json::from_slice(&received[0].body).unwrap();
+    assert_eq!(chat["response_format"]["type"], "json_schema");
+    assert_eq!(chat["response_format"]["json_schema"]["schema"], schema);
+    let responses: Value = serde_json::from_slice(&received[1].body).unwrap();
+    assert_eq!(responses["text"]["format"]["schema"], schema);
+    assert_eq!(responses["text"]["format"]["strict"], true);
+}
+
+#[tokio::test]
+async fn reports_http_errors_as_error_events() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500).set_body_string("model not loaded"))
+        .mount(&server)
+        .await;
+
+    let service = OpenAiService::new();
+    let handler = RecordingEventHandler::new();
+
+    let result = service
+        .invoke_stream(
+            request("Hi"),
+            &agent(server.uri(), AgentApiStyle::ChatCompletions),
+            handler.clone(),
+        )
+        .await;
+
+    let error = result.expect_err("server error should fail the turn");
+    assert!(error.contains("model not loaded"));
+    assert_eq!(done(&handler)["type"], "error");
+}
+
+#[tokio::test]
+async fn keeps_usage_reported_before_a_failed_response() {
+    let server = MockServer::start().await;
+    let body = sse(&[
+        r#"{"type":"response.output_text.delta","delta":"Partial"}"#,
+        r#"{"type":"response.failed","response":{"error":{"message":"context window exceeded"},"usage":{"input_tokens":30,"output_tokens":12}}}"#,
+    ]);
+    Mock::given(method("POST"))
+        .and(path("/responses"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .mount(&server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let data_service = Mutex::new(DataService::new(dir.path().to_path_buf()).unwrap());
+    let handler = PersistingEventHandler::new(
+        &data_service,
+        None,
+        "llama3".into(),
+        RecordingEventHandler::new(),
+    );
+
+    let result = OpenAiService::new()
+        .invoke_stream(
+            request("Hi"),
+            &agent(server.uri(), AgentApiStyle::Responses),
+            &handler,
+        )
+        .await;
+
+    let error = result.expect_err("failed response should fail the turn");
+    assert!(error.contains("context window exceeded"));
+    assert_eq!(handler.usage().input_tokens, 30);
+    assert_eq!(handler.usage().output_tokens, 12);
+}
+
+#[test]
+fn sse_decoder_handles_split_chunks_and_multiline_data() {
+    let mut decoder = SseDecoder::new();
+
+    assert!(decoder.push(b"event: message\ndata: {\"a\":").is_empty());
+    assert_eq!(
+        decoder.push(b"1}\r\n\r\ndata: line one\n"),
+        vec!["{\"a\":1}"]
+    );
+    assert!(decoder.push(b": keep-alive\ndata: line two\n").is_empty());
+    assert_eq!(decoder.push(b"\n"), vec!["line one\nline two"]);
+    assert_eq!(decoder.push(b"data: tail").len(), 0);
+    assert_eq!(decoder.finish(), vec!["tail"]);
+}
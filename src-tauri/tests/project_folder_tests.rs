@@ -0,0 +1,206 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use vibeflow_lib::models::agent_rule::AgentRule;
+use vibeflow_lib::models::manifest::MovedFile;
+use vibeflow_lib::models::project::Project;
+use vibeflow_lib::services::project_folder_service::{
+    ProjectFolderService, LAYOUT_VERSION, MANIFEST_FILE,
+};
+use vibeflow_lib::services::rule_sync_service::{content_hash, RuleSyncService};
+
+fn project(root: &Path, rules: Vec<AgentRule>) -> Project {
+    Project {
+        rules,
+        ..common::project(&root.to_string_lossy())
+    }
+}
+
+fn linked_rule(name: &str, path: &str, content: &str) -> AgentRule {
+    AgentRule {
+        id: format!("rule-{name}"),
+        name: name.into(),
+        description: None,
+        content: content.into(),
+        enabled: true,
+        priority: 0,
+        scopes: Vec::new(),
+        tags: Vec::new(),
+        source_path: Some(path.into()),
+        synced_hash: Some(content_hash(content)),
+        created_at: 1,
+        updated_at: 1,
+    }
+}
+
+fn write(root: &Path, path: &str, content: &str) {
+    let path = root.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+#[test]
+fn scaffolds_a_versioned_folder_once() {
+    let root = tempfile::tempdir().unwrap();
+    let project = project(root.path(), Vec::new());
+    let service = ProjectFolderService::new();
+
+    let report = service.scaffold(&project).unwrap().unwrap();
+    assert_eq!(report.version, LAYOUT_VERSION);
+    assert_eq!(report.previous_version, None);
+    assert_eq!(
+        report.created,
+        [
+            ".vibeflow/rules",
+            ".vibeflow/tasks",
+            ".vibeflow/README.md",
+            ".vibeflow/ignore",
+            ".vibeflow/project.json",
+        ]
+    );
+    let manifest = ProjectFolderService::read_manifest(root.path())
+        .unwrap()
+        .unwrap();
+    assert_eq!(manifest.version, LAYOUT_VERSION);
+    assert_eq!(manifest.name, "demo");
+
+    write(root.path(), ".vibeflow/ignore", "/docs\n");
+    assert!(service.scaffold(&project).unwrap().is_none());
+    assert_eq!(
+        fs::read_to_string(root.path().join(".vibeflow/ignore")).unwrap(),
+        "/docs\n"
+    );
+}
+
+#[test]
+fn scaffold_leaves_legacy_files_for_repair() {
+    let root = tempfile::tempdir().unwrap();
+    write(root.path(), ".codex/style.md", "Keep it short.");
+    let mut project = project(
+        root.path(),
+        vec![linked_rule("style", ".codex/style.md", "Keep it short.")],
+    );
+    let service = ProjectFolderService::new();
+
+    let report = service.scaffold(&project).unwrap().unwrap();
+    assert!(report.moved.is_empty());
+    assert!(root.path().join(".codex/style.md").is_file());
+
+    let report = service.repair(&mut project).unwrap();
+    assert_eq!(report.previous_version, Some(LAYOUT_VERSION));
+    assert_eq!(
+        report.moved,
+        [MovedFile {
+            from: ".codex/style.md".into(),
+            to: ".vibeflow/rules/style.md".into(),
+        }]
+    );
+    assert_eq!(
+        project.rules[0].source_path.as_deref(),
+        Some(".vibeflow/rules/style.md")
+    );
+}
+
+#[test]
+fn repair_moves_legacy_rules_and_tasks() {
+    let root = tempfile::tempdir().unwrap();
+    write(root.path(), ".codex/style.md", "Keep it short.");
+    write(
+        root.path(),
+        ".codex/release.md",
+        "# Release\n\n## Main Steps\n\n### Tag\n\nTag the release.\n",
+    );
+    write(root.path(), ".codex/config.toml", "model = \"o3\"\n");
+    let mut project = project(
+        root.path(),
+        vec![
+            linked_rule("style", ".codex/style.md", "Keep it short."),
+            linked_rule(
+                "release",
+                ".codex/release.md",
+                "# Release\n\n## Main Steps\n\n### Tag\n\nTag the release.\n",
+            ),
+        ],
+    );
+
+    let report = ProjectFolderService::new().repair(&mut project).unwrap();
+    assert_eq!(
+        report.moved,
+        [
+            MovedFile {
+                from: ".codex/release.md".into(),
+                to: ".vibeflow/tasks/release.md".into(),
+            },
+            MovedFile {
+                from: ".codex/style.md".into(),
+                to: ".vibeflow/rules/style.md".into(),
+            },
+        ]
+    );
+    assert!(root.path().join(".codex/config.toml").is_file());
+    assert_eq!(project.rules.len(), 1);
+    assert_eq!(
+        project.rules[0].source_path.as_deref(),
+        Some(".vibeflow/rules/style.md")
+    );
+
+    let sync = RuleSyncService::new().sync(&mut project).unwrap();
+    assert!(sync.conflicts.is_empty() && sync.imported.is_empty());
+}
+
+#[test]
+fn repair_restores_missing_files_and_refuses_newer_layouts() {
+    let root = tempfile::tempdir().unwrap();
+    write(
+        root.path(),
+        MANIFEST_FILE,
+        r#"{ "version": 0, "name": "shared", "createdAt": 42 }"#,
+    );
+    let mut project = project(root.path(), Vec::new());
+    let service = ProjectFolderService::new();
+
+    let report = service.repair(&mut project).unwrap();
+    assert_eq!(report.previous_version, Some(0));
+    assert!(report.created.contains(&".vibeflow/README.md".to_string()));
+    assert!(report.moved.is_empty());
+    let manifest = ProjectFolderService::read_manifest(root.path())
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        (
+            manifest.version,
+            manifest.name.as_str(),
+            manifest.created_at
+        ),
+        (LAYOUT_VERSION, "shared", 42)
+    );
+
+    fs::remove_file(root.path().join(".vibeflow/README.md")).unwrap();
+    let report = service.repair(&mut project).unwrap();
+    assert_eq!(report.created, [".vibeflow/README.md"]);
+
+    write(
+        root.path(),
+        MANIFEST_FILE,
+        r#"{ "version": 99, "name": "shared", "createdAt": 42 }"#,
+    );
+    let error = service.repair(&mut project).unwrap_err();
+    assert!(error.contains("layout 99"));
+}
+
+#[test]
+fn rule_sync_uses_the_vibeflow_rules_folder_once_scaffolded() {
+    let root = tempfile::tempdir().unwrap();
+    let mut project = project(root.path(), Vec::new());
+    ProjectFolderService::new().scaffold(&project).unwrap();
+    project.rules.push(AgentRule {
+        source_path: None,
+        synced_hash: None,
+        ..linked_rule("Code Style", "", "Use tabs.")
+    });
+
+    let report = RuleSyncService::new().sync(&mut project).unwrap();
+    assert_eq!(report.exported, [".vibeflow/rules/code-style.md"]);
+}
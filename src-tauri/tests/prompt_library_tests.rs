@@ -0,0 +1,110 @@
+mod common;
+
+use vibeflow_lib::models::chat::ChatTurnStatus;
+use vibeflow_lib::models::prompt::{PromptQuery, PromptRecord};
+use vibeflow_lib::services::data_service::DataService;
+
+use common::data_service_with_project;
+
+fn record(service: &DataService, run_id: &str, project_id: Option<&str>, content: &str) {
+    service
+        .save_prompt(&PromptRecord::for_run(
+            run_id.into(),
+            project_id.map(str::to_string),
+            None,
+            None,
+            content.into(),
+        ))
+        .unwrap();
+}
+
+fn ids(prompts: Vec<PromptRecord>) -> Vec<String> {
+    prompts.into_iter().map(|prompt| prompt.id).collect()
+}
+
+#[test]
+fn records_prompts_with_their_outcome() {
+    let (_data_dir, service) = data_service_with_project(&common::project("/tmp/demo"));
+    record(&service, "run-1", Some("project-1"), "Add a login page");
+    record(&service, "run-2", None, "Explain the build");
+
+    service
+        .finish_prompt("run-1", ChatTurnStatus::Failed, Some("agent crashed"))
+        .unwrap();
+    let prompt = service.load_prompt("run-1").unwrap().unwrap();
+    assert_eq!(prompt.outcome, ChatTurnStatus::Failed);
+    assert_eq!(prompt.error.as_deref(), Some("agent crashed"));
+    assert_eq!(prompt.project_id.as_deref(), Some("project-1"));
+
+    assert_eq!(service.recover_interrupted_turns().unwrap(), 0);
+    let prompt = service.load_prompt("run-2").unwrap().unwrap();
+    assert_eq!(prompt.outcome, ChatTurnStatus::Interrupted);
+}
+
+#[test]
+fn searches_by_text_project_and_star() {
+    let (_data_dir, service) = data_service_with_project(&common::project("/tmp/demo"));
+    record(&service, "run-1", Some("project-1"), "Add a LOGIN page");
+    record(&service, "run-2", Some("project-1"), "Write tests");
+    record(&service, "run-3", None, "Fix the login redirect");
+
+    let login = PromptQuery {
+        text: Some("login".into()),
+        ..PromptQuery::default()
+    };
+    assert_eq!(
+        ids(service.search_prompts(&login).unwrap()),
+        ["run-3", "run-1"]
+    );
+
+    let in_project = PromptQuery {
+        project_id: Some("project-1".into()),
+        limit: Some(1),
+        ..PromptQuery::default()
+    };
+    assert_eq!(ids(service.search_prompts(&in_project).unwrap()), ["run-2"]);
+
+    service.set_prompt_starred("run-1", true).unwrap();
+    let starred = PromptQuery {
+        starred_only: true,
+        ..PromptQuery::default()
+    };
+    assert_eq!(ids(service.search_prompts(&starred).unwrap()), ["run-1"]);
+}
+
+#[test]
+fn pinned_prompts_form_a_tagged_library() {
+    let (_data_dir, service) = data_service_with_project(&common::project("/tmp/demo"));
+    record(
+        &service,
+        "run-1",
+        None,
+        "Review the diff for security issues",
+    );
+    record(&service, "run-2", None, "Write tests");
+
+    service
+        .set_prompt_pinned("run-1", Some("Security review"), &["review".into()])
+        .unwrap();
+    let prompt = service.load_prompt("run-1").unwrap().unwrap();
+    assert!(prompt.pinned);
+    assert_eq!(prompt.name.as_deref(), Some("Security review"));
+    assert_eq!(prompt.tags, ["review"]);
+
+    let library = PromptQuery {
+        pinned_only: true,
+        ..PromptQuery::default()
+    };
+    assert_eq!(ids(service.search_prompts(&library).unwrap()), ["run-1"]);
+    let by_name = PromptQuery {
+        text: Some("security review".into()),
+        tag: Some("review".into()),
+        ..PromptQuery::default()
+    };
+    assert_eq!(ids(service.search_prompts(&by_name).unwrap()), ["run-1"]);
+
+    service.set_prompt_pinned("run-1", None, &[]).unwrap();
+    assert!(service.search_prompts(&library).unwrap().is_empty());
+    let prompt = service.load_prompt("run-1").unwrap().unwrap();
+    assert!(prompt.name.is_none() && prompt.tags.is_empty());
+}
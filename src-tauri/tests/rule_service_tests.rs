@@ -0,0 +1,177 @@
+mod common;
+
+use vibeflow_lib::models::agent_rule::{AgentRule, RuleContext};
+use vibeflow_lib::models::chat::ChatRequest;
+use vibeflow_lib::models::project::Project;
+use vibeflow_lib::models::task::{Task, TaskStep};
+use vibeflow_lib::services::rule_service::RuleService;
+
+fn rule(id: &str, priority: i64, scopes: &[&str], tags: &[&str]) -> AgentRule {
+    AgentRule {
+        id: id.into(),
+        name: id.into(),
+        description: None,
+        content: format!("Follow {id}."),
+        enabled: true,
+        priority,
+        scopes: scopes.iter().map(|scope| scope.to_string()).collect(),
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        source_path: None,
+        synced_hash: None,
+        created_at: 1,
+        updated_at: 1,
+    }
+}
+
+fn project(rules: Vec<AgentRule>) -> Project {
+    Project {
+        rules,
+        tasks: vec![Task {
+            id: "task-1".into(),
+            project_id: "project-1".into(),
+            title: "Docs".into(),
+            steps: vec![TaskStep {
+                id: "step-1".into(),
+                title: "Write".into(),
+                content: "update the docs".into(),
+                created_at: 1,
+                updated_at: 1,
+                ..Default::default()
+            }],
+            created_at: 1,
+            updated_at: 1,
+            ..Default::default()
+        }],
+        ..common::project("/work/demo")
+    }
+}
+
+fn names(rules: Vec<&AgentRule>) -> Vec<&str> {
+    rules.into_iter().map(|rule| rule.name.as_str()).collect()
+}
+
+#[test]
+fn selects_enabled_rules_by_scope_and_tags_in_priority_order() {
+    let mut disabled = rule("disabled", 0, &[], &[]);
+    disabled.enabled = false;
+    let rules = vec![
+        rule("frontend", 2, &["src/app/**"], &[]),
+        rule("general", 1, &[], &[]),
+        rule("backend", 0, &["src-tauri"], &["rust"]),
+        disabled,
+    ];
+    let service = RuleService::new();
+
+    let at_root = RuleContext::default();
+    assert_eq!(
+        names(service.applicable_rules(&rules, &at_root)),
+        ["general"]
+    );
+
+    let in_backend = RuleContext {
+        paths: vec!["src-tauri/src/services".into()],
+        tags: Vec::new(),
+    };
+    assert_eq!(
+        names(service.applicable_rules(&rules, &in_backend)),
+        ["backend", "general"]
+    );
+
+    let in_frontend = RuleContext {
+        paths: vec!["src/app/components".into()],
+        tags: vec!["typescript".into()],
+    };
+    assert_eq!(
+        names(service.applicable_rules(&rules, &in_frontend)),
+        ["general", "frontend"]
+    );
+
+    let backend_other_tag = RuleContext {
+        paths: vec!["src-tauri".into()],
+        tags: vec!["docs".into()],
+    };
+    assert_eq!(
+        names(service.applicable_rules(&rules, &backend_other_tag)),
+        ["general"]
+    );
+}
+
+#[test]
+fn assembles_rules_ahead_of_the_prompt() {
+    let service = RuleService::new();
+    let rules = vec![rule("tests", 0, &[], &[])];
+    let request = ChatRequest {
+        content: "add a feature".into(),
+        working_directory: Some("/work/demo".into()),
+        ..ChatRequest::default()
+    };
+
+    let context = service.context_for(&project(Vec::new()), &request, &[]);
+    assert_eq!(context, RuleContext::default());
+    assert_eq!(
+        service.assemble_prompt(&rules, &context, &request.content),
+        "# Project rules\n\n## tests\n\nFollow tests.\n\n---\n\nadd a feature"
+    );
+    assert_eq!(
+        service.assemble_prompt(&[], &context, &request.content),
+        "add a feature"
+    );
+}
+
+#[test]
+fn task_tags_and_step_files_select_rules() {
+    let service = RuleService::new();
+    let rules = vec![
+        rule("general", 0, &[], &[]),
+        rule("backend", 1, &["src-tauri/**"], &[]),
+        rule("docs", 2, &["docs"], &[]),
+        rule("rust", 3, &[], &["rust"]),
+        rule("typescript", 4, &[], &["typescript"]),
+    ];
+    let mut project = project(rules.clone());
+    project.tasks[0].tags = vec!["rust".into()];
+    project.tasks[0].steps[0].if_changed = vec!["docs/**/*.md".into()];
+    let request = ChatRequest {
+        content: "update the docs".into(),
+        working_directory: Some(project.path.clone()),
+        task_id: Some("task-1".into()),
+        step_id: Some("step-1".into()),
+        ..ChatRequest::default()
+    };
+
+    let context = service.context_for(&project, &request, &["src-tauri/src/lib.rs".into()]);
+    assert_eq!(context.paths, ["docs", "src-tauri/src/lib.rs"]);
+    assert_eq!(context.tags, ["rust"]);
+    assert_eq!(
+        names(service.applicable_rules(&rules, &context)),
+        ["general", "backend", "docs", "rust"]
+    );
+}
+
+#[test]
+fn rule_fields_round_trip_through_the_database() {
+    let mut disabled = rule("backend", 3, &["src-tauri/**"], &["rust", "tauri"]);
+    disabled.enabled = false;
+    let (_data_dir, data_service) = common::data_service_with_project(&Project {
+        rules: vec![disabled, rule("general", 1, &[], &[])],
+        ..common::project("/tmp/demo")
+    });
+
+    let rules = data_service
+        .load_project("project-1")
+        .unwrap()
+        .unwrap()
+        .rules;
+    assert_eq!(
+        rules
+            .iter()
+            .map(|rule| rule.name.as_str())
+            .collect::<Vec<_>>(),
+        ["general", "backend"]
+    );
+    assert!(!rules[1].enabled);
+    assert_eq!(rules[1].priority, 3);
+    assert_eq!(rules[1].content, "Follow backend.");
+    assert_eq!(rules[1].scopes, ["src-tauri/**"]);
+    assert_eq!(rules[1].tags, ["rust", "tauri"]);
+}
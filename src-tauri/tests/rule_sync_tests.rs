@@ -0,0 +1,132 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use vibeflow_lib::models::agent_rule::{AgentRule, RuleConflictKind, RuleConflictResolution};
+use vibeflow_lib::models::project::Project;
+use vibeflow_lib::services::rule_sync_service::RuleSyncService;
+
+fn project(root: &Path, rules: Vec<AgentRule>) -> Project {
+    Project {
+        rules,
+        ..common::project(&root.to_string_lossy())
+    }
+}
+
+fn rule(name: &str, content: &str) -> AgentRule {
+    AgentRule {
+        id: format!("rule-{name}"),
+        name: name.into(),
+        description: None,
+        content: content.into(),
+        enabled: true,
+        priority: 0,
+        scopes: Vec::new(),
+        tags: Vec::new(),
+        source_path: None,
+        synced_hash: None,
+        created_at: 1,
+        updated_at: 1,
+    }
+}
+
+fn write(root: &Path, path: &str, content: &str) {
+    let path = root.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+#[test]
+fn imports_agents_files_and_rule_folder() {
+    let root = tempfile::tempdir().unwrap();
+    write(root.path(), "AGENTS.md", "Use tabs.");
+    write(root.path(), "src-tauri/AGENTS.md", "Run clippy.");
+    write(root.path(), ".codex/style.md", "Keep it short.");
+    write(root.path(), "docs/notes.md", "Not a rule.");
+    let mut project = project(root.path(), Vec::new());
+    let service = RuleSyncService::new();
+
+    let report = service.sync(&mut project).unwrap();
+
+    assert_eq!(
+        report.imported,
+        [".codex/style.md", "AGENTS.md", "src-tauri/AGENTS.md"]
+    );
+    let nested = project
+        .rules
+        .iter()
+        .find(|rule| rule.source_path.as_deref() == Some("src-tauri/AGENTS.md"))
+        .unwrap();
+    assert_eq!(nested.content, "Run clippy.");
+    assert_eq!(nested.scopes, ["src-tauri"]);
+    assert!(project.rules.iter().any(|rule| rule.name == "style"));
+
+    let report = service.sync(&mut project).unwrap();
+    assert!(report.imported.is_empty() && report.exported.is_empty());
+    assert!(report.updated.is_empty() && report.conflicts.is_empty());
+}
+
+#[test]
+fn writes_new_rules_and_follows_the_side_that_changed() {
+    let root = tempfile::tempdir().unwrap();
+    let mut project = project(root.path(), vec![rule("Code Style", "Be terse.")]);
+    let service = RuleSyncService::new();
+
+    let report = service.sync(&mut project).unwrap();
+    assert_eq!(report.exported, [".codex/code-style.md"]);
+    let file = root.path().join(".codex/code-style.md");
+    assert_eq!(fs::read_to_string(&file).unwrap(), "Be terse.");
+
+    fs::write(&file, "Be terse and precise.").unwrap();
+    let report = service.sync(&mut project).unwrap();
+    assert_eq!(report.updated, [".codex/code-style.md"]);
+    assert_eq!(project.rules[0].content, "Be terse and precise.");
+
+    project.rules[0].content = "Be precise.".into();
+    let report = service.sync(&mut project).unwrap();
+    assert_eq!(report.exported, [".codex/code-style.md"]);
+    assert_eq!(fs::read_to_string(&file).unwrap(), "Be precise.");
+}
+
+#[test]
+fn reports_conflicts_instead_of_overwriting() {
+    let root = tempfile::tempdir().unwrap();
+    let mut project = project(root.path(), vec![rule("style", "v1")]);
+    let service = RuleSyncService::new();
+    service.sync(&mut project).unwrap();
+
+    let file = root.path().join(".codex/style.md");
+    fs::write(&file, "edited on disk").unwrap();
+    project.rules[0].content = "edited in app".into();
+
+    let report = service.sync(&mut project).unwrap();
+    assert_eq!(report.conflicts.len(), 1);
+    assert_eq!(report.conflicts[0].kind, RuleConflictKind::BothChanged);
+    assert_eq!(fs::read_to_string(&file).unwrap(), "edited on disk");
+    assert_eq!(project.rules[0].content, "edited in app");
+
+    service
+        .resolve_conflict(&mut project, "rule-style", RuleConflictResolution::KeepDisk)
+        .unwrap();
+    assert_eq!(project.rules[0].content, "edited on disk");
+    assert!(service.sync(&mut project).unwrap().conflicts.is_empty());
+}
+
+#[test]
+fn file_deleted_on_disk_is_a_conflict() {
+    let root = tempfile::tempdir().unwrap();
+    let mut project = project(root.path(), vec![rule("style", "v1")]);
+    let service = RuleSyncService::new();
+    service.sync(&mut project).unwrap();
+    fs::remove_file(root.path().join(".codex/style.md")).unwrap();
+
+    let report = service.sync(&mut project).unwrap();
+    assert_eq!(report.conflicts[0].kind, RuleConflictKind::DeletedOnDisk);
+    assert_eq!(project.rules.len(), 1);
+
+    service
+        .resolve_conflict(&mut project, "rule-style", RuleConflictResolution::KeepDisk)
+        .unwrap();
+    assert!(project.rules.is_empty());
+}
@@ -0,0 +1,283 @@
+mod common;
+
+use vibeflow_lib::models::project::Project;
+use vibeflow_lib::models::schedule::{MissedRunPolicy, ScheduleDecision, TaskSchedule};
+use vibeflow_lib::models::task::{Task, TaskStatus};
+use vibeflow_lib::services::scheduler_service::{CronSchedule, CronTimeZone, SchedulerService};
+
+const MINUTE: i64 = 60_000;
+const HOUR: i64 = 60 * MINUTE;
+const DAY: i64 = 24 * HOUR;
+/// Monday 2024-01-01 00:00 UTC.
+const JAN_1: i64 = 1_704_067_200_000;
+
+fn next(cron: &str, after: i64, utc_offset_minutes: i32) -> Option<i64> {
+    CronSchedule::parse(cron)
+        .unwrap()
+        .next_after(after, &CronTimeZone::Fixed(utc_offset_minutes))
+}
+
+fn schedule(cron: &str, policy: MissedRunPolicy, next_run_at: Option<i64>) -> TaskSchedule {
+    TaskSchedule {
+        id: "schedule-1".into(),
+        project_id: "project-1".into(),
+        task_id: "task-1".into(),
+        cron: cron.into(),
+        enabled: true,
+        missed_run_policy: policy,
+        time_zone: None,
+        utc_offset_minutes: 0,
+        next_run_at,
+        last_run_at: None,
+        last_run_id: None,
+        created_at: 1,
+        updated_at: 1,
+    }
+}
+
+fn project() -> Project {
+    Project {
+        tasks: vec![Task {
+            id: "task-1".into(),
+            project_id: "project-1".into(),
+            title: "Nightly".into(),
+            created_at: 1,
+            updated_at: 1,
+            ..Default::default()
+        }],
+        ..common::project("/tmp/demo")
+    }
+}
+
+#[test]
+fn finds_the_next_matching_minute() {
+    assert_eq!(
+        next("*/15 * * * *", JAN_1 + 7 * MINUTE, 0),
+        Some(JAN_1 + 15 * MINUTE)
+    );
+    // An occurrence at `after` itself is not the next one.
+    assert_eq!(
+        next("*/15 * * * *", JAN_1 + 15 * MINUTE, 0),
+        Some(JAN_1 + 30 * MINUTE)
+    );
+    // From Saturday morning to Monday.
+    assert_eq!(
+        next("30 9 * * mon-fri", JAN_1 + 5 * DAY + 10 * HOUR, 0),
+        Some(JAN_1 + 7 * DAY + 9 * HOUR + 30 * MINUTE)
+    );
+    // Local midnight in UTC+2 is 22:00 UTC.
+    assert_eq!(next("@daily", JAN_1, 120), Some(JAN_1 + 22 * HOUR));
+    // Day of month or day of week, whichever comes first.
+    assert_eq!(next("0 0 13 * fri", JAN_1, 0), Some(JAN_1 + 4 * DAY));
+    assert_eq!(next("0 0 * * 7", JAN_1, 0), Some(JAN_1 + 6 * DAY));
+    // 2024-03-01 to 2028-02-29.
+    assert_eq!(
+        next("0 0 29 feb *", 1_709_251_200_000, 0),
+        Some(1_835_395_200_000)
+    );
+}
+
+#[test]
+fn named_time_zones_follow_daylight_saving_time() {
+    let service = SchedulerService::new();
+    let berlin = CronTimeZone::new(Some("Europe/Berlin"), 0).unwrap();
+
+    // 09:00 in Berlin is 08:00 UTC in January and 07:00 UTC in July.
+    assert_eq!(
+        service.preview("0 9 * * *", &berlin, JAN_1, 1).unwrap(),
+        vec![JAN_1 + 8 * HOUR]
+    );
+    assert_eq!(
+        service
+            .preview("0 9 * * *", &berlin, 1_719_792_000_000, 1)
+            .unwrap(),
+        vec![1_719_817_200_000]
+    );
+    // 02:30 does not exist on 2024-03-31 and runs at 03:30 CEST instead.
+    assert_eq!(
+        service
+            .preview("30 2 * * *", &berlin, 1_711_800_000_000, 2)
+            .unwrap(),
+        vec![1_711_848_600_000, 1_711_931_400_000]
+    );
+    // 02:30 happens twice on 2024-10-27 and runs only the first time.
+    assert_eq!(
+        service
+            .preview("30 2 * * *", &berlin, 1_729_944_000_000, 2)
+            .unwrap(),
+        vec![1_729_989_000_000, 1_730_079_000_000]
+    );
+
+    let mut unknown = schedule("@daily", MissedRunPolicy::Skip, None);
+    unknown.time_zone = Some("Mars/Olympus_Mons".into());
+    let error = service.reschedule(&mut unknown, JAN_1).unwrap_err();
+    assert!(error.contains("unknown time zone"), "{error}");
+}
+
+#[test]
+fn rejects_invalid_cron_expressions() {
+    let cases = [
+        ("* * *", "needs five fields"),
+        ("61 * * * *", "invalid minute '61'; expected 0-59"),
+        ("*/0 * * * *", "invalid step '0'"),
+        ("0 0 5-1 * *", "invalid range '5-1'"),
+        ("0 0 * * funday", "invalid day of week 'funday'"),
+        ("0 0 30 2 *", "never matches a date"),
+    ];
+    for (cron, message) in cases {
+        let error = CronSchedule::parse(cron).unwrap_err();
+        assert!(error.contains(message), "{cron}: {error}");
+    }
+}
+
+#[test]
+fn missed_runs_follow_the_schedule_policy() {
+    let service = SchedulerService::new();
+    let hourly = |policy, next_run_at| schedule("0 * * * *", policy, next_run_at);
+
+    // A new schedule waits for its first occurrence.
+    assert_eq!(
+        service
+            .plan(&hourly(MissedRunPolicy::Skip, None), JAN_1 + 10 * MINUTE)
+            .unwrap(),
+        ScheduleDecision {
+            run_for: None,
+            missed: 0,
+            next_run_at: Some(JAN_1 + HOUR),
+        }
+    );
+
+    // Due just now.
+    let on_time = ScheduleDecision {
+        run_for: Some(JAN_1 + HOUR),
+        missed: 0,
+        next_run_at: Some(JAN_1 + 2 * HOUR),
+    };
+    assert_eq!(
+        service
+            .plan(
+                &hourly(MissedRunPolicy::Skip, Some(JAN_1 + HOUR)),
+                JAN_1 + HOUR + MINUTE / 2
+            )
+            .unwrap(),
+        on_time
+    );
+
+    // The app was closed from 01:00 until 05:30.
+    let late = JAN_1 + 5 * HOUR + 30 * MINUTE;
+    assert_eq!(
+        service
+            .plan(&hourly(MissedRunPolicy::Skip, Some(JAN_1 + HOUR)), late)
+            .unwrap(),
+        ScheduleDecision {
+            run_for: None,
+            missed: 5,
+            next_run_at: Some(JAN_1 + 6 * HOUR),
+        }
+    );
+    assert_eq!(
+        service
+            .plan(&hourly(MissedRunPolicy::CatchUp, Some(JAN_1 + HOUR)), late)
+            .unwrap(),
+        ScheduleDecision {
+            run_for: Some(JAN_1 + 5 * HOUR),
+            missed: 4,
+            next_run_at: Some(JAN_1 + 6 * HOUR),
+        }
+    );
+
+    // Back in time for the latest occurrence, which runs either way.
+    let decision = service
+        .plan(
+            &hourly(MissedRunPolicy::Skip, Some(JAN_1 + HOUR)),
+            JAN_1 + 5 * HOUR + 2 * MINUTE,
+        )
+        .unwrap();
+    assert_eq!(decision.run_for, Some(JAN_1 + 5 * HOUR));
+    assert_eq!(decision.missed, 4);
+}
+
+#[test]
+fn due_runs_are_recorded_in_the_run_history() {
+    let (_data_dir, data_service) = common::data_service_with_project(&project());
+    let service = SchedulerService::new();
+
+    let mut nightly = schedule("0 2 * * *", MissedRunPolicy::CatchUp, None);
+    service.reschedule(&mut nightly, JAN_1).unwrap();
+    assert_eq!(nightly.next_run_at, Some(JAN_1 + 2 * HOUR));
+    data_service.save_task_schedule(&nightly).unwrap();
+
+    let mut orphan = schedule("@hourly", MissedRunPolicy::Skip, Some(JAN_1));
+    orphan.id = "schedule-2".into();
+    orphan.task_id = "deleted-task".into();
+    data_service.save_task_schedule(&orphan).unwrap();
+
+    let mut paused = schedule("@hourly", MissedRunPolicy::Skip, None);
+    paused.id = "schedule-3".into();
+    paused.enabled = false;
+    service.reschedule(&mut paused, JAN_1).unwrap();
+    assert_eq!(paused.next_run_at, None);
+    data_service.save_task_schedule(&paused).unwrap();
+
+    // Three nights later, with the app closed in between.
+    let now = JAN_1 + 2 * DAY + 9 * HOUR;
+    let events = service.due_runs(&data_service, now).unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].schedule_id, "schedule-1");
+    assert_eq!(events[0].scheduled_for, JAN_1 + 2 * DAY + 2 * HOUR);
+    assert_eq!(events[0].missed, 2);
+    assert!(service.due_runs(&data_service, now).unwrap().is_empty());
+
+    let schedules = data_service.load_task_schedules(Some("project-1")).unwrap();
+    let ids: Vec<_> = schedules
+        .iter()
+        .map(|schedule| schedule.id.as_str())
+        .collect();
+    assert_eq!(ids, ["schedule-1", "schedule-3"]);
+    assert_eq!(
+        schedules[0].last_run_id.as_deref(),
+        Some(events[0].task_run_id.as_str())
+    );
+    assert_eq!(schedules[0].next_run_at, Some(JAN_1 + 3 * DAY + 2 * HOUR));
+
+    let history = data_service.load_task_run_history("task-1", 10).unwrap();
+    assert_eq!(history.len(), 2);
+    assert!(history
+        .iter()
+        .all(|run| run.schedule_id.as_deref() == Some("schedule-1")));
+    let run = history
+        .iter()
+        .find(|run| run.id == events[0].task_run_id)
+        .unwrap();
+    assert_eq!(run.status, TaskStatus::Pending);
+    let missed = history
+        .iter()
+        .find(|run| run.status == TaskStatus::Skipped)
+        .unwrap();
+    assert!(missed
+        .status_reason
+        .as_deref()
+        .unwrap()
+        .contains("missed 2 scheduled runs that did not start within 5 minutes"));
+
+    // The UI takes due runs from the queue once.
+    service.queue(events.clone());
+    assert_eq!(service.take_queued(), events);
+    assert!(service.take_queued().is_empty());
+
+    // A scheduled run the app never got to start is not resumed but skipped
+    // on the next launch.
+    data_service.recover_interrupted_task_runs().unwrap();
+    let run = data_service
+        .load_task_run(&events[0].task_run_id)
+        .unwrap()
+        .unwrap();
+    assert_eq!(run.status, TaskStatus::Pending);
+    assert_eq!(data_service.skip_unstarted_scheduled_runs().unwrap(), 1);
+    let run = data_service
+        .load_task_run(&events[0].task_run_id)
+        .unwrap()
+        .unwrap();
+    assert_eq!(run.status, TaskStatus::Skipped);
+    assert!(run.status_reason.is_some());
+}
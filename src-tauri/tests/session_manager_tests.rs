@@ -0,0 +1,124 @@
+mod common;
+
+use futures::FutureExt;
+use vibeflow_lib::models::chat::ChatRequest;
+use vibeflow_lib::models::event_handler::RecordingEventHandler;
+use vibeflow_lib::models::session::SessionStatus;
+use vibeflow_lib::services::scripted_agent::ScriptedAgent;
+use vibeflow_lib::services::session_manager::SessionManager;
+
+use common::fixture;
+
+fn statuses(manager: &SessionManager) -> Vec<(String, SessionStatus)> {
+    manager
+        .list_sessions()
+        .into_iter()
+        .map(|session| (session.run_id, session.status))
+        .collect()
+}
+
+#[tokio::test]
+async fn queues_runs_beyond_the_concurrency_limit() {
+    let manager = SessionManager::new();
+    manager.set_max_concurrent(1);
+
+    let first = manager
+        .acquire("run-1", Some("project-a".into()), None, "codex".into())
+        .await
+        .unwrap();
+    let mut second =
+        Box::pin(manager.acquire("run-2", Some("project-b".into()), None, "codex".into()));
+    assert!((&mut second).now_or_never().is_none());
+
+    assert_eq!(
+        statuses(&manager),
+        vec![
+            ("run-1".to_string(), SessionStatus::Running),
+            ("run-2".to_string(), SessionStatus::Queued),
+        ]
+    );
+
+    drop(first);
+    let second = second.await.unwrap();
+    assert_eq!(second.run_id(), "run-2");
+    assert_eq!(
+        statuses(&manager),
+        vec![("run-2".to_string(), SessionStatus::Running)]
+    );
+
+    drop(second);
+    assert!(manager.list_sessions().is_empty());
+}
+
+#[tokio::test]
+async fn raising_the_limit_starts_queued_runs() {
+    let manager = SessionManager::new();
+    manager.set_max_concurrent(1);
+
+    let _first = manager
+        .acquire("run-1", None, None, "codex".into())
+        .await
+        .unwrap();
+    let mut second = Box::pin(manager.acquire("run-2", None, None, "codex".into()));
+    assert!((&mut second).now_or_never().is_none());
+
+    manager.set_max_concurrent(2);
+
+    let second = second.await.unwrap();
+    assert_eq!(second.run_id(), "run-2");
+    assert_eq!(manager.list_sessions().len(), 2);
+}
+
+#[tokio::test]
+async fn abandoned_queued_run_leaves_the_queue() {
+    let manager = SessionManager::new();
+    manager.set_max_concurrent(1);
+
+    let first = manager
+        .acquire("run-1", None, None, "codex".into())
+        .await
+        .unwrap();
+    let mut second = Box::pin(manager.acquire("run-2", None, None, "codex".into()));
+    assert!((&mut second).now_or_never().is_none());
+    drop(second);
+
+    assert_eq!(
+        statuses(&manager),
+        vec![("run-1".to_string(), SessionStatus::Running)]
+    );
+    drop(first);
+    assert!(manager.list_sessions().is_empty());
+}
+
+#[tokio::test]
+async fn session_handler_tracks_thread_and_current_item() {
+    let manager = SessionManager::new();
+    let _guard = manager
+        .acquire("run-1", Some("project-a".into()), None, "scripted".into())
+        .await
+        .unwrap();
+    let agent = ScriptedAgent::from_jsonl_file(fixture("successful_turn.jsonl")).unwrap();
+    let recorder = RecordingEventHandler::new();
+
+    agent
+        .invoke_stream(
+            ChatRequest {
+                content: "run the tests".into(),
+                run_id: Some("run-1".into()),
+                ..ChatRequest::default()
+            },
+            manager.handler(recorder.clone()),
+        )
+        .await
+        .unwrap();
+
+    let session = &manager.list_sessions()[0];
+    assert_eq!(session.project_id.as_deref(), Some("project-a"));
+    assert_eq!(session.thread_id.as_deref(), Some("thread-abc"));
+    assert_eq!(
+        session.current_item.as_deref(),
+        Some("assistant: All tests pass.")
+    );
+    assert!(session.elapsed_ms >= 0);
+    assert_eq!(recorder.events().len(), 5);
+}
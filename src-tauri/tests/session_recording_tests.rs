@@ -0,0 +1,57 @@
+mod common;
+
+use vibeflow_lib::models::event_handler::{RecordingEventHandler, RunEventKind};
+use vibeflow_lib::services::recording_service::RecordingService;
+use vibeflow_lib::services::scripted_agent::ScriptedAgent;
+
+use common::fixture;
+
+#[tokio::test]
+async fn recorded_session_replays_the_same_events() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let service = RecordingService::new(data_dir.path().to_path_buf());
+    let source = ScriptedAgent::from_jsonl_file(fixture("successful_turn.jsonl")).unwrap();
+
+    let mut recorder = service.start_recording().unwrap();
+    for event in source.events() {
+        recorder.record(event);
+    }
+    drop(recorder);
+
+    let recordings = service.list_recordings().unwrap();
+    assert_eq!(recordings.len(), 1);
+    assert_eq!(recordings[0].thread_id.as_deref(), Some("thread-abc"));
+    assert_eq!(recordings[0].event_count, source.events().len());
+
+    let original = RecordingEventHandler::new();
+    source.replay("run-1", original.clone()).await.unwrap();
+
+    let replayed = RecordingEventHandler::new();
+    service
+        .load_recording(&recordings[0].id)
+        .unwrap()
+        .replay("run-2", replayed.clone())
+        .await
+        .unwrap();
+
+    let payloads = |handler: &RecordingEventHandler| {
+        handler
+            .events()
+            .into_iter()
+            .map(|event| (event.kind, event.payload))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(payloads(&original), payloads(&replayed));
+    assert_eq!(replayed.payloads(RunEventKind::Done).len(), 1);
+}
+
+#[test]
+fn rejects_recording_ids_outside_the_recordings_folder() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let service = RecordingService::new(data_dir.path().to_path_buf());
+
+    let error = service.load_recording("../projects.db").err().unwrap();
+
+    assert_eq!(error, "invalid recording id: ../projects.db");
+    assert!(service.list_recordings().unwrap().is_empty());
+}
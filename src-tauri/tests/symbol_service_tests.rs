@@ -0,0 +1,211 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use vibeflow_lib::models::symbol::{CodeSymbol, SymbolKind};
+use vibeflow_lib::services::data_service::DataService;
+use vibeflow_lib::services::symbol_service::SymbolService;
+
+fn write(root: &Path, path: &str, content: &str) {
+    let path = root.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+fn data_service(root: &Path) -> (tempfile::TempDir, DataService) {
+    common::data_service_with_project(&common::project(&root.to_string_lossy()))
+}
+
+fn outline(symbols: &[CodeSymbol]) -> Vec<(SymbolKind, &str, Option<&str>)> {
+    symbols
+        .iter()
+        .map(|symbol| {
+            (
+                symbol.kind,
+                symbol.name.as_str(),
+                symbol.container.as_deref(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn extracts_rust_definitions() {
+    let source = "pub struct Parser<T> {\n    items: Vec<T>,\n}\n\nimpl<T> Parser<T> {\n    pub fn parse(&self) -> usize {\n        fn helper() {}\n        0\n    }\n}\n\npub trait Visit {\n    fn visit(&self);\n}\n\nconst LIMIT: usize = 3;\n\nmacro_rules! check { () => {} }\n\nfn main() {}\n";
+    let symbols = SymbolService::new().extract("src/lib.rs", source).unwrap();
+
+    assert_eq!(
+        outline(&symbols),
+        [
+            (SymbolKind::Struct, "Parser", None),
+            (SymbolKind::Method, "parse", Some("Parser")),
+            (SymbolKind::Trait, "Visit", None),
+            (SymbolKind::Method, "visit", Some("Visit")),
+            (SymbolKind::Constant, "LIMIT", None),
+            (SymbolKind::Macro, "check", None),
+            (SymbolKind::Function, "main", None),
+        ]
+    );
+    assert_eq!((symbols[1].start_line, symbols[1].end_line), (6, 9));
+    assert_eq!(symbols[1].signature, "pub fn parse(&self) -> usize {");
+    assert_eq!(symbols[0].language, "rust");
+}
+
+#[test]
+fn extracts_typescript_and_python_definitions() {
+    let typescript = "export interface Task { id: string }\n\nexport type Id = string;\n\nexport class TaskService {\n  load(id: Id) { return id; }\n}\n\nexport const formatTask = (task: Task) => task.id;\n\nfunction helper() {}\n";
+    let symbols = SymbolService::new()
+        .extract("src/app/task.service.ts", typescript)
+        .unwrap();
+    assert_eq!(
+        outline(&symbols),
+        [
+            (SymbolKind::Interface, "Task", None),
+            (SymbolKind::Type, "Id", None),
+            (SymbolKind::Class, "TaskService", None),
+            (SymbolKind::Method, "load", Some("TaskService")),
+            (SymbolKind::Function, "formatTask", None),
+            (SymbolKind::Function, "helper", None),
+        ]
+    );
+
+    let python = "class Runner:\n    @property\n    def name(self):\n        return 'runner'\n\ndef run(runner):\n    def inner():\n        pass\n    return runner\n";
+    let symbols = SymbolService::new()
+        .extract("tools/run.py", python)
+        .unwrap();
+    assert_eq!(
+        outline(&symbols),
+        [
+            (SymbolKind::Class, "Runner", None),
+            (SymbolKind::Method, "name", Some("Runner")),
+            (SymbolKind::Function, "run", None),
+        ]
+    );
+
+    assert!(SymbolService::new()
+        .extract("README.md", "# fn main() {}")
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn refreshes_only_changed_files() {
+    let root = tempfile::tempdir().unwrap();
+    let (_data_dir, service) = data_service(root.path());
+    write(root.path(), ".gitignore", "target/\n");
+    write(root.path(), "src/lib.rs", "pub fn build() {}\n");
+    write(root.path(), "src/util.rs", "pub fn helper() {}\n");
+    write(root.path(), "target/gen.rs", "pub fn generated() {}\n");
+    write(root.path(), "notes.txt", "fn not_code() {}\n");
+
+    let symbols = SymbolService::new();
+    let report = symbols.refresh(&service, "project-1", root.path()).unwrap();
+    assert_eq!(report.indexed, ["src/lib.rs", "src/util.rs"]);
+    assert_eq!(report.symbols, 2);
+
+    let report = symbols.refresh(&service, "project-1", root.path()).unwrap();
+    assert!(report.indexed.is_empty() && report.removed.is_empty());
+
+    write(
+        root.path(),
+        "src/lib.rs",
+        "pub fn build() {}\npub fn build_all() {}\n",
+    );
+    fs::remove_file(root.path().join("src/util.rs")).unwrap();
+    let report = symbols.refresh(&service, "project-1", root.path()).unwrap();
+    assert_eq!(report.indexed, ["src/lib.rs"]);
+    assert_eq!(report.removed, ["src/util.rs"]);
+    assert!(service
+        .search_symbols("project-1", "helper", 10)
+        .unwrap()
+        .is_empty());
+
+    write(root.path(), "src/util.rs", "pub fn helper() {}\n");
+    let report = symbols
+        .refresh_paths(
+            &service,
+            "project-1",
+            root.path(),
+            &["src/util.rs".into(), "src/missing.rs".into()],
+        )
+        .unwrap();
+    assert_eq!(report.indexed, ["src/util.rs"]);
+    assert_eq!(
+        service
+            .search_symbols("project-1", "helper", 10)
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn searches_ranked_by_match_quality() {
+    let root = tempfile::tempdir().unwrap();
+    let (_data_dir, service) = data_service(root.path());
+    write(
+        root.path(),
+        "src/lib.rs",
+        "fn rebuild() {}\nfn build_all() {}\nfn build() {}\nfn unrelated() {}\n",
+    );
+    SymbolService::new()
+        .refresh(&service, "project-1", root.path())
+        .unwrap();
+
+    let names = service
+        .search_symbols("project-1", "Build", 10)
+        .unwrap()
+        .into_iter()
+        .map(|symbol| symbol.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["build", "build_all", "rebuild"]);
+    assert_eq!(
+        service
+            .search_symbols("project-1", "build", 1)
+            .unwrap()
+            .len(),
+        1
+    );
+    assert!(service
+        .search_symbols("project-1", "b_i", 10)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn builds_symbol_context_with_references() {
+    let root = tempfile::tempdir().unwrap();
+    let (_data_dir, service) = data_service(root.path());
+    write(
+        root.path(),
+        "src/config.rs",
+        "use std::fs;\n\npub fn load_config() -> String {\n    String::new()\n}\n\nfn other() {}\n",
+    );
+    write(
+        root.path(),
+        "src/main.rs",
+        "fn main() {\n    let config = load_config();\n    let reload_config = 1;\n}\n",
+    );
+    let symbols = SymbolService::new();
+    symbols.refresh(&service, "project-1", root.path()).unwrap();
+
+    let contexts = symbols
+        .symbol_context(&service, "project-1", root.path(), "load_config", None, 1)
+        .unwrap();
+    assert_eq!(contexts.len(), 1);
+    let context = &contexts[0];
+    assert_eq!((context.start_line, context.end_line), (2, 6));
+    assert_eq!(
+        context.code,
+        "\npub fn load_config() -> String {\n    String::new()\n}\n"
+    );
+    assert_eq!(context.references.len(), 1);
+    assert_eq!(context.references[0].path, "src/main.rs");
+    assert_eq!(context.references[0].line, 2);
+
+    assert!(symbols
+        .symbol_context(&service, "project-1", root.path(), "missing", None, 1)
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,220 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use vibeflow_lib::models::project::Project;
+use vibeflow_lib::models::task::{
+    StepRunWhen, Task, TaskParameter, TaskSandbox, TaskStatus, TaskStep, TaskStepType,
+};
+use vibeflow_lib::models::task_file::TaskFile;
+use vibeflow_lib::services::project_folder_service::ProjectFolderService;
+use vibeflow_lib::services::task_file_service::{
+    TaskFileService, TASK_FILE_VERSION, TASK_SCHEMA_FILE,
+};
+
+fn project(root: &Path, tasks: Vec<Task>) -> Project {
+    Project {
+        tasks,
+        ..common::project(&root.to_string_lossy())
+    }
+}
+
+fn step(id: &str, title: &str, step_type: TaskStepType) -> TaskStep {
+    TaskStep {
+        id: id.into(),
+        title: title.into(),
+        content: format!("Do {title}."),
+        status: TaskStatus::Completed,
+        created_at: 1,
+        updated_at: 1,
+        r#type: step_type,
+        ..Default::default()
+    }
+}
+
+fn task(id: &str, title: &str) -> Task {
+    Task {
+        id: id.into(),
+        project_id: "project-1".into(),
+        title: title.into(),
+        description: "Ship it.\nCarefully.".into(),
+        steps: vec![step("build", "Build", TaskStepType::Normal)],
+        presteps: vec![step("fetch", "Fetch", TaskStepType::Pre)],
+        parameters: vec![TaskParameter {
+            name: "version".into(),
+            value: "1.0".into(),
+        }],
+        status: TaskStatus::InProgress,
+        created_at: 1,
+        updated_at: 1,
+        ..Default::default()
+    }
+}
+
+fn write(root: &Path, path: &str, content: &str) {
+    let path = root.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+#[test]
+fn enabling_writes_tasks_and_merges_existing_files() {
+    let root = tempfile::tempdir().unwrap();
+    write(
+        root.path(),
+        ".vibeflow/tasks/lint.yaml",
+        "version: 1\ntitle: Lint\nsteps:\n  - title: Clippy\n    content: cargo clippy\n",
+    );
+    let mut project = project(root.path(), vec![task("task-1", "Release Build")]);
+    let service = TaskFileService::new();
+
+    let report = service.set_enabled(&mut project, true).unwrap();
+    assert_eq!(report.updated, [".vibeflow/tasks/lint.yaml"]);
+    assert_eq!(report.written, [".vibeflow/tasks/release-build.yaml"]);
+    assert!(TaskFileService::is_enabled(root.path()));
+    assert!(
+        ProjectFolderService::read_manifest(root.path())
+            .unwrap()
+            .unwrap()
+            .store_in_repo
+    );
+
+    let lint = project.tasks.iter().find(|task| task.id == "lint").unwrap();
+    assert_eq!(lint.steps[0].id, "lint-main-1");
+    assert!(matches!(lint.steps[0].r#type, TaskStepType::Normal));
+
+    let written =
+        fs::read_to_string(root.path().join(".vibeflow/tasks/release-build.yaml")).unwrap();
+    assert!(!written.contains("status"));
+    let parsed = TaskFileService::parse(".vibeflow/tasks/release-build.yaml", &written).unwrap();
+    assert_eq!(parsed.title, "Release Build");
+    assert_eq!(parsed.description, "Ship it.\nCarefully.");
+    assert_eq!(parsed.presteps[0].id, "fetch");
+}
+
+#[test]
+fn import_takes_file_edits_and_keeps_run_state() {
+    let root = tempfile::tempdir().unwrap();
+    let mut project = project(root.path(), vec![task("task-1", "Release")]);
+    let service = TaskFileService::new();
+    service.set_enabled(&mut project, true).unwrap();
+
+    let path = root.path().join(".vibeflow/tasks/release.yaml");
+    let edited = fs::read_to_string(&path)
+        .unwrap()
+        .replace("title: Release", "title: Release v2")
+        .replace("Do Build.", "Do Build twice.");
+    fs::write(&path, edited).unwrap();
+
+    let report = service.import(&mut project).unwrap();
+    assert_eq!(report.updated, [".vibeflow/tasks/release.yaml"]);
+    let task = &project.tasks[0];
+    assert_eq!(task.title, "Release v2");
+    assert_eq!(task.steps[0].content, "Do Build twice.");
+    assert!(matches!(task.steps[0].status, TaskStatus::Completed));
+    assert!(matches!(task.presteps[0].status, TaskStatus::Completed));
+    assert_eq!(task.presteps[0].updated_at, 1);
+
+    assert!(!service.import(&mut project).unwrap().changed_project());
+    assert!(service.export(&project, &[]).unwrap().written.is_empty());
+}
+
+#[test]
+fn removed_files_and_tasks_propagate_unless_a_file_is_broken() {
+    let root = tempfile::tempdir().unwrap();
+    let mut project = project(
+        root.path(),
+        vec![task("task-1", "Release"), task("task-2", "Deploy")],
+    );
+    let service = TaskFileService::new();
+    service.set_enabled(&mut project, true).unwrap();
+
+    write(root.path(), ".vibeflow/tasks/broken.yaml", "title: [");
+    fs::remove_file(root.path().join(".vibeflow/tasks/deploy.yaml")).unwrap();
+    let report = service.import(&mut project).unwrap();
+    assert_eq!(report.errors.len(), 1);
+    assert!(report.removed.is_empty());
+    assert_eq!(project.tasks.len(), 2);
+
+    fs::remove_file(root.path().join(".vibeflow/tasks/broken.yaml")).unwrap();
+    let report = service.import(&mut project).unwrap();
+    assert_eq!(report.removed, ["task-2"]);
+    assert_eq!(project.tasks.len(), 1);
+
+    project.tasks.clear();
+    let report = service.export(&project, &["task-1".to_string()]).unwrap();
+    assert_eq!(report.deleted, [".vibeflow/tasks/release.yaml"]);
+    assert!(!root.path().join(".vibeflow/tasks/release.yaml").exists());
+}
+
+#[test]
+fn refuses_newer_task_files() {
+    let error = TaskFileService::parse(".vibeflow/tasks/next.yaml", "version: 9\ntitle: Next\n")
+        .unwrap_err();
+    assert!(error.contains("version 9"));
+    assert!(TaskFileService::is_task_file(".vibeflow/tasks/next.yml"));
+    assert!(!TaskFileService::is_task_file(
+        ".vibeflow/tasks/notes/next.yaml"
+    ));
+    assert!(!TaskFileService::is_task_file(".vibeflow/rules/next.yaml"));
+}
+
+#[test]
+fn round_trips_step_options_through_yaml_and_json() {
+    let mut original = task("task-1", "Release");
+    original.steps[0].model = Some("o3".into());
+    original.steps[0].sandbox = Some(TaskSandbox::WorkspaceWrite);
+    original.steps[0].verify = vec!["cargo test".into()];
+    original.steps[0].depends_on = vec!["fetch".into()];
+    original.steps[0].run_when = StepRunWhen::Always;
+    original.steps[0].if_changed = vec!["src/**".into()];
+
+    for path in [
+        ".vibeflow/tasks/release.yaml",
+        ".vibeflow/tasks/release.json",
+    ] {
+        let content = TaskFileService::render(&original, path).unwrap();
+        let file = TaskFileService::parse(path, &content).unwrap();
+        assert_eq!(file.version, TASK_FILE_VERSION);
+        assert_eq!(file, TaskFile::from(&original));
+
+        let task = file.to_task("project-1", 5);
+        assert!(matches!(task.status, TaskStatus::Pending));
+        assert_eq!(task.steps[0].sandbox, Some(TaskSandbox::WorkspaceWrite));
+        assert_eq!(task.steps[0].verify, ["cargo test"]);
+        assert_eq!(task.steps[0].depends_on, ["fetch"]);
+        assert_eq!(task.steps[0].run_when, StepRunWhen::Always);
+        assert_eq!(task.steps[0].if_changed, ["src/**"]);
+        assert!(matches!(task.presteps[0].r#type, TaskStepType::Pre));
+    }
+
+    let yaml = TaskFileService::render(&original, ".vibeflow/tasks/release.yaml").unwrap();
+    assert!(yaml.starts_with("# yaml-language-server: $schema=../task.schema.json\n"));
+    assert!(yaml.contains("sandbox: workspace-write"));
+    assert!(yaml.contains("dependsOn:"));
+    assert!(yaml.contains("runWhen: always"));
+    assert_eq!(TaskFile::from(&task("task-2", "Plain")).version, 1);
+}
+
+#[test]
+fn writes_a_schema_for_editors() {
+    let root = tempfile::tempdir().unwrap();
+    let mut project = project(root.path(), vec![task("task-1", "Release")]);
+    TaskFileService::new()
+        .set_enabled(&mut project, true)
+        .unwrap();
+
+    let schema: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(root.path().join(TASK_SCHEMA_FILE)).unwrap())
+            .unwrap();
+    assert_eq!(schema["title"], "VibeFlow task");
+    assert!(schema["required"]
+        .as_array()
+        .unwrap()
+        .contains(&"title".into()));
+    let step = serde_json::to_string(&schema["$defs"]["TaskFileStep"]).unwrap();
+    for property in ["model", "sandbox", "verify", "dependsOn"] {
+        assert!(step.contains(property), "{property} missing from {step}");
+    }
+}
@@ -0,0 +1,300 @@
+mod common;
+
+use std::collections::HashMap;
+
+use vibeflow_lib::models::project::Project;
+use vibeflow_lib::models::task::{StepRunWhen, Task, TaskRun, TaskRunStep, TaskStatus, TaskStep};
+use vibeflow_lib::services::data_service::DataService;
+use vibeflow_lib::services::task_graph_service::TaskGraphService;
+
+fn step(id: &str, depends_on: &[&str]) -> TaskStep {
+    TaskStep {
+        id: id.into(),
+        title: id.into(),
+        content: format!("Do {id}."),
+        created_at: 1,
+        updated_at: 1,
+        depends_on: depends_on.iter().map(|id| id.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+fn task(steps: Vec<TaskStep>) -> Task {
+    Task {
+        id: "task-1".into(),
+        project_id: "project-1".into(),
+        title: "Release".into(),
+        steps,
+        created_at: 1,
+        updated_at: 1,
+        ..Default::default()
+    }
+}
+
+fn statuses(entries: &[(&str, TaskStatus)]) -> HashMap<String, TaskStatus> {
+    entries
+        .iter()
+        .map(|(id, status)| (id.to_string(), status.clone()))
+        .collect()
+}
+
+#[test]
+fn steps_without_dependencies_run_in_order() {
+    let service = TaskGraphService::new();
+    let graph = service
+        .build(&task(vec![step("a", &[]), step("b", &[]), step("c", &[])]))
+        .unwrap();
+    assert!(!graph.declares_dependencies);
+    assert_eq!(graph.nodes[2].depends_on, ["b"]);
+
+    let advance = service.advance(&graph, &HashMap::new(), None).unwrap();
+    assert_eq!(advance.ready, ["a"]);
+
+    let done = statuses(&[("a", TaskStatus::Failed)]);
+    let advance = service.advance(&graph, &done, None).unwrap();
+    assert!(advance.ready.is_empty());
+    assert_eq!(advance.skipped, ["b", "c"]);
+}
+
+#[test]
+fn independent_branches_are_ready_together() {
+    let service = TaskGraphService::new();
+    let graph = service
+        .build(&task(vec![
+            step("merge", &["api", "ui"]),
+            step("api", &[]),
+            step("ui", &[]),
+        ]))
+        .unwrap();
+    assert!(graph.declares_dependencies);
+    let order: Vec<_> = graph
+        .nodes
+        .iter()
+        .map(|node| node.step_id.as_str())
+        .collect();
+    assert_eq!(order, ["api", "ui", "merge"]);
+
+    let advance = service.advance(&graph, &HashMap::new(), None).unwrap();
+    assert_eq!(advance.ready, ["api", "ui"]);
+
+    let running = statuses(&[
+        ("api", TaskStatus::Completed),
+        ("ui", TaskStatus::InProgress),
+    ]);
+    assert_eq!(
+        service.advance(&graph, &running, None).unwrap(),
+        Default::default()
+    );
+
+    let done = statuses(&[
+        ("api", TaskStatus::Completed),
+        ("ui", TaskStatus::Completed),
+    ]);
+    assert_eq!(
+        service.advance(&graph, &done, None).unwrap().ready,
+        ["merge"]
+    );
+}
+
+#[test]
+fn conditions_decide_which_steps_run() {
+    let service = TaskGraphService::new();
+    let mut rollback = step("rollback", &["deploy"]);
+    rollback.run_when = StepRunWhen::OnFailure;
+    let mut report = step("report", &["deploy"]);
+    report.run_when = StepRunWhen::Always;
+    let mut docs = step("docs", &["deploy"]);
+    docs.run_when = StepRunWhen::Always;
+    docs.if_changed = vec!["docs/**".into()];
+    let graph = service
+        .build(&task(vec![step("deploy", &[]), rollback, report, docs]))
+        .unwrap();
+
+    let failed = statuses(&[("deploy", TaskStatus::Failed)]);
+    let changed = ["src/main.rs".to_string()];
+    let advance = service.advance(&graph, &failed, Some(&changed)).unwrap();
+    assert_eq!(advance.ready, ["rollback", "report"]);
+    assert_eq!(advance.skipped, ["docs"]);
+
+    let completed = statuses(&[("deploy", TaskStatus::Completed)]);
+    let changed = ["docs/guide.md".to_string()];
+    let advance = service.advance(&graph, &completed, Some(&changed)).unwrap();
+    assert_eq!(advance.ready, ["report", "docs"]);
+    assert_eq!(advance.skipped, ["rollback"]);
+
+    // Without a file watcher the changes are unknown, so the step runs.
+    let advance = service.advance(&graph, &completed, None).unwrap();
+    assert_eq!(advance.ready, ["report", "docs"]);
+}
+
+#[test]
+fn rejects_invalid_dependencies() {
+    let service = TaskGraphService::new();
+    let cases = [
+        (vec![step("a", &["a"])], "step \"a\" depends on itself"),
+        (
+            vec![step("a", &["missing"])],
+            "step \"a\" depends on unknown step \"missing\"",
+        ),
+        (
+            vec![step("a", &["b"]), step("b", &["c"]), step("c", &["a"])],
+            "steps \"a\", \"b\", \"c\" depend on each other",
+        ),
+    ];
+    for (steps, message) in cases {
+        assert_eq!(service.build(&task(steps)).unwrap_err(), message);
+    }
+
+    let mut with_prestep = task(vec![step("a", &["setup"])]);
+    with_prestep.presteps = vec![step("setup", &[])];
+    assert!(service
+        .build(&with_prestep)
+        .unwrap_err()
+        .contains("only main steps can be dependencies"));
+
+    let mut bad_glob = step("a", &[]);
+    bad_glob.if_changed = vec!["src/[".into()];
+    assert!(service
+        .build(&task(vec![bad_glob]))
+        .unwrap_err()
+        .contains("invalid ifChanged glob"));
+}
+
+#[test]
+fn stores_conditions_and_run_step_statuses() {
+    let mut docs = step("docs", &["build"]);
+    docs.run_when = StepRunWhen::Always;
+    docs.if_changed = vec!["docs/**".into()];
+    let (_data_dir, data_service) = common::data_service_with_project(&Project {
+        tasks: vec![task(vec![step("build", &[]), docs])],
+        ..common::project("/tmp/demo")
+    });
+
+    let loaded = data_service.load_project("project-1").unwrap().unwrap();
+    let docs = &loaded.tasks[0].steps[1];
+    assert_eq!(docs.run_when, StepRunWhen::Always);
+    assert_eq!(docs.if_changed, ["docs/**"]);
+    assert_eq!(docs.depends_on, ["build"]);
+
+    for status in [TaskStatus::InProgress, TaskStatus::Skipped] {
+        data_service
+            .save_task_run_step(&TaskRunStep::new(
+                "run-1".into(),
+                "task-1".into(),
+                "docs".into(),
+                status,
+                None,
+            ))
+            .unwrap();
+    }
+    let steps = data_service.load_task_run_steps("run-1").unwrap();
+    assert_eq!(steps.len(), 1);
+    assert_eq!(steps[0].status, TaskStatus::Skipped);
+    assert!(data_service
+        .load_task_run_steps("run-2")
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn restarts_from_failed_or_chosen_steps() {
+    let service = TaskGraphService::new();
+    let graph = service
+        .build(&task(vec![
+            step("api", &[]),
+            step("ui", &[]),
+            step("merge", &["api", "ui"]),
+            step("docs", &[]),
+        ]))
+        .unwrap();
+    let previous = statuses(&[
+        ("api", TaskStatus::Completed),
+        ("ui", TaskStatus::Failed),
+        ("merge", TaskStatus::Skipped),
+        ("docs", TaskStatus::Completed),
+    ]);
+
+    let kept = service.restart(&graph, &previous, None).unwrap();
+    assert_eq!(
+        kept,
+        statuses(&[
+            ("api", TaskStatus::Completed),
+            ("docs", TaskStatus::Completed)
+        ])
+    );
+    assert_eq!(service.advance(&graph, &kept, None).unwrap().ready, ["ui"]);
+
+    // Other branches keep their status, failed or not.
+    let kept = service.restart(&graph, &previous, Some("api")).unwrap();
+    assert_eq!(
+        kept,
+        statuses(&[("ui", TaskStatus::Failed), ("docs", TaskStatus::Completed)])
+    );
+
+    assert_eq!(
+        service
+            .restart(&graph, &previous, Some("nope"))
+            .unwrap_err(),
+        "task has no main step \"nope\""
+    );
+}
+
+#[test]
+fn interrupted_runs_are_paused_for_resuming() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let data_service = DataService::new(data_dir.path().to_path_buf()).unwrap();
+    for (id, started_at, status) in [
+        ("run-1", 1, TaskStatus::Failed),
+        ("run-2", 2, TaskStatus::InProgress),
+    ] {
+        data_service
+            .save_task_run(&TaskRun {
+                id: id.into(),
+                project_id: "project-1".into(),
+                task_id: "task-1".into(),
+                status,
+                status_reason: None,
+                chat_thread_id: Some("thread-1".into()),
+                schedule_id: None,
+                started_at,
+                updated_at: started_at,
+            })
+            .unwrap();
+    }
+    for (step_id, status) in [
+        ("build", TaskStatus::Completed),
+        ("test", TaskStatus::InProgress),
+    ] {
+        data_service
+            .save_task_run_step(&TaskRunStep::new(
+                "run-2".into(),
+                "task-1".into(),
+                step_id.into(),
+                status,
+                None,
+            ))
+            .unwrap();
+    }
+
+    assert_eq!(data_service.recover_interrupted_task_runs().unwrap(), 1);
+    let latest = data_service
+        .load_latest_task_run("task-1")
+        .unwrap()
+        .unwrap();
+    assert_eq!(latest.id, "run-2");
+    assert_eq!(latest.status, TaskStatus::Paused);
+    assert!(latest.status_reason.is_some());
+    assert_eq!(latest.chat_thread_id.as_deref(), Some("thread-1"));
+
+    let steps = data_service.load_task_run_steps("run-2").unwrap();
+    let test = steps.iter().find(|step| step.step_id == "test").unwrap();
+    assert_eq!(test.status, TaskStatus::Pending);
+    assert_eq!(
+        data_service.load_task_run("run-1").unwrap().unwrap().status,
+        TaskStatus::Failed
+    );
+    assert!(data_service
+        .load_latest_task_run("task-2")
+        .unwrap()
+        .is_none());
+}
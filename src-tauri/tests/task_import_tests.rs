@@ -0,0 +1,126 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use vibeflow_lib::models::task::{TaskStatus, TaskStepType};
+use vibeflow_lib::models::task_import::TaskImportKind;
+use vibeflow_lib::services::task_import_service::TaskImportService;
+
+/// What the frontend's `TaskService.exportTask` writes.
+const EXPORTED: &str = "# Release\n\nCut a release.\n\n## Pre Steps\n\n_None_\n\n## Main Steps\n\n### 1. Bump\n\nBump the version.\n\n```sh\n# not a heading\ncargo set-version\n```\n\n### 2. Tag\n\n_No content_\n\n## Post Steps\n\n### 1. Announce\n\n- post in chat\n- update the changelog\n";
+
+fn write(root: &Path, path: &str, content: &str) {
+    let path = root.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+#[test]
+fn parses_exported_tasks() {
+    let task = TaskImportService::new().parse("project-1", ".vibeflow/tasks/release.md", EXPORTED);
+
+    assert_eq!(task.title, "Release");
+    assert_eq!(task.description, "Cut a release.");
+    assert!(task.presteps.is_empty());
+    assert_eq!(
+        task.steps
+            .iter()
+            .map(|step| step.title.as_str())
+            .collect::<Vec<_>>(),
+        ["Bump", "Tag"]
+    );
+    assert!(task.steps[0]
+        .content
+        .contains("# not a heading\ncargo set-version"));
+    assert_eq!(task.steps[1].content, "");
+    assert!(matches!(task.poststeps[0].r#type, TaskStepType::Post));
+    assert_eq!(
+        task.poststeps[0].content,
+        "- post in chat\n- update the changelog"
+    );
+
+    let again = TaskImportService::new().parse("project-1", ".vibeflow/tasks/release.md", EXPORTED);
+    assert_eq!(again.id, task.id);
+    assert_eq!(again.steps[1].id, task.steps[1].id);
+    assert!(task.steps[1].id.ends_with("-main-tag"));
+}
+
+#[test]
+fn parses_hand_written_checklists() {
+    let content = "# Upgrade deps\r\n\r\nKeep the lockfile small.\r\n\r\n- [x] Run cargo update\r\n- [ ] Fix breaking changes\r\n  in the `api` crate\r\n- [ ] Fix breaking changes\r\n\r\nThanks!\r\n\r\n## Post-steps\r\n\r\n* Run the tests\r\n";
+    let task = TaskImportService::new().parse("project-1", "notes/upgrade.md", content);
+
+    assert_eq!(task.description, "Keep the lockfile small.\n\nThanks!");
+    assert_eq!(task.steps.len(), 3);
+    assert!(matches!(task.steps[0].status, TaskStatus::Completed));
+    assert_eq!(task.steps[0].content, "Run cargo update");
+    assert_eq!(task.steps[1].content, "in the `api` crate");
+    assert_ne!(task.steps[1].id, task.steps[2].id);
+    assert_eq!(task.poststeps[0].title, "Run the tests");
+}
+
+#[test]
+fn previews_changes_against_existing_tasks() {
+    let root = tempfile::tempdir().unwrap();
+    write(root.path(), ".vibeflow/tasks/release.md", EXPORTED);
+    write(root.path(), ".codex/style.md", "Use tabs.");
+    write(
+        root.path(),
+        ".codex/deploy.md",
+        "# Deploy\n\n## Steps\n\n- [ ] Ship\n",
+    );
+    let mut project = common::project(&root.path().to_string_lossy());
+    let service = TaskImportService::new();
+
+    let paths = service.discover(root.path()).unwrap();
+    assert_eq!(paths, [".codex/deploy.md", ".vibeflow/tasks/release.md"]);
+    let imports = service.preview(&project, &paths).unwrap();
+    assert!(imports
+        .iter()
+        .all(|import| import.kind == TaskImportKind::New));
+    service.apply(&mut project, &imports);
+    assert_eq!(project.tasks.len(), 2);
+
+    let release = project
+        .tasks
+        .iter_mut()
+        .find(|task| task.title == "Release")
+        .unwrap();
+    release.steps[0].status = TaskStatus::Completed;
+    let bump_id = release.steps[0].id.clone();
+    assert!(service
+        .preview(&project, &paths)
+        .unwrap()
+        .iter()
+        .all(|import| import.kind == TaskImportKind::Unchanged));
+
+    write(
+        root.path(),
+        ".vibeflow/tasks/release.md",
+        &EXPORTED
+            .replace("Bump the version.", "Bump every version.")
+            .replace(
+                "### 2. Tag\n\n_No content_\n\n",
+                "### 2. Build\n\nBuild it.\n\n",
+            ),
+    );
+    let imports = service
+        .preview(&project, &[".vibeflow/tasks/release.md".to_string()])
+        .unwrap();
+    assert_eq!(imports[0].kind, TaskImportKind::Updated);
+    assert_eq!(
+        imports[0].changes,
+        [
+            "changes main step \"Bump\"",
+            "adds main step \"Build\"",
+            "removes main step \"Tag\"",
+        ]
+    );
+    let bump = &imports[0].task.steps[0];
+    assert_eq!(bump.id, bump_id);
+    assert!(matches!(bump.status, TaskStatus::Completed));
+
+    service.apply(&mut project, &imports);
+    assert_eq!(project.tasks.len(), 2);
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use std::fs;
+
+use vibeflow_lib::models::task::{TaskStatus, TaskStepType};
+use vibeflow_lib::services::task_planner_service::TaskPlannerService;
+
+const PLAN: &str = r#"{
+  "title": "Add a health check",
+  "description": "",
+  "presteps": [],
+  "steps": [
+    { "title": "Route", "content": "Add GET /health.", "verify": ["cargo test", " "] },
+    { "title": "Docs", "content": "Document the endpoint.", "verify": [] }
+  ],
+  "poststeps": [
+    { "title": "Lint", "content": "Run clippy and fix warnings.", "verify": ["cargo clippy"] }
+  ]
+}"#;
+
+#[test]
+fn prompt_carries_goal_and_project_files() {
+    let root = tempfile::tempdir().unwrap();
+    fs::create_dir_all(root.path().join("src")).unwrap();
+    fs::write(root.path().join("src/main.rs"), "fn main() {}").unwrap();
+    fs::write(root.path().join("README.md"), "# Demo\n\nA web service.").unwrap();
+    let project = common::project(&root.path().to_string_lossy());
+
+    let prompt = TaskPlannerService::new()
+        .build_prompt("  Add a health check  ", &project)
+        .unwrap();
+    assert!(prompt.contains("Goal:\nAdd a health check\n"));
+    assert!(prompt.contains("- src/main.rs\n"));
+    assert!(prompt.contains("A web service."));
+    assert!(prompt.contains("do not use `{{`"));
+
+    assert!(TaskPlannerService::new()
+        .build_prompt(" ", &project)
+        .is_err());
+}
+
+#[test]
+fn parses_plans_into_draft_tasks() {
+    let output = format!("Here is the plan:\n```json\n{PLAN}\n```");
+    let task = TaskPlannerService::new()
+        .parse("project-1", "Add a health check", &output)
+        .unwrap();
+
+    assert_eq!(task.title, "Add a health check");
+    assert_eq!(task.description, "Add a health check");
+    assert!(matches!(task.status, TaskStatus::Pending));
+    assert_eq!(task.steps.len(), 2);
+    assert_eq!(task.steps[0].verify, ["cargo test"]);
+    assert!(matches!(task.poststeps[0].r#type, TaskStepType::Post));
+    assert_ne!(task.steps[0].id, task.steps[1].id);
+}
+
+#[test]
+fn rejects_invalid_plans() {
+    let planner = TaskPlannerService::new();
+
+    let no_steps = PLAN.replace(
+        r#"{ "title": "Route", "content": "Add GET /health.", "verify": ["cargo test", " "] },
+    { "title": "Docs", "content": "Document the endpoint.", "verify": [] }"#,
+        "",
+    );
+    assert!(planner
+        .parse("project-1", "goal", &no_steps)
+        .unwrap_err()
+        .contains("without steps"));
+
+    let empty_step = PLAN.replace("Document the endpoint.", " ");
+    assert!(planner
+        .parse("project-1", "goal", &empty_step)
+        .unwrap_err()
+        .contains("empty main step at position 2"));
+
+    let template = PLAN.replace("Add GET /health.", "Render {{ name }}.");
+    assert!(planner
+        .parse("project-1", "goal", &template)
+        .unwrap_err()
+        .contains("\"Route\""));
+
+    let extra_field = PLAN.replace(r#""presteps": [],"#, r#""presteps": [], "priority": 1,"#);
+    assert!(planner.parse("project-1", "goal", &extra_field).is_err());
+    assert!(planner
+        .parse("project-1", "goal", "I could not plan this.")
+        .is_err());
+}
+
+#[test]
+fn schema_suits_strict_structured_output() {
+    let schema = TaskPlannerService::output_schema();
+
+    assert_eq!(schema["additionalProperties"], false);
+    let required = schema["required"].as_array().unwrap();
+    for field in ["title", "description", "presteps", "steps", "poststeps"] {
+        assert!(required.contains(&field.into()), "{field} is optional");
+    }
+    let step = &schema["$defs"]["PlannedStep"];
+    assert_eq!(step["additionalProperties"], false);
+    assert_eq!(step["required"].as_array().unwrap().len(), 3);
+}
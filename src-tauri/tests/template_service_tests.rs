@@ -0,0 +1,197 @@
+mod common;
+
+use std::collections::BTreeMap;
+
+use vibeflow_lib::models::project::Project;
+use vibeflow_lib::models::task::{Task, TaskParameter, TaskStep, TaskStepResult};
+use vibeflow_lib::services::data_service::DataService;
+use vibeflow_lib::services::template_service::{PromptTemplate, TemplateService};
+
+fn step(id: &str, title: &str, content: &str) -> TaskStep {
+    TaskStep {
+        id: id.into(),
+        title: title.into(),
+        content: content.into(),
+        created_at: 1,
+        updated_at: 1,
+        ..Default::default()
+    }
+}
+
+fn task(steps: Vec<TaskStep>) -> Task {
+    Task {
+        id: "task-1".into(),
+        project_id: "project-1".into(),
+        title: "Add login".into(),
+        description: "Email and password".into(),
+        steps,
+        parameters: vec![TaskParameter {
+            name: "framework".into(),
+            value: "axum".into(),
+        }],
+        created_at: 1,
+        updated_at: 1,
+        ..Default::default()
+    }
+}
+
+fn project(tasks: Vec<Task>) -> Project {
+    Project {
+        tasks,
+        ..common::project("/nonexistent/demo")
+    }
+}
+
+#[test]
+fn reports_syntax_errors_with_their_position() {
+    let unclosed = PromptTemplate::parse("Hello\n  {{task.title").unwrap_err();
+    assert_eq!((unclosed.line, unclosed.column), (2, 3));
+    assert_eq!(unclosed.to_string(), "line 2, column 3: unclosed '{{'");
+
+    let invalid = PromptTemplate::parse("{{task title}}").unwrap_err();
+    assert_eq!((invalid.line, invalid.column), (1, 1));
+
+    let service = TemplateService::new();
+    let unknown = service
+        .validate(
+            "Use {{params.db}} with {{params.framework}}",
+            &["framework".into()],
+        )
+        .unwrap_err();
+    assert_eq!(unknown.column, 5);
+    assert_eq!(unknown.message, "unknown variable 'params.db'");
+    assert!(service.validate("{{ step.title }}", &[]).is_ok());
+}
+
+#[test]
+fn escaped_braces_are_left_as_text() {
+    let template = PromptTemplate::parse(r"Keep \{{literal}} and {{step.index}}").unwrap();
+    let values = BTreeMap::from([("step.index".to_string(), "2".to_string())]);
+
+    assert_eq!(template.render(&values).unwrap(), "Keep {{literal}} and 2");
+}
+
+#[test]
+fn renders_step_prompts_with_task_and_previous_step_values() {
+    let steps = vec![step("step-1", "Plan", ""), step("step-2", "Build", "")];
+    let task = task(steps);
+    let project = project(Vec::new());
+    let previous = TaskStepResult::new(
+        "run-1".into(),
+        "task-1".into(),
+        "step-1".into(),
+        "Planned the routes.".into(),
+    );
+
+    let rendered = TemplateService::new().render_step(
+        "{{project.name}}: {{task.title}} step {{step.index}} ({{step.title}}) \
+             with {{params.framework}}. {{previous_step.summary}} [{{git.branch}}]",
+        &project,
+        &task,
+        &task.steps[1],
+        Some(&previous),
+    );
+
+    assert_eq!(
+        rendered,
+        "demo: Add login step 2 (Build) with axum. Planned the routes. []"
+    );
+}
+
+#[test]
+fn project_validation_names_the_broken_step() {
+    let service = TemplateService::new();
+    let valid = project(vec![task(vec![step(
+        "step-1",
+        "Plan",
+        "{{params.framework}}",
+    )])]);
+    assert!(service.validate_project(&valid, None).is_ok());
+
+    let broken = project(vec![task(vec![step("step-1", "Plan", "{{params.db}}")])]);
+    assert_eq!(
+        service.validate_project(&broken, None).unwrap_err(),
+        "step 'Plan' of task 'Add login' has an invalid prompt: \
+         line 1, column 1: unknown variable 'params.db'"
+    );
+}
+
+#[test]
+fn project_validation_skips_prompts_that_were_not_edited() {
+    let service = TemplateService::new();
+    let stored = project(vec![task(vec![step(
+        "step-1",
+        "Plan",
+        "Use {{ user.name }}",
+    )])]);
+
+    let mut renamed = stored.clone();
+    renamed.tasks[0].title = "Sign in".into();
+    assert!(service.validate_project(&renamed, Some(&stored)).is_ok());
+
+    let mut edited = stored.clone();
+    edited.tasks[0].steps[0].content = "Use {{ user.email }}".into();
+    assert!(service.validate_project(&edited, Some(&stored)).is_err());
+}
+
+#[test]
+fn step_prompts_keep_placeholders_that_are_not_variables() {
+    let task = task(vec![step("step-1", "Plan", "")]);
+
+    let rendered = TemplateService::new().render_step(
+        "{{step.title}} the {{ user.name }} template, then {{ close it }} or {{",
+        &project(Vec::new()),
+        &task,
+        &task.steps[0],
+        None,
+    );
+
+    assert_eq!(
+        rendered,
+        "Plan the {{ user.name }} template, then {{ close it }} or {{"
+    );
+}
+
+#[test]
+fn parameters_and_step_results_are_stored() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let data_service = DataService::new(data_dir.path().to_path_buf()).unwrap();
+    data_service
+        .upsert_project(&project(vec![task(vec![step("step-1", "Plan", "")])]))
+        .unwrap();
+
+    let loaded = data_service.load_project("project-1").unwrap().unwrap();
+    assert_eq!(
+        loaded.tasks[0].parameters,
+        [TaskParameter {
+            name: "framework".into(),
+            value: "axum".into(),
+        }]
+    );
+
+    for (step_id, summary) in [("step-1", "first"), ("step-2", "second")] {
+        data_service
+            .save_task_step_result(&TaskStepResult::new(
+                "run-1".into(),
+                "task-1".into(),
+                step_id.into(),
+                summary.into(),
+            ))
+            .unwrap();
+    }
+
+    let previous = data_service
+        .load_previous_step_result("run-1", "step-3")
+        .unwrap()
+        .unwrap();
+    assert_eq!(previous.summary, "second");
+    let previous = data_service
+        .load_previous_step_result("run-1", "step-2")
+        .unwrap()
+        .unwrap();
+    assert_eq!(previous.summary, "first");
+    assert!(data_service
+        .load_previous_step_result("run-2", "step-1")
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,155 @@
+use vibeflow_lib::models::project::Project;
+use vibeflow_lib::models::usage::{ModelPricing, TokenUsage, UsageRecord, UsageReportQuery};
+use vibeflow_lib::services::data_service::DataService;
+
+fn pricing() -> Vec<ModelPricing> {
+    vec![
+        ModelPricing {
+            model: "gpt-5".into(),
+            input_per_million: 1.0,
+            cached_input_per_million: 0.1,
+            output_per_million: 10.0,
+        },
+        ModelPricing {
+            model: "gpt-5-codex".into(),
+            input_per_million: 2.0,
+            cached_input_per_million: 0.2,
+            output_per_million: 20.0,
+        },
+    ]
+}
+
+fn usage(input: u64, cached: u64, output: u64) -> TokenUsage {
+    TokenUsage {
+        input_tokens: input,
+        cached_input_tokens: cached,
+        output_tokens: output,
+        reasoning_output_tokens: 0,
+    }
+}
+
+fn project(id: &str) -> Project {
+    Project {
+        id: id.into(),
+        name: id.into(),
+        path: format!("/tmp/{id}"),
+        created_at: 1,
+        updated_at: 1,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn estimates_cost_with_cached_input_discount() {
+    let cost =
+        ModelPricing::estimate_cost(&pricing(), "gpt-5", &usage(1_000_000, 400_000, 100_000));
+
+    assert!((cost - (0.6 + 0.04 + 1.0)).abs() < 1e-9);
+}
+
+#[test]
+fn prefers_exact_then_longest_prefix_pricing() {
+    let pricing = pricing();
+
+    assert_eq!(
+        ModelPricing::find(&pricing, "gpt-5-codex")
+            .unwrap()
+            .input_per_million,
+        2.0
+    );
+    assert_eq!(
+        ModelPricing::find(&pricing, "gpt-5-codex-mini")
+            .unwrap()
+            .input_per_million,
+        2.0
+    );
+    assert_eq!(
+        ModelPricing::find(&pricing, "gpt-5-mini")
+            .unwrap()
+            .input_per_million,
+        1.0
+    );
+    assert!(ModelPricing::find(&pricing, "llama3").is_none());
+    assert_eq!(
+        ModelPricing::estimate_cost(&pricing, "llama3", &usage(10, 0, 10)),
+        0.0
+    );
+}
+
+#[test]
+fn aggregates_usage_by_day_project_and_model() {
+    let data_dir = tempfile::tempdir().unwrap();
+    let data_service = DataService::new(data_dir.path().to_path_buf()).unwrap();
+    data_service.upsert_project(&project("alpha")).unwrap();
+    data_service.upsert_project(&project("beta")).unwrap();
+
+    let records = [
+        (
+            "alpha",
+            "thread-1",
+            Some("run-1"),
+            "gpt-5",
+            usage(100, 20, 10),
+        ),
+        (
+            "alpha",
+            "thread-1",
+            Some("run-1"),
+            "gpt-5-codex",
+            usage(200, 0, 30),
+        ),
+        ("beta", "thread-2", None, "gpt-5", usage(50, 0, 5)),
+    ];
+    for (project_id, thread_id, task_run_id, model, usage) in records {
+        let record = UsageRecord::for_turn(
+            Some(project_id.into()),
+            Some(thread_id.into()),
+            task_run_id.map(str::to_string),
+            model.into(),
+            usage,
+            &pricing(),
+        );
+        data_service.save_usage_record(&record).unwrap();
+    }
+
+    let report = data_service
+        .load_usage_report(&UsageReportQuery::default())
+        .unwrap();
+    assert_eq!(report.total.turns, 3);
+    assert_eq!(report.total.usage.input_tokens, 350);
+    assert_eq!(report.total.usage.cached_input_tokens, 20);
+    assert_eq!(report.total.total_tokens, 395);
+    assert_eq!(report.by_day.len(), 1);
+
+    let by_project = report
+        .by_project
+        .iter()
+        .map(|row| (row.key.as_str(), row.turns))
+        .collect::<Vec<_>>();
+    assert_eq!(by_project, vec![("alpha", 2), ("beta", 1)]);
+
+    let by_model = report
+        .by_model
+        .iter()
+        .map(|row| (row.key.as_str(), row.usage.output_tokens))
+        .collect::<Vec<_>>();
+    assert_eq!(by_model, vec![("gpt-5", 15), ("gpt-5-codex", 30)]);
+
+    let run_report = data_service
+        .load_usage_report(&UsageReportQuery {
+            task_run_id: Some("run-1".into()),
+            ..UsageReportQuery::default()
+        })
+        .unwrap();
+    assert_eq!(run_report.total.turns, 2);
+    assert_eq!(run_report.total.usage.output_tokens, 40);
+
+    let empty = data_service
+        .load_usage_report(&UsageReportQuery {
+            from: Some(i64::MAX - 1),
+            ..UsageReportQuery::default()
+        })
+        .unwrap();
+    assert_eq!(empty.total.turns, 0);
+    assert!(empty.by_model.is_empty());
+}
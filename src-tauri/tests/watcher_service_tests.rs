@@ -0,0 +1,130 @@
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+use vibeflow_lib::models::file_change::{FileChangeKind, FilesChangedEvent};
+use vibeflow_lib::services::watcher_service::WatcherService;
+
+fn write(root: &Path, path: &str, content: &str) {
+    let path = root.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+fn watch(service: &WatcherService, root: &Path) -> Receiver<FilesChangedEvent> {
+    let (sender, receiver) = mpsc::channel();
+    service
+        .watch("project-1", root, move |event| {
+            let _ = sender.send(event.clone());
+        })
+        .unwrap();
+    receiver
+}
+
+/// Merges batches until `done` holds for the merged event or time runs out.
+fn wait_for(
+    receiver: &Receiver<FilesChangedEvent>,
+    done: impl Fn(&FilesChangedEvent) -> bool,
+) -> FilesChangedEvent {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut merged = FilesChangedEvent::default();
+    while !done(&merged) {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let Ok(event) = receiver.recv_timeout(remaining) else {
+            break;
+        };
+        merged.project_id = event.project_id;
+        merged.added.extend(event.added);
+        merged.modified.extend(event.modified);
+        merged.removed.extend(event.removed);
+    }
+    merged
+}
+
+#[test]
+fn reports_added_modified_and_removed_files_outside_ignores() {
+    let root = tempfile::tempdir().unwrap();
+    write(root.path(), ".gitignore", "target/\n");
+    write(root.path(), "src/lib.rs", "pub fn build() {}\n");
+    write(root.path(), "src/old.rs", "pub fn old() {}\n");
+    let service = WatcherService::new();
+    let receiver = watch(&service, root.path());
+    assert_eq!(service.watched_project().as_deref(), Some("project-1"));
+
+    write(root.path(), "target/debug/out.rs", "generated\n");
+    write(root.path(), "src/new.rs", "pub fn new() {}\n");
+    write(root.path(), "src/lib.rs", "pub fn build_all() {}\n");
+    fs::remove_file(root.path().join("src/old.rs")).unwrap();
+
+    let event = wait_for(&receiver, |event| {
+        !event.added.is_empty() && !event.modified.is_empty() && !event.removed.is_empty()
+    });
+    assert_eq!(event.project_id, "project-1");
+    assert_eq!(event.added, ["src/new.rs"]);
+    assert_eq!(event.modified, ["src/lib.rs"]);
+    assert_eq!(event.removed, ["src/old.rs"]);
+
+    service.unwatch();
+    assert!(service.watched_project().is_none());
+}
+
+#[test]
+fn answers_files_changed_since_a_timestamp() {
+    let root = tempfile::tempdir().unwrap();
+    write(root.path(), "notes.md", "# Notes\n");
+    let service = WatcherService::new();
+    let receiver = watch(&service, root.path());
+
+    write(root.path(), "scratch.txt", "draft\n");
+    let first = wait_for(&receiver, |event| !event.added.is_empty());
+    assert_eq!(first.added, ["scratch.txt"]);
+    let since = service.changes_since("project-1", 0)[0].changed_at;
+
+    fs::remove_file(root.path().join("scratch.txt")).unwrap();
+    write(root.path(), "notes.md", "# Notes\n\nMore.\n");
+    wait_for(&receiver, |event| {
+        !event.removed.is_empty() && !event.modified.is_empty()
+    });
+
+    let net = service.changes_since("project-1", 0);
+    assert_eq!(net.len(), 1);
+    assert_eq!(
+        (net[0].path.as_str(), net[0].kind),
+        ("notes.md", FileChangeKind::Modified)
+    );
+
+    let later = service
+        .changes_since("project-1", since)
+        .into_iter()
+        .map(|change| (change.path, change.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        later,
+        [
+            ("notes.md".to_string(), FileChangeKind::Modified),
+            ("scratch.txt".to_string(), FileChangeKind::Removed),
+        ]
+    );
+    assert!(service.changes_since("other-project", 0).is_empty());
+}
+
+#[test]
+fn skips_changes_under_nested_ignores_and_rescans_when_an_ignore_file_changes() {
+    let root = tempfile::tempdir().unwrap();
+    write(root.path(), "web/.gitignore", "dist/\n");
+    write(root.path(), "web/src/app.ts", "export {};\n");
+    let service = WatcherService::new();
+    let receiver = watch(&service, root.path());
+
+    write(root.path(), "web/dist/app.js", "bundle\n");
+    write(root.path(), "web/src/main.ts", "export {};\n");
+    let event = wait_for(&receiver, |event| !event.added.is_empty());
+    assert_eq!(event.added, ["web/src/main.ts"]);
+    assert!(event.modified.is_empty() && event.removed.is_empty());
+
+    write(root.path(), "web/.gitignore", "dist/\nsrc/main.ts\n");
+    let event = wait_for(&receiver, |event| !event.removed.is_empty());
+    assert_eq!(event.modified, ["web/.gitignore"]);
+    assert_eq!(event.removed, ["web/src/main.ts"]);
+}
@@ -3,6 +3,7 @@ import { RouterModule } from '@angular/router';
 import { NavService } from '../../services/nav.service';
 import { NotificationService } from '../../services/notification.service';
 import { ProjectService } from '../../services/project.service';
+import { ScheduleService } from '../../services/schedule.service';
 import { NotificationComponent } from '../notification/notification.component';
 import { SidebarComponent } from '../sidebar/sidebar.component';
 
@@ -15,6 +16,7 @@ export class AppLayoutComponent implements OnInit {
     private readonly notificationService = inject(NotificationService);
     private readonly navService = inject(NavService);
     private readonly projectService = inject(ProjectService);
+    private readonly scheduleService = inject(ScheduleService);
 
     readonly navItems = computed(() => this.navService.navItems);
     readonly bottomNavItems = computed(() => this.navService.bottomNavItems);
@@ -24,5 +26,6 @@ export class AppLayoutComponent implements OnInit {
 
     async ngOnInit() {
         await this.projectService.initialize();
+        await this.scheduleService.start();
     }
 }
\ No newline at end of file
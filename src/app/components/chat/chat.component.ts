@@ -5,10 +5,13 @@ import { ChatMessage, ChatThread } from '../../models/chat.message';
 import { TaskStatus } from '../../models/task';
 import { FormatTimestampPipe } from '../../pipes/format.timestamp.pipe';
 import { ChatService } from '../../services/chat.service';
+import { ContextService } from '../../services/context.service';
+import { DialogService } from '../../services/dialog.service';
 import { MessageStoreService } from '../../services/message.store.service';
 import { ProjectService } from '../../services/project.service';
 import { TaskExecuteService } from '../../services/task.execuer.service';
 import { AgentSelectorComponent } from '../agent-selector/agent.selector.component.';
+import { SymbolPickerComponent } from '../context/symbol.picker.component';
 import { IconComponent } from "../icon/icon.component";
 import { LoaderComponent } from '../loader/loader.component';
 import { MarkdownEditorComponent } from '../md-editor/md.editor.component';
@@ -42,6 +45,8 @@ export class ChatComponent implements OnInit, OnDestroy {
     private readonly taskRuntimeService = inject(TaskExecuteService);
     private readonly messageService = inject(MessageStoreService);
     private readonly projectService = inject(ProjectService);
+    private readonly contextService = inject(ContextService);
+    private readonly dialogService = inject(DialogService);
 
     readonly messages = computed(() => this.messageService.messages());
     readonly hasMessages = computed(() => this.messages().length > 0);
@@ -50,6 +55,9 @@ export class ChatComponent implements OnInit, OnDestroy {
     readonly currentThread = computed(() => this.messageService.currentThread());
     readonly hasThreads = computed(() => this.threads().length > 0);
     readonly showThreads = signal(false);
+    readonly hasProject = computed(() => !!this.projectService.currentProject().id);
+    readonly contextSets = this.contextService.contextSets;
+    readonly selectedContextSetIds = signal<string[]>([]);
 
     private readonly runningTaskSubscription = this.taskRuntimeService.onRunTask.subscribe(data => {
         let isRunning = data.status === TaskStatus.InProgress;
@@ -128,7 +136,23 @@ export class ChatComponent implements OnInit, OnDestroy {
             this.selectedAgent()!, (message) => {
                 this.composerText.set('');
                 this.resetComposerHeight();
-            });
+            }, undefined, { contextSetIds: this.selectedContextSetIds() });
+    }
+
+    toggleContextSet(contextSetId: string): void {
+        this.selectedContextSetIds.update(ids => ids.includes(contextSetId)
+            ? ids.filter(id => id !== contextSetId)
+            : [...ids, contextSetId]);
+    }
+
+    insertSymbol(): void {
+        this.dialogService.openContent({
+            title: 'Insert Symbol',
+            component: SymbolPickerComponent,
+            componentInputs: {
+                pick: (content: string) => this.composerText.update(text => text.trim() ? `${text.trimEnd()}\n\n${content}` : content)
+            }
+        });
     }
 
     async copyMessage(message: ChatMessage): Promise<void> {
@@ -1,7 +1,7 @@
 import { DatePipe } from '@angular/common';
 import { Component, computed, inject, OnDestroy } from '@angular/core';
 
-import { AgentRule, AgentRuleViewModel } from '../../models/agent.rule';
+import { AgentRule, AgentRuleViewModel, RuleConflict, RuleConflictResolution } from '../../models/agent.rule';
 import { CommandDescriptor } from '../../models/command';
 import { IdGenerator } from '../../models/id';
 import { ProjectExtensions } from "../../models/project.extensions";
@@ -9,13 +9,14 @@ import { ProjectService } from '../../services/project.service';
 import { IconComponent } from "../icon/icon.component";
 import { MarkdownRendererComponent } from '../md-renderer/md.renderer.component';
 import { WorkspaceHeaderComponent } from '../workspace/workspace.header.component';
+import { ContextSetsComponent } from './context.sets.component';
 import { RuleEditorComponent } from './rule.editor.component';
 
 @Component({
     selector: 'mtx-context-manage',
     templateUrl: 'context.component.html',
     imports: [DatePipe, IconComponent, MarkdownRendererComponent,
-        WorkspaceHeaderComponent, RuleEditorComponent]
+        WorkspaceHeaderComponent, RuleEditorComponent, ContextSetsComponent]
 })
 export class ContextComponent implements OnDestroy {
     private readonly projectService = inject(ProjectService);
@@ -52,14 +53,22 @@ export class ContextComponent implements OnDestroy {
             action: () => this.addAgentRule()
         },
         {
-            id: 'download-rules',
-            title: 'Download',
-            description: `Download all rules as markdown files to the project folder/${ProjectService.AGENT_FOLDER}.`,
+            id: 'sync-rules',
+            title: 'Sync',
+            description: `Sync rules with AGENTS.md files and the project folder/${ProjectService.VIBEFLOW_FOLDER}/rules.`,
             icon: 'box-arrow-down',
-            action: () => this.downloadAgentRules()
+            action: () => this.syncAgentRules()
+        },
+        {
+            id: 'repair-folder',
+            title: 'Repair Folder',
+            description: `Create or upgrade the project folder/${ProjectService.VIBEFLOW_FOLDER} and move legacy .codex files into it.`,
+            icon: 'arrow-down-up',
+            action: () => this.projectService.repairProjectFolder()
         }
     ]);
     readonly hasRules = computed(() => this.ruleViewModels().length > 0);
+    readonly ruleConflicts = this.projectService.ruleConflicts;
 
     ngOnDestroy(): void {
         this.savingSubscription.unsubscribe();
@@ -72,7 +81,11 @@ export class ContextComponent implements OnDestroy {
         const newRule: AgentRule = {
             id: IdGenerator.generateId(),
             name: name || `rule-${nextIndex}`,
-            description: `Project rule ${nextIndex}. Update this with specific context guidance.`,
+            content: `Project rule ${nextIndex}. Update this with specific context guidance.`,
+            enabled: true,
+            priority: nextIndex,
+            scopes: [],
+            tags: [],
             createdAt: now,
             updatedAt: now
         };
@@ -80,18 +93,19 @@ export class ContextComponent implements OnDestroy {
         ProjectExtensions.addRule(this.projectService.currentProject, newRule);
     }
 
-    async downloadAgentRules(): Promise<void> {
-        let project = this.projectService.currentProject();
-        if (!project) {
-            return;
+    async syncAgentRules(): Promise<void> {
+        try {
+            await this.projectService.syncRules();
+        } catch (error) {
+            console.error('Failed to sync agent rules:', error);
         }
+    }
 
+    async resolveConflict(conflict: RuleConflict, resolution: RuleConflictResolution): Promise<void> {
         try {
-            for (const rule of project.rules) {
-                await this.projectService.saveRuleToProjectFolder(rule);
-            }
+            await this.projectService.resolveRuleConflict(conflict.ruleId, resolution);
         } catch (error) {
-            console.error('Failed to export agent rules:', error);
+            console.error('Failed to resolve rule conflict:', error);
         }
     }
 
@@ -121,8 +135,8 @@ export class ContextComponent implements OnDestroy {
         } : existingRule;
 
         ProjectExtensions.updateRule(this.projectService.currentProject, existingRule!);
-        await this.projectService.saveRuleToProjectFolder(existingRule!);
         await this.projectService.saveProject();
+        await this.syncAgentRules();
 
         viewModel?.isEditing.set(false);
     }
@@ -0,0 +1,95 @@
+import { Component, computed, effect, inject, signal, untracked } from '@angular/core';
+
+import { ContextBlock, ContextSet } from '../../models/context.set';
+import { IdGenerator } from '../../models/id';
+import { ContextService } from '../../services/context.service';
+import { ProjectService } from '../../services/project.service';
+import { IconComponent } from '../icon/icon.component';
+
+@Component({
+    selector: 'mtx-context-sets',
+    templateUrl: 'context.sets.component.html',
+    imports: [IconComponent]
+})
+export class ContextSetsComponent {
+    private readonly contextService = inject(ContextService);
+    private readonly projectService = inject(ProjectService);
+
+    readonly contextSets = this.contextService.contextSets;
+    readonly editingId = signal<string | null>(null);
+    readonly draftName = signal('');
+    readonly draftPatterns = signal('');
+    readonly previews = signal<Record<string, ContextBlock>>({});
+    readonly hasProject = computed(() => !!this.projectService.currentProject()?.id);
+
+    constructor() {
+        effect(() => {
+            if (!this.contextService.filesChanged()) {
+                return;
+            }
+
+            // Estimates shown so far may no longer match the files on disk.
+            const estimated = untracked(() => this.previews());
+            for (const contextSet of untracked(() => this.contextSets())) {
+                if (estimated[contextSet.id]) {
+                    this.estimate(contextSet);
+                }
+            }
+        });
+    }
+
+    async addContextSet(): Promise<void> {
+        const projectId = this.projectService.currentProject()?.id;
+        if (!projectId) {
+            return;
+        }
+
+        const now = Date.now();
+        const contextSet: ContextSet = {
+            id: IdGenerator.generateId(),
+            projectId,
+            name: `context-${this.contextSets().length + 1}`,
+            patterns: [],
+            createdAt: now,
+            updatedAt: now
+        };
+        await this.contextService.save(contextSet);
+        this.startEdit(contextSet);
+    }
+
+    startEdit(contextSet: ContextSet): void {
+        this.editingId.set(contextSet.id);
+        this.draftName.set(contextSet.name);
+        this.draftPatterns.set(contextSet.patterns.join('\n'));
+    }
+
+    cancelEdit(): void {
+        this.editingId.set(null);
+    }
+
+    async saveEdit(contextSet: ContextSet): Promise<void> {
+        const patterns = this.draftPatterns().split('\n')
+            .map(pattern => pattern.trim())
+            .filter(pattern => pattern.length > 0);
+
+        await this.contextService.save({
+            ...contextSet,
+            name: this.draftName().trim() || contextSet.name,
+            patterns,
+            updatedAt: Date.now()
+        });
+        this.editingId.set(null);
+        await this.estimate(contextSet);
+    }
+
+    async deleteContextSet(contextSet: ContextSet): Promise<void> {
+        await this.contextService.delete(contextSet);
+    }
+
+    async estimate(contextSet: ContextSet): Promise<void> {
+        const block = await this.contextService.preview([contextSet.id]);
+        if (block) {
+            this.previews.update(previews => ({ ...previews, [contextSet.id]: block }));
+        }
+    }
+}
@@ -17,14 +17,22 @@ export class RuleEditorComponent {
     readonly cancelled = output<AgentRuleViewModel>();
 
     readonly name = signal('');
-    readonly description = signal('');
+    readonly content = signal('');
+    readonly scopes = signal('');
+    readonly tags = signal('');
+    readonly priority = signal(0);
+    readonly enabled = signal(true);
     readonly isGenerating = signal(false);
 
     constructor() {
         effect(() => {
             const rule = this.rule();
             this.name.set(rule.name);
-            this.description.set(rule.description ?? '');
+            this.content.set(rule.content ?? '');
+            this.scopes.set((rule.scopes ?? []).join(', '));
+            this.tags.set((rule.tags ?? []).join(', '));
+            this.priority.set(rule.priority ?? 0);
+            this.enabled.set(rule.enabled ?? true);
         });
     }
 
@@ -41,21 +49,29 @@ export class RuleEditorComponent {
         this.saved.emit({
             ...this.rule(),
             name: trimmedName,
-            description: this.description().trim() || undefined,
+            content: this.content().trim(),
+            scopes: this.toList(this.scopes()),
+            tags: this.toList(this.tags()),
+            priority: this.priority(),
+            enabled: this.enabled(),
             updatedAt: Date.now()
         });
     }
 
     async optimizePrompt(): Promise<void> {
         this.isGenerating.set(true);
-        let content = this.description().trim();
+        let content = this.content().trim();
         if (!content) {
             this.isGenerating.set(false);
             return;
         }
 
         let optimizedContent = await this.chatService.optimizePrompt(content);
-        this.description.set(optimizedContent);
+        this.content.set(optimizedContent);
         this.isGenerating.set(false);
     }
+
+    private toList(value: string): string[] {
+        return value.split(',').map(item => item.trim()).filter(item => item.length > 0);
+    }
 }
@@ -0,0 +1,40 @@
+import { Component, effect, inject, input, signal } from '@angular/core';
+
+import { CodeSymbol, SymbolExtensions } from '../../models/symbol';
+import { ContextService } from '../../services/context.service';
+import { DialogService } from '../../services/dialog.service';
+
+/** Dialog content searching the symbol index; `pick` receives the rendered definition. */
+@Component({
+    selector: 'mtx-symbol-picker',
+    templateUrl: 'symbol.picker.component.html'
+})
+export class SymbolPickerComponent {
+    private readonly contextService = inject(ContextService);
+    private readonly dialogService = inject(DialogService);
+
+    readonly pick = input.required<(content: string) => void>();
+    readonly searchText = signal('');
+    readonly symbols = signal<CodeSymbol[]>([]);
+
+    constructor() {
+        effect(() => {
+            this.searchText();
+            this.refresh();
+        });
+    }
+
+    async refresh(): Promise<void> {
+        this.symbols.set(await this.contextService.searchSymbols(this.searchText()));
+    }
+
+    async choose(symbol: CodeSymbol): Promise<void> {
+        const contexts = await this.contextService.symbolContext(symbol);
+        if (contexts.length === 0) {
+            return;
+        }
+
+        this.pick()(SymbolExtensions.render(contexts));
+        this.dialogService.close();
+    }
+}
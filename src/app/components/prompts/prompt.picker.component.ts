@@ -0,0 +1,36 @@
+import { Component, effect, inject, input, signal } from '@angular/core';
+
+import { PromptRecord } from '../../models/prompt';
+import { DialogService } from '../../services/dialog.service';
+import { PromptService } from '../../services/prompt.service';
+
+/** Dialog content listing library prompts; `pick` receives the chosen one. */
+@Component({
+    selector: 'mtx-prompt-picker',
+    templateUrl: 'prompt.picker.component.html'
+})
+export class PromptPickerComponent {
+    private readonly promptService = inject(PromptService);
+    private readonly dialogService = inject(DialogService);
+
+    readonly pick = input.required<(prompt: PromptRecord) => void>();
+    readonly searchText = signal('');
+    readonly prompts = signal<PromptRecord[]>([]);
+
+    constructor() {
+        effect(() => {
+            this.searchText();
+            this.refresh();
+        });
+    }
+
+    async refresh(): Promise<void> {
+        const text = this.searchText();
+        this.prompts.set(await this.promptService.search({ text: text || undefined, pinnedOnly: true }));
+    }
+
+    choose(prompt: PromptRecord): void {
+        this.pick()(prompt);
+        this.dialogService.close();
+    }
+}
@@ -0,0 +1,103 @@
+import { DatePipe } from '@angular/common';
+import { Component, computed, effect, inject, signal } from '@angular/core';
+
+import { CommandDescriptor } from '../../models/command';
+import { PromptRecord } from '../../models/prompt';
+import { ProjectService } from '../../services/project.service';
+import { PromptService } from '../../services/prompt.service';
+import { IconComponent } from '../icon/icon.component';
+import { WorkspaceHeaderComponent } from '../workspace/workspace.header.component';
+
+type PromptTab = 'history' | 'starred' | 'library';
+
+const PROMPT_TABS: { key: PromptTab, label: string }[] = [
+    { key: 'history', label: 'History' },
+    { key: 'starred', label: 'Starred' },
+    { key: 'library', label: 'Library' }
+];
+
+@Component({
+    selector: 'mtx-prompts',
+    templateUrl: 'prompts.component.html',
+    imports: [DatePipe, IconComponent, WorkspaceHeaderComponent]
+})
+export class PromptsComponent {
+    private readonly projectService = inject(ProjectService);
+    private readonly promptService = inject(PromptService);
+
+    readonly selectedTab = signal<PromptTab>('history');
+    readonly searchText = signal('');
+    readonly prompts = signal<PromptRecord[]>([]);
+    readonly pinningId = signal<string | null>(null);
+    readonly pinName = signal('');
+    readonly pinTags = signal('');
+
+    readonly headerLeftCommands = computed<CommandDescriptor[]>(() => {
+        const selected = this.selectedTab();
+        return PROMPT_TABS.map((tab) => ({
+            id: `filter-${tab.key}`,
+            title: tab.label,
+            icon: 'text-xs',
+            tag: selected === tab.key ? '  border border-emerald-700' : '',
+            action: () => this.selectedTab.set(tab.key)
+        }));
+    });
+    readonly emptyMessage = computed(() => this.selectedTab() === 'library'
+        ? 'No prompts pinned to the library yet.'
+        : 'No prompts found.');
+
+    constructor() {
+        effect(() => {
+            this.selectedTab();
+            this.searchText();
+            this.projectService.currentProject();
+            this.refresh();
+        });
+    }
+
+    async refresh(): Promise<void> {
+        const tab = this.selectedTab();
+        const projectId = this.projectService.currentProject()?.id || undefined;
+        const prompts = await this.promptService.search({
+            text: this.searchText() || undefined,
+            // The library is shared across projects.
+            projectId: tab === 'library' ? undefined : projectId,
+            starredOnly: tab === 'starred',
+            pinnedOnly: tab === 'library'
+        });
+        this.prompts.set(prompts);
+    }
+
+    async toggleStar(prompt: PromptRecord): Promise<void> {
+        this.replace(await this.promptService.star(prompt, !prompt.starred));
+    }
+
+    startPin(prompt: PromptRecord): void {
+        this.pinningId.set(prompt.id);
+        this.pinName.set(prompt.name ?? prompt.content.split('\n')[0].slice(0, 60));
+        this.pinTags.set(prompt.tags.join(', '));
+    }
+
+    cancelPin(): void {
+        this.pinningId.set(null);
+    }
+
+    async savePin(prompt: PromptRecord): Promise<void> {
+        const updated = await this.promptService.pin(prompt, this.pinName(), PromptService.parseTags(this.pinTags()));
+        if (updated) {
+            this.replace(updated);
+            this.pinningId.set(null);
+        }
+    }
+
+    async unpin(prompt: PromptRecord): Promise<void> {
+        this.replace(await this.promptService.unpin(prompt));
+        if (this.selectedTab() === 'library') {
+            await this.refresh();
+        }
+    }
+
+    private replace(updated: PromptRecord): void {
+        this.prompts.update(prompts => prompts.map(prompt => prompt.id === updated.id ? updated : prompt));
+    }
+}
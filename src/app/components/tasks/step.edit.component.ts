@@ -1,6 +1,7 @@
 import { Component, effect, inject, input, output, signal } from '@angular/core';
-import { StepViewModel } from '../../models/task';
+import { StepRunWhen, StepViewModel, TaskSandbox } from '../../models/task';
 import { ChatService } from '../../services/chat.service';
+import { TaskService } from '../../services/task.service';
 import { IconComponent } from "../icon/icon.component";
 import { MarkdownEditorComponent } from '../md-editor/md.editor.component';
 
@@ -11,11 +12,22 @@ import { MarkdownEditorComponent } from '../md-editor/md.editor.component';
 })
 export class StepEditorComponent {
     private readonly chatService = inject(ChatService);
+    private readonly taskService = inject(TaskService);
 
     readonly step = input.required<StepViewModel>();
+    readonly parameters = input<string[]>([]);
+    /** Main steps of the task this step can depend on. */
+    readonly dependencyOptions = input<{ id: string; title: string }[]>([]);
     readonly title = signal<string>('');
     readonly content = signal<string>('');
+    readonly model = signal<string>('');
+    readonly sandbox = signal<string>('');
+    readonly verify = signal<string>('');
+    readonly dependsOn = signal<string[]>([]);
+    readonly runWhen = signal<StepRunWhen>('on-success');
+    readonly ifChanged = signal<string>('');
     readonly isGenerating = signal(false);
+    readonly templateError = signal<string>('');
 
     readonly cancel = output<StepViewModel>();
     readonly save = output<StepViewModel>();
@@ -25,21 +37,58 @@ export class StepEditorComponent {
             const step = this.step();
             this.title.set(step.title);
             this.content.set(step.content);
+            this.model.set(step.model ?? '');
+            this.sandbox.set(step.sandbox ?? '');
+            this.verify.set((step.verify ?? []).join('\n'));
+            this.dependsOn.set([...(step.dependsOn ?? [])]);
+            this.runWhen.set(step.runWhen ?? 'on-success');
+            this.ifChanged.set((step.ifChanged ?? []).join('\n'));
         });
     }
 
+    toggleDependency(stepId: string): void {
+        this.dependsOn.update(ids => ids.includes(stepId) ? ids.filter(id => id !== stepId) : [...ids, stepId]);
+    }
+
     onCancel(): void {
         this.cancel.emit(this.step());
     }
 
-    onSave(): void {
+    async onSave(): Promise<void> {
+        const error = await this.taskService.validatePromptTemplate(this.content(), this.parameters());
+        if (error) {
+            this.templateError.set(`Line ${error.line}, column ${error.column}: ${error.message}. Write \\{{ for a literal {{.`);
+            return;
+        }
+        this.templateError.set('');
+
+        const model = this.model().trim() || undefined;
+        const sandbox = (this.sandbox() || undefined) as TaskSandbox | undefined;
+        const verify = this.verify().split('\n').map(line => line.trim()).filter(line => line.length > 0);
+        const options = this.dependencyOptions().map(option => option.id);
+        const dependsOn = this.dependsOn().filter(id => options.includes(id));
+        const runWhen = this.runWhen() === 'on-success' ? undefined : this.runWhen();
+        const ifChanged = this.ifChanged().split('\n').map(line => line.trim()).filter(line => line.length > 0);
+
         this.step().title = this.title();
         this.step().content = this.content();
+        this.step().model = model;
+        this.step().sandbox = sandbox;
+        this.step().verify = verify;
+        this.step().dependsOn = dependsOn;
+        this.step().runWhen = runWhen;
+        this.step().ifChanged = ifChanged;
 
         const updatedStep: StepViewModel = {
             ...this.step(),
             title: this.title(),
-            content: this.content()
+            content: this.content(),
+            model,
+            sandbox,
+            verify,
+            dependsOn,
+            runWhen,
+            ifChanged
         };
 
         this.save.emit(updatedStep);
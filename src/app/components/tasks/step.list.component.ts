@@ -1,6 +1,6 @@
 import { CdkDrag, CdkDragDrop, CdkDragHandle, CdkDropList, moveItemInArray } from '@angular/cdk/drag-drop';
 import { DatePipe } from '@angular/common';
-import { Component, effect, inject, input, model, signal } from '@angular/core';
+import { Component, computed, effect, inject, input, model, signal } from '@angular/core';
 
 import { Router } from '@angular/router';
 import { EMPTY_TASK, StepViewModel, TaskExtensions, TaskStepType, TaskViewModel } from '../../models/task';
@@ -33,6 +33,8 @@ export class StepListComponent {
     readonly titleIcon = input<string>('arrow-right');
     readonly isCollapsed = signal(false);
     readonly allowReorder = input<boolean>(false);
+    readonly parameterNames = computed(() => TaskExtensions.parameterNames(this.task()));
+    readonly mainSteps = computed(() => this.task().steps.map(step => ({ id: step.id, title: step.title })));
 
     constructor() {
         effect(() => {
@@ -43,6 +45,11 @@ export class StepListComponent {
         });
     }
 
+    /** Main steps other than `step` that it can depend on. */
+    dependencyOptions(step: StepViewModel): { id: string; title: string }[] {
+        return this.mainSteps().filter(option => option.id !== step.id);
+    }
+
     toggleStepExpanded(step: StepViewModel): void {
         step.isExpanded.set(!step.isExpanded());
     }
@@ -4,14 +4,17 @@ import { ActivatedRoute, Router } from '@angular/router';
 import { map } from 'rxjs';
 
 import { CommandDescriptor } from '../../models/command';
+import { PromptRecord } from '../../models/prompt';
 import { ProjectExtensions } from '../../models/project.extensions';
 import { SettingKeys } from '../../models/setting.model';
 import { EMPTY_TASK, TaskExtensions, TaskStepExtensions, TaskStepType, TaskViewModel } from '../../models/task';
+import { DialogService } from '../../services/dialog.service';
 import { ProjectService } from '../../services/project.service';
 import { SettingService } from '../../services/setting.service';
 import { TaskExecuteService } from '../../services/task.execuer.service';
 import { TaskService } from '../../services/task.service';
 import { InputEditableComponent } from '../input-editable/input.editable.component';
+import { PromptPickerComponent } from '../prompts/prompt.picker.component';
 import { WorkspaceHeaderComponent } from '../workspace/workspace.header.component';
 import { StepListComponent } from './step.list.component';
 
@@ -22,6 +25,7 @@ import { StepListComponent } from './step.list.component';
 })
 export class TaskEditComponent implements OnDestroy {
     private readonly projectService = inject(ProjectService);
+    private readonly dialogService = inject(DialogService);
     private readonly settingService = inject(SettingService);
     private readonly tasksService = inject(TaskService);
     private readonly taskExecuteService = inject(TaskExecuteService);
@@ -52,6 +56,8 @@ export class TaskEditComponent implements OnDestroy {
     readonly postSteps = computed(() => {
         return TaskStepExtensions.toViewModels(this.editableTask().poststeps);
     });
+    readonly parametersText = computed(() => TaskExtensions.formatParameters(this.editableTask().parameters));
+    readonly tagsText = computed(() => (this.editableTask().tags ?? []).join(', '));
     readonly headerRightCommands = computed<CommandDescriptor[]>(() => {
         return [
             {
@@ -62,6 +68,7 @@ export class TaskEditComponent implements OnDestroy {
                 subCommands: [
                     { id: 'add-pre-step', title: 'Add PreStep', action: () => { this.addStepByType('pre') } },
                     { id: 'add-post-step', title: 'Add PostStep', action: () => { this.addStepByType('post') } },
+                    { id: 'add-library-step', title: 'Add Step from Library', action: () => { this.addStepFromLibrary() } },
                 ],
                 action: () => { this.addStepByType('normal') }
             },
@@ -130,6 +137,20 @@ export class TaskEditComponent implements OnDestroy {
         }));
     }
 
+    submitTaskParameters(value: string): void {
+        this.editableTask.update(t => ({
+            ...t,
+            parameters: TaskExtensions.parseParameters(value)
+        }));
+    }
+
+    submitTaskTags(value: string): void {
+        this.editableTask.update(t => ({
+            ...t,
+            tags: TaskExtensions.parseTags(value)
+        }));
+    }
+
     submitTaskDescription(value: string): void {
         const trimmedDescription = value.trim();
         if (!trimmedDescription) {
@@ -142,15 +163,28 @@ export class TaskEditComponent implements OnDestroy {
         }));
     }
 
-    private addStepByType(stepType: TaskStepType): void {
+    addStepFromLibrary(): void {
+        this.dialogService.openContent({
+            title: 'Add Step from Library',
+            component: PromptPickerComponent,
+            componentInputs: {
+                pick: (prompt: PromptRecord) => this.addStepByType('normal', prompt.content, prompt.name)
+            }
+        });
+    }
+
+    private addStepByType(stepType: TaskStepType, content?: string, title?: string): void {
         const taskId = this.taskId();
         if (!taskId) {
             return;
         }
         let appSetting = this.settingService.appSetting();
-        const template = appSetting.getSetting<string>(SettingKeys.PROMPT_TEMPLATE_SETTING) ?? '';
+        const template = content ?? appSetting.getSetting<string>(SettingKeys.PROMPT_TEMPLATE_SETTING) ?? '';
 
         let step = TaskExtensions.addStep(this.editableTask, this.editableTask().steps.length + 1, template, stepType);
+        if (step && title) {
+            TaskExtensions.updateStep(this.editableTask, { ...step, title });
+        }
         if (step) {
             let viewModels = [...this.preSteps(), ...this.mainSteps(), ...this.postSteps()];
             let viewModel = viewModels.find(s => s.id === step.id);
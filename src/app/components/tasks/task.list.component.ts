@@ -4,9 +4,11 @@ import { Component, inject, input, OnDestroy, output } from '@angular/core';
 import { Router } from '@angular/router';
 import { CommandDescriptor } from '../../models/command';
 import { Task, TaskStatus } from '../../models/task';
+import { DialogService } from '../../services/dialog.service';
 import { ProjectService } from '../../services/project.service';
 import { TaskExecuteService } from '../../services/task.execuer.service';
 import { IconComponent } from '../icon/icon.component';
+import { TaskScheduleComponent } from './task.schedule.component';
 
 @Component({
     selector: 'mtx-task-list',
@@ -17,6 +19,7 @@ export class TaskListComponent implements OnDestroy {
     private readonly projectService = inject(ProjectService);
     private readonly router = inject(Router);
     private readonly taskExecuteService = inject(TaskExecuteService);
+    private readonly dialogService = inject(DialogService);
 
     private readonly savingSubscription = this.projectService.onSaving.subscribe(() => {
         console.log('Project is saving, refreshing task list...');
@@ -34,6 +37,20 @@ export class TaskListComponent implements OnDestroy {
             tag: 'text-green-500 transition hover:bg-emerald-600/30 hover:text-emerald-300',
             action: (taskId: string) => this.onRunTask(taskId)
         },
+        {
+            id: 'resume-task',
+            title: 'Resume',
+            icon: 'skip-end',
+            tag: 'text-amber-300 transition hover:bg-amber-600/30 hover:text-amber-200',
+            action: (taskId: string) => this.onResumeTask(taskId)
+        },
+        {
+            id: 'schedule-task',
+            title: 'Schedule',
+            icon: 'alarm',
+            tag: 'text-violet-300 transition hover:bg-violet-600/30 hover:text-violet-200',
+            action: (taskId: string) => this.onScheduleTask(taskId)
+        },
         {
             id: 'view-task',
             title: 'View',
@@ -74,6 +91,10 @@ export class TaskListComponent implements OnDestroy {
             return 'arrow-repeat text-amber-300';
         }
 
+        if (status === TaskStatus.Paused) {
+            return 'pause-circle text-amber-300';
+        }
+
         return 'clock text-slate-300';
     }
 
@@ -84,6 +105,25 @@ export class TaskListComponent implements OnDestroy {
         }
     }
 
+    onResumeTask(taskId: string): void {
+        const task = this.tasks().find((t) => t.id === taskId);
+        if (task) {
+            this.taskExecuteService.resumeLatest(task);
+        }
+    }
+
+    onScheduleTask(taskId: string): void {
+        const task = this.tasks().find((t) => t.id === taskId);
+        if (task) {
+            this.dialogService.openContent({
+                title: `Schedule ${task.title}`,
+                component: TaskScheduleComponent,
+                componentInputs: { task },
+                size: 'lg'
+            });
+        }
+    }
+
     onEditTask(taskId: string): void {
         this.router.navigate(['/app/workspace/tasks/edit', taskId]);
     }
@@ -0,0 +1,37 @@
+import { Component, inject, input, signal } from '@angular/core';
+
+import { Task } from '../../models/task';
+import { DialogService } from '../../services/dialog.service';
+import { TaskService } from '../../services/task.service';
+import { IconComponent } from '../icon/icon.component';
+
+/** Dialog content asking the agent to plan a task; `plan` receives the draft. */
+@Component({
+    selector: 'mtx-task-planner',
+    templateUrl: 'task.planner.component.html',
+    imports: [IconComponent]
+})
+export class TaskPlannerComponent {
+    private readonly taskService = inject(TaskService);
+    private readonly dialogService = inject(DialogService);
+
+    readonly plan = input.required<(task: Task) => void>();
+    readonly goal = signal('');
+    readonly isPlanning = signal(false);
+
+    async submit(): Promise<void> {
+        if (this.isPlanning() || !this.goal().trim()) {
+            return;
+        }
+
+        this.isPlanning.set(true);
+        const task = await this.taskService.planTask(this.goal());
+        this.isPlanning.set(false);
+        if (!task) {
+            return;
+        }
+
+        this.plan()(task);
+        this.dialogService.close();
+    }
+}
@@ -47,6 +47,8 @@ export class TaskRuntimeComponent implements OnDestroy, OnInit {
     });
 
     readonly runtimeTask = signal<RuntimeTaskViewModel>(EMPTY_RUNTIME_TASK);
+    readonly isRunning = this.taskRuntimeService.isRunning;
+    readonly isPausing = this.taskRuntimeService.isPausing;
     readonly timeline = computed(() => {
         const selectedTask = this.runtimeTask();
         if (!selectedTask?.id || selectedTask.steps.length === 0) {
@@ -94,6 +96,10 @@ export class TaskRuntimeComponent implements OnDestroy, OnInit {
             return 'border-rose-500/30';
         }
 
+        if (item.step.status === TaskStatus.Skipped) {
+            return 'border-dashed border-slate-700/50';
+        }
+
         if (item.step.status === TaskStatus.InProgress) {
             return 'border-amber-500/30';
         }
@@ -230,6 +236,21 @@ export class TaskRuntimeComponent implements OnDestroy, OnInit {
         return !!next && next.kind === 'post' && next.mainStepIndex === item.mainStepIndex;
     }
 
+    pause(): void {
+        this.taskRuntimeService.pause();
+    }
+
+    resume(): void {
+        const runtimeTask = this.runtimeTask();
+        this.taskRuntimeService.resume(runtimeTask.task, runtimeTask.runId);
+    }
+
+    /** Runs the main step again, and every step after it, in the same run. */
+    runFrom(item: TaskTimelineItem): void {
+        const runtimeTask = this.runtimeTask();
+        this.taskRuntimeService.resume(runtimeTask.task, runtimeTask.runId, item.step.id);
+    }
+
     ngOnInit() {
         let task = this.taskRuntimeService.runtimeTask();
         if (task) {
@@ -0,0 +1,104 @@
+import { DatePipe } from '@angular/common';
+import { Component, computed, inject, input, OnInit, signal } from '@angular/core';
+
+import { IdGenerator } from '../../models/id';
+import { MissedRunPolicy, TaskSchedule } from '../../models/schedule';
+import { Task, TaskRun } from '../../models/task';
+import { ScheduleService } from '../../services/schedule.service';
+import { IconComponent } from '../icon/icon.component';
+
+/** Dialog content listing a task's schedules and recent runs, and adding schedules. */
+@Component({
+    selector: 'mtx-task-schedule',
+    templateUrl: 'task.schedule.component.html',
+    imports: [DatePipe, IconComponent]
+})
+export class TaskScheduleComponent implements OnInit {
+    private readonly scheduleService = inject(ScheduleService);
+
+    readonly task = input.required<Task>();
+    readonly schedules = signal<TaskSchedule[]>([]);
+    readonly history = signal<TaskRun[]>([]);
+
+    readonly cron = signal('0 9 * * 1-5');
+    readonly missedRunPolicy = signal<MissedRunPolicy>('skip');
+    readonly preview = signal<number[] | string>([]);
+    readonly previewError = computed(() => {
+        const preview = this.preview();
+        return typeof preview === 'string' ? preview : '';
+    });
+    readonly previewTimes = computed(() => {
+        const preview = this.preview();
+        return typeof preview === 'string' ? [] : preview;
+    });
+    readonly isSaving = signal(false);
+
+    async ngOnInit(): Promise<void> {
+        await Promise.all([this.loadSchedules(), this.loadHistory(), this.updatePreview(this.cron())]);
+    }
+
+    async updatePreview(cron: string): Promise<void> {
+        this.cron.set(cron);
+        this.preview.set(await this.scheduleService.preview(cron, this.timeZone(), this.utcOffsetMinutes()));
+    }
+
+    async addSchedule(): Promise<void> {
+        if (this.isSaving() || this.previewError() || !this.cron().trim()) {
+            return;
+        }
+
+        const now = Date.now();
+        this.isSaving.set(true);
+        const saved = await this.scheduleService.saveSchedule({
+            id: IdGenerator.generateId(),
+            projectId: this.task().projectId,
+            taskId: this.task().id,
+            cron: this.cron().trim(),
+            enabled: true,
+            missedRunPolicy: this.missedRunPolicy(),
+            timeZone: this.timeZone(),
+            utcOffsetMinutes: this.utcOffsetMinutes(),
+            createdAt: now,
+            updatedAt: now
+        });
+        this.isSaving.set(false);
+        if (saved) {
+            this.schedules.update((schedules) => [...schedules, saved]);
+        }
+    }
+
+    async toggleSchedule(schedule: TaskSchedule): Promise<void> {
+        const saved = await this.scheduleService.saveSchedule({ ...schedule, enabled: !schedule.enabled });
+        if (saved) {
+            this.schedules.update((schedules) => schedules.map((item) => item.id === saved.id ? saved : item));
+        }
+    }
+
+    async deleteSchedule(schedule: TaskSchedule): Promise<void> {
+        await this.scheduleService.deleteSchedule(schedule.id);
+        await this.loadSchedules();
+    }
+
+    policyLabel(policy: MissedRunPolicy): string {
+        return policy === 'catch-up' ? 'catches up once after missed runs' : 'skips missed runs';
+    }
+
+    private async loadSchedules(): Promise<void> {
+        const schedules = await this.scheduleService.loadSchedules();
+        this.schedules.set(schedules.filter((schedule) => schedule.taskId === this.task().id));
+    }
+
+    private async loadHistory(): Promise<void> {
+        this.history.set(await this.scheduleService.loadRunHistory(this.task().id));
+    }
+
+    /** Schedules follow the local time zone, daylight saving time included. */
+    private timeZone(): string | undefined {
+        return Intl.DateTimeFormat().resolvedOptions().timeZone || undefined;
+    }
+
+    /** The current local offset, for when the time zone has no name. */
+    private utcOffsetMinutes(): number {
+        return -new Date().getTimezoneOffset();
+    }
+}
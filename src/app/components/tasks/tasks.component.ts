@@ -8,6 +8,7 @@ import { ProjectService } from '../../services/project.service';
 import { TaskService } from '../../services/task.service';
 import { WorkspaceHeaderComponent } from '../workspace/workspace.header.component';
 import { TaskListComponent } from './task.list.component';
+import { TaskPlannerComponent } from './task.planner.component';
 
 const TASK_TABS: TaskTabItem[] = [
     { key: 'pending', label: 'Pending', tag: '' },
@@ -39,7 +40,7 @@ export class TasksComponent {
         const selectedTab = this.selectedTab();
 
         if (selectedTab === TaskFilterTab.Pending) {
-            return this.taskViewModels().filter((task) => task.status === TaskStatus.Pending || task.status === TaskStatus.InProgress);
+            return this.taskViewModels().filter((task) => task.status === TaskStatus.Pending || task.status === TaskStatus.InProgress || task.status === TaskStatus.Paused);
         }
 
         if (selectedTab === TaskFilterTab.Finished) {
@@ -62,7 +63,19 @@ export class TasksComponent {
     readonly headerRightCommands = computed<CommandDescriptor[]>(() => {
         return [
             { id: 'add-task', title: 'Add Task', icon: 'plus-lg', action: () => this.addTask() },
-            { id: 'export-tasks', title: 'Export', icon: 'box-arrow-down', description: 'Export each task to a markdown file', action: () => { this.exportTasks(); } }
+            { id: 'plan-task', title: 'Plan', icon: 'openai', description: 'Let the agent break a goal into steps', action: () => this.planTask() },
+            { id: 'import-tasks', title: 'Import', icon: 'box-arrow-in-up', description: 'Import the markdown task files in the project folder', action: () => this.taskService.importTasks() },
+            { id: 'export-tasks', title: 'Export', icon: 'box-arrow-down', description: 'Export each task to a markdown file', action: () => { this.exportTasks(); } },
+            {
+                id: 'store-in-repo',
+                title: 'Store in Repo',
+                icon: 'git',
+                tag: this.projectService.storeInRepo() ? 'border border-emerald-700' : '',
+                description: this.projectService.storeInRepo()
+                    ? `Tasks are kept in ${ProjectService.VIBEFLOW_FOLDER}/tasks; click to keep them in the app only.`
+                    : `Keep tasks in ${ProjectService.VIBEFLOW_FOLDER}/tasks, one YAML file each, so they can be reviewed and shared.`,
+                action: () => this.projectService.setStoreInRepo(!this.projectService.storeInRepo())
+            }
         ];
     });
 
@@ -74,6 +87,19 @@ export class TasksComponent {
         this.selectedTab.set('pending');
     }
 
+    planTask(): void {
+        this.dialogService.openContent({
+            title: 'Plan Task',
+            component: TaskPlannerComponent,
+            componentInputs: {
+                plan: (task: Task) => {
+                    ProjectExtensions.addTask(this.projectService.currentProject, task);
+                    this.selectedTab.set('pending');
+                }
+            }
+        });
+    }
+
     async exportTasks(): Promise<void> {
         const tasks = this.taskViewModels();
         await this.taskService.exportTasks(tasks);
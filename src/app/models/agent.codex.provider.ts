@@ -1,6 +1,5 @@
 import { signal } from '@angular/core';
-import { invoke } from '@tauri-apps/api/core';
-import { listen } from '@tauri-apps/api/event';
+import { Channel, invoke } from '@tauri-apps/api/core';
 import {
     AgentCapabilities,
     AgentConfig,
@@ -10,7 +9,7 @@ import {
     EMPTY_AGENT_RESULT,
 } from './agent.provider';
 import { AgentProviderNames } from './agents';
-import { ChatResponsePayload } from './chat.message';
+import { ChatResponsePayload, RunEvent } from './chat.message';
 
 export class CodexCliProvider implements AgentProvider {
     readonly id = AgentProviderNames.ID_CODEX_CLI;
@@ -19,6 +18,7 @@ export class CodexCliProvider implements AgentProvider {
         supportsStreaming: true,
         supportsJsonMode: true,
         supportsTools: true,
+        persistsMessages: true,
     };
     threadId: string | null = null;
     isStreaming = signal(false);
@@ -43,54 +43,42 @@ export class CodexCliProvider implements AgentProvider {
     }
 
     async runStream(request: AgentRequest, onChunk: (chunk: AgentResponse) => void): Promise<void> {
-        return new Promise(async (resolve, reject) => {
-            const unlistenItem = await listen('codex:message', (e) => {
-                const payload = e.payload as ChatResponsePayload;
-                console.log('Received codex:message:', payload);
-                if (payload.type === 'message') {
-                    onChunk({ text: payload.data.content, raw: payload, durationMs: 0 });
-                }
-            });
-
-            const unlistenThreadStarted = await listen('codex:thread-started', (e) => {
-                const payload = e.payload as { thread_id: string };
-                console.log('Received codex:thread-started:', payload);
-                this.threadId = payload.thread_id;
-            });
+        const channel = new Channel<RunEvent>();
+        channel.onmessage = (event) => {
+            const payload = event.payload as ChatResponsePayload;
+            console.log(`Received ${event.kind} for run ${event.runId}:`, payload);
+            if (payload.type === 'threadStarted') {
+                this.threadId = payload.data.thread_id;
+                onChunk({ text: '', raw: payload, durationMs: 0 });
+            } else if (payload.type === 'message' || payload.type === 'done') {
+                onChunk({ text: payload.data.content, raw: payload, durationMs: 0 });
+            }
+        };
 
-            const unlistenAll = () => {
-                unlistenItem();
-                unlistenThreadStarted();
-                unlistenDone();
+        try {
+            this.isStreaming.set(true);
+            let payload = {
+                content: request.prompt,
+                model: request.extra?.['model'] ?? this.config.model,
+                sandbox: request.extra?.['sandbox'],
+                agentId: this.config.id,
+                chatThreadId: request.extra?.['chatThreadId'],
+                threadId: request.extra?.['threadId'],
+                freshThread: request.extra?.['freshThread'],
+                verbatim: request.extra?.['verbatim'],
+                workingDirectory: request.workingDirectory,
+                projectId: request.extra?.['projectId'],
+                taskId: request.extra?.['taskId'],
+                stepId: request.extra?.['stepId'],
+                taskRunId: request.extra?.['taskRunId'],
+                runId: request.extra?.['runId'],
+                contextSetIds: request.extra?.['contextSetIds'],
             };
-
-            const unlistenDone = await listen('codex:done', (e) => {
-                const payload = e.payload as ChatResponsePayload;
-                console.log('Received codex:done:', payload);
-                if (payload.type === 'done') {
-                    onChunk({ text: payload.data.content, raw: payload, durationMs: 0 });
-                }
-                unlistenAll();
-                resolve();
-            });
-
-            try {
-                this.isStreaming.set(true);
-                let payload = {
-                    content: request.prompt,
-                    model: this.config.model,
-                    threadId: this.threadId,
-                    workingDirectory: request.workingDirectory,
-                };
-                console.log('Invoking chat command with payload:', payload);
-                await invoke('chat', { payload });
-            } catch (err) {
-                unlistenAll();
-                reject(err);
-            } finally {
-                this.isStreaming.set(false);
-            }
-        });
+            console.log('Invoking chat command with payload:', payload);
+            await invoke('chat', { payload, onEvent: channel });
+        } finally {
+            this.isStreaming.set(false);
+        }
     }
 
     private async runProcess(cmd: string, args: string[]): Promise<AgentResponse> {
@@ -30,6 +30,7 @@ export interface AgentCapabilities {
     supportsTools: boolean;
     canModifyFiles?: boolean;
     canExecuteShell?: boolean;
+    persistsMessages?: boolean;
 }
 
 export interface AgentProvider {
@@ -5,6 +5,13 @@ export interface AgentRule {
     id: string;
     name: string;
     description?: string;
+    content: string;
+    enabled: boolean;
+    priority: number;
+    scopes: string[];
+    tags: string[];
+    sourcePath?: string;
+    syncedHash?: string;
     createdAt: number;
     updatedAt: number;
 }
@@ -13,6 +20,13 @@ export class AgentRuleViewModel implements AgentRule {
     id: string = IdGenerator.generateId();
     name: string = '';
     description?: string;
+    content: string = '';
+    enabled: boolean = true;
+    priority: number = 0;
+    scopes: string[] = [];
+    tags: string[] = [];
+    sourcePath?: string;
+    syncedHash?: string;
     createdAt: number = Date.now();
     updatedAt: number = Date.now();
 
@@ -28,8 +42,32 @@ export class AgentRuleViewModel implements AgentRule {
             id: this.id,
             name: this.name,
             description: this.description,
+            content: this.content,
+            enabled: this.enabled,
+            priority: this.priority,
+            scopes: this.scopes,
+            tags: this.tags,
+            sourcePath: this.sourcePath,
+            syncedHash: this.syncedHash,
             createdAt: this.createdAt,
             updatedAt: this.updatedAt
         };
     }
 }
+
+export type RuleConflictResolution = 'keep_app' | 'keep_disk';
+
+export interface RuleConflict {
+    ruleId: string;
+    ruleName: string;
+    path: string;
+    kind: 'both_changed' | 'deleted_on_disk';
+}
+
+export interface RuleSyncReport {
+    imported: string[];
+    exported: string[];
+    updated: string[];
+    conflicts: RuleConflict[];
+    rules: AgentRule[];
+}
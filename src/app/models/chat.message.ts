@@ -14,6 +14,9 @@ export interface ChatThread {
     title: string;
     createdAt: number;
     updatedAt: number;
+    agentThreadId?: string;
+    agentId?: string;
+    model?: string;
 }
 
 export const EMPYT_THREAD: ChatThread = {
@@ -24,10 +27,34 @@ export const EMPYT_THREAD: ChatThread = {
     updatedAt: 0
 };
 
-export type ChatRole = 'user' | 'agent';
+export type ChatRole = 'user' | 'agent' | 'system';
 
 export type ChatResponsePayload =
     | { type: 'token'; data: { text: string } }
+    | { type: 'threadStarted'; data: { thread_id: string } }
     | { type: 'message'; data: { role: string; content: string } }
     | { type: 'done'; data: { totalTokens: number, content: string } }
     | { type: 'error'; data: { message: string } };
+
+export type RunEventKind = 'item' | 'done' | 'thread-started';
+
+export interface RunEvent {
+    runId: string;
+    kind: RunEventKind;
+    payload: unknown;
+}
+
+export type SessionStatus = 'queued' | 'running';
+
+export interface ActiveSession {
+    runId: string;
+    projectId?: string;
+    taskRunId?: string;
+    threadId?: string;
+    agent: string;
+    status: SessionStatus;
+    queuedAt: number;
+    startedAt?: number;
+    elapsedMs: number;
+    currentItem?: string;
+}
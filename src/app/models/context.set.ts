@@ -0,0 +1,22 @@
+/** Files and globs, relative to the project root, attached to prompts under one name. */
+export interface ContextSet {
+    id: string;
+    projectId: string;
+    name: string;
+    patterns: string[];
+    createdAt: number;
+    updatedAt: number;
+}
+
+export interface ContextFile {
+    path: string;
+    tokens: number;
+}
+
+export interface ContextBlock {
+    content: string;
+    files: ContextFile[];
+    omitted: ContextFile[];
+    estimatedTokens: number;
+    tokenBudget: number;
+}
@@ -0,0 +1,19 @@
+export type FileChangeKind = 'added' | 'modified' | 'removed';
+
+/** A project file, relative to the project root, that changed on disk. */
+export interface FileChange {
+    path: string;
+    kind: FileChangeKind;
+    changedAt: number;
+}
+
+/** Payload of the `project:files-changed` event, one per debounced batch. */
+export interface FilesChangedEvent {
+    projectId: string;
+    added: string[];
+    modified: string[];
+    removed: string[];
+    changedAt: number;
+}
+
+export const FILES_CHANGED_EVENT = 'project:files-changed';
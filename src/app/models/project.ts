@@ -11,6 +11,31 @@ export interface Project {
     updatedAt: number;
 }
 
+export interface MovedFile {
+    from: string;
+    to: string;
+}
+
+/** What scaffolding or repairing the project's `.vibeflow/` folder changed. */
+export interface ScaffoldReport {
+    version: number;
+    previousVersion?: number;
+    created: string[];
+    moved: MovedFile[];
+}
+
+/** Contents of `.vibeflow/project.json`. */
+export interface ProjectManifest {
+    version: number;
+    name: string;
+    createdAt: number;
+    /** Whether tasks are kept in `.vibeflow/tasks/`, one YAML file each. */
+    storeInRepo: boolean;
+}
+
+/** Emitted with the project id after task files on disk changed its tasks. */
+export const TASKS_SYNCED_EVENT = 'project:tasks-synced';
+
 export const EMPTY_PROJECT: Project = {
     id: '',
     name: '',
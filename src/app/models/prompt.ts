@@ -0,0 +1,27 @@
+export type PromptOutcome = 'running' | 'completed' | 'failed' | 'interrupted';
+
+/** A prompt sent to an agent; pinned prompts make up the library. */
+export interface PromptRecord {
+    id: string;
+    projectId?: string;
+    threadId?: string;
+    taskRunId?: string;
+    content: string;
+    outcome: PromptOutcome;
+    error?: string;
+    starred: boolean;
+    pinned: boolean;
+    name?: string;
+    tags: string[];
+    createdAt: number;
+    updatedAt: number;
+}
+
+export interface PromptQuery {
+    text?: string;
+    projectId?: string;
+    tag?: string;
+    starredOnly?: boolean;
+    pinnedOnly?: boolean;
+    limit?: number;
+}
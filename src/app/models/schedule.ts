@@ -0,0 +1,36 @@
+/** What happens to runs that came due while the app was not running. */
+export type MissedRunPolicy = 'skip' | 'catch-up';
+
+/** A cron-style schedule that runs a task while the app is open. */
+export interface TaskSchedule {
+    id: string;
+    projectId: string;
+    taskId: string;
+    /** Minute, hour, day of month, month and day of week, or `@hourly`, `@daily`/`@midnight`, `@weekly`, `@monthly`, `@yearly`/`@annually`. */
+    cron: string;
+    enabled: boolean;
+    missedRunPolicy: MissedRunPolicy;
+    /** IANA name of the time zone `cron` is read in, e.g. `Europe/Berlin`. */
+    timeZone?: string;
+    /** Fixed offset `cron` is read in when there is no `timeZone`, e.g. 120 for UTC+2. */
+    utcOffsetMinutes: number;
+    nextRunAt?: number;
+    lastRunAt?: number;
+    lastRunId?: string;
+    createdAt: number;
+    updatedAt: number;
+}
+
+/** A due run to start now, taken from the backend queue. */
+export interface ScheduledRunEvent {
+    scheduleId: string;
+    projectId: string;
+    taskId: string;
+    taskRunId: string;
+    scheduledFor: number;
+    /** Runs before this one that were missed, e.g. while the app was closed. */
+    missed: number;
+}
+
+/** Fired when runs came due; `take_due_task_runs` returns them. */
+export const TASK_SCHEDULE_DUE_EVENT = 'task-schedule:due';
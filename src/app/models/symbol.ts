@@ -0,0 +1,46 @@
+export type SymbolKind = 'function' | 'method' | 'struct' | 'enum' | 'trait' | 'class'
+    | 'interface' | 'type' | 'constant' | 'module' | 'macro';
+
+/** A definition from the project's symbol index; lines are 1-based. */
+export interface CodeSymbol {
+    name: string;
+    kind: SymbolKind;
+    path: string;
+    language: string;
+    startLine: number;
+    endLine: number;
+    container?: string;
+    signature: string;
+}
+
+export interface SymbolReference {
+    path: string;
+    line: number;
+    text: string;
+}
+
+export interface SymbolContext {
+    symbol: CodeSymbol;
+    startLine: number;
+    endLine: number;
+    code: string;
+    references: SymbolReference[];
+}
+
+export class SymbolExtensions {
+    /** Markdown for the given definitions, ready to paste into a prompt. */
+    static render(contexts: SymbolContext[]): string {
+        return contexts.map(context => {
+            const symbol = context.symbol;
+            const fence = context.code.includes('```') ? '````' : '```';
+            let section = `## \`${symbol.name}\` (${symbol.kind}) in ${symbol.path}:${context.startLine}-${context.endLine}\n\n`
+                + `${fence}${symbol.language}\n${context.code}\n${fence}\n`;
+            if (context.references.length > 0) {
+                section += '\nReferences:\n'
+                    + context.references.map(reference => `- ${reference.path}:${reference.line}: \`${reference.text}\``).join('\n')
+                    + '\n';
+            }
+            return section;
+        }).join('\n').trimEnd();
+    }
+}
@@ -1,4 +1,5 @@
 import { signal, WritableSignal } from "@angular/core";
+import { ChatThread } from "./chat.message";
 import { IdGenerator } from "./id";
 
 
@@ -7,6 +8,8 @@ export const TaskStatus = {
     InProgress: 'in_progress',
     Completed: 'completed',
     Failed: 'failed',
+    Skipped: 'skipped',
+    Paused: 'paused',
 } as const;
 export type TaskStatus = typeof TaskStatus[keyof typeof TaskStatus];
 
@@ -19,6 +22,11 @@ export const TaskStepType = {
 export type TaskStepType =
     typeof TaskStepType[keyof typeof TaskStepType];
 
+export type TaskSandbox = 'read-only' | 'workspace-write' | 'danger-full-access';
+
+/** When a step runs, judged by the steps it depends on. */
+export type StepRunWhen = 'on-success' | 'on-failure' | 'always';
+
 export const TaskFilterTab = {
     Pending: 'pending',
     Finished: 'finished',
@@ -36,11 +44,26 @@ export interface Task {
     steps: TaskStep[];
     presteps: TaskStep[];
     poststeps: TaskStep[];
+    parameters?: TaskParameter[];
+    /** Tagged project rules only apply to the task's steps when they share one of these. */
+    tags?: string[];
     status: TaskStatus;
     createdAt: number;
     updatedAt: number;
 }
 
+/** Value available to step prompts as `{{params.<name>}}`. */
+export interface TaskParameter {
+    name: string;
+    value: string;
+}
+
+export interface TemplateError {
+    line: number;
+    column: number;
+    message: string;
+}
+
 export interface TaskStep {
     id: string;
     title: string;
@@ -49,6 +72,99 @@ export interface TaskStep {
     createdAt: number;
     updatedAt: number;
     type: TaskStepType;
+    statusReason?: string;
+    /** Model used instead of the agent's default. */
+    model?: string;
+    sandbox?: TaskSandbox;
+    /** Shell commands that must exit with 0 for the step to pass. */
+    verify?: string[];
+    /** Ids of steps of the same task that must finish first. */
+    dependsOn?: string[];
+    runWhen?: StepRunWhen;
+    /** Globs of which at least one must match a file changed during the run. */
+    ifChanged?: string[];
+}
+
+/** A main step of a task together with the steps it waits for. */
+export interface TaskGraphNode {
+    stepId: string;
+    title: string;
+    dependsOn: string[];
+    runWhen: StepRunWhen;
+    ifChanged: string[];
+}
+
+export interface TaskGraph {
+    taskId: string;
+    /** Whether any step declares dependencies; otherwise main steps run in order. Nodes still run one at a time. */
+    declaresDependencies: boolean;
+    nodes: TaskGraphNode[];
+}
+
+export interface TaskGraphAdvance {
+    ready: string[];
+    skipped: string[];
+}
+
+/** Status of a main step, with its pre and post steps, within one task run. */
+export interface TaskRunStep {
+    taskRunId: string;
+    taskId: string;
+    stepId: string;
+    status: TaskStatus;
+    statusReason?: string;
+    updatedAt: number;
+}
+
+/** One run of a task, checkpointed after each main step. */
+export interface TaskRun {
+    id: string;
+    projectId: string;
+    taskId: string;
+    status: TaskStatus;
+    statusReason?: string;
+    /** Chat thread the run's turns go to; a resumed run continues it. */
+    chatThreadId?: string;
+    /** Schedule that started the run, if it was not started by hand. */
+    scheduleId?: string;
+    startedAt: number;
+    updatedAt: number;
+}
+
+/** A task run picked up again, with the statuses of the main steps it keeps. */
+export interface TaskRunResume {
+    run: TaskRun;
+    statuses: Record<string, TaskStatus>;
+    chatThread?: ChatThread;
+}
+
+/** One verification command and how it ended. */
+export interface CommandCheck {
+    command: string;
+    exitCode?: number;
+    output: string;
+    timedOut: boolean;
+    durationMs: number;
+}
+
+export interface StepVerification {
+    passed: boolean;
+    checks: CommandCheck[];
+    attempt: number;
+    maxAttempts: number;
+    reason?: string;
+    /** Prompt for the next attempt; missing once the attempts are used up. */
+    retryPrompt?: string;
+}
+
+export type TaskImportKind = 'new' | 'updated' | 'unchanged';
+
+/** A Markdown task file and what importing it would change. */
+export interface TaskImport {
+    path: string;
+    kind: TaskImportKind;
+    task: Task;
+    changes: string[];
 }
 
 export class StepViewModel implements TaskStep {
@@ -61,25 +177,36 @@ export class StepViewModel implements TaskStep {
     createdAt: number = Date.now();
     updatedAt: number = Date.now();
     type: TaskStepType = 'normal';
+    statusReason?: string;
+    model?: string;
+    sandbox?: TaskSandbox;
+    verify?: string[];
+    dependsOn?: string[];
+    runWhen?: StepRunWhen;
+    ifChanged?: string[];
     isExpanded = signal<boolean>(false);
     isEditing = signal<boolean>(false);
     tag = signal<string>('');
 }
 
 export class StepViewModelGroup {
+    /** Id of the main step the group runs. */
+    stepId: string = '';
     name: string = '';
     steps: StepViewModel[] = [];
 }
 
 export class RuntimeTaskViewModel {
     id: string;
+    runId: string;
     title: string = '';
     description: string = '';
     task: TaskViewModel;
     steps: StepViewModelGroup[];
 
-    constructor(task: TaskViewModel) {
+    constructor(task: TaskViewModel, runId: string = IdGenerator.generateId()) {
         this.id = task.id;
+        this.runId = runId;
         this.title = task.title;
         this.description = task.description;
         this.task = task;
@@ -87,6 +214,7 @@ export class RuntimeTaskViewModel {
         this.steps = [];
         for (const step of task.steps) {
             let group = new StepViewModelGroup();
+            group.stepId = step.id;
             group.name = step.title;
             group.steps.push(...task.presteps.map(TaskStepExtensions.fromTaskStep));
             group.steps.push(TaskStepExtensions.fromTaskStep(step));
@@ -104,6 +232,8 @@ export const EMPTY_TASK: Task = {
     steps: [],
     presteps: [],
     poststeps: [],
+    parameters: [],
+    tags: [],
     status: 'pending',
     createdAt: 0,
     updatedAt: 0,
@@ -117,6 +247,8 @@ export const EMPTY_TASK_VIEW_MODEL: TaskViewModel = {
     steps: [],
     presteps: [],
     poststeps: [],
+    parameters: [],
+    tags: [],
     status: 'pending',
     createdAt: 0,
     updatedAt: 0,
@@ -159,7 +291,14 @@ export class TaskStepExtensions {
             status: stepViewModel.status,
             createdAt: stepViewModel.createdAt,
             updatedAt: stepViewModel.updatedAt,
-            type: stepViewModel.type
+            type: stepViewModel.type,
+            statusReason: stepViewModel.statusReason,
+            model: stepViewModel.model,
+            sandbox: stepViewModel.sandbox,
+            verify: stepViewModel.verify,
+            dependsOn: stepViewModel.dependsOn,
+            runWhen: stepViewModel.runWhen,
+            ifChanged: stepViewModel.ifChanged
         };
     }
 
@@ -197,6 +336,8 @@ export class TaskViewModel implements Task {
     steps: TaskStep[] = [];
     presteps: TaskStep[] = [];
     poststeps: TaskStep[] = [];
+    parameters: TaskParameter[] = [];
+    tags: string[] = [];
     status: TaskStatus = 'pending';
     createdAt: number = Date.now();
     updatedAt: number = Date.now();
@@ -208,7 +349,9 @@ export class TaskExtensions {
             ...task,
             steps: task.steps.map(TaskStepExtensions.fromTaskStep),
             presteps: task.presteps.map(TaskStepExtensions.fromTaskStep),
-            poststeps: task.poststeps.map(TaskStepExtensions.fromTaskStep)
+            poststeps: task.poststeps.map(TaskStepExtensions.fromTaskStep),
+            parameters: task.parameters?.map(x => ({ ...x })) ?? [],
+            tags: [...task.tags ?? []]
         };
     }
 
@@ -217,7 +360,9 @@ export class TaskExtensions {
             ...taskViewModel,
             steps: taskViewModel.steps.map(x => x),
             presteps: taskViewModel.presteps.map(x => x),
-            poststeps: taskViewModel.poststeps.map(x => x)
+            poststeps: taskViewModel.poststeps.map(x => x),
+            parameters: taskViewModel.parameters?.map(x => x) ?? [],
+            tags: taskViewModel.tags?.map(x => x) ?? []
         };
     }
 
@@ -231,12 +376,39 @@ export class TaskExtensions {
             steps: [],
             presteps: [],
             poststeps: [],
+            parameters: [],
+            tags: [],
             status: 'pending',
             createdAt: currentTimestamp,
             updatedAt: currentTimestamp
         };
     }
 
+    static parameterNames(task: Task): string[] {
+        return (task.parameters ?? []).map(parameter => parameter.name);
+    }
+
+    /** Parses `name=value` lines, skipping blank lines and lines without a name. */
+    static parseParameters(text: string): TaskParameter[] {
+        return text.split('\n')
+            .map(line => {
+                const separator = line.indexOf('=');
+                const name = (separator < 0 ? line : line.slice(0, separator)).trim();
+                const value = separator < 0 ? '' : line.slice(separator + 1).trim();
+                return { name, value };
+            })
+            .filter(parameter => parameter.name.length > 0);
+    }
+
+    static formatParameters(parameters: TaskParameter[] | undefined): string {
+        return (parameters ?? []).map(parameter => `${parameter.name}=${parameter.value}`).join('\n');
+    }
+
+    /** Parses a comma separated list of tags, dropping blanks and duplicates. */
+    static parseTags(text: string): string[] {
+        return [...new Set(text.split(',').map(tag => tag.trim()).filter(tag => tag.length > 0))];
+    }
+
     static addStep(task: WritableSignal<TaskViewModel>, index: number,
         template: string, stepType: TaskStepType) {
         const now = Date.now();
@@ -1,8 +1,9 @@
 import { computed, inject, Injectable } from '@angular/core';
+import { invoke } from '@tauri-apps/api/core';
 
 import { AgentConfig, AgentProvider, AgentResponse } from '../models/agent.provider';
 import { AgentProviderRegistry } from '../models/agents';
-import { ChatMessage, ChatRole } from '../models/chat.message';
+import { ActiveSession, ChatMessage, ChatRole } from '../models/chat.message';
 import { MessageStoreService } from './message.store.service';
 import { ProjectService } from './project.service';
 import { SettingService } from './setting.service';
@@ -25,24 +26,26 @@ export class ChatService {
 
     currentThread = computed(() => this.messageStoreService.currentThread());
 
+    /** Sends a prompt in the current chat; resolves to the agent thread the turn ran on. */
     async chat(content: string, agentConfig?: AgentConfig,
         messageSentHandler?: (message: ChatMessage) => void,
-        chunkHandler: (chunk: AgentResponse, agentConfig: AgentConfig) => void = this.handleChunk.bind(this)
-    ): Promise<void> {
+        chunkHandler: (chunk: AgentResponse, agentConfig: AgentConfig) => void = this.handleChunk.bind(this),
+        extra?: Record<string, any>
+    ): Promise<string | undefined> {
         const prompt = content.trim();
         if (!prompt) {
-            return;
+            return undefined;
         }
         const threadReady = await this.messageStoreService.startThreadIfEmpty();
         if (!threadReady) {
-            return;
+            return undefined;
         }
 
         if (agentConfig === undefined) {
             agentConfig = await this.settingService.getActiveAgentConfig();
         }
 
-        await this.agentStreaming(prompt, agentConfig, messageSentHandler, chunkHandler);
+        return await this.agentStreaming(prompt, agentConfig, messageSentHandler, chunkHandler, extra);
     }
 
     async ask(question: string, agentConfig?: AgentConfig): Promise<string> {
@@ -69,6 +72,10 @@ export class ChatService {
         return answer.join('');
     }
 
+    async listActiveSessions(): Promise<ActiveSession[]> {
+        return await invoke<ActiveSession[]>('list_active_sessions');
+    }
+
     async optimizePrompt(rawPrompt: string): Promise<string> {
         const prompt = `
         Rewrite the following prompt to be clearer, more precise, and better structured for an AI model:
@@ -98,37 +105,52 @@ export class ChatService {
     private handleChunk(chunk: AgentResponse, agentConfig: AgentConfig): void {
         if (chunk.text !== undefined && chunk.text !== '') {
             let agentMessage = this.toMessage(chunk.text, agentConfig.agentType, agentConfig.model, AGENT_ROLE);
-            this.messageStoreService.add(agentMessage);
+            this.messageStoreService.add(agentMessage, !this.resolveAgent(agentConfig).capabilities.persistsMessages);
         }
     }
 
     private async agentStreaming(text: string,
         agentConfig: AgentConfig,
         messageSentHandler?: (message: ChatMessage) => void,
-        chunkHandler?: (chunk: AgentResponse, agentConfig: AgentConfig) => void
-    ): Promise<void> {
+        chunkHandler?: (chunk: AgentResponse, agentConfig: AgentConfig) => void,
+        extra?: Record<string, any>
+    ): Promise<string | undefined> {
+        const provider = this.resolveAgent(agentConfig);
         const message = this.toMessage(text, agentConfig.agentType, agentConfig.model);
-        this.messageStoreService.add(message);
+        this.messageStoreService.add(message, !provider.capabilities.persistsMessages);
         if (messageSentHandler) {
             messageSentHandler(message);
         }
 
-        const provider = this.resolveAgent(agentConfig);
         const request = {
             prompt: text,
             model: agentConfig.model,
             timeoutMs: TIMEOUT_MS,
             stream: true,
             workingDirectory: this.projectService.currentProject()?.path || undefined,
+            extra: {
+                projectId: this.projectService.currentProject()?.id,
+                chatThreadId: this.currentThread().id,
+                ...extra
+            }
         }
 
+        let threadId: string | undefined;
         const onChunk = (chunk: AgentResponse) => {
+            if (chunk.raw?.type === 'threadStarted') {
+                threadId = chunk.raw.data.thread_id;
+                return;
+            }
             chunkHandler?.(chunk, agentConfig);
         };
 
         this.messageStoreService.isStreaming.set(true);
-        await provider.runStream?.(request, onChunk);
-        this.messageStoreService.isStreaming.set(false);
+        try {
+            await provider.runStream?.(request, onChunk);
+        } finally {
+            this.messageStoreService.isStreaming.set(false);
+        }
+        return threadId;
     }
 
     private resolveAgent(agentConfig: AgentConfig): AgentProvider {
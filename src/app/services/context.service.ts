@@ -0,0 +1,129 @@
+import { effect, inject, Injectable, signal } from '@angular/core';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+import { ContextBlock, ContextSet } from '../models/context.set';
+import { FILES_CHANGED_EVENT, FileChange, FilesChangedEvent } from '../models/file.change';
+import { CodeSymbol, SymbolContext } from '../models/symbol';
+import { NotificationService } from './notification.service';
+import { ProjectService } from './project.service';
+
+@Injectable({ providedIn: 'root' })
+export class ContextService {
+    private readonly projectService = inject(ProjectService);
+    private readonly notificationService = inject(NotificationService);
+    private readonly contextSetsState = signal<ContextSet[]>([]);
+
+    private readonly filesChangedState = signal<FilesChangedEvent | null>(null);
+
+    readonly contextSets = this.contextSetsState.asReadonly();
+    /** The latest batch of file changes in the open project. */
+    readonly filesChanged = this.filesChangedState.asReadonly();
+
+    constructor() {
+        effect(() => {
+            const projectId = this.projectService.currentProject()?.id;
+            this.load(projectId);
+            this.watch(projectId);
+        });
+
+        listen<FilesChangedEvent>(FILES_CHANGED_EVENT, event => {
+            if (event.payload.projectId === this.projectService.currentProject()?.id) {
+                this.filesChangedState.set(event.payload);
+            }
+        });
+    }
+
+    async load(projectId: string | undefined): Promise<void> {
+        if (!projectId) {
+            this.contextSetsState.set([]);
+            return;
+        }
+
+        try {
+            this.contextSetsState.set(await invoke<ContextSet[]>('load_context_sets', { projectId }));
+        } catch (error) {
+            console.error('Failed to load context sets:', error);
+        }
+    }
+
+    async save(contextSet: ContextSet): Promise<void> {
+        try {
+            await invoke('save_context_set', { contextSet });
+            await this.load(contextSet.projectId);
+        } catch (error) {
+            this.notificationService.error(typeof error === 'string' ? error : 'Failed to save context set');
+        }
+    }
+
+    async delete(contextSet: ContextSet): Promise<void> {
+        await invoke('delete_context_set', { contextSetId: contextSet.id });
+        await this.load(contextSet.projectId);
+    }
+
+    async preview(contextSetIds: string[]): Promise<ContextBlock | null> {
+        const projectId = this.projectService.currentProject()?.id;
+        if (!projectId) {
+            return null;
+        }
+
+        try {
+            return await invoke<ContextBlock>('preview_context', { projectId, contextSetIds });
+        } catch (error) {
+            this.notificationService.error(typeof error === 'string' ? error : 'Failed to build context');
+            return null;
+        }
+    }
+
+    async filesChangedSince(since: number): Promise<FileChange[]> {
+        const projectId = this.projectService.currentProject()?.id;
+        if (!projectId) {
+            return [];
+        }
+
+        return await invoke<FileChange[]>('files_changed_since', { projectId, since });
+    }
+
+    async searchSymbols(query: string): Promise<CodeSymbol[]> {
+        const projectId = this.projectService.currentProject()?.id;
+        if (!projectId || !query.trim()) {
+            return [];
+        }
+
+        try {
+            return await invoke<CodeSymbol[]>('search_symbols', { projectId, query });
+        } catch (error) {
+            console.error('Failed to search symbols:', error);
+            return [];
+        }
+    }
+
+    async symbolContext(symbol: CodeSymbol): Promise<SymbolContext[]> {
+        const projectId = this.projectService.currentProject()?.id;
+        if (!projectId) {
+            return [];
+        }
+
+        try {
+            return await invoke<SymbolContext[]>('get_symbol_context', {
+                projectId, name: symbol.name, path: symbol.path
+            });
+        } catch (error) {
+            this.notificationService.error(typeof error === 'string' ? error : 'Failed to load symbol context');
+            return [];
+        }
+    }
+
+    private async watch(projectId: string | undefined): Promise<void> {
+        try {
+            if (projectId) {
+                await invoke('watch_project', { projectId });
+                await invoke('index_symbols', { projectId });
+            } else {
+                await invoke('unwatch_project');
+            }
+        } catch (error) {
+            console.error('Failed to watch or index project files:', error);
+        }
+    }
+}
@@ -19,11 +19,11 @@ export class MessageStoreService {
 
     constructor() { }
 
-    async add(message: ChatMessage) {
+    async add(message: ChatMessage, persist = true) {
         console.log('[MessageStore] Adding message:', message);
         this._messages.update(list => [...list, message]);
 
-        if (message.threadId?.trim()) {
+        if (persist && message.threadId?.trim()) {
             await invoke('save_chat_message', { message });
         }
 
@@ -10,6 +10,7 @@ export class NavService {
         { label: 'Chat', icon: 'chat-dots text-xl', route: '/app/workspace/chat' },
         { label: 'Context', icon: 'briefcase text-xl', route: '/app/workspace/context' },
         { label: 'Tasks', icon: 'list-task text-xl', route: '/app/workspace/tasks' },
+        { label: 'Prompts', icon: 'journal-text text-xl', route: '/app/workspace/prompts' },
     ];
 
     readonly bottomNavItems: NavItem[] = [
@@ -1,12 +1,11 @@
 import { computed, inject, Injectable, signal, WritableSignal } from '@angular/core';
 import { invoke } from '@tauri-apps/api/core';
-import { join } from '@tauri-apps/api/path';
+import { listen } from '@tauri-apps/api/event';
 import { open } from '@tauri-apps/plugin-dialog';
 import { Subject } from 'rxjs';
-import { AgentRule } from '../models/agent.rule';
-import { IdGenerator } from '../models/id';
-import { EMPTY_PROJECT, Project } from '../models/project';
-import { ProjectExtensions } from '../models/project.extensions';
+import { RuleConflict, RuleConflictResolution, RuleSyncReport } from '../models/agent.rule';
+import { FILES_CHANGED_EVENT, FilesChangedEvent } from '../models/file.change';
+import { EMPTY_PROJECT, Project, ProjectManifest, ScaffoldReport, TASKS_SYNCED_EVENT } from '../models/project';
 import { CommandService } from './command.service';
 import { LocalService } from './local.service';
 import { NotificationService } from './notification.service';
@@ -14,7 +13,7 @@ import { NotificationService } from './notification.service';
 @Injectable({ providedIn: 'root' })
 export class ProjectService {
     static readonly MAX_RECENT_PROJECT_PATHS = 8;
-    static readonly AGENT_FOLDER = '.codex';
+    static readonly VIBEFLOW_FOLDER = '.vibeflow';
     static readonly LOCAL_PROJECT = 'local_project';
 
     private readonly notificationService = inject(NotificationService);
@@ -23,12 +22,30 @@ export class ProjectService {
 
     recentProjects = signal<Project[]>([]);
     currentProject: WritableSignal<Project> = signal<Project>(EMPTY_PROJECT);
+    ruleConflicts = signal<RuleConflict[]>([]);
+    manifest = signal<ProjectManifest | null>(null);
     projectPath = computed(() => this.currentProject()?.path ?? '');
+    storeInRepo = computed(() => this.manifest()?.storeInRepo ?? false);
 
     private readonly savingSubject = new Subject<void>();
     readonly onSaving = this.savingSubject.asObservable();
 
-    constructor() { }
+    constructor() {
+        listen<string>(TASKS_SYNCED_EVENT, event => {
+            if (event.payload === this.currentProject().id) {
+                this.reloadProject('Tasks were updated from the project folder.');
+            }
+        });
+
+        listen<FilesChangedEvent>(FILES_CHANGED_EVENT, event => {
+            const { projectId, added, modified, removed } = event.payload;
+            const rulesFolder = `${ProjectService.VIBEFLOW_FOLDER}/rules/`;
+            if (projectId === this.currentProject().id && this.storeInRepo()
+                && [...added, ...modified, ...removed].some(path => path.startsWith(rulesFolder))) {
+                this.syncRules();
+            }
+        });
+    }
 
     async initialize(): Promise<void> {
         const savedProjectPath = this.projectPath().trim();
@@ -58,6 +75,16 @@ export class ProjectService {
         return true;
     }
 
+    /** Opens a known project, e.g. to run one of its scheduled tasks. */
+    async openProjectById(projectId: string): Promise<boolean> {
+        try {
+            const project = await invoke<Project | null>('load_project', { projectId });
+            return !!project && !!await this.loadOrCreateProjectByPath(project.path);
+        } catch {
+            return false;
+        }
+    }
+
     async newProject(): Promise<boolean> {
         const selectedProjectPath = await this.chooseFolder();
         if (!selectedProjectPath) {
@@ -82,7 +109,7 @@ export class ProjectService {
             await invoke('save_project', { project });
             this.notificationService.success(`Project "${title}" is updated.`);
         } catch (error) {
-            this.notificationService.error('Failed to save project');
+            this.notificationService.error(typeof error === 'string' ? error : 'Failed to save project');
             throw error;
         }
     }
@@ -100,52 +127,77 @@ export class ProjectService {
         }
     }
 
-    async syncAgentsMd(): Promise<void> {
+    async syncRules(): Promise<RuleSyncReport | null> {
         const project = this.currentProject();
-        const projectPath = project?.path?.trim();
-
-        if (!projectPath) {
-            return;
+        if (!project?.id || !project.path?.trim()) {
+            return null;
         }
 
-        const agentsMdPath = await join(projectPath, ProjectService.AGENT_FOLDER, 'AGENTS.md');
-        const exists = await this.fileExists(agentsMdPath);
-        if (!exists) {
-            return;
-        }
+        const report = await invoke<RuleSyncReport>('sync_project_rules', { projectId: project.id });
+        this.currentProject.update((current) => ({ ...current, rules: report.rules }));
+        this.ruleConflicts.set(report.conflicts);
 
-        const alreadyInDb = (project.rules ?? []).some(
-            (rule) => (rule.name ?? '').trim().toLowerCase() === 'agents.md'
-        );
-        if (alreadyInDb) {
-            return;
+        if (report.conflicts.length > 0) {
+            const paths = report.conflicts.map((conflict) => conflict.path).join(', ');
+            this.notificationService.error(`Rules changed both in the app and on disk: ${paths}`);
         }
 
-        const content = await this.readFile(agentsMdPath);
-        const now = Date.now();
-        const rule: AgentRule = {
-            id: IdGenerator.generateId(),
-            name: 'AGENTS.md',
-            description: content,
-            createdAt: now,
-            updatedAt: now
-        };
+        return report;
+    }
 
-        ProjectExtensions.addRule(this.currentProject, rule);
-        await this.saveProject();
+    async resolveRuleConflict(ruleId: string, resolution: RuleConflictResolution): Promise<void> {
+        const projectId = this.currentProject().id;
+        const project = await invoke<Project>('resolve_rule_conflict', { projectId, ruleId, resolution });
+        this.currentProject.set(project);
+        this.ruleConflicts.update((conflicts) => conflicts.filter((conflict) => conflict.ruleId !== ruleId));
     }
 
-    async saveRuleToProjectFolder(rule: AgentRule): Promise<void> {
+    /**
+     * Keeps the project's tasks in `.vibeflow/tasks/` so they can be reviewed and shared
+     * through the repository, or goes back to keeping them only in the app.
+     */
+    async setStoreInRepo(enabled: boolean): Promise<void> {
         const project = this.currentProject();
-        if (!project?.path?.trim()) {
+        if (!project?.id) {
             return;
         }
 
-        const normalizedProjectPath = project.path.replace(/[\\/]+$/, '');
-        const fileName = this.toSafeFileName(rule.name || rule.id);
-        const targetFilePath = `${normalizedProjectPath}/.codex/${fileName}.md`;
+        try {
+            // Unsaved task edits would otherwise be replaced by the stored project.
+            await invoke('save_project', { project });
+            const updated = await invoke<Project>('set_store_in_repo', { projectId: project.id, enabled });
+            this.currentProject.set(updated);
+            await this.loadManifest();
+            this.notificationService.success(enabled
+                ? `Tasks are stored in ${ProjectService.VIBEFLOW_FOLDER}/tasks.`
+                : `Tasks are stored in the app only.`);
+        } catch (error) {
+            this.notificationService.error(typeof error === 'string' ? error : 'Failed to change task storage');
+        }
+    }
 
-        await this.writeFile(targetFilePath, rule.description?.trim() ?? '');
+    /** Creates or upgrades `.vibeflow/`, then reloads the project since rules may have moved. */
+    async repairProjectFolder(): Promise<ScaffoldReport | null> {
+        const project = this.currentProject();
+        if (!project?.id) {
+            return null;
+        }
+
+        try {
+            const report = await invoke<ScaffoldReport>('repair_project_folder', { projectId: project.id });
+            await this.reloadProject();
+            await this.loadManifest();
+            await this.syncRules();
+
+            const changes = report.created.length + report.moved.length;
+            this.notificationService.success(changes > 0
+                ? `Project folder is at layout ${report.version}: ${report.created.length} created, ${report.moved.length} moved.`
+                : `Project folder is up to date.`);
+            return report;
+        } catch (error) {
+            this.notificationService.error(typeof error === 'string' ? error : 'Failed to repair project folder');
+            return null;
+        }
     }
 
     async openInCode(project: Project): Promise<void> {
@@ -157,7 +209,8 @@ export class ProjectService {
             const project = await this.loadProjectFromPath(projectPath);
             this.currentProject.set(project);
             this.addRecentProjectPath(project.path);
-            await this.syncAgentsMd();
+            await this.loadManifest();
+            await this.syncRules();
 
             this.recentProjects.update((projects) => {
                 const deduplicatedProjects = projects.filter((existingProject) => existingProject.id !== project.id);
@@ -172,20 +225,27 @@ export class ProjectService {
         }
     }
 
-    private async openFolder(path: string): Promise<void> {
-        await invoke('open_folder', { path });
-    }
-
-    private async fileExists(path: string): Promise<boolean> {
-        return await invoke<boolean>('path_exists', { path });
+    private async reloadProject(message?: string): Promise<void> {
+        const projectId = this.currentProject().id;
+        const reloaded = await invoke<Project | null>('load_project', { projectId });
+        if (reloaded) {
+            this.currentProject.set(reloaded);
+            if (message) {
+                this.notificationService.info(message);
+            }
+        }
     }
 
-    private async readFile(path: string): Promise<string> {
-        return await invoke<string>('read_text_file', { path });
+    private async loadManifest(): Promise<void> {
+        try {
+            this.manifest.set(await invoke<ProjectManifest | null>('load_project_manifest', { projectId: this.currentProject().id }));
+        } catch {
+            this.manifest.set(null);
+        }
     }
 
-    private async writeFile(path: string, content: string): Promise<void> {
-        await invoke('write_text_file', { path, content });
+    private async openFolder(path: string): Promise<void> {
+        await invoke('open_folder', { path });
     }
 
     private async loadRecentProjects(count: number = ProjectService.MAX_RECENT_PROJECT_PATHS): Promise<Project[]> {
@@ -237,16 +297,4 @@ export class ProjectService {
         const nextPaths = [normalizedPath, ...deduplicatedPaths].slice(0, ProjectService.MAX_RECENT_PROJECT_PATHS);
 
     }
-
-    private toSafeFileName(value: string): string {
-        const sanitized = (value ?? '')
-            .trim()
-            .toLowerCase()
-            .replace(/[<>:"/\\|?*\x00-\x1F]/g, ' ')
-            .replace(/\s+/g, '-')
-            .replace(/-+/g, '-')
-            .replace(/^-|-$/g, '');
-
-        return sanitized || 'rule';
-    }
 }
@@ -0,0 +1,40 @@
+import { inject, Injectable } from '@angular/core';
+import { invoke } from '@tauri-apps/api/core';
+
+import { PromptQuery, PromptRecord } from '../models/prompt';
+import { NotificationService } from './notification.service';
+
+@Injectable({ providedIn: 'root' })
+export class PromptService {
+    private readonly notificationService = inject(NotificationService);
+
+    async search(query: PromptQuery): Promise<PromptRecord[]> {
+        try {
+            return await invoke<PromptRecord[]>('search_prompts', { query });
+        } catch (error) {
+            console.error('Failed to search prompts:', error);
+            return [];
+        }
+    }
+
+    async star(prompt: PromptRecord, starred: boolean): Promise<PromptRecord> {
+        return await invoke<PromptRecord>('star_prompt', { promptId: prompt.id, starred });
+    }
+
+    async pin(prompt: PromptRecord, name: string, tags: string[]): Promise<PromptRecord | null> {
+        try {
+            return await invoke<PromptRecord>('pin_prompt', { promptId: prompt.id, name, tags });
+        } catch (error) {
+            this.notificationService.error(typeof error === 'string' ? error : 'Failed to pin prompt');
+            return null;
+        }
+    }
+
+    async unpin(prompt: PromptRecord): Promise<PromptRecord> {
+        return await invoke<PromptRecord>('unpin_prompt', { promptId: prompt.id });
+    }
+
+    static parseTags(value: string): string[] {
+        return value.split(',').map(tag => tag.trim()).filter(tag => tag.length > 0);
+    }
+}
@@ -0,0 +1,150 @@
+import { inject, Injectable } from '@angular/core';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { ScheduledRunEvent, TASK_SCHEDULE_DUE_EVENT, TaskSchedule } from '../models/schedule';
+import { TaskRun, TaskStatus } from '../models/task';
+import { NotificationService } from './notification.service';
+import { ProjectService } from './project.service';
+import { TaskExecuteService } from './task.execuer.service';
+
+@Injectable({ providedIn: 'root' })
+export class ScheduleService {
+    static readonly HISTORY_LIMIT = 10;
+
+    private readonly projectService = inject(ProjectService);
+    private readonly notificationService = inject(NotificationService);
+    private readonly taskExecuteService = inject(TaskExecuteService);
+
+    private readonly dueRuns: ScheduledRunEvent[] = [];
+    private isStartingDueRuns = false;
+    private isStarted = false;
+
+    constructor() {
+        this.taskExecuteService.onRunTask.subscribe(() => {
+            if (!this.taskExecuteService.isRunning()) {
+                this.startDueRuns();
+            }
+        });
+    }
+
+    /** Starts the runs that came due before the UI loaded, and those that come due later. */
+    async start(): Promise<void> {
+        if (this.isStarted) {
+            return;
+        }
+
+        this.isStarted = true;
+        try {
+            await listen(TASK_SCHEDULE_DUE_EVENT, () => this.takeDueRuns());
+            await this.takeDueRuns();
+        } catch (error) {
+            console.error('Failed to start the task scheduler:', error);
+        }
+    }
+
+    async loadSchedules(): Promise<TaskSchedule[]> {
+        try {
+            return await invoke<TaskSchedule[]>('load_task_schedules', {
+                projectId: this.projectService.currentProject().id
+            });
+        } catch (error) {
+            console.error('Failed to load task schedules:', error);
+            return [];
+        }
+    }
+
+    async saveSchedule(schedule: TaskSchedule): Promise<TaskSchedule | null> {
+        try {
+            return await invoke<TaskSchedule>('save_task_schedule', { schedule });
+        } catch (error) {
+            this.notificationService.error(typeof error === 'string' ? error : 'Failed to save the schedule.');
+            return null;
+        }
+    }
+
+    async deleteSchedule(scheduleId: string): Promise<void> {
+        try {
+            await invoke('delete_task_schedule', { scheduleId });
+        } catch (error) {
+            this.notificationService.error(typeof error === 'string' ? error : 'Failed to delete the schedule.');
+        }
+    }
+
+    /** The next few times `cron` matches, or the reason it is invalid. */
+    async preview(cron: string, timeZone: string | undefined, utcOffsetMinutes: number): Promise<number[] | string> {
+        try {
+            return await invoke<number[]>('preview_task_schedule', { cron, timeZone, utcOffsetMinutes });
+        } catch (error) {
+            return typeof error === 'string' ? error : 'Invalid cron expression.';
+        }
+    }
+
+    async loadRunHistory(taskId: string): Promise<TaskRun[]> {
+        try {
+            return await invoke<TaskRun[]>('load_task_run_history', {
+                taskId,
+                limit: ScheduleService.HISTORY_LIMIT
+            });
+        } catch (error) {
+            console.error('Failed to load task run history:', error);
+            return [];
+        }
+    }
+
+    private async takeDueRuns(): Promise<void> {
+        try {
+            this.dueRuns.push(...await invoke<ScheduledRunEvent[]>('take_due_task_runs'));
+        } catch (error) {
+            console.error('Failed to load due task runs:', error);
+        }
+        await this.startDueRuns();
+    }
+
+    /**
+     * Starts due runs one after another once no task is running, opening the
+     * run's project first when another one is open. A run for a project that
+     * cannot be opened, or for a task that is gone, is skipped.
+     */
+    private async startDueRuns(): Promise<void> {
+        if (this.isStartingDueRuns) {
+            return;
+        }
+
+        this.isStartingDueRuns = true;
+        try {
+            while (this.dueRuns.length && !this.taskExecuteService.isRunning()) {
+                const run = this.dueRuns.shift()!;
+                if (run.projectId !== this.projectService.currentProject().id
+                    && !await this.projectService.openProjectById(run.projectId)) {
+                    await this.skipRun(run, 'the project could not be opened');
+                    continue;
+                }
+
+                const task = this.projectService.currentProject().tasks.find((task) => task.id === run.taskId);
+                if (!task) {
+                    await this.skipRun(run, 'the task no longer exists');
+                } else {
+                    if (run.missed) {
+                        const runs = run.missed === 1 ? 'run' : 'runs';
+                        this.notificationService.info(`${task.title}: catching up after ${run.missed} missed scheduled ${runs}.`);
+                    }
+                    await this.taskExecuteService.execute(task, run.taskRunId);
+                }
+            }
+        } finally {
+            this.isStartingDueRuns = false;
+        }
+    }
+
+    private async skipRun(run: ScheduledRunEvent, reason: string): Promise<void> {
+        try {
+            await invoke('set_task_run_status', {
+                taskRunId: run.taskRunId,
+                status: TaskStatus.Skipped,
+                statusReason: `Scheduled run skipped: ${reason}.`
+            });
+        } catch (error) {
+            console.error('Failed to skip scheduled task run:', error);
+        }
+    }
+}
@@ -1,38 +1,119 @@
 import { inject, Injectable, signal } from '@angular/core';
 import { Router } from '@angular/router';
+import { invoke } from '@tauri-apps/api/core';
 import { Subject } from 'rxjs';
-import { RuntimeTaskViewModel, StepViewModel, StepViewModelGroup, TaskRuntimeData, TaskStatus, TaskViewModel } from '../models/task';
+import { RuntimeTaskViewModel, StepVerification, StepViewModel, StepViewModelGroup, TaskExtensions, TaskGraph, TaskGraphAdvance, TaskRun, TaskRunResume, TaskRunStep, TaskRuntimeData, TaskStatus, TaskViewModel } from '../models/task';
 import { ChatService } from './chat.service';
+import { MessageStoreService } from './message.store.service';
+import { NotificationService } from './notification.service';
 import { ProjectService } from './project.service';
 
+/** The agent thread a chain of step groups shares; unset until the chain's first turn. */
+interface StepBranch {
+    threadId?: string;
+}
+
 @Injectable({ providedIn: 'root' })
 export class TaskExecuteService {
     private readonly projectService = inject(ProjectService);
     private readonly router = inject(Router);
     private readonly chatService = inject(ChatService);
+    private readonly notificationService = inject(NotificationService);
+    private readonly messageStoreService = inject(MessageStoreService);
 
     private readonly runTaskSubject = new Subject<TaskRuntimeData>();
     readonly onRunTask = this.runTaskSubject.asObservable();
     readonly currentTask = signal<TaskViewModel | null>(null);
     readonly runtimeTask = signal<RuntimeTaskViewModel | null>(null);
+    readonly isRunning = signal(false);
+    readonly isPausing = signal(false);
 
-    async execute(task: TaskViewModel): Promise<void> {
-        if (!task?.id) {
+    /** Runs the task from the start; a scheduled run passes the id the scheduler recorded it under. */
+    async execute(task: TaskViewModel, taskRunId?: string): Promise<void> {
+        if (!task?.id || this.isRunning()) {
             return;
         }
 
-        let runtimeTask = await this.prepareExecute(task);
+        let runtimeTask = await this.prepareExecute(task, new RuntimeTaskViewModel(task, taskRunId), {});
+        await this.messageStoreService.startThreadIfEmpty();
 
+        let since = Date.now();
         try {
-            for (const stepGroup of runtimeTask.steps) {
-                await this.executeStepGroup(stepGroup, task);
-            }
+            const run = await invoke<TaskRun>('start_task_run', {
+                projectId: this.projectService.currentProject().id,
+                taskId: task.id,
+                taskRunId: runtimeTask.runId,
+                chatThreadId: this.messageStoreService.isEmptyThread() ? undefined : this.messageStoreService.currentThread().id
+            });
+            since = run.startedAt;
+        } catch (error) {
+            console.error('Failed to save task run:', error);
+        }
+
+        await this.runTask(runtimeTask, task, {}, since);
+    }
+
+    /**
+     * Continues a run from `fromStepId`, or without it from its failed and unfinished
+     * steps, in the run's chat thread so the agent keeps its context.
+     */
+    async resume(task: TaskViewModel, taskRunId: string, fromStepId?: string): Promise<void> {
+        if (!task?.id || this.isRunning()) {
+            return;
+        }
 
-            this.updateTaskStatus(task, TaskStatus.Completed);
+        let resume: TaskRunResume;
+        try {
+            resume = await invoke<TaskRunResume>('resume_task_run', {
+                task: TaskExtensions.toTask(task),
+                taskRunId,
+                fromStepId
+            });
         } catch (error) {
-            this.updateTaskStatus(task, TaskStatus.Failed);
-            throw error;
+            this.notificationService.error(typeof error === 'string' ? error : 'Failed to resume the task run.');
+            return;
+        }
+
+        let runtimeTask = await this.prepareExecute(task, new RuntimeTaskViewModel(task, taskRunId), resume.statuses);
+        if (resume.chatThread) {
+            await this.messageStoreService.switchToThread(resume.chatThread);
+        }
+
+        await this.runTask(runtimeTask, task, resume.statuses, resume.run.startedAt);
+    }
+
+    /** Resumes the task's latest run unless it completed. */
+    async resumeLatest(task: TaskViewModel): Promise<void> {
+        const run = await invoke<TaskRun | null>('load_latest_task_run', { taskId: task.id });
+        if (!run || run.status === TaskStatus.Completed) {
+            this.notificationService.info(`${task.title} has no run to resume.`);
+            return;
+        }
+
+        await this.resume(task, run.id);
+    }
+
+    /** Lets the running steps finish and starts no more; the run can be resumed later. */
+    pause(): void {
+        if (this.isRunning()) {
+            this.isPausing.set(true);
+        }
+    }
+
+    private async runTask(runtimeTask: RuntimeTaskViewModel, task: TaskViewModel,
+        statuses: Record<string, TaskStatus>, since: number): Promise<void> {
+        this.isRunning.set(true);
+        this.isPausing.set(false);
+
+        let status: TaskStatus = TaskStatus.Failed;
+        try {
+            status = await this.executeGraph(runtimeTask, task, statuses, since);
         } finally {
+            this.isRunning.set(false);
+            this.isPausing.set(false);
+            this.updateTaskStatus(task, status);
+            await this.setRunStatus(runtimeTask.runId, status);
+
             this.syncTaskToProject(task);
             this.runTaskSubject.next({
                 runtimeTask: runtimeTask,
@@ -43,15 +124,14 @@ export class TaskExecuteService {
         }
     }
 
-    private async prepareExecute(task: TaskViewModel) {
+    private async prepareExecute(task: TaskViewModel, runtimeTask: RuntimeTaskViewModel, statuses: Record<string, TaskStatus>) {
         this.router.navigate(['/app/workspace/chat']);
         await this.delay(1000);
 
-        let runtimeTask = new RuntimeTaskViewModel(task);
         this.runtimeTask.set(runtimeTask);
 
         this.updateTaskStatus(task, TaskStatus.InProgress);
-        this.resetStepStatuses(runtimeTask);
+        this.resetStepStatuses(runtimeTask, statuses);
 
         this.runTaskSubject.next({
             runtimeTask,
@@ -60,27 +140,202 @@ export class TaskExecuteService {
         return runtimeTask;
     }
 
-    private async executeStepGroup(stepGroup: StepViewModelGroup, task: TaskViewModel): Promise<void> {
+    /**
+     * Runs the step groups as the backend releases them; groups whose condition does
+     * not hold are skipped. Independent branches are not run in parallel: every group
+     * works in the project's own working tree, so they run one at a time, each branch
+     * on its own agent thread. `statuses` holds the main steps a resumed run keeps.
+     * Returns the run's status.
+     */
+    private async executeGraph(runtimeTask: RuntimeTaskViewModel, task: TaskViewModel,
+        statuses: Record<string, TaskStatus>, since: number): Promise<TaskStatus> {
+        const graph = await invoke<TaskGraph>('task_graph', { task: TaskExtensions.toTask(task) });
+        const groups = new Map<string, StepViewModelGroup>(runtimeTask.steps.map((group) => [group.stepId, group]));
+        const running = new Map<string, Promise<void>>();
+        // The agent thread each finished group ended on, continued by the branch's next group.
+        const threads = new Map<string, string>();
+
+        for (;;) {
+            const advance: TaskGraphAdvance = this.isPausing()
+                ? { ready: [], skipped: [] }
+                : await invoke<TaskGraphAdvance>('advance_task_run', {
+                    projectId: this.projectService.currentProject().id,
+                    task: TaskExtensions.toTask(task),
+                    taskRunId: runtimeTask.runId,
+                    statuses,
+                    since
+                });
+
+            for (const stepId of advance.skipped) {
+                statuses[stepId] = TaskStatus.Skipped;
+                for (const step of groups.get(stepId)?.steps ?? []) {
+                    this.updateStepStatus(step, TaskStatus.Skipped);
+                }
+            }
+
+            // Only one group runs at a time; ready groups left waiting are released
+            // again by the next advance.
+            for (const stepId of advance.ready.slice(0, 1)) {
+                const group = groups.get(stepId);
+                if (!group) {
+                    continue;
+                }
+
+                const branch: StepBranch | undefined = graph.declaresDependencies
+                    ? { threadId: this.branchThread(graph, stepId, threads) }
+                    : undefined;
+                statuses[stepId] = TaskStatus.InProgress;
+                running.set(stepId, this.executeStepGroup(group, task, runtimeTask.runId, branch)
+                    .then((status) => {
+                        statuses[stepId] = status;
+                        if (branch?.threadId) {
+                            threads.set(stepId, branch.threadId);
+                        }
+                        running.delete(stepId);
+                    }));
+            }
+
+            if (!running.size) {
+                const finished = [TaskStatus.Completed, TaskStatus.Failed, TaskStatus.Skipped] as TaskStatus[];
+                if (graph.nodes.some((node) => !finished.includes(statuses[node.stepId]))) {
+                    return TaskStatus.Paused;
+                }
+                return Object.values(statuses).includes(TaskStatus.Failed) ? TaskStatus.Failed : TaskStatus.Completed;
+            }
+            await Promise.race(running.values());
+        }
+    }
+
+    /**
+     * The thread a node continues: its dependency's, when it is the only node that
+     * dependency leads to. Otherwise the node starts a new branch.
+     */
+    private branchThread(graph: TaskGraph, stepId: string, threads: Map<string, string>): string | undefined {
+        const dependsOn = graph.nodes.find((node) => node.stepId === stepId)?.dependsOn ?? [];
+        if (dependsOn.length !== 1) {
+            return undefined;
+        }
+
+        const dependents = graph.nodes.filter((node) => node.dependsOn.includes(dependsOn[0]));
+        return dependents.length === 1 ? threads.get(dependsOn[0]) : undefined;
+    }
+
+    /**
+     * Runs a main step wrapped in the pre and post steps; a failure ends the group, not the run.
+     * Without a `branch` the steps run in the chat's agent thread.
+     */
+    private async executeStepGroup(stepGroup: StepViewModelGroup, task: TaskViewModel, runId: string, branch?: StepBranch): Promise<TaskStatus> {
+        await this.saveRunStep(runId, task.id, stepGroup.stepId, TaskStatus.InProgress);
+
+        let status: TaskStatus = TaskStatus.Completed;
+        let reason: string | undefined;
         for (const step of stepGroup.steps) {
-            await this.executeStep(step);
+            try {
+                await this.executeStep(step, task, runId, branch);
+            } catch (error) {
+                status = TaskStatus.Failed;
+                reason = step.statusReason ?? String(error);
+                break;
+            }
+        }
+
+        await this.saveRunStep(runId, task.id, stepGroup.stepId, status, reason);
+        return status;
+    }
+
+    private async saveRunStep(taskRunId: string, taskId: string, stepId: string, status: TaskStatus, statusReason?: string): Promise<void> {
+        const step: TaskRunStep = { taskRunId, taskId, stepId, status, statusReason, updatedAt: Date.now() };
+        try {
+            await invoke('save_task_run_step', { step });
+        } catch (error) {
+            console.error('Failed to save task run step:', error);
+        }
+    }
+
+    private async setRunStatus(taskRunId: string, status: TaskStatus): Promise<void> {
+        try {
+            await invoke('set_task_run_status', { taskRunId, status });
+        } catch (error) {
+            console.error('Failed to save task run status:', error);
         }
     }
 
     private async executeStep(
-        step: StepViewModel
+        step: StepViewModel,
+        task: TaskViewModel,
+        runId: string,
+        branch?: StepBranch
     ): Promise<void> {
         this.updateStepStatus(step, TaskStatus.InProgress);
 
         let prompt = step.content;
-        await this.chatService.chat(prompt);
+        let extra: Record<string, any> = {
+            taskId: task.id,
+            stepId: step.id,
+            taskRunId: runId,
+            model: step.model,
+            sandbox: step.sandbox
+        };
+        for (let attempt = 1; ; attempt++) {
+            let verification: StepVerification | null;
+            try {
+                // A branch's first turn starts its thread and every later turn continues it.
+                const thread = branch ? (branch.threadId ? { threadId: branch.threadId } : { freshThread: true }) : {};
+                const threadId = await this.chatService.chat(prompt, undefined, undefined, undefined, { ...extra, ...thread });
+                if (branch && !branch.threadId) {
+                    branch.threadId = threadId;
+                }
+                verification = await this.verifyStep(step, task, attempt);
+            } catch (error) {
+                this.failStep(step, task, String(error));
+                throw error;
+            }
+
+            if (!verification || verification.passed) {
+                break;
+            }
+
+            const reason = verification.reason ?? 'verification failed';
+            if (!verification.retryPrompt) {
+                this.failStep(step, task, reason);
+                throw new Error(reason);
+            }
+
+            this.notificationService.info(`${step.title}: ${reason}; retrying (${attempt + 1}/${verification.maxAttempts}).`);
+            // The failure output is plain text, so it is not rendered as the step's template.
+            prompt = verification.retryPrompt;
+            extra = { ...extra, verbatim: true };
+        }
 
         this.updateStepStatus(step, TaskStatus.Completed);
     }
 
-    private resetStepStatuses(task: RuntimeTaskViewModel): void {
+    /** Runs the saved step's verification commands; `null` when it has none. */
+    private async verifyStep(step: StepViewModel, task: TaskViewModel, attempt: number): Promise<StepVerification | null> {
+        if (!(step.verify ?? []).some((command) => command.trim().length > 0)) {
+            return null;
+        }
+
+        return await invoke<StepVerification>('verify_step', {
+            projectId: this.projectService.currentProject().id,
+            taskId: task.id,
+            stepId: step.id,
+            attempt
+        });
+    }
+
+    private failStep(step: StepViewModel, task: TaskViewModel, reason: string): void {
+        this.updateStepStatus(step, TaskStatus.Failed, reason);
+        this.markTaskStepFailed(task, step.id, reason);
+        this.notificationService.error(reason);
+    }
+
+    /** Shows the main steps a resumed run keeps with their status, the rest as pending. */
+    private resetStepStatuses(task: RuntimeTaskViewModel, statuses: Record<string, TaskStatus>): void {
         for (const stepGroup of task.steps) {
+            const status = statuses[stepGroup.stepId];
             for (const step of stepGroup.steps) {
-                this.updateStepStatus(step, TaskStatus.Pending);
+                this.updateStepStatus(step, status === TaskStatus.Completed || status === TaskStatus.Skipped ? status : TaskStatus.Pending);
             }
         }
     }
@@ -90,12 +345,23 @@ export class TaskExecuteService {
         task.updatedAt = Date.now();
     }
 
-    private updateStepStatus(step: StepViewModel, status: TaskStatus): void {
+    private updateStepStatus(step: StepViewModel, status: TaskStatus, reason?: string): void {
         step.status = status;
+        step.statusReason = reason;
         step.runtimeStatus.set(status);
         step.updatedAt = Date.now();
     }
 
+    private markTaskStepFailed(task: TaskViewModel, stepId: string, reason: string): void {
+        for (const step of [...task.presteps, ...task.steps, ...task.poststeps]) {
+            if (step.id === stepId) {
+                step.status = TaskStatus.Failed;
+                step.statusReason = reason;
+                step.updatedAt = Date.now();
+            }
+        }
+    }
+
     private syncTaskToProject(task: TaskViewModel): void {
         this.projectService.currentProject.update((project) => {
             if (!project) {
@@ -1,12 +1,19 @@
 import { effect, inject, Injectable, signal } from '@angular/core';
 import { invoke } from '@tauri-apps/api/core';
 
-import { Task, TaskStep } from '../models/task';
+import { Project } from '../models/project';
+import { Task, TaskImport, TaskStep, TemplateError } from '../models/task';
+import { DialogService } from './dialog.service';
+import { NotificationService } from './notification.service';
 import { ProjectService } from './project.service';
+import { SettingService } from './setting.service';
 
 @Injectable({ providedIn: 'root' })
 export class TaskService {
     private readonly projectService = inject(ProjectService);
+    private readonly dialogService = inject(DialogService);
+    private readonly notificationService = inject(NotificationService);
+    private readonly settingService = inject(SettingService);
     private readonly tasksState = signal<Task[]>([]);
 
     readonly tasks = this.tasksState.asReadonly();
@@ -36,7 +43,7 @@ export class TaskService {
 
         const normalizedProjectPath = project.path.replace(/[\\/]+$/, '');
         const safeTaskName = this.toSafeFileName(task.title || task.id);
-        const targetFilePath = `${normalizedProjectPath}/.codex/${safeTaskName}.md`;
+        const targetFilePath = `${normalizedProjectPath}/${ProjectService.VIBEFLOW_FOLDER}/tasks/${safeTaskName}.md`;
         const content = this.toTaskMarkdown(task);
 
         try {
@@ -49,10 +56,87 @@ export class TaskService {
         }
     }
 
+    /**
+     * Reads the Markdown task files in the project folder, shows what they would add or
+     * change, and saves them once confirmed.
+     */
+    async importTasks(): Promise<void> {
+        const projectId = this.projectService.currentProject().id;
+        if (!projectId) {
+            return;
+        }
+
+        try {
+            const preview = await invoke<TaskImport[]>('import_tasks', { projectId, paths: null, dryRun: true });
+            const pending = preview.filter((entry) => entry.kind !== 'unchanged');
+            if (!pending.length) {
+                this.notificationService.info(preview.length ? 'Tasks are up to date with their files.' : 'No task files to import.');
+                return;
+            }
+
+            const isConfirmed = await this.dialogService.openPrompt({
+                title: 'Import Tasks',
+                message: pending.map((entry) => this.describeImport(entry)).join('\n\n'),
+                confirmLabel: 'Import',
+                cancelLabel: 'Cancel',
+            });
+            if (!isConfirmed) {
+                return;
+            }
+
+            await invoke<TaskImport[]>('import_tasks', { projectId, paths: pending.map((entry) => entry.path), dryRun: false });
+            const project = await invoke<Project | null>('load_project', { projectId });
+            if (project) {
+                this.projectService.currentProject.set(project);
+            }
+            this.notificationService.success(`Imported ${pending.length} task${pending.length === 1 ? '' : 's'}.`);
+        } catch (error) {
+            this.notificationService.error(typeof error === 'string' ? error : 'Failed to import tasks');
+        }
+    }
+
+    /**
+     * Asks the default agent to break a goal into steps. The returned task is a draft; it
+     * is not part of the project until the caller adds it.
+     */
+    async planTask(goal: string): Promise<Task | null> {
+        const projectId = this.projectService.currentProject().id;
+        if (!projectId || !goal.trim()) {
+            return null;
+        }
+
+        try {
+            const agentConfig = await this.settingService.getActiveAgentConfig();
+            return await invoke<Task>('plan_task', { projectId, goal, agentId: agentConfig?.id ?? null });
+        } catch (error) {
+            this.notificationService.error(typeof error === 'string' ? error : 'Failed to plan task');
+            return null;
+        }
+    }
+
+    /** Checks a step prompt against the built-in variables and the task's parameters. */
+    async validatePromptTemplate(template: string, parameters: string[]): Promise<TemplateError | null> {
+        try {
+            await invoke('validate_prompt_template', { template, parameters });
+            return null;
+        } catch (error) {
+            return error as TemplateError;
+        }
+    }
+
     findTask(taskId: string): Task | undefined {
         return this.tasksState().find((task) => task.id === taskId);
     }
 
+    private describeImport(entry: TaskImport): string {
+        if (entry.kind === 'new') {
+            const stepCount = entry.task.presteps.length + entry.task.steps.length + entry.task.poststeps.length;
+            return `${entry.path}: new task "${entry.task.title}" with ${stepCount} steps`;
+        }
+
+        return [`${entry.path}: updates "${entry.task.title}"`, ...entry.changes.map((change) => `  - ${change}`)].join('\n');
+    }
+
     private toTaskMarkdown(task: Task): string {
         const lines: string[] = [`# ${task.title || 'Task'}`, ''];
 
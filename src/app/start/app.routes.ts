@@ -3,6 +3,7 @@ import { AppLayoutComponent } from '../components/app-layout/app.layout.componen
 import { ChatComponent } from '../components/chat/chat.component';
 import { ContextComponent } from '../components/context/context.component';
 import { HomeComponent } from '../components/home/home.component';
+import { PromptsComponent } from '../components/prompts/prompts.component';
 import { SettingsComponent } from '../components/settings/settings.component';
 import { TasksComponent } from '../components/tasks/tasks.component';
 import { WorkspaceComponent } from '../components/workspace/workspace.component';
@@ -23,6 +24,7 @@ export const routes: Routes = [
                     { path: 'chat', component: ChatComponent },
                     { path: 'context', component: ContextComponent },
                     { path: 'tasks', component: TasksComponent },
+                    { path: 'prompts', component: PromptsComponent },
                     {
                         path: 'tasks/edit/:taskId',
                         loadComponent: () =>